@@ -177,6 +177,16 @@ std::string defaultPluginCachePath() {
 constexpr uint32_t kPatcherScratchpadCapacity = 1024;
 constexpr uint32_t kPatcherNodeCapacity = 1024;
 constexpr uint32_t kPatcherMaxModOutputs = 8;
+constexpr double kPreviewChordSeconds = 0.4;
+constexpr uint8_t kPreviewChordVelocity = 100;
+constexpr uint8_t kPreviewHarmonyBaseOctave = 4;
+constexpr uint32_t kCountInMaxBars = 2;
+constexpr uint64_t kCountInBeatsPerBar = 4;
+constexpr double kCountInClickSeconds = 0.05;
+constexpr uint8_t kCountInAccentPitch = 84;
+constexpr uint8_t kCountInClickPitch = 79;
+constexpr double kMinTempoBpm = 20.0;
+constexpr double kMaxTempoBpm = 300.0;
 
 struct PatcherNodeBuffer {
   std::array<daw::EventEntry, kPatcherNodeCapacity> events{};
@@ -226,6 +236,11 @@ inline void dispatchRustKernel(daw::PatcherNodeType type, daw::PatcherContext& c
         daw::patcher_process(&ctx);
       }
       break;
+    case daw::PatcherNodeType::HarmonyArp:
+      if (daw::patcher_process_harmony_arp) {
+        daw::patcher_process_harmony_arp(&ctx);
+      }
+      break;
   }
 }
 
@@ -388,6 +403,24 @@ public:
       }
     }
 
+    const int meterChannels =
+        std::min(numOutputChannels, static_cast<int>(daw::kUiMasterMeterChannels));
+    for (int ch = 0; ch < meterChannels; ++ch) {
+      const float* output = outputChannelData[ch];
+      if (!output) {
+        continue;
+      }
+      float peak = 0.0f;
+      for (int i = 0; i < numSamples; ++i) {
+        peak = std::max(peak, std::fabs(output[i]));
+      }
+      float held = m_masterPeak[ch].load(std::memory_order_relaxed);
+      while (peak > held &&
+             !m_masterPeak[ch].compare_exchange_weak(held, peak,
+                                                     std::memory_order_relaxed)) {
+      }
+    }
+
     // Update last played block if we successfully played audio
     if (playedBlock) {
       m_lastPlayedBlockId = nextBlockToPlay;
@@ -404,6 +437,14 @@ public:
     }
   }
 
+  // Returns the master peak held since the previous call and resets it.
+  float takeMasterPeak(uint32_t channel) {
+    if (channel >= daw::kUiMasterMeterChannels) {
+      return 0.0f;
+    }
+    return m_masterPeak[channel].exchange(0.0f, std::memory_order_relaxed);
+  }
+
   void updateTracks(const std::vector<TrackInfo>& tracks) {
     auto next = std::make_shared<std::vector<TrackInfo>>(tracks);
     std::atomic_store_explicit(&m_tracks, std::move(next), std::memory_order_release);
@@ -418,6 +459,7 @@ private:
   std::chrono::steady_clock::time_point m_startTime;
   uint64_t m_totalSamplesProcessed = 0;
   uint32_t m_lastPlayedBlockId = 0;  // Track which block we played last
+  std::atomic<float> m_masterPeak[daw::kUiMasterMeterChannels]{};
 
   std::shared_ptr<std::vector<TrackInfo>> m_tracks;
 };
@@ -686,6 +728,15 @@ struct Track {
     bool hasScheduledEnd = false;
   };
 
+  struct PreviewNote {
+    uint32_t noteId = 0;
+    uint8_t pitch = 0;
+    uint8_t velocity = 0;
+    float tuningCents = 0.0f;
+    uint64_t remainingSamples = 0;
+    bool started = false;
+  };
+
 struct TrackRuntime {
     uint32_t trackId = 0;
     Track track;
@@ -704,6 +755,10 @@ struct TrackRuntime {
     std::atomic<bool> mirrorPending{false};
     std::atomic<uint64_t> mirrorGateSampleTime{0};
     std::atomic<bool> mirrorPrimed{false};
+    // Armed for recording; live input will be routed to armed tracks.
+    std::atomic<bool> armed{false};
+    // Signed playback offset in nanoticks; positive plays the clip late.
+    std::atomic<int64_t> timeOffsetNanoticks{0};
 
     // Track notes that are currently playing and may need note-offs in future blocks
     std::map<uint32_t, ActiveNote> activeNotes;  // Key is noteId
@@ -732,6 +787,7 @@ struct TrackRuntime {
     std::vector<float*> inputAudioChannels;
     std::vector<daw::EventEntry> inboundMidiEvents;
     std::vector<daw::EventEntry> inboundMidiScratch;
+    std::vector<PreviewNote> previewNotes;
     std::mutex inboundMutex;
 
     std::vector<float> modOutputSamples;
@@ -981,6 +1037,9 @@ struct TrackRuntime {
   loopEndNanotick.store(patternTicks, std::memory_order_release);
   std::atomic<bool> clipDirty{true};
   std::atomic<bool> playing{false};
+  std::atomic<uint32_t> countInBars{0};
+  std::atomic<uint64_t> countInTotalTicks{0};
+  std::atomic<uint64_t> countInRemainingTicks{0};
   std::atomic<uint32_t> clipVersion{0};
   std::atomic<uint32_t> chainVersion{0};
   std::atomic<uint32_t> routingVersion{0};
@@ -2696,7 +2755,7 @@ struct TrackRuntime {
       constexpr uint16_t kGraphErrInvalidPort = 6;
       if (commandType == daw::UiCommandType::AddPatcherNode) {
         if (graphPayload.nodeType >
-            static_cast<uint32_t>(daw::PatcherNodeType::EventOut)) {
+            static_cast<uint32_t>(daw::PatcherNodeType::HarmonyArp)) {
           emitPatcherGraphError(kGraphErrInvalidType,
                                 graphPayload.trackId,
                                 graphPayload.nodeId,
@@ -3203,8 +3262,28 @@ struct TrackRuntime {
     } else if (payload.commandType ==
                static_cast<uint16_t>(daw::UiCommandType::TogglePlay)) {
       const bool next = !playing.load(std::memory_order_acquire);
+      const uint64_t countInTicks = next
+          ? static_cast<uint64_t>(countInBars.load(std::memory_order_acquire)) *
+              kCountInBeatsPerBar * ticksPerBeat
+          : 0;
+      countInTotalTicks.store(countInTicks, std::memory_order_release);
+      countInRemainingTicks.store(countInTicks, std::memory_order_release);
       playing.store(next, std::memory_order_release);
       std::cout << "UI: Transport " << (next ? "Play" : "Stop") << std::endl;
+    } else if (payload.commandType ==
+               static_cast<uint16_t>(daw::UiCommandType::SetTempo)) {
+      const double bpm = static_cast<double>(payload.value0) / 100.0;
+      if (bpm < kMinTempoBpm || bpm > kMaxTempoBpm) {
+        std::cerr << "UI: SetTempo rejected " << bpm << " BPM" << std::endl;
+        return;
+      }
+      tempoProvider.setBpm(bpm);
+      std::cout << "UI: Tempo " << bpm << " BPM" << std::endl;
+    } else if (payload.commandType ==
+               static_cast<uint16_t>(daw::UiCommandType::SetCountIn)) {
+      const uint32_t bars = std::min(payload.value0, kCountInMaxBars);
+      countInBars.store(bars, std::memory_order_release);
+      std::cout << "UI: Count-in " << bars << " bar(s)" << std::endl;
     } else if (payload.commandType ==
                static_cast<uint16_t>(daw::UiCommandType::RequestClipWindow)) {
       daw::UiClipWindowCommandPayload windowPayload{};
@@ -3339,6 +3418,93 @@ struct TrackRuntime {
           applyRemoveChord(chordPayload.trackId, chordId, true);
         }
       }
+    } else if (payload.commandType ==
+               static_cast<uint16_t>(daw::UiCommandType::PreviewChord)) {
+      daw::UiChordCommandPayload chordPayload{};
+      std::memcpy(&chordPayload, entry.payload, sizeof(chordPayload));
+      TrackRuntime* runtime = nullptr;
+      {
+        std::lock_guard<std::mutex> lock(tracksMutex);
+        if (chordPayload.trackId < tracks.size()) {
+          runtime = tracks[chordPayload.trackId].get();
+        }
+      }
+      if (!runtime) {
+        std::cerr << "UI: PreviewChord failed - track "
+                  << chordPayload.trackId << " not found" << std::endl;
+        return;
+      }
+      const uint64_t nanotick =
+          static_cast<uint64_t>(chordPayload.nanotickLo) |
+          (static_cast<uint64_t>(chordPayload.nanotickHi) << 32);
+      const auto harmony = getHarmonyAt(nanotick);
+      if (!harmony.has_value()) {
+        return;
+      }
+      const auto* scale = getScaleForHarmony(*harmony);
+      if (!scale) {
+        return;
+      }
+      const uint8_t rootPc = static_cast<uint8_t>(harmony->root % 12);
+      const auto chordPitches = daw::resolveChordPitches(
+          static_cast<uint8_t>(chordPayload.degree),
+          chordPayload.quality,
+          chordPayload.inversion,
+          chordPayload.baseOctave,
+          rootPc,
+          *scale);
+      const uint64_t previewSamples = static_cast<uint64_t>(
+          engineConfig.sampleRate * kPreviewChordSeconds);
+      std::lock_guard<std::mutex> lock(runtime->inboundMutex);
+      for (const auto& chordPitch : chordPitches) {
+        PreviewNote note;
+        note.noteId = nextNoteId.fetch_add(1, std::memory_order_acq_rel);
+        note.pitch = clampMidi(chordPitch.midi);
+        note.velocity = kPreviewChordVelocity;
+        note.tuningCents = chordPitch.cents;
+        note.remainingSamples = previewSamples;
+        runtime->previewNotes.push_back(note);
+      }
+    } else if (payload.commandType ==
+               static_cast<uint16_t>(daw::UiCommandType::PreviewHarmony)) {
+      TrackRuntime* runtime = nullptr;
+      {
+        std::lock_guard<std::mutex> lock(tracksMutex);
+        if (payload.trackId < tracks.size()) {
+          runtime = tracks[payload.trackId].get();
+        }
+      }
+      if (!runtime) {
+        std::cerr << "UI: PreviewHarmony failed - track "
+                  << payload.trackId << " not found" << std::endl;
+        return;
+      }
+      const auto* scale = scaleRegistry.find(payload.value0);
+      if (!scale) {
+        std::cerr << "UI: PreviewHarmony failed - scale "
+                  << payload.value0 << " not found" << std::endl;
+        return;
+      }
+      // Root-position triad on the first degree of the harmony.
+      const auto chordPitches = daw::resolveChordPitches(
+          1,
+          1,
+          0,
+          kPreviewHarmonyBaseOctave,
+          static_cast<uint8_t>(payload.notePitch % 12),
+          *scale);
+      const uint64_t previewSamples = static_cast<uint64_t>(
+          engineConfig.sampleRate * kPreviewChordSeconds);
+      std::lock_guard<std::mutex> lock(runtime->inboundMutex);
+      for (const auto& chordPitch : chordPitches) {
+        PreviewNote note;
+        note.noteId = nextNoteId.fetch_add(1, std::memory_order_acq_rel);
+        note.pitch = clampMidi(chordPitch.midi);
+        note.velocity = kPreviewChordVelocity;
+        note.tuningCents = chordPitch.cents;
+        note.remainingSamples = previewSamples;
+        runtime->previewNotes.push_back(note);
+      }
     } else if (payload.commandType ==
                static_cast<uint16_t>(daw::UiCommandType::SetTrackHarmonyQuantize)) {
       TrackRuntime* runtime = nullptr;
@@ -3364,6 +3530,44 @@ struct TrackRuntime {
           std::memory_order_release);
       std::cout << "UI: Track " << payload.trackId
                 << " harmony quantize " << (enable ? "on" : "off") << std::endl;
+    } else if (payload.commandType ==
+               static_cast<uint16_t>(daw::UiCommandType::SetTrackArmed)) {
+      TrackRuntime* runtime = nullptr;
+      {
+        std::lock_guard<std::mutex> lock(tracksMutex);
+        if (payload.trackId < tracks.size()) {
+          runtime = tracks[payload.trackId].get();
+        }
+      }
+      if (!runtime) {
+        std::cerr << "UI: SetTrackArmed failed - track "
+                  << payload.trackId << " not found" << std::endl;
+        return;
+      }
+      const bool armed = payload.value0 != 0;
+      runtime->armed.store(armed, std::memory_order_release);
+      std::cout << "UI: Track " << payload.trackId
+                << (armed ? " armed" : " disarmed") << std::endl;
+    } else if (payload.commandType ==
+               static_cast<uint16_t>(daw::UiCommandType::SetTrackTimeOffset)) {
+      TrackRuntime* runtime = nullptr;
+      {
+        std::lock_guard<std::mutex> lock(tracksMutex);
+        if (payload.trackId < tracks.size()) {
+          runtime = tracks[payload.trackId].get();
+        }
+      }
+      if (!runtime) {
+        std::cerr << "UI: SetTrackTimeOffset failed - track "
+                  << payload.trackId << " not found" << std::endl;
+        return;
+      }
+      const int64_t offset = static_cast<int64_t>(
+          (static_cast<uint64_t>(payload.noteNanotickHi) << 32) |
+          payload.noteNanotickLo);
+      runtime->timeOffsetNanoticks.store(offset, std::memory_order_release);
+      std::cout << "UI: Track " << payload.trackId
+                << " time offset " << offset << " nanoticks" << std::endl;
     } else if (payload.commandType ==
                static_cast<uint16_t>(daw::UiCommandType::SetLoopRange)) {
       const uint64_t start =
@@ -3459,7 +3663,34 @@ struct TrackRuntime {
           break;
         }
       }
-      const bool isPlaying = playing.load(std::memory_order_acquire);
+      const uint64_t countInRemaining =
+          countInRemainingTicks.load(std::memory_order_acquire);
+      if (countInRemaining > 0) {
+        const uint64_t countInTotal =
+            countInTotalTicks.load(std::memory_order_acquire);
+        const uint64_t elapsed = countInTotal - std::min(countInTotal, countInRemaining);
+        const uint64_t blockTicks =
+            blockTicksFor(transportNanotick.load(std::memory_order_acquire));
+        const uint64_t beatIndex = (elapsed + ticksPerBeat - 1) / ticksPerBeat;
+        if (beatIndex * ticksPerBeat < elapsed + blockTicks &&
+            beatIndex * ticksPerBeat < countInTotal) {
+          PreviewNote click;
+          click.noteId = nextNoteId.fetch_add(1, std::memory_order_acq_rel);
+          click.pitch = beatIndex % kCountInBeatsPerBar == 0
+              ? kCountInAccentPitch
+              : kCountInClickPitch;
+          click.velocity = kPreviewChordVelocity;
+          click.remainingSamples = static_cast<uint64_t>(
+              engineConfig.sampleRate * kCountInClickSeconds);
+          std::lock_guard<std::mutex> lock(trackSnapshot.front()->inboundMutex);
+          trackSnapshot.front()->previewNotes.push_back(click);
+        }
+        countInRemainingTicks.store(
+            countInRemaining > blockTicks ? countInRemaining - blockTicks : 0,
+            std::memory_order_release);
+      }
+      const bool isPlaying =
+          playing.load(std::memory_order_acquire) && countInRemaining == 0;
       auto advanceTransport = [&]() {
         uint64_t loopStartTicks =
             loopStartNanotick.load(std::memory_order_acquire);
@@ -3696,6 +3927,39 @@ struct TrackRuntime {
           std::lock_guard<std::mutex> lock(runtime.inboundMutex);
           runtime.inboundMidiEvents.swap(inboundEvents);
           runtime.inboundMidiEvents.clear();
+          auto& previews = runtime.previewNotes;
+          for (auto it = previews.begin(); it != previews.end();) {
+            daw::EventEntry previewEntry;
+            previewEntry.blockId = 0;
+            previewEntry.type = static_cast<uint16_t>(daw::EventType::Midi);
+            previewEntry.size = sizeof(daw::MidiPayload);
+            daw::MidiPayload previewPayload{};
+            previewPayload.data1 = it->pitch;
+            previewPayload.channel = 0;
+            previewPayload.tuningCents = it->tuningCents;
+            previewPayload.noteId = it->noteId;
+            if (!it->started) {
+              it->started = true;
+              previewEntry.sampleTime = blockSampleStart;
+              previewPayload.status = 0x90;
+              previewPayload.data2 = it->velocity;
+              std::memcpy(previewEntry.payload, &previewPayload,
+                          sizeof(previewPayload));
+              inboundEvents.push_back(previewEntry);
+            }
+            if (it->remainingSamples >= engineConfig.blockSize) {
+              it->remainingSamples -= engineConfig.blockSize;
+              ++it;
+              continue;
+            }
+            previewEntry.sampleTime = blockSampleStart + it->remainingSamples;
+            previewPayload.status = 0x80;
+            previewPayload.data2 = 0;
+            std::memcpy(previewEntry.payload, &previewPayload,
+                        sizeof(previewPayload));
+            inboundEvents.push_back(previewEntry);
+            it = previews.erase(it);
+          }
         }
         if (!inboundEvents.empty()) {
           for (const auto& entry : inboundEvents) {
@@ -4811,14 +5075,66 @@ struct TrackRuntime {
           }
         }
 
+        // A delayed track reads the clip behind the transport and an early
+        // one reads ahead of it; the clip itself is left untouched. Inside a
+        // loop the shifted range wraps, so just after the loop start a
+        // delayed track plays the loop's tail and near the end an early
+        // track plays its head.
+        const int64_t timeOffset =
+            runtime.timeOffsetNanoticks.load(std::memory_order_acquire);
+        auto emitOffsetNotes = [&](uint64_t rangeStart,
+                                   uint64_t rangeEnd,
+                                   uint64_t baseTickDelta) {
+          if (timeOffset == 0) {
+            emitNotes(rangeStart, rangeEnd, baseTickDelta);
+            return;
+          }
+          if (loopLen > 0) {
+            const int64_t loopLenSigned = static_cast<int64_t>(loopLen);
+            int64_t wrapped =
+                (static_cast<int64_t>(rangeStart) -
+                 static_cast<int64_t>(loopStartTicks) - timeOffset) %
+                loopLenSigned;
+            if (wrapped < 0) {
+              wrapped += loopLenSigned;
+            }
+            const uint64_t shiftedStart =
+                loopStartTicks + static_cast<uint64_t>(wrapped);
+            const uint64_t rangeLen = rangeEnd - rangeStart;
+            if (shiftedStart + rangeLen <= loopEndTicks) {
+              emitNotes(shiftedStart, shiftedStart + rangeLen, baseTickDelta);
+            } else {
+              const uint64_t firstLen = loopEndTicks - shiftedStart;
+              emitNotes(shiftedStart, loopEndTicks, baseTickDelta);
+              emitNotes(loopStartTicks,
+                        loopStartTicks + (rangeLen - firstLen),
+                        baseTickDelta + firstLen);
+            }
+            return;
+          }
+          const int64_t shiftedStart =
+              static_cast<int64_t>(rangeStart) - timeOffset;
+          const int64_t shiftedEnd = static_cast<int64_t>(rangeEnd) - timeOffset;
+          if (shiftedEnd <= 0) {
+            return;
+          }
+          if (shiftedStart < 0) {
+            emitNotes(0, static_cast<uint64_t>(shiftedEnd),
+                      baseTickDelta + static_cast<uint64_t>(-shiftedStart));
+            return;
+          }
+          emitNotes(static_cast<uint64_t>(shiftedStart),
+                    static_cast<uint64_t>(shiftedEnd), baseTickDelta);
+        };
+
         if (loopLen == 0 || windowEndTicks <= loopEndTicks) {
-          emitNotes(windowStartTicks, windowEndTicks, 0);
+          emitOffsetNotes(windowStartTicks, windowEndTicks, 0);
         } else {
           const uint64_t firstLen = loopEndTicks - windowStartTicks;
-          emitNotes(windowStartTicks, loopEndTicks, 0);
-          emitNotes(loopStartTicks,
-                    loopStartTicks + (windowEndTicks - loopEndTicks),
-                    firstLen);
+          emitOffsetNotes(windowStartTicks, loopEndTicks, 0);
+          emitOffsetNotes(loopStartTicks,
+                          loopStartTicks + (windowEndTicks - loopEndTicks),
+                          firstLen);
         }
 
         auto applyModUpdates = [&]() {
@@ -5329,7 +5645,7 @@ struct TrackRuntime {
         transportEntry.type = static_cast<uint16_t>(daw::EventType::Transport);
         transportEntry.size = sizeof(daw::TransportPayload);
         daw::TransportPayload transportPayload;
-        transportPayload.tempoBpm = 120.0;
+        transportPayload.tempoBpm = tempoProvider.bpmAtNanotick(blockStartTicks);
         transportPayload.timeSigNum = 4;
         transportPayload.timeSigDen = 4;
         transportPayload.playState = isPlaying ? 1 : 0;
@@ -5872,6 +6188,10 @@ struct TrackRuntime {
         if (writeHarmony) {
           writeUiHarmonySnapshot();
         }
+        for (uint32_t ch = 0; ch < daw::kUiMasterMeterChannels; ++ch) {
+          uiShm.header->uiMasterPeak[ch] =
+              audioCallback ? audioCallback->takeMasterPeak(ch) : 0.0f;
+        }
         uiShm.header->uiVersion.fetch_add(1, std::memory_order_release);
       }
 
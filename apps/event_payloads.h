@@ -65,6 +65,12 @@ enum class UiCommandType : uint16_t {
   SetPatcherNodeConfig = 28,
   SavePatcherPreset = 29,
   RequestClipWindow = 30,
+  PreviewChord = 31,
+  SetCountIn = 32,
+  SetTempo = 33,
+  SetTrackArmed = 34,
+  PreviewHarmony = 35,
+  SetTrackTimeOffset = 36,
 };
 
 enum class UiDiffType : uint16_t {
@@ -28,12 +28,22 @@ struct PatcherEuclideanConfig {
   uint32_t steps = 16;
   uint32_t hits = 5;
   uint32_t offset = 0;
+  int32_t rotation = 0;  // signed, added to offset; negative rotates backwards
   uint64_t duration_ticks = 0;
   uint8_t degree = 1;
   int8_t octave_offset = 0;
   uint8_t velocity = 100;
   uint8_t base_octave = 4;
+  uint8_t distribution = 0;  // 0 = Bjorklund, 1 = modulo
+  uint8_t swing = 0;         // percent of a half step applied to odd steps, 0-100
+  uint64_t seed = 0;         // mixed into generative hashes; vary per track
+  uint16_t humanize_timing = 0;  // max timing jitter in nanoticks
+  uint8_t humanize_velocity = 0;
+  uint8_t accent_velocity = 0;   // used on steps whose accent_mask bit is set
+  uint8_t loop_advance = 0;      // extra steps of rotation per loop
+  uint8_t loop_random = 0;       // nonzero: seeded shift in 0..=loop_advance per loop
   uint8_t _pad0[2]{};
+  uint64_t accent_mask = 0;      // bit per raw pattern step; 0 disables accents
 };
 
 struct PatcherRandomDegreeConfig {
@@ -41,6 +51,7 @@ struct PatcherRandomDegreeConfig {
   uint8_t velocity = 100;
   uint8_t _pad0[2]{};
   uint64_t duration_ticks = 0;
+  uint64_t seed = 0;
 };
 
 struct PatcherLfoConfig {
@@ -92,9 +103,13 @@ struct alignas(64) PatcherContext {
 extern "C" void atomic_store_u64(uint64_t* ptr, uint64_t value);
 extern "C" void patcher_process(PatcherContext* ctx) DAW_WEAK;
 extern "C" void patcher_process_euclidean(PatcherContext* ctx) DAW_WEAK;
+extern "C" uint32_t patcher_euclidean_pattern(const PatcherEuclideanConfig* config,
+                                              uint8_t* out,
+                                              uint32_t capacity) DAW_WEAK;
 extern "C" void patcher_process_random_degree(PatcherContext* ctx) DAW_WEAK;
 extern "C" void patcher_process_event_out(PatcherContext* ctx) DAW_WEAK;
 extern "C" void patcher_process_lfo(PatcherContext* ctx) DAW_WEAK;
+extern "C" void patcher_process_harmony_arp(PatcherContext* ctx) DAW_WEAK;
 extern "C" void patcher_process_passthrough(PatcherContext* ctx) DAW_WEAK;
 extern "C" void patcher_process_audio_passthrough(PatcherContext* ctx) DAW_WEAK;
 #undef DAW_WEAK
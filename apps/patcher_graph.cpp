@@ -48,6 +48,7 @@ bool nodeAcceptsEventInput(PatcherNodeType type) {
     case PatcherNodeType::Euclidean:
     case PatcherNodeType::AudioPassthrough:
     case PatcherNodeType::Lfo:
+    case PatcherNodeType::HarmonyArp:
       return false;
   }
   return false;
@@ -59,6 +60,7 @@ bool nodeProvidesEventOutput(PatcherNodeType type) {
     case PatcherNodeType::Euclidean:
     case PatcherNodeType::Passthrough:
     case PatcherNodeType::RandomDegree:
+    case PatcherNodeType::HarmonyArp:
       return true;
     case PatcherNodeType::AudioPassthrough:
     case PatcherNodeType::Lfo:
@@ -113,6 +115,7 @@ const std::vector<PatcherPortDesc>& portsForNode(PatcherNodeType type) {
     case PatcherNodeType::RustKernel:
       return kRustPorts;
     case PatcherNodeType::Euclidean:
+    case PatcherNodeType::HarmonyArp:
       return kEuclideanPorts;
     case PatcherNodeType::Passthrough:
       return kPassthroughPorts;
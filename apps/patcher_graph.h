@@ -20,6 +20,7 @@ enum class PatcherNodeType : uint8_t {
   Lfo = 4,
   RandomDegree = 5,
   EventOut = 6,
+  HarmonyArp = 7,
 };
 
 enum class PatcherPortKind : uint8_t {
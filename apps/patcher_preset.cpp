@@ -35,6 +35,8 @@ const char* nodeTypeToString(PatcherNodeType type) {
       return "random_degree";
     case PatcherNodeType::EventOut:
       return "event_out";
+    case PatcherNodeType::HarmonyArp:
+      return "harmony_arp";
   }
   return "unknown";
 }
@@ -68,6 +70,10 @@ bool stringToNodeType(const std::string& value, PatcherNodeType& out) {
     out = PatcherNodeType::EventOut;
     return true;
   }
+  if (value == "harmony_arp") {
+    out = PatcherNodeType::HarmonyArp;
+    return true;
+  }
   return false;
 }
 
@@ -104,11 +110,21 @@ boost::property_tree::ptree serializeEuclidean(const PatcherEuclideanConfig& con
   node.put("steps", config.steps);
   node.put("hits", config.hits);
   node.put("offset", config.offset);
+  node.put("rotation", config.rotation);
   node.put("duration_ticks", config.duration_ticks);
   node.put("degree", config.degree);
   node.put("octave_offset", config.octave_offset);
   node.put("velocity", config.velocity);
   node.put("base_octave", config.base_octave);
+  node.put("distribution", config.distribution);
+  node.put("swing", config.swing);
+  node.put("seed", config.seed);
+  node.put("humanize_timing", config.humanize_timing);
+  node.put("humanize_velocity", config.humanize_velocity);
+  node.put("accent_mask", config.accent_mask);
+  node.put("accent_velocity", config.accent_velocity);
+  node.put("loop_advance", config.loop_advance);
+  node.put("loop_random", config.loop_random);
   return node;
 }
 
@@ -126,6 +142,7 @@ boost::property_tree::ptree serializeRandomDegree(const PatcherRandomDegreeConfi
   node.put("degree", config.degree);
   node.put("velocity", config.velocity);
   node.put("duration_ticks", config.duration_ticks);
+  node.put("seed", config.seed);
   return node;
 }
 
@@ -134,11 +151,21 @@ void deserializeEuclidean(const boost::property_tree::ptree& node,
   config.steps = node.get<uint32_t>("steps", config.steps);
   config.hits = node.get<uint32_t>("hits", config.hits);
   config.offset = node.get<uint32_t>("offset", config.offset);
+  config.rotation = node.get<int32_t>("rotation", config.rotation);
   config.duration_ticks = node.get<uint64_t>("duration_ticks", config.duration_ticks);
   config.degree = node.get<uint8_t>("degree", config.degree);
   config.octave_offset = node.get<int8_t>("octave_offset", config.octave_offset);
   config.velocity = node.get<uint8_t>("velocity", config.velocity);
   config.base_octave = node.get<uint8_t>("base_octave", config.base_octave);
+  config.distribution = node.get<uint8_t>("distribution", config.distribution);
+  config.swing = node.get<uint8_t>("swing", config.swing);
+  config.seed = node.get<uint64_t>("seed", config.seed);
+  config.humanize_timing = node.get<uint16_t>("humanize_timing", config.humanize_timing);
+  config.humanize_velocity = node.get<uint8_t>("humanize_velocity", config.humanize_velocity);
+  config.accent_mask = node.get<uint64_t>("accent_mask", config.accent_mask);
+  config.accent_velocity = node.get<uint8_t>("accent_velocity", config.accent_velocity);
+  config.loop_advance = node.get<uint8_t>("loop_advance", config.loop_advance);
+  config.loop_random = node.get<uint8_t>("loop_random", config.loop_random);
 }
 
 void deserializeLfo(const boost::property_tree::ptree& node,
@@ -154,6 +181,7 @@ void deserializeRandomDegree(const boost::property_tree::ptree& node,
   config.degree = node.get<uint8_t>("degree", config.degree);
   config.velocity = node.get<uint8_t>("velocity", config.velocity);
   config.duration_ticks = node.get<uint64_t>("duration_ticks", config.duration_ticks);
+  config.seed = node.get<uint64_t>("seed", config.seed);
 }
 
 }  // namespace
@@ -12,6 +12,7 @@ constexpr uint32_t kUiMaxTracks = 8;
 constexpr uint32_t kUiMaxClipNotes = 4096;
 constexpr uint32_t kUiMaxClipChords = 1024;
 constexpr uint32_t kUiMaxHarmonyEvents = 512;
+constexpr uint32_t kUiMasterMeterChannels = 2;
 
 struct alignas(64) ShmHeader {
   uint32_t magic = kShmMagic;
@@ -44,6 +45,9 @@ struct alignas(64) ShmHeader {
   uint64_t uiHarmonyOffset = 0;
   uint64_t uiHarmonyBytes = 0;
   float uiTrackPeakRms[kUiMaxTracks]{};
+  // Highest absolute master output sample per channel since the last UI
+  // snapshot; values above 1.0 mean the output clipped.
+  float uiMasterPeak[kUiMasterMeterChannels]{};
 };
 
 struct alignas(64) RingHeader {
@@ -1,5 +1,6 @@
 #pragma once
 
+#include <atomic>
 #include <cstdint>
 #include <cmath>
 
@@ -15,10 +16,14 @@ class StaticTempoProvider final : public ITempoProvider {
  public:
   explicit StaticTempoProvider(double bpm) : bpm_(bpm) {}
 
-  double bpmAtNanotick(uint64_t /*nanotick*/) const override { return bpm_; }
+  double bpmAtNanotick(uint64_t /*nanotick*/) const override {
+    return bpm_.load(std::memory_order_relaxed);
+  }
+
+  void setBpm(double bpm) { bpm_.store(bpm, std::memory_order_relaxed); }
 
  private:
-  double bpm_ = 120.0;
+  std::atomic<double> bpm_{120.0};
 };
 
 class NanotickConverter {
@@ -8,11 +8,17 @@ const DEFAULT_BPM: f64 = 120.0;
 const EUCLIDEAN_STEPS: u32 = 16;
 const EUCLIDEAN_HITS: u32 = 5;
 const EUCLIDEAN_OFFSET: u32 = 0;
-const EUCLIDEAN_DEGREE: u8 = 1;
-const EUCLIDEAN_OCTAVE_OFFSET: i8 = 0;
-const EUCLIDEAN_MAX_STEPS: usize = 64;
+pub const EUCLIDEAN_MAX_STEPS: usize = 64;
+pub const EUCLIDEAN_DISTRIBUTION_BJORKLUND: u8 = 0;
+const EUCLIDEAN_DISTRIBUTION_MODULO: u8 = 1;
+const EUCLIDEAN_MAX_SWING: u8 = 100;
 const MUSICAL_LOGIC_KIND_GATE: u8 = 1;
 const MUSICAL_LOGIC_KIND_DEGREE: u8 = 2;
+const HARMONY_ARP_STEPS_PER_BAR: u64 = 16;
+const HARMONY_ARP_VELOCITY: u8 = 100;
+const HARMONY_ARP_BASE_OCTAVE: u8 = 4;
+/// Every scale in the engine's library has seven degrees.
+const HARMONY_ARP_SCALE_DEGREES: u64 = 7;
 
 #[repr(C)]
 pub struct HarmonyEvent {
@@ -40,12 +46,22 @@ pub struct PatcherEuclideanConfig {
     pub steps: u32,
     pub hits: u32,
     pub offset: u32,
+    pub rotation: i32,
     pub duration_ticks: u64,
     pub degree: u8,
     pub octave_offset: i8,
     pub velocity: u8,
     pub base_octave: u8,
+    pub distribution: u8,
+    pub swing: u8,
+    pub seed: u64,
+    pub humanize_timing: u16,
+    pub humanize_velocity: u8,
+    pub accent_velocity: u8,
+    pub loop_advance: u8,
+    pub loop_random: u8,
     pub _pad0: [u8; 2],
+    pub accent_mask: u64,
 }
 
 #[repr(C)]
@@ -62,6 +78,7 @@ pub struct PatcherRandomDegreeConfig {
     pub velocity: u8,
     pub _pad0: [u8; 2],
     pub duration_ticks: u64,
+    pub seed: u64,
 }
 
 #[repr(C, align(64))]
@@ -134,21 +151,150 @@ fn euclidean_hit(step_index: u32, hits: u32, steps: u32) -> bool {
     (step_index * hits) % steps < hits
 }
 
+/// Net rotation in steps, wrapped into `0..steps` so negative rotations shift
+/// the pattern the other way.
+fn euclidean_rotation_steps(offset: u32, rotation: i32, steps: u32) -> u64 {
+    if steps == 0 {
+        return 0;
+    }
+    (offset as i64 + rotation as i64).rem_euclid(steps as i64) as u64
+}
+
+/// Extra steps the pattern is rotated by in the loop containing `tick`. A fixed
+/// `advance` accumulates every loop; with `random` set each loop instead draws
+/// its own seeded shift in `0..=advance`, so playback is repeatable.
+fn euclidean_loop_shift(
+    tick: u64,
+    loop_ticks: u64,
+    steps: u32,
+    advance: u8,
+    random: bool,
+    seed: u64,
+) -> u64 {
+    if advance == 0 || steps == 0 || loop_ticks == 0 {
+        return 0;
+    }
+    let loop_index = tick / loop_ticks;
+    let shift = if random {
+        seeded_hash(seed, !loop_index) % (advance as u64 + 1)
+    } else {
+        loop_index.wrapping_mul(advance as u64)
+    };
+    shift % steps as u64
+}
+
+/// Delays odd steps of the loop by `swing` percent of half a step, so 100
+/// lands them halfway to the next step. `loop_step` counts from the loop
+/// start, so odd step counts swing the same steps on every pass. The delay is
+/// clamped to stay inside the step's own window so a swung event never lands
+/// on or past the next step, keeping emitted sample times in order for
+/// `push_event`.
+fn swung_tick(step_tick: u64, loop_step: u64, step_ticks: u64, swing: u8) -> u64 {
+    if swing == 0 || step_ticks == 0 || loop_step.is_multiple_of(2) {
+        return step_tick;
+    }
+    let swing = swing.min(EUCLIDEAN_MAX_SWING) as u64;
+    let delay = (step_ticks * swing / 200).min(step_ticks - 1);
+    step_tick + delay
+}
+
+/// Signed jitter in `-range..=range` drawn from `hash`.
+fn humanize_jitter(hash: u64, range: u64) -> i64 {
+    if range == 0 {
+        return 0;
+    }
+    (hash % (range * 2 + 1)) as i64 - range as i64
+}
+
+/// Applies timing jitter to `event_tick`, clamped to the window of the step
+/// starting at `step_tick` so humanized hits never cross into the next step.
+fn humanized_tick(step_tick: u64, event_tick: u64, step_ticks: u64, range: u16, hash: u64) -> u64 {
+    if range == 0 || step_ticks == 0 {
+        return event_tick;
+    }
+    let jittered = event_tick as i64 + humanize_jitter(hash, range as u64);
+    jittered.clamp(step_tick as i64, (step_tick + step_ticks - 1) as i64) as u64
+}
+
+/// Jittered velocity around `velocity`, kept in 1..=127. A zero range returns
+/// 0 so un-humanized gates keep deferring to the downstream default.
+fn humanized_velocity(velocity: u8, range: u8, hash: u64) -> u8 {
+    if range == 0 {
+        return 0;
+    }
+    let base = if velocity == 0 { 100 } else { velocity } as i64;
+    (base + humanize_jitter(hash, range as u64)).clamp(1, 127) as u8
+}
+
+fn modulo_fill(steps: u32, hits: u32, pattern: &mut [u8]) {
+    for (index, slot) in pattern.iter_mut().enumerate() {
+        *slot = euclidean_hit(index as u32, hits, steps) as u8;
+    }
+}
+
+/// Fills `pattern` with the selected distribution; anything other than
+/// `EUCLIDEAN_DISTRIBUTION_MODULO` falls back to Bjorklund.
+fn euclidean_pattern(
+    distribution: u8,
+    steps: u32,
+    hits: u32,
+    pattern: &mut [u8; EUCLIDEAN_MAX_STEPS],
+) {
+    if distribution == EUCLIDEAN_DISTRIBUTION_MODULO {
+        pattern.fill(0);
+        let len = (steps as usize).min(EUCLIDEAN_MAX_STEPS);
+        modulo_fill(steps, hits, &mut pattern[..len]);
+    } else {
+        bjorklund_pattern(steps, hits, pattern);
+    }
+}
+
+fn euclidean_pattern_heap(distribution: u8, steps: u32, hits: u32) -> Vec<u8> {
+    if distribution == EUCLIDEAN_DISTRIBUTION_MODULO {
+        let mut pattern = vec![0u8; steps as usize];
+        modulo_fill(steps, hits, &mut pattern);
+        pattern
+    } else {
+        bjorklund_pattern_heap(steps, hits)
+    }
+}
+
 fn bjorklund_pattern(steps: u32, hits: u32, pattern: &mut [u8; EUCLIDEAN_MAX_STEPS]) {
+    let mut counts = [0usize; EUCLIDEAN_MAX_STEPS];
+    let mut remainders = [0usize; EUCLIDEAN_MAX_STEPS];
+    bjorklund_fill(steps, hits, pattern, &mut counts, &mut remainders);
+}
+
+/// Heap-backed Bjorklund for step counts above `EUCLIDEAN_MAX_STEPS`, so long
+/// patterns use the same distribution as the fixed-size path.
+fn bjorklund_pattern_heap(steps: u32, hits: u32) -> Vec<u8> {
+    let len = steps as usize;
+    let mut pattern = vec![0u8; len];
+    let mut counts = vec![0usize; len.max(1)];
+    let mut remainders = vec![0usize; len.max(1)];
+    bjorklund_fill(steps, hits, &mut pattern, &mut counts, &mut remainders);
+    pattern
+}
+
+fn bjorklund_fill(
+    steps: u32,
+    hits: u32,
+    pattern: &mut [u8],
+    counts: &mut [usize],
+    remainders: &mut [usize],
+) {
     for slot in pattern.iter_mut() {
         *slot = 0;
     }
     if steps == 0 || hits == 0 {
         return;
     }
-    let steps_usize = steps as usize;
+    let steps_usize = (steps as usize).min(pattern.len());
     let hits_usize = hits.min(steps) as usize;
     if hits_usize == 0 || steps_usize == 0 {
         return;
     }
 
-    let mut counts = [0usize; EUCLIDEAN_MAX_STEPS];
-    let mut remainders = [0usize; EUCLIDEAN_MAX_STEPS];
     remainders[0] = hits_usize;
     let mut divisor = steps_usize - hits_usize;
     let mut level = 0usize;
@@ -167,7 +313,7 @@ fn bjorklund_pattern(steps: u32, hits: u32, pattern: &mut [u8; EUCLIDEAN_MAX_STE
         level: isize,
         counts: &[usize],
         remainders: &[usize],
-        out: &mut [u8; EUCLIDEAN_MAX_STEPS],
+        out: &mut [u8],
         out_index: &mut usize,
         max_len: usize,
     ) {
@@ -197,14 +343,36 @@ fn bjorklund_pattern(steps: u32, hits: u32, pattern: &mut [u8; EUCLIDEAN_MAX_STE
     let mut out_index = 0usize;
     build(
         level as isize,
-        &counts,
-        &remainders,
+        counts,
+        remainders,
         pattern,
         &mut out_index,
         steps_usize,
     );
 }
 
+/// Harmony event in effect at `tick`: the last one starting at or before it.
+fn active_harmony(events: &[HarmonyEvent], tick: u64) -> Option<&HarmonyEvent> {
+    let index = events.partition_point(|event| event.nanotick <= tick);
+    if index == 0 {
+        return None;
+    }
+    events.get(index - 1)
+}
+
+/// Scale degree (1-based) for a rising figure that restarts on each harmony
+/// change, so the arp follows chord boundaries rather than the bar grid.
+fn harmony_arp_degree(harmony: &HarmonyEvent, tick: u64, step_ticks: u64) -> u8 {
+    let steps_since_change = tick.saturating_sub(harmony.nanotick) / step_ticks.max(1);
+    (steps_since_change % HARMONY_ARP_SCALE_DEGREES) as u8 + 1
+}
+
+/// Mixes a node seed into a position hash so nodes with different seeds vary
+/// independently while each stays reproducible; seed 0 leaves `value` as is.
+fn seeded_hash(seed: u64, value: u64) -> u64 {
+    mix64(value ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+}
+
 fn mix64(mut x: u64) -> u64 {
     // SplitMix64 finalizer for stable, deterministic hashing.
     x ^= x >> 30;
@@ -246,7 +414,18 @@ pub extern "C" fn patcher_process_euclidean(ctx: *mut PatcherContext) {
         let mut steps = EUCLIDEAN_STEPS;
         let mut hits = EUCLIDEAN_HITS;
         let mut offset = EUCLIDEAN_OFFSET;
+        let mut rotation = 0i32;
         let mut duration_ticks = 0u64;
+        let mut distribution = EUCLIDEAN_DISTRIBUTION_BJORKLUND;
+        let mut swing = 0u8;
+        let mut seed = 0u64;
+        let mut velocity = 0u8;
+        let mut humanize_timing = 0u16;
+        let mut humanize_velocity = 0u8;
+        let mut accent_mask = 0u64;
+        let mut accent_velocity = 0u8;
+        let mut loop_advance = 0u8;
+        let mut loop_random = false;
         if !ctx_ref.node_config.is_null()
             && ctx_ref.node_config_size as usize >= core::mem::size_of::<PatcherEuclideanConfig>()
         {
@@ -254,7 +433,18 @@ pub extern "C" fn patcher_process_euclidean(ctx: *mut PatcherContext) {
             steps = if config.steps == 0 { steps } else { config.steps };
             hits = if config.hits == 0 { hits } else { config.hits };
             offset = config.offset;
+            rotation = config.rotation;
             duration_ticks = config.duration_ticks;
+            distribution = config.distribution;
+            swing = config.swing;
+            seed = config.seed;
+            velocity = config.velocity;
+            humanize_timing = config.humanize_timing;
+            humanize_velocity = config.humanize_velocity;
+            accent_mask = config.accent_mask;
+            accent_velocity = config.accent_velocity;
+            loop_advance = config.loop_advance;
+            loop_random = config.loop_random != 0;
         }
 
         let loop_ticks = NANOTICKS_PER_QUARTER * 4;
@@ -263,7 +453,7 @@ pub extern "C" fn patcher_process_euclidean(ctx: *mut PatcherContext) {
             return;
         }
 
-        let offset_ticks = (offset as u64) * step_ticks;
+        let offset_ticks = euclidean_rotation_steps(offset, rotation, steps) * step_ticks;
         let tempo_bpm = if ctx_ref.tempo_bpm > 0.0 {
             ctx_ref.tempo_bpm as f64
         } else {
@@ -272,28 +462,68 @@ pub extern "C" fn patcher_process_euclidean(ctx: *mut PatcherContext) {
         let samples_per_tick =
             (ctx_ref.sample_rate as f64 * 60.0) / (tempo_bpm * NANOTICKS_PER_QUARTER as f64);
         let block_start_sample = ctx_ref.block_start_sample;
+        let block_samples = ((ctx_ref.block_end_tick - ctx_ref.block_start_tick) as f64
+            * samples_per_tick)
+            .round() as u64;
 
         let mut pattern: [u8; EUCLIDEAN_MAX_STEPS] = [0u8; EUCLIDEAN_MAX_STEPS];
-        if steps as usize <= EUCLIDEAN_MAX_STEPS {
-            bjorklund_pattern(steps, hits, &mut pattern);
-        }
+        let large_pattern = if steps as usize <= EUCLIDEAN_MAX_STEPS {
+            euclidean_pattern(distribution, steps, hits, &mut pattern);
+            Vec::new()
+        } else {
+            euclidean_pattern_heap(distribution, steps, hits)
+        };
+        let pattern: &[u8] = if steps as usize <= EUCLIDEAN_MAX_STEPS {
+            &pattern[..steps as usize]
+        } else {
+            &large_pattern
+        };
 
-        let mut tick = ctx_ref.block_start_tick;
+        // With swing or timing humanize, the previous step may have been
+        // delayed into this block.
+        let mut tick = if swing == 0 && humanize_timing == 0 {
+            ctx_ref.block_start_tick
+        } else {
+            ctx_ref.block_start_tick.saturating_sub(step_ticks)
+        };
         let remainder = (tick + offset_ticks) % step_ticks;
         if remainder != 0 {
             tick = tick.saturating_add(step_ticks - remainder);
         }
 
         while tick < ctx_ref.block_end_tick {
-            let step_index = ((tick + offset_ticks) % loop_ticks) / step_ticks;
-            let hit = if steps as usize <= EUCLIDEAN_MAX_STEPS {
-                pattern[step_index as usize] != 0
-            } else {
-                euclidean_hit(step_index as u32, hits, steps)
-            };
+            let loop_shift =
+                euclidean_loop_shift(tick, loop_ticks, steps, loop_advance, loop_random, seed);
+            let loop_step = ((tick + offset_ticks) % loop_ticks) / step_ticks;
+            let step_index = (loop_step + loop_shift) % steps as u64;
+            let jitter_hash = seeded_hash(seed, tick);
+            let event_tick = humanized_tick(
+                tick,
+                swung_tick(tick, loop_step, step_ticks, swing),
+                step_ticks,
+                humanize_timing,
+                jitter_hash,
+            );
+            let in_block =
+                event_tick >= ctx_ref.block_start_tick && event_tick < ctx_ref.block_end_tick;
+            let hit = in_block && pattern[step_index as usize] != 0;
             if hit {
-                let tick_delta = tick - ctx_ref.block_start_tick;
-                let sample_delta = (tick_delta as f64 * samples_per_tick).round() as u64;
+                let tick_delta = event_tick - ctx_ref.block_start_tick;
+                // A step swung to the last tick can round onto the next block's
+                // first sample; keep it inside this block.
+                let sample_delta = ((tick_delta as f64 * samples_per_tick).round() as u64)
+                    .min(block_samples.saturating_sub(1));
+                // Accents follow the raw pattern index, so they rotate with
+                // the hits. Without a mask gates keep the humanize-only
+                // velocity and defer to the downstream default.
+                let accented =
+                    step_index < u64::BITS as u64 && (accent_mask >> step_index) & 1 != 0;
+                let gate_velocity = if accented { accent_velocity } else { velocity };
+                let gate_velocity = if accent_mask == 0 || humanize_velocity != 0 {
+                    humanized_velocity(gate_velocity, humanize_velocity, mix64(jitter_hash))
+                } else {
+                    gate_velocity
+                };
                 let mut entry = EventEntry {
                     sample_time: block_start_sample + sample_delta,
                     block_id: 0,
@@ -313,7 +543,7 @@ pub extern "C" fn patcher_process_euclidean(ctx: *mut PatcherContext) {
                         duration_ticks
                     },
                     priority_hint: 0,
-                    velocity: 0,
+                    velocity: gate_velocity,
                     base_octave: 0,
                     metadata: {
                         let mut data = [0u8; 21];
@@ -327,13 +557,53 @@ pub extern "C" fn patcher_process_euclidean(ctx: *mut PatcherContext) {
                     entry.payload.as_mut_ptr(),
                     payload_bytes,
                 );
-                push_event(ctx_ref, entry, tick);
+                push_event(ctx_ref, entry, event_tick);
             }
             tick = tick.saturating_add(step_ticks);
         }
     }
 }
 
+/// Writes the step pattern a Euclidean node plays over one bar into `out`, one
+/// byte per step (1 = hit), with offset and rotation applied so index 0 is the
+/// bar's first step. A null `config` reports the node defaults. Returns the
+/// full step count, which may exceed `capacity`; only `capacity` bytes are
+/// written.
+///
+/// # Safety
+/// `config` must be null or point to a valid `PatcherEuclideanConfig`, and
+/// `out` must be null or valid for `capacity` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn patcher_euclidean_pattern(
+    config: *const PatcherEuclideanConfig,
+    out: *mut u8,
+    capacity: u32,
+) -> u32 {
+    let mut steps = EUCLIDEAN_STEPS;
+    let mut hits = EUCLIDEAN_HITS;
+    let mut offset = EUCLIDEAN_OFFSET;
+    let mut rotation = 0i32;
+    let mut distribution = EUCLIDEAN_DISTRIBUTION_BJORKLUND;
+    if !config.is_null() {
+        let config = &*config;
+        steps = if config.steps == 0 { steps } else { config.steps };
+        hits = if config.hits == 0 { hits } else { config.hits };
+        offset = config.offset;
+        rotation = config.rotation;
+        distribution = config.distribution;
+    }
+    if out.is_null() || capacity == 0 {
+        return steps;
+    }
+    let pattern = euclidean_pattern_heap(distribution, steps, hits);
+    let shift = euclidean_rotation_steps(offset, rotation, steps);
+    let out = core::slice::from_raw_parts_mut(out, capacity.min(steps) as usize);
+    for (step, slot) in out.iter_mut().enumerate() {
+        *slot = pattern[((step as u64 + shift) % steps as u64) as usize];
+    }
+    steps
+}
+
 #[no_mangle]
 pub extern "C" fn patcher_process_random_degree(ctx: *mut PatcherContext) {
     if ctx.is_null() {
@@ -352,6 +622,7 @@ pub extern "C" fn patcher_process_random_degree(ctx: *mut PatcherContext) {
             velocity: 100,
             _pad0: [0u8; 2],
             duration_ticks: 0,
+            seed: 0,
         };
         if !ctx_ref.node_config.is_null()
             && ctx_ref.node_config_size as usize >= core::mem::size_of::<PatcherRandomDegreeConfig>()
@@ -366,6 +637,7 @@ pub extern "C" fn patcher_process_random_degree(ctx: *mut PatcherContext) {
             if cfg.duration_ticks != 0 {
                 config.duration_ticks = cfg.duration_ticks;
             }
+            config.seed = cfg.seed;
         }
         let degree_max = config.degree.max(1);
         let count = *ctx_ref.event_count;
@@ -396,7 +668,7 @@ pub extern "C" fn patcher_process_random_degree(ctx: *mut PatcherContext) {
             let seed = (ctx_ref.block_start_tick as u64)
                 ^ (entry.sample_time as u64)
                 ^ (index as u64).wrapping_mul(0x9e37_79b9);
-            let random = (mix64(seed) % degree_max as u64) as u8;
+            let random = (seeded_hash(config.seed, seed) % degree_max as u64) as u8;
             payload.degree = random.saturating_add(1);
             payload.velocity = if config.velocity != 0 {
                 config.velocity
@@ -421,6 +693,89 @@ pub extern "C" fn patcher_process_random_degree(ctx: *mut PatcherContext) {
     }
 }
 
+/// # Safety
+/// `ctx` must be null or point to a valid `PatcherContext` whose buffer and
+/// harmony snapshot pointers are valid for their stated counts.
+#[no_mangle]
+pub unsafe extern "C" fn patcher_process_harmony_arp(ctx: *mut PatcherContext) {
+    if ctx.is_null() {
+        return;
+    }
+    unsafe {
+        let ctx_ref = &mut *ctx;
+        if ctx_ref.abi_version != PATCHER_ABI_VERSION {
+            return;
+        }
+        if ctx_ref.event_buffer.is_null() || ctx_ref.event_count.is_null() {
+            return;
+        }
+        let fallback = HarmonyEvent {
+            nanotick: 0,
+            root: 0,
+            scale_id: 1,
+            flags: 0,
+        };
+        let events: &[HarmonyEvent] =
+            if ctx_ref.harmony_snapshot.is_null() || ctx_ref.harmony_count == 0 {
+                &[]
+            } else {
+                core::slice::from_raw_parts(ctx_ref.harmony_snapshot, ctx_ref.harmony_count as usize)
+            };
+
+        let step_ticks = (NANOTICKS_PER_QUARTER * 4) / HARMONY_ARP_STEPS_PER_BAR;
+        let tempo_bpm = if ctx_ref.tempo_bpm > 0.0 {
+            ctx_ref.tempo_bpm as f64
+        } else {
+            DEFAULT_BPM
+        };
+        let samples_per_tick =
+            (ctx_ref.sample_rate as f64 * 60.0) / (tempo_bpm * NANOTICKS_PER_QUARTER as f64);
+
+        let mut tick = ctx_ref.block_start_tick;
+        let remainder = tick % step_ticks;
+        if remainder != 0 {
+            tick = tick.saturating_add(step_ticks - remainder);
+        }
+        while tick < ctx_ref.block_end_tick {
+            // Look the harmony up per step so a change mid-block takes effect
+            // on its own step.
+            let harmony = active_harmony(events, tick).unwrap_or(&fallback);
+            let tick_delta = tick - ctx_ref.block_start_tick;
+            let sample_delta = (tick_delta as f64 * samples_per_tick).round() as u64;
+            let mut entry = EventEntry {
+                sample_time: ctx_ref.block_start_sample + sample_delta,
+                block_id: 0,
+                type_: 9,
+                size: core::mem::size_of::<MusicalLogicPayload>() as u16,
+                flags: 0,
+                payload: [0u8; 40],
+            };
+            let payload = MusicalLogicPayload {
+                degree: harmony_arp_degree(harmony, tick, step_ticks),
+                octave_offset: 0,
+                _pad0: [0u8; 2],
+                chord_id: 0,
+                duration_ticks: step_ticks / 2,
+                priority_hint: 0,
+                velocity: HARMONY_ARP_VELOCITY,
+                base_octave: HARMONY_ARP_BASE_OCTAVE,
+                metadata: {
+                    let mut data = [0u8; 21];
+                    data[0] = MUSICAL_LOGIC_KIND_DEGREE;
+                    data
+                },
+            };
+            core::ptr::copy_nonoverlapping(
+                &payload as *const MusicalLogicPayload as *const u8,
+                entry.payload.as_mut_ptr(),
+                core::mem::size_of::<MusicalLogicPayload>(),
+            );
+            push_event(ctx_ref, entry, tick);
+            tick = tick.saturating_add(step_ticks);
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn patcher_process_lfo(ctx: *mut PatcherContext) {
     if ctx.is_null() {
@@ -574,6 +929,13 @@ pub extern "C" fn patcher_process_audio_passthrough(ctx: *mut PatcherContext) {
 mod tests {
     use super::*;
 
+    // The engine provides this symbol; tests that push events link against
+    // a plain store instead.
+    #[no_mangle]
+    extern "C" fn atomic_store_u64(ptr: *mut u64, value: u64) {
+        unsafe { *ptr = value };
+    }
+
     #[test]
     fn euclidean_hit_distribution() {
         let steps = 8;
@@ -586,4 +948,580 @@ mod tests {
         }
         assert_eq!(count, hits);
     }
+
+    fn onset_gaps(pattern: &[u8]) -> Vec<usize> {
+        let onsets: Vec<usize> = pattern
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != 0)
+            .map(|(index, _)| index)
+            .collect();
+        onsets
+            .iter()
+            .enumerate()
+            .map(|(i, start)| {
+                let next = onsets
+                    .get(i + 1)
+                    .copied()
+                    .unwrap_or(onsets[0] + pattern.len());
+                next - start
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bjorklund_heap_path_matches_fixed_path() {
+        for hits in [1, 5, 13, 31, 63] {
+            let mut fixed = [0u8; EUCLIDEAN_MAX_STEPS];
+            bjorklund_pattern(64, hits, &mut fixed);
+            let heap = bjorklund_pattern_heap(64, hits);
+            assert_eq!(&fixed[..], &heap[..], "steps=64 hits={hits}");
+        }
+        for hits in [1, 5, 13, 31, 64] {
+            let heap = bjorklund_pattern_heap(65, hits);
+            assert_eq!(heap.len(), 65);
+            assert_eq!(heap.iter().filter(|value| **value != 0).count(), hits as usize);
+            let gaps = onset_gaps(&heap);
+            let min_gap = *gaps.iter().min().unwrap();
+            let max_gap = *gaps.iter().max().unwrap();
+            assert!(max_gap - min_gap <= 1, "steps=65 hits={hits} gaps={gaps:?}");
+        }
+    }
+
+    #[test]
+    fn euclidean_distribution_selector() {
+        let mut bjorklund = [0u8; EUCLIDEAN_MAX_STEPS];
+        bjorklund_pattern(8, 3, &mut bjorklund);
+        let modulo: Vec<u8> = (0..8).map(|i| euclidean_hit(i, 3, 8) as u8).collect();
+        assert_ne!(&bjorklund[..8], &modulo[..]);
+
+        let mut selected = [0u8; EUCLIDEAN_MAX_STEPS];
+        euclidean_pattern(EUCLIDEAN_DISTRIBUTION_BJORKLUND, 8, 3, &mut selected);
+        assert_eq!(&selected[..8], &bjorklund[..8]);
+        euclidean_pattern(EUCLIDEAN_DISTRIBUTION_MODULO, 8, 3, &mut selected);
+        assert_eq!(&selected[..8], &modulo[..]);
+        assert!(selected[8..].iter().all(|value| *value == 0));
+
+        assert_eq!(
+            euclidean_pattern_heap(EUCLIDEAN_DISTRIBUTION_MODULO, 8, 3),
+            modulo
+        );
+        assert_eq!(
+            euclidean_pattern_heap(EUCLIDEAN_DISTRIBUTION_BJORKLUND, 8, 3),
+            &bjorklund[..8]
+        );
+    }
+
+    #[test]
+    fn euclidean_negative_rotation_inverts_positive() {
+        let steps = 16;
+        assert_eq!(euclidean_rotation_steps(0, -1, steps), 15);
+        assert_eq!(euclidean_rotation_steps(3, -5, steps), 14);
+        assert_eq!(euclidean_rotation_steps(0, -33, steps), 15);
+
+        let mut pattern = [0u8; EUCLIDEAN_MAX_STEPS];
+        bjorklund_pattern(steps, 5, &mut pattern);
+        let rotate = |source: &[u8], rotation: i32| -> Vec<u8> {
+            let shift = euclidean_rotation_steps(0, rotation, steps);
+            (0..steps as u64)
+                .map(|step| source[((step + shift) % steps as u64) as usize])
+                .collect()
+        };
+        let forward = rotate(&pattern[..steps as usize], 1);
+        assert_ne!(&forward[..], &pattern[..steps as usize]);
+        let restored = rotate(&forward, -1);
+        assert_eq!(&restored[..], &pattern[..steps as usize]);
+    }
+
+    #[test]
+    fn euclidean_rotation_rotates_onset_set() {
+        let onsets = |pattern: &[u8]| -> Vec<usize> {
+            let mut onsets: Vec<usize> = pattern
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| **value != 0)
+                .map(|(index, _)| index)
+                .collect();
+            onsets.sort_unstable();
+            onsets
+        };
+        let mut base = [0u8; EUCLIDEAN_MAX_STEPS];
+        bjorklund_pattern(8, 3, &mut base);
+        let mut expected: Vec<usize> = onsets(&base[..8])
+            .into_iter()
+            .map(|index| (index + 8 - 1) % 8)
+            .collect();
+        expected.sort_unstable();
+
+        let mut config = euclidean_config();
+        config.steps = 8;
+        config.hits = 3;
+        for rotation in [1, 9, -7] {
+            config.rotation = rotation;
+            let mut out = [0u8; 8];
+            unsafe { patcher_euclidean_pattern(&config, out.as_mut_ptr(), 8) };
+            assert_eq!(onsets(&out), expected, "rotation={rotation}");
+        }
+    }
+
+    #[test]
+    fn euclidean_pattern_export_applies_rotation() {
+        let mut config = euclidean_config();
+        config.steps = 8;
+        config.hits = 3;
+        config.rotation = 1;
+        let mut base = [0u8; EUCLIDEAN_MAX_STEPS];
+        bjorklund_pattern(8, 3, &mut base);
+
+        let mut out = [0u8; 8];
+        let steps = unsafe { patcher_euclidean_pattern(&config, out.as_mut_ptr(), 8) };
+        assert_eq!(steps, 8);
+        let expected: Vec<u8> = (0..8).map(|step| base[(step + 1) % 8]).collect();
+        assert_eq!(&out[..], &expected[..]);
+
+        let mut short = [0u8; 4];
+        let steps = unsafe { patcher_euclidean_pattern(&config, short.as_mut_ptr(), 4) };
+        assert_eq!(steps, 8);
+        assert_eq!(&short[..], &expected[..4]);
+
+        let steps = unsafe { patcher_euclidean_pattern(core::ptr::null(), core::ptr::null_mut(), 0) };
+        assert_eq!(steps, EUCLIDEAN_STEPS);
+    }
+
+    #[test]
+    fn harmony_arp_follows_active_harmony() {
+        let bar = NANOTICKS_PER_QUARTER * 4;
+        let harmony = [
+            HarmonyEvent { nanotick: 0, root: 0, scale_id: 1, flags: 0 },
+            HarmonyEvent { nanotick: bar, root: 2, scale_id: 3, flags: 0 },
+            HarmonyEvent { nanotick: bar * 2, root: 9, scale_id: 2, flags: 0 },
+        ];
+        assert!(active_harmony(&harmony[1..], 0).is_none());
+        assert_eq!(active_harmony(&harmony, bar - 1).unwrap().root, 0);
+        assert_eq!(active_harmony(&harmony, bar).unwrap().root, 2);
+        assert_eq!(active_harmony(&harmony, bar * 5).unwrap().root, 9);
+
+        // Half a bar from the second change, then a block that straddles the
+        // third: the figure restarts on the step where the chord changes.
+        let quarter = NANOTICKS_PER_QUARTER;
+        assert_eq!(
+            harmony_arp_degrees(&harmony, bar, bar + quarter * 2),
+            vec![1, 2, 3, 4, 5, 6, 7, 1]
+        );
+        assert_eq!(
+            harmony_arp_degrees(&harmony, bar * 2 - quarter, bar * 2 + quarter),
+            vec![6, 7, 1, 2, 1, 2, 3, 4]
+        );
+    }
+
+    /// Runs the harmony arp over one block and returns the degrees it emits.
+    fn harmony_arp_degrees(harmony: &[HarmonyEvent], start_tick: u64, end_tick: u64) -> Vec<u8> {
+        let mut events: Vec<EventEntry> = (0..32)
+            .map(|_| EventEntry {
+                sample_time: 0,
+                block_id: 0,
+                type_: 0,
+                size: 0,
+                flags: 0,
+                payload: [0u8; 40],
+            })
+            .collect();
+        let mut event_count = 0u32;
+        let mut ctx = PatcherContext {
+            abi_version: PATCHER_ABI_VERSION,
+            block_start_tick: start_tick,
+            block_end_tick: end_tick,
+            block_start_sample: 0,
+            sample_rate: 48_000.0,
+            tempo_bpm: 120.0,
+            num_frames: 0,
+            event_buffer: events.as_mut_ptr(),
+            event_capacity: events.len() as u32,
+            event_count: &mut event_count,
+            last_overflow_tick: core::ptr::null_mut(),
+            audio_channels: core::ptr::null_mut(),
+            num_channels: 0,
+            node_config: core::ptr::null(),
+            node_config_size: 0,
+            harmony_snapshot: harmony.as_ptr(),
+            harmony_count: harmony.len() as u32,
+            mod_outputs: core::ptr::null_mut(),
+            mod_output_count: 0,
+            mod_output_samples: core::ptr::null_mut(),
+            mod_output_stride: 0,
+            mod_inputs: core::ptr::null_mut(),
+            mod_input_count: 0,
+            mod_input_stride: 0,
+        };
+        unsafe { patcher_process_harmony_arp(&mut ctx) };
+        events[..event_count as usize]
+            .iter()
+            .map(|entry| {
+                let payload = unsafe {
+                    core::ptr::read_unaligned(entry.payload.as_ptr() as *const MusicalLogicPayload)
+                };
+                assert_eq!(payload.metadata[0], MUSICAL_LOGIC_KIND_DEGREE);
+                payload.degree
+            })
+            .collect()
+    }
+
+    fn euclidean_config() -> PatcherEuclideanConfig {
+        PatcherEuclideanConfig {
+            steps: 16,
+            hits: 16,
+            offset: 0,
+            rotation: 0,
+            duration_ticks: 0,
+            degree: 1,
+            octave_offset: 0,
+            velocity: 100,
+            base_octave: 4,
+            distribution: EUCLIDEAN_DISTRIBUTION_BJORKLUND,
+            swing: 0,
+            seed: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            accent_velocity: 0,
+            loop_advance: 0,
+            loop_random: 0,
+            _pad0: [0u8; 2],
+            accent_mask: 0,
+        }
+    }
+
+    /// Runs the Euclidean node over `blocks` 256-frame blocks at 120 bpm and
+    /// 48 kHz (deliberately not step aligned), returning sample times and
+    /// payloads in emission order.
+    fn euclidean_events(
+        config: &PatcherEuclideanConfig,
+        blocks: u64,
+    ) -> Vec<(u64, MusicalLogicPayload)> {
+        euclidean_events_at(config, blocks, 120.0)
+    }
+
+    /// Like `euclidean_events`, with the context reporting `tempo_bpm`. Block
+    /// ticks are derived from 120 bpm when the tempo is unset, matching the
+    /// node's fallback.
+    fn euclidean_events_at(
+        config: &PatcherEuclideanConfig,
+        blocks: u64,
+        tempo_bpm: f32,
+    ) -> Vec<(u64, MusicalLogicPayload)> {
+        let bpm = if tempo_bpm > 0.0 { tempo_bpm as u64 } else { 120 };
+        let block_ticks = 256 * NANOTICKS_PER_QUARTER * bpm / (60 * 48_000);
+        let mut out = Vec::new();
+        for block in 0..blocks {
+            let mut events: Vec<EventEntry> = (0..8)
+                .map(|_| EventEntry {
+                    sample_time: 0,
+                    block_id: 0,
+                    type_: 0,
+                    size: 0,
+                    flags: 0,
+                    payload: [0u8; 40],
+                })
+                .collect();
+            let mut event_count = 0u32;
+            let mut ctx = PatcherContext {
+                abi_version: PATCHER_ABI_VERSION,
+                block_start_tick: block * block_ticks,
+                block_end_tick: (block + 1) * block_ticks,
+                block_start_sample: block * 256,
+                sample_rate: 48_000.0,
+                tempo_bpm,
+                num_frames: 256,
+                event_buffer: events.as_mut_ptr(),
+                event_capacity: events.len() as u32,
+                event_count: &mut event_count,
+                last_overflow_tick: core::ptr::null_mut(),
+                audio_channels: core::ptr::null_mut(),
+                num_channels: 0,
+                node_config: config as *const PatcherEuclideanConfig as *const c_void,
+                node_config_size: core::mem::size_of::<PatcherEuclideanConfig>() as u32,
+                harmony_snapshot: core::ptr::null(),
+                harmony_count: 0,
+                mod_outputs: core::ptr::null_mut(),
+                mod_output_count: 0,
+                mod_output_samples: core::ptr::null_mut(),
+                mod_output_stride: 0,
+                mod_inputs: core::ptr::null_mut(),
+                mod_input_count: 0,
+                mod_input_stride: 0,
+            };
+            patcher_process_euclidean(&mut ctx);
+            for entry in &events[..event_count as usize] {
+                assert!(entry.sample_time >= block * 256);
+                assert!(entry.sample_time < (block + 1) * 256);
+                let payload = unsafe {
+                    core::ptr::read_unaligned(
+                        entry.payload.as_ptr() as *const MusicalLogicPayload,
+                    )
+                };
+                out.push((entry.sample_time, payload));
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn euclidean_sample_times_follow_context_tempo() {
+        let spacing = |tempo_bpm: f32| -> Vec<u64> {
+            let times: Vec<u64> = euclidean_events_at(&euclidean_config(), 256, tempo_bpm)
+                .iter()
+                .map(|(sample_time, _)| *sample_time)
+                .collect();
+            assert!(times.len() > 2, "tempo={tempo_bpm}");
+            times.windows(2).map(|pair| pair[1] - pair[0]).collect()
+        };
+        // A sixteenth at 48 kHz is 8000 frames at 90 bpm and 6000 at 120.
+        assert!(spacing(90.0).iter().all(|gap| *gap == 8000));
+        assert!(spacing(120.0).iter().all(|gap| *gap == 6000));
+        assert!(spacing(0.0).iter().all(|gap| *gap == 6000));
+    }
+
+    #[test]
+    fn euclidean_swing_repeats_each_loop_with_odd_steps() {
+        let mut config = euclidean_config();
+        config.steps = 5;
+        config.hits = 5;
+        config.swing = 100;
+        // Two bars at 120 bpm are 192000 samples, five steps of 19200 each.
+        let sample_times: Vec<u64> = euclidean_events(&config, 760)
+            .iter()
+            .map(|(sample_time, _)| *sample_time)
+            .collect();
+        let first: Vec<u64> = sample_times.iter().copied().filter(|time| *time < 96_000).collect();
+        let second: Vec<u64> = sample_times
+            .iter()
+            .filter(|time| (96_000..192_000).contains(*time))
+            .map(|time| time - 96_000)
+            .collect();
+        assert_eq!(first, vec![0, 28_800, 38_400, 67_200, 76_800]);
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    fn euclidean_max_swing_keeps_sample_times_ordered() {
+        let step_ticks = NANOTICKS_PER_QUARTER / 4;
+        assert_eq!(swung_tick(0, 0, step_ticks, 255), 0);
+        assert_eq!(swung_tick(step_ticks, 1, step_ticks, 255), step_ticks + step_ticks / 2);
+        assert_eq!(swung_tick(step_ticks, 1, step_ticks, 50), step_ticks + step_ticks / 4);
+
+        let mut config = euclidean_config();
+        config.swing = EUCLIDEAN_MAX_SWING;
+        let sample_times: Vec<u64> = euclidean_events(&config, 512)
+            .iter()
+            .map(|(sample_time, _)| *sample_time)
+            .collect();
+        // 512 blocks cover 131072 samples: sixteenths 0..=21 at 6000 samples
+        // each, with step 21 swung to 129000.
+        assert_eq!(sample_times.len(), 22);
+        assert!(sample_times.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn euclidean_quarter_swing_delays_odd_steps_across_blocks() {
+        // Swing is a percent of half a step, so 50 is a quarter step late.
+        let mut config = euclidean_config();
+        config.swing = 50;
+        let sample_times: Vec<u64> = euclidean_events(&config, 512)
+            .iter()
+            .map(|(sample_time, _)| *sample_time)
+            .collect();
+        assert!(sample_times.len() >= 16);
+        for (step, sample_time) in sample_times.iter().enumerate() {
+            let grid = step as u64 * 6000;
+            let expected = if step % 2 == 1 { grid + 1500 } else { grid };
+            assert_eq!(*sample_time, expected, "step={step}");
+        }
+    }
+
+    #[test]
+    fn euclidean_humanize_zero_matches_plain_output() {
+        let plain = euclidean_events(&euclidean_config(), 128);
+        let mut seeded = euclidean_config();
+        seeded.seed = 0x5eed;
+        let seeded = euclidean_events(&seeded, 128);
+        assert_eq!(plain.len(), seeded.len());
+        for (index, ((time, payload), (seeded_time, seeded_payload))) in
+            plain.iter().zip(seeded.iter()).enumerate()
+        {
+            assert_eq!(*time, index as u64 * 6000);
+            assert_eq!(time, seeded_time);
+            assert_eq!(payload.velocity, 0);
+            assert_eq!(seeded_payload.velocity, 0);
+            assert_eq!(payload.duration_ticks, seeded_payload.duration_ticks);
+        }
+
+        let mut humanized = euclidean_config();
+        humanized.seed = 0x5eed;
+        humanized.humanize_timing = u16::MAX;
+        humanized.humanize_velocity = u8::MAX;
+        let events = euclidean_events(&humanized, 128);
+        assert_eq!(events.len(), plain.len());
+        assert!(events.iter().any(|(time, _)| time % 6000 != 0));
+        for (index, (time, payload)) in events.iter().enumerate() {
+            assert!(*time >= index as u64 * 6000 && *time < (index as u64 + 1) * 6000);
+            assert!((1..=127).contains(&payload.velocity));
+        }
+    }
+
+    #[test]
+    fn euclidean_accents_follow_raw_step_index() {
+        let mut config = euclidean_config();
+        config.velocity = 80;
+        config.accent_velocity = 120;
+        config.accent_mask = 0x1111;
+        let velocities: Vec<u8> = euclidean_events(&config, 512)
+            .iter()
+            .map(|(_, payload)| payload.velocity)
+            .collect();
+        assert!(velocities.len() >= 16);
+        for (step, velocity) in velocities.iter().enumerate() {
+            let expected = if step % 4 == 0 { 120 } else { 80 };
+            assert_eq!(*velocity, expected, "step={step}");
+        }
+
+        config.rotation = 1;
+        let rotated: Vec<u8> = euclidean_events(&config, 512)
+            .iter()
+            .map(|(_, payload)| payload.velocity)
+            .collect();
+        for (step, velocity) in rotated.iter().enumerate() {
+            let expected = if (step + 1) % 4 == 0 { 120 } else { 80 };
+            assert_eq!(*velocity, expected, "rotated step={step}");
+        }
+    }
+
+    #[test]
+    fn euclidean_loop_advance_rotates_each_loop() {
+        let bar_ticks = NANOTICKS_PER_QUARTER * 4;
+        assert_eq!(euclidean_loop_shift(0, bar_ticks, 8, 3, false, 0), 0);
+        assert_eq!(euclidean_loop_shift(bar_ticks, bar_ticks, 8, 3, false, 0), 3);
+        assert_eq!(euclidean_loop_shift(bar_ticks * 3 + 1, bar_ticks, 8, 3, false, 0), 1);
+        for loop_index in 0..16 {
+            let tick = loop_index * bar_ticks;
+            let shift = euclidean_loop_shift(tick, bar_ticks, 8, 3, true, 0x5eed);
+            assert!(shift <= 3);
+            assert_eq!(shift, euclidean_loop_shift(tick, bar_ticks, 8, 3, true, 0x5eed));
+        }
+
+        // E(3,8) on eighths, 12000 samples each; a four-step advance per loop
+        // moves every onset into the other half of the bar.
+        let mut config = euclidean_config();
+        config.steps = 8;
+        config.hits = 3;
+        config.loop_advance = 4;
+        let mut base = [0u8; EUCLIDEAN_MAX_STEPS];
+        bjorklund_pattern(8, 3, &mut base);
+        let step_samples = 12_000;
+        let onsets = |loop_index: u64, events: &[(u64, MusicalLogicPayload)]| -> Vec<u64> {
+            events
+                .iter()
+                .map(|(time, _)| *time / step_samples)
+                .filter(|step| step / 8 == loop_index)
+                .map(|step| step % 8)
+                .collect()
+        };
+        let events = euclidean_events(&config, 1536);
+        for loop_index in 0..4u64 {
+            let shift = (loop_index * 4) % 8;
+            let expected: Vec<u64> = (0..8u64)
+                .filter(|step| base[((step + shift) % 8) as usize] != 0)
+                .collect();
+            assert_eq!(onsets(loop_index, &events), expected, "loop={loop_index}");
+        }
+    }
+
+    fn random_degrees(seed: u64) -> Vec<u8> {
+        let gate = MusicalLogicPayload {
+            degree: 0,
+            octave_offset: 0,
+            _pad0: [0u8; 2],
+            chord_id: 0,
+            duration_ticks: 0,
+            priority_hint: 0,
+            velocity: 0,
+            base_octave: 0,
+            metadata: {
+                let mut data = [0u8; 21];
+                data[0] = MUSICAL_LOGIC_KIND_GATE;
+                data
+            },
+        };
+        let mut events: Vec<EventEntry> = (0..16u64)
+            .map(|index| {
+                let mut entry = EventEntry {
+                    sample_time: index * 6000,
+                    block_id: 0,
+                    type_: 9,
+                    size: core::mem::size_of::<MusicalLogicPayload>() as u16,
+                    flags: 0,
+                    payload: [0u8; 40],
+                };
+                unsafe {
+                    core::ptr::copy_nonoverlapping(
+                        &gate as *const MusicalLogicPayload as *const u8,
+                        entry.payload.as_mut_ptr(),
+                        core::mem::size_of::<MusicalLogicPayload>(),
+                    );
+                }
+                entry
+            })
+            .collect();
+        let mut event_count = events.len() as u32;
+        let config = PatcherRandomDegreeConfig {
+            degree: 7,
+            velocity: 100,
+            _pad0: [0u8; 2],
+            duration_ticks: 0,
+            seed,
+        };
+        let mut ctx = PatcherContext {
+            abi_version: PATCHER_ABI_VERSION,
+            block_start_tick: 0,
+            block_end_tick: NANOTICKS_PER_QUARTER * 4,
+            block_start_sample: 0,
+            sample_rate: 48_000.0,
+            tempo_bpm: 120.0,
+            num_frames: 0,
+            event_buffer: events.as_mut_ptr(),
+            event_capacity: events.len() as u32,
+            event_count: &mut event_count,
+            last_overflow_tick: core::ptr::null_mut(),
+            audio_channels: core::ptr::null_mut(),
+            num_channels: 0,
+            node_config: &config as *const PatcherRandomDegreeConfig as *const c_void,
+            node_config_size: core::mem::size_of::<PatcherRandomDegreeConfig>() as u32,
+            harmony_snapshot: core::ptr::null(),
+            harmony_count: 0,
+            mod_outputs: core::ptr::null_mut(),
+            mod_output_count: 0,
+            mod_output_samples: core::ptr::null_mut(),
+            mod_output_stride: 0,
+            mod_inputs: core::ptr::null_mut(),
+            mod_input_count: 0,
+            mod_input_stride: 0,
+        };
+        patcher_process_random_degree(&mut ctx);
+        events
+            .iter()
+            .map(|entry| unsafe {
+                core::ptr::read_unaligned(entry.payload.as_ptr() as *const MusicalLogicPayload)
+                    .degree
+            })
+            .collect()
+    }
+
+    #[test]
+    fn random_degree_seed_is_reproducible_and_distinct() {
+        let first = random_degrees(1);
+        let second = random_degrees(2);
+        assert_eq!(first, random_degrees(1));
+        assert_eq!(second, random_degrees(2));
+        assert_ne!(first, second);
+        assert!(first.iter().chain(second.iter()).all(|degree| (1..=7).contains(degree)));
+        assert_eq!(seeded_hash(0, 42), mix64(42));
+    }
 }
@@ -1,17 +1,17 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use gpui::{
-    div, px, rgb, size, App, Application, Bounds, Context, MouseButton,
+    div, px, rgb, size, App, Application, Bounds, ClipboardItem, Context, MouseButton,
     ScrollWheelEvent, SharedString, Timer, Window, WindowBounds,
     WindowOptions, prelude::*,
 };
 
 use daw_bridge::layout::{
-    K_CHAIN_DEVICE_ID_AUTO, K_UI_MAX_TRACKS, UiChainCommandPayload,
+    K_CHAIN_DEVICE_ID_AUTO, K_UI_MASTER_METER_CHANNELS, K_UI_MAX_TRACKS, UiChainCommandPayload,
     UiClipWindowCommandPayload, UiCommandPayload, UiCommandType, UiDiffType,
     UiHarmonyDiffType, UiChordCommandPayload, UiChordDiffType,
     UiPatcherGraphCommandPayload, UiPatcherNodeConfigPayload,
@@ -28,19 +28,31 @@ use crate::engine::bridge::{
 use crate::engine::supervisor::{
     default_engine_path, spawn_engine_process, stop_engine_process, EngineSupervisor,
 };
-use crate::clipboard::{ClipboardChord, ClipboardData, ClipboardHarmony, ClipboardNote};
+use crate::clipboard::{
+    ClipboardChord, ClipboardData, ClipboardHarmony, ClipboardNote, RegionStash,
+};
+use crate::euclidean::{euclidean_pattern, euclidean_steps, EUCLIDEAN_MAX_STEPS};
+use crate::groove::{GrooveTemplate, GROOVE_TEMPLATES};
 use crate::plugins::{load_plugin_cache, PluginEntry};
-use crate::harmony::{harmony_root_name, harmony_scale_name, SCALE_LIBRARY};
+use crate::harmony::{
+    harmony_root_from_name, harmony_root_name, harmony_scale_name,
+    chord_name_for_pitches, implied_chord_name, resolve_chord_pitches, resolve_degree_pitch, scale_contains_pitch,
+    SCALE_LIBRARY,
+};
 use crate::palette::{PaletteCommandId, PaletteMode, PALETTE_COMMANDS};
 use crate::selection::{SelectionMask, SelectionRange};
 use crate::state::{
-    CellEntry, CellKind, ClipChord, ClipNote, HarmonyEntry, PendingChord, PendingNote,
-    QueuedCommand,
+    CellContentFilter, CellEntry, CellKind, ClipChord, ClipNote, HarmonyEntry, PendingChord, PendingNote,
+    QueuedCommand, VelocityDigitScale,
 };
 use crate::ui::actions::{self, *};
 use crate::util::split_u64;
+use crate::project_markers::{
+    load_project_markers, save_project_markers, ProjectMarker, ProjectMarkers,
+};
+use crate::window_layout::{load_window_layout, save_window_layout, WindowLayout};
 use crate::tracker::{
-    FOLLOW_PLAYHEAD_LOWER, FOLLOW_PLAYHEAD_UPPER, MAX_NOTE_COLUMNS, ROW_HEIGHT,
+    COLUMN_WIDTH, FOLLOW_PLAYHEAD_LOWER, FOLLOW_PLAYHEAD_UPPER, MAX_NOTE_COLUMNS, ROW_HEIGHT,
     TIME_COLUMN_WIDTH, VISIBLE_ROWS,
 };
 
@@ -49,8 +61,36 @@ pub const BEATS_PER_BAR: u64 = 4;
 pub const ZOOM_LEVELS: [u64; 7] = [1, 2, 4, 8, 16, 32, 64];
 pub const DEFAULT_ZOOM_INDEX: usize = 2;
 pub const TRACK_COUNT: usize = 8;
+const MAX_COUNT_IN_BARS: u32 = 2;
+const MAX_CHORD_BASE_OCTAVE: u8 = 9;
+const MAX_QUANTIZE_STRENGTH: u8 = 100;
+const CHORD_QUALITY_TRIAD: u8 = 1;
+const CHORD_QUALITY_SEVENTH: u8 = 2;
+const QUANTIZE_STRENGTH_STEP: u8 = 25;
+/// Velocity change per Up/Down press in velocity focus.
+const VELOCITY_FOCUS_STEP: i32 = 8;
+const DEFAULT_TEMPO_BPM: f64 = 120.0;
+/// Rate assumed for sample positions when no engine is attached.
+const DEFAULT_SAMPLE_RATE: f64 = 48_000.0;
+const MIN_TEMPO_BPM: f64 = 20.0;
+const MAX_TEMPO_BPM: f64 = 300.0;
 const EDIT_STEP_ROWS: i64 = 1;
+/// Track time offset change per nudge: one 96 PPQN tick.
+const TRACK_TIME_OFFSET_STEP: i64 = (NANOTICKS_PER_QUARTER / 96) as i64;
+/// Furthest a track may be pushed either way, one quarter note.
+const MAX_TRACK_TIME_OFFSET: i64 = NANOTICKS_PER_QUARTER as i64;
+/// Furthest bar the jump box accepts; positions past it are unusable in the
+/// grid and far from overflowing nanoticks.
+const MAX_JUMP_BAR: u64 = 9_999;
+/// How long a note or chord write may go without a confirming diff before the
+/// view assumes the engine dropped it and resyncs.
+pub(crate) const COMMAND_ACK_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long a queued command may wait on a full UI ring before the flush
+/// gives up until the next frame. Override with `DAW_UI_SEND_RETRY_MS`.
+pub(crate) const DEFAULT_UI_SEND_RETRY: Duration = Duration::from_millis(3);
 const CLIP_WINDOW_MARGIN_ROWS: i64 = 4;
+/// Octave of the lower keyjazz row ("z" = C-3); the upper row sits one above.
+pub(crate) const KEYJAZZ_BASE_OCTAVE: u8 = 3;
 pub(crate) const PATCHER_NODE_RUST: u32 = 0;
 pub(crate) const PATCHER_NODE_EUCLIDEAN: u32 = 1;
 pub(crate) const PATCHER_NODE_PASSTHROUGH: u32 = 2;
@@ -58,6 +98,7 @@ pub(crate) const PATCHER_NODE_AUDIO_PASSTHROUGH: u32 = 3;
 pub(crate) const PATCHER_NODE_LFO: u32 = 4;
 pub(crate) const PATCHER_NODE_RANDOM_DEGREE: u32 = 5;
 pub(crate) const PATCHER_NODE_EVENT_OUT: u32 = 6;
+pub(crate) const PATCHER_NODE_HARMONY_ARP: u32 = 7;
 pub(crate) const PATCHER_EVENT_INPUT_PORT: u32 = 0;
 pub(crate) const PATCHER_EVENT_OUTPUT_PORT: u32 = 1;
 pub(crate) const PATCHER_CONTROL_INPUT_PORT: u32 = 2;
@@ -149,6 +190,30 @@ pub enum ChainAddMode {
     Effect,
 }
 
+/// What the take-name prompt does once a name is entered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StashPrompt {
+    Stash,
+    Swap,
+}
+
+/// An edit that reads whole tracks. With an engine, `clip_notes` and
+/// `clip_chords` hold only the requested clip window, so these wait in
+/// `pending_whole_clip_edits` until every event has been loaded.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum WholeClipEdit {
+    SwapTracks(usize, usize),
+    TransposeProject(i32),
+    TrimToLoop(Option<usize>),
+    DuplicateBar(u64),
+    FitTrackColumns(usize),
+    MoveTrackColumn { track: usize, from_col: usize, to_col: usize },
+    ApplyGroove(usize, GrooveTemplate),
+    ClampDurations { track: usize, max_rows: u64 },
+    DeleteColumnNoteOffs { track: usize, column: u8 },
+    LegatoColumn { track: usize, column: u8 },
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct ClipWindowState {
     pub request_id: u32,
@@ -160,6 +225,15 @@ pub struct ClipWindowState {
     pub complete: bool,
 }
 
+/// Versions of the state that feeds a render. The async loop compares the
+/// current tuple against the last rendered one to skip idle repaints.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct RenderVersions {
+    pub snapshot: u64,
+    pub clip: u64,
+    pub harmony: u64,
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 struct ClipWindowRequestState {
     window_start: u64,
@@ -187,23 +261,74 @@ pub struct EngineView {
     pub scale_browser_open: bool,
     pub scale_browser_query: String,
     pub scale_browser_selection: usize,
+    pub scale_browser_reharmonize: bool,
+    pub scale_browser_range: bool,
     pub focused_track_index: usize,
     pub cursor_nanotick: u64,
     pub cursor_col: usize,
     pub scroll_nanotick_offset: i64,
     pub zoom_index: usize,
+    /// Track columns scrolled off the left of the track area; TIME and HARM
+    /// stay pinned.
+    pub horizontal_scroll_offset: usize,
     pub follow_playhead: bool,
+    pub markers: Vec<(u64, String)>,
+    pub follow_playhead_lower: f32,
+    pub follow_playhead_upper: f32,
+    pub count_in_bars: u32,
+    pub insert_mode: bool,
+    pub read_only: bool,
+    pub diatonic_entry: bool,
+    /// Digits enter chromatic keyjazz pitches by default; Alt then gives
+    /// degree entry instead of the other way round.
+    pub chromatic_digits: bool,
     pub harmony_focus: bool,
+    /// Note-grid `(track, column)` to come back to when harmony focus ends.
+    pub harmony_return_cell: (usize, usize),
     pub harmony_scale_id: u32,
+    pub pitch_ruler_visible: bool,
+    pub minimap_bar_numbers: bool,
+    pub minimap_track_dots: bool,
+    pub show_velocity: bool,
+    pub show_durations: bool,
+    pub show_resolved_chords: bool,
+    pub show_rests: bool,
+    /// Notes spelled with flats instead of sharps, keyed by
+    /// `(track, column, nanotick)`. Display only; pitches are unchanged.
+    pub flat_spellings: HashSet<(usize, u8, u64)>,
+    pub keyjazz_pitch: u8,
+    pub velocity_entry: bool,
+    pub velocity_digit_scale: VelocityDigitScale,
+    pub velocity_digits: String,
+    pub velocity_focus: bool,
+    pub advance_up: bool,
+    pub harmony_audition: bool,
+    pub show_inherited_harmony: bool,
+    pub master_clip_latched: bool,
     pub edit_active: bool,
     pub edit_text: String,
     pub jump_open: bool,
     pub jump_text: String,
+    pub tempo_open: bool,
+    pub tempo_text: String,
+    pub mirror_open: bool,
+    pub mirror_text: String,
+    pub bar_harmony_open: bool,
+    pub bar_harmony_text: String,
+    pub euclidean_open: bool,
+    pub euclidean_text: String,
+    pub sustain_chord_open: bool,
+    pub sustain_chord_text: String,
+    pub(crate) stash_prompt: Option<StashPrompt>,
+    pub stash_name_text: String,
+    pub tempo_bpm: f64,
     pub selection: Option<SelectionRange>,
     pub selection_mask: SelectionMask,
     pub selection_anchor_nanotick: Option<u64>,
+    pub harmony_drag_anchor: Option<u64>,
     pub loop_range: Option<(u64, u64)>,
     pub clipboard: Option<ClipboardData>,
+    pub region_stashes: BTreeMap<String, RegionStash>,
     pub toast_message: Option<String>,
     pub toast_deadline: Option<Instant>,
     pub pending_notes: Vec<PendingNote>,
@@ -216,11 +341,19 @@ pub struct EngineView {
     pub harmony_events: Vec<HarmonyEntry>,
     pub clip_chords: Vec<Vec<ClipChord>>,
     pub queued_commands: VecDeque<QueuedCommand>,
+    pub(crate) command_sent_at: HashMap<(u32, u64, u8), Instant>,
     pub clip_resync_pending: bool,
     pub harmony_resync_pending: bool,
+    pub(crate) pending_whole_clip_edits: Vec<WholeClipEdit>,
     pub track_columns: Vec<usize>,
     pub track_quantize: Vec<bool>,
+    pub track_armed: Vec<bool>,
+    /// Signed playback offset per track in nanoticks; positive plays late.
+    pub track_time_offset: Vec<i64>,
+    pub track_quantize_strength: Vec<u8>,
+    pub track_content_filter: Vec<CellContentFilter>,
     pub track_names: Vec<Option<String>>,
+    pub auto_name_tracks: bool,
     pub chain_versions: Vec<u32>,
     pub chain_devices: Vec<Vec<ChainDevice>>,
     pub chain_focus: bool,
@@ -242,7 +375,11 @@ pub struct EngineView {
     pub preset_save_open: bool,
     pub preset_save_text: String,
     pub ui_debug: bool,
+    pub ui_send_retry: Duration,
+    pub show_version_overlay: bool,
+    pub solo_column: Option<(usize, usize)>,
     pub render_count: u32,
+    pub(crate) last_render_versions: RenderVersions,
     // Minimap cache fields for memoization
     pub(crate) minimap_cache_clip_version: u32,
     pub(crate) minimap_cache_harmony_version: u32,
@@ -259,6 +396,11 @@ pub(crate) struct TrackerCacheKey {
     pub clip_render_version: u64,
     pub harmony_render_version: u64,
     pub track_columns: Vec<usize>,
+    pub track_content_filter: Vec<CellContentFilter>,
+    pub show_velocity: bool,
+    pub show_durations: bool,
+    pub show_resolved_chords: bool,
+    pub show_rests: bool,
 }
 
 #[derive(Clone)]
@@ -291,6 +433,10 @@ impl EngineView {
 
     fn new_state() -> Self {
         let plugins = load_plugin_cache();
+        let (follow_playhead_lower, follow_playhead_upper) = std::env::var("DAW_FOLLOW_BAND")
+            .ok()
+            .and_then(|text| parse_follow_band(&text))
+            .unwrap_or((FOLLOW_PLAYHEAD_LOWER, FOLLOW_PLAYHEAD_UPPER));
         Self {
             bridge: None,
             snapshot: UiSnapshot {
@@ -306,6 +452,7 @@ impl EngineView {
                 ui_harmony_offset: 0,
                 ui_harmony_bytes: 0,
                 ui_track_peak_rms: [0.0; K_UI_MAX_TRACKS],
+                ui_master_peak: [0.0; K_UI_MASTER_METER_CHANNELS],
             },
             clip_window: vec![ClipWindowState::default(); TRACK_COUNT],
             status: "SHM: disconnected".into(),
@@ -318,23 +465,67 @@ impl EngineView {
             scale_browser_open: false,
             scale_browser_query: String::new(),
             scale_browser_selection: 0,
+            scale_browser_reharmonize: false,
+            scale_browser_range: false,
             focused_track_index: 0,
             cursor_nanotick: 0,
             cursor_col: 0,
             scroll_nanotick_offset: 0,
             zoom_index: DEFAULT_ZOOM_INDEX,
+            horizontal_scroll_offset: 0,
             follow_playhead: true,
+            markers: Vec::new(),
+            follow_playhead_lower,
+            follow_playhead_upper,
+            count_in_bars: 0,
+            insert_mode: false,
+            read_only: false,
+            diatonic_entry: false,
+            chromatic_digits: false,
             harmony_focus: false,
+            harmony_return_cell: (0, 0),
             harmony_scale_id: 1,
+            pitch_ruler_visible: false,
+            minimap_bar_numbers: false,
+            minimap_track_dots: false,
+            show_velocity: false,
+            show_durations: false,
+            show_resolved_chords: false,
+            show_rests: false,
+            flat_spellings: HashSet::new(),
+            keyjazz_pitch: 48,
+            velocity_entry: false,
+            velocity_digit_scale: VelocityDigitScale::default(),
+            velocity_digits: String::new(),
+            velocity_focus: false,
+            advance_up: false,
+            harmony_audition: false,
+            show_inherited_harmony: false,
+            master_clip_latched: false,
             edit_active: false,
             edit_text: String::new(),
             jump_open: false,
             jump_text: String::new(),
+            tempo_open: false,
+            tempo_text: String::new(),
+            mirror_open: false,
+            mirror_text: String::new(),
+            bar_harmony_open: false,
+            bar_harmony_text: String::new(),
+            euclidean_open: false,
+            euclidean_text: String::new(),
+            sustain_chord_open: false,
+            sustain_chord_text: String::new(),
+            stash_prompt: None,
+            stash_name_text: String::new(),
+            tempo_bpm: DEFAULT_TEMPO_BPM,
             selection: None,
             selection_mask: SelectionMask::empty(TRACK_COUNT),
             selection_anchor_nanotick: None,
+            harmony_drag_anchor: None,
             loop_range: None,
             clipboard: None,
+            region_stashes: BTreeMap::new(),
             toast_message: None,
             toast_deadline: None,
             pending_notes: Vec::new(),
@@ -347,11 +538,18 @@ impl EngineView {
             harmony_events: Vec::new(),
             clip_chords: vec![Vec::new(); TRACK_COUNT],
             queued_commands: VecDeque::new(),
+            command_sent_at: HashMap::new(),
             clip_resync_pending: false,
             harmony_resync_pending: false,
+            pending_whole_clip_edits: Vec::new(),
             track_columns: vec![1; TRACK_COUNT],
             track_quantize: vec![true; TRACK_COUNT],
+            track_armed: vec![false; TRACK_COUNT],
+            track_time_offset: vec![0; TRACK_COUNT],
+            track_quantize_strength: vec![MAX_QUANTIZE_STRENGTH; TRACK_COUNT],
+            track_content_filter: vec![CellContentFilter::All; TRACK_COUNT],
             track_names: vec![None; TRACK_COUNT],
+            auto_name_tracks: true,
             chain_versions: vec![0; TRACK_COUNT],
             chain_devices: vec![Vec::new(); TRACK_COUNT],
             chain_focus: false,
@@ -373,7 +571,14 @@ impl EngineView {
             preset_save_open: false,
             preset_save_text: String::new(),
             ui_debug: std::env::var("DAW_UI_DEBUG").map_or(false, |v| v == "1"),
+            ui_send_retry: std::env::var("DAW_UI_SEND_RETRY_MS")
+                .ok()
+                .and_then(|text| text.trim().parse().ok())
+                .map_or(DEFAULT_UI_SEND_RETRY, Duration::from_millis),
+            show_version_overlay: false,
+            solo_column: None,
             render_count: 0,
+            last_render_versions: RenderVersions::default(),
             // Minimap cache initialization
             minimap_cache_clip_version: 0,
             minimap_cache_harmony_version: 0,
@@ -398,25 +603,64 @@ impl EngineView {
         }
 
         let row_nanoticks = self.row_nanoticks() as i64;
-        if row_nanoticks <= 0 {
-            return false;
-        }
 
         let playhead_view_row = (self.snapshot.ui_global_nanotick_playhead as i64
             - self.scroll_nanotick_offset)
             / row_nanoticks;
-        let lower = (VISIBLE_ROWS as f32 * FOLLOW_PLAYHEAD_LOWER).floor() as i64;
-        let upper = (VISIBLE_ROWS as f32 * FOLLOW_PLAYHEAD_UPPER).ceil() as i64;
+        let lower = (VISIBLE_ROWS as f32 * self.follow_playhead_lower).floor() as i64;
+        let upper = (VISIBLE_ROWS as f32 * self.follow_playhead_upper).ceil() as i64;
 
         if playhead_view_row < lower || playhead_view_row > upper {
-            let target = self.snapshot.ui_global_nanotick_playhead as i64
-                - (VISIBLE_ROWS as i64 / 2) * row_nanoticks;
-            self.scroll_nanotick_offset = target.max(0);
+            self.scroll_nanotick_offset = self.playhead_centered_offset();
             return true;
         }
         false
     }
 
+    /// Scroll offset that puts the playhead in the middle of the visible rows.
+    fn playhead_centered_offset(&self) -> i64 {
+        let target = self.snapshot.ui_global_nanotick_playhead as i64
+            - (VISIBLE_ROWS as i64 / 2) * self.row_nanoticks() as i64;
+        target.max(0)
+    }
+
+    /// Centers the view on the playhead once without turning on follow.
+    fn jump_to_playhead(&mut self, cx: &mut impl UiNotify) {
+        self.scroll_nanotick_offset = self.playhead_centered_offset();
+        cx.notify();
+    }
+
+    /// Sets the fraction of visible rows the playhead may roam before the
+    /// view re-centers. Returns false and keeps the current band when the
+    /// bounds are not ordered fractions strictly inside (0, 1).
+    pub fn set_follow_playhead_band(&mut self, lower: f32, upper: f32) -> bool {
+        if !follow_band_is_valid(lower, upper) {
+            return false;
+        }
+        self.follow_playhead_lower = lower;
+        self.follow_playhead_upper = upper;
+        true
+    }
+
+    pub(crate) fn render_versions(&self) -> RenderVersions {
+        RenderVersions {
+            snapshot: self.snapshot.version,
+            clip: self.clip_render_version,
+            harmony: self.harmony_render_version,
+        }
+    }
+
+    /// Returns true if state consumed by render() changed since the last frame.
+    pub(crate) fn needs_render(&self) -> bool {
+        should_notify(self.last_render_versions, self.render_versions())
+    }
+
+    /// Marks the current snapshot as rendered when it carries no visual change,
+    /// so a bare version tick does not force the next poll to repaint.
+    pub(crate) fn acknowledge_snapshot_version(&mut self) {
+        self.last_render_versions.snapshot = self.snapshot.version;
+    }
+
     pub fn toggle_palette(&mut self, cx: &mut impl UiNotify) {
         if self.scale_browser_open {
             self.scale_browser_open = false;
@@ -480,6 +724,315 @@ impl EngineView {
         cx.notify();
     }
 
+    fn open_tempo(&mut self, cx: &mut impl UiNotify) {
+        self.palette_open = false;
+        self.edit_active = false;
+        self.edit_text.clear();
+        self.tempo_open = true;
+        self.tempo_text.clear();
+        cx.notify();
+    }
+
+    fn close_tempo(&mut self, cx: &mut impl UiNotify) {
+        if self.tempo_open {
+            self.tempo_open = false;
+            self.tempo_text.clear();
+            cx.notify();
+        }
+    }
+
+    fn confirm_tempo(&mut self, cx: &mut impl UiNotify) {
+        let Some(bpm) = parse_tempo_text(&self.tempo_text) else {
+            self.show_toast("Tempo must be 20-300 BPM", cx);
+            return;
+        };
+        self.tempo_bpm = bpm;
+        if self.bridge.is_some() {
+            let centi_bpm = (bpm * 100.0).round() as u32;
+            self.enqueue_ui_command(transport_command(UiCommandType::SetTempo, centi_bpm));
+        }
+        self.close_tempo(cx);
+    }
+
+    fn open_mirror(&mut self, cx: &mut impl UiNotify) {
+        self.palette_open = false;
+        if self.selection_bounds().is_none() {
+            self.show_toast("No selection", cx);
+            return;
+        }
+        self.edit_active = false;
+        self.edit_text.clear();
+        self.mirror_open = true;
+        self.mirror_text.clear();
+        cx.notify();
+    }
+
+    fn close_mirror(&mut self, cx: &mut impl UiNotify) {
+        if self.mirror_open {
+            self.mirror_open = false;
+            self.mirror_text.clear();
+            cx.notify();
+        }
+    }
+
+    fn confirm_mirror(&mut self, cx: &mut impl UiNotify) {
+        let target = self
+            .mirror_text
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|track| (1..=TRACK_COUNT).contains(track));
+        let Some(target) = target else {
+            self.show_toast(&format!("Track must be 1-{TRACK_COUNT}"), cx);
+            return;
+        };
+        self.close_mirror(cx);
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let written = self.mirror_selection_to_track(target - 1, cx);
+        self.show_toast(&format!("Mirrored {written} event(s) to T{target}"), cx);
+    }
+
+    /// Writes the selected notes and chords of the other tracks into `target`
+    /// at the same times and columns, keeping pitches and chord settings.
+    /// Returns how many events were written.
+    fn mirror_selection_to_track(&mut self, target: usize, cx: &mut impl UiNotify) -> usize {
+        let Some((start, end)) = self.selection_bounds() else {
+            return 0;
+        };
+        if target >= TRACK_COUNT {
+            return 0;
+        }
+        let mut mask = self.selection_mask.clone();
+        if let Some(bits) = mask.tracks.get_mut(target) {
+            *bits = 0;
+        }
+        let notes = self.collect_notes_in_range(start, end, &mask, true);
+        let chords = self.collect_chords_in_range(start, end, &mask, true);
+        let widest = notes
+            .iter()
+            .map(|(_, note)| note.column as usize)
+            .chain(chords.iter().map(|(_, chord)| chord.column as usize))
+            .max();
+        if let Some(column) = widest {
+            if self.track_columns[target] <= column {
+                self.track_columns[target] = column + 1;
+            }
+        }
+        let written = notes.len() + chords.len();
+        for (_, note) in notes {
+            self.write_note_at(
+                target,
+                note.column,
+                note.nanotick,
+                note.pitch,
+                note.velocity,
+                note.duration,
+                cx,
+            );
+        }
+        for (_, chord) in chords {
+            self.write_chord_at(
+                target,
+                chord.column,
+                chord.nanotick,
+                chord.duration,
+                chord.degree,
+                chord.quality,
+                chord.inversion,
+                chord.base_octave,
+                chord.spread,
+                chord.humanize_timing,
+                chord.humanize_velocity,
+                cx,
+            );
+        }
+        written
+    }
+
+    fn open_bar_harmony(&mut self, cx: &mut impl UiNotify) {
+        self.palette_open = false;
+        self.edit_active = false;
+        self.edit_text.clear();
+        self.bar_harmony_open = true;
+        self.bar_harmony_text.clear();
+        cx.notify();
+    }
+
+    fn close_bar_harmony(&mut self, cx: &mut impl UiNotify) {
+        if self.bar_harmony_open {
+            self.bar_harmony_open = false;
+            self.bar_harmony_text.clear();
+            cx.notify();
+        }
+    }
+
+    fn confirm_bar_harmony(&mut self, cx: &mut impl UiNotify) {
+        let Some(roots) = parse_root_list(&self.bar_harmony_text) else {
+            self.show_toast("Roots must be note names, e.g. C,G,A,F", cx);
+            return;
+        };
+        self.close_bar_harmony(cx);
+        let (start, bars) = self.bar_harmony_span(roots.len());
+        let written = self.write_bar_harmony(start, bars, &roots, cx);
+        self.show_toast(&format!("Wrote harmony on {written} bar(s)"), cx);
+    }
+
+    /// First bar line and bar count to fill: the bars the selection touches,
+    /// or one bar per root from the cursor's bar without a selection.
+    fn bar_harmony_span(&self, root_count: usize) -> (u64, u64) {
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        match self.selection_bounds() {
+            Some((start, end)) => {
+                let first = start / bar * bar;
+                let last = end.saturating_add(self.row_nanoticks()).div_ceil(bar) * bar;
+                (first, ((last - first) / bar).max(1))
+            }
+            None => (self.current_row_nanotick() / bar * bar, root_count as u64),
+        }
+    }
+
+    /// Writes a harmony event at each of `bars` bar lines from `start`,
+    /// cycling through `roots` in the current harmony scale. Returns how many
+    /// events were written.
+    fn write_bar_harmony(
+        &mut self,
+        start: u64,
+        bars: u64,
+        roots: &[u32],
+        cx: &mut impl UiNotify,
+    ) -> usize {
+        if roots.is_empty() || self.read_only_blocked(cx) {
+            return 0;
+        }
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        let scale_id = self.harmony_scale_id;
+        for index in 0..bars {
+            let root = roots[(index % roots.len() as u64) as usize];
+            self.write_harmony_at(start + index * bar, root, scale_id, cx);
+        }
+        bars as usize
+    }
+
+    fn open_euclidean(&mut self, cx: &mut impl UiNotify) {
+        self.palette_open = false;
+        self.edit_active = false;
+        self.edit_text.clear();
+        self.euclidean_open = true;
+        self.euclidean_text.clear();
+        cx.notify();
+    }
+
+    fn close_euclidean(&mut self, cx: &mut impl UiNotify) {
+        if self.euclidean_open {
+            self.euclidean_open = false;
+            self.euclidean_text.clear();
+            cx.notify();
+        }
+    }
+
+    fn confirm_euclidean(&mut self, cx: &mut impl UiNotify) {
+        let Some((steps, hits, offset, pitch)) = parse_euclidean_spec(&self.euclidean_text)
+        else {
+            self.show_toast("Expected steps,hits[,offset[,pitch]], e.g. 8,3,0,C-4", cx);
+            return;
+        };
+        self.close_euclidean(cx);
+        let pitch = pitch.unwrap_or(self.keyjazz_pitch);
+        let written = self.write_euclidean_bar(steps, hits, offset, pitch, cx);
+        self.show_toast(&format!("Wrote {written} Euclidean hits"), cx);
+    }
+
+    fn open_sustain_chord(&mut self, cx: &mut impl UiNotify) {
+        self.palette_open = false;
+        if self.selection_bounds().is_none() {
+            self.show_toast("No selection", cx);
+            return;
+        }
+        self.edit_active = false;
+        self.edit_text.clear();
+        self.sustain_chord_open = true;
+        self.sustain_chord_text.clear();
+        cx.notify();
+    }
+
+    fn close_sustain_chord(&mut self, cx: &mut impl UiNotify) {
+        if self.sustain_chord_open {
+            self.sustain_chord_open = false;
+            self.sustain_chord_text.clear();
+            cx.notify();
+        }
+    }
+
+    fn confirm_sustain_chord(&mut self, cx: &mut impl UiNotify) {
+        let Some(chord) = parse_chord_token(&self.sustain_chord_text) else {
+            self.show_toast("Expected a chord token, e.g. @1^7", cx);
+            return;
+        };
+        self.close_sustain_chord(cx);
+        self.write_selection_chord(&chord, cx);
+    }
+
+    /// Writes `chord` at the selection start in the focused column, held for
+    /// the whole selection. Any duration in the token is ignored.
+    fn write_selection_chord(&mut self, chord: &ParsedChordToken, cx: &mut impl UiNotify) -> bool {
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return false;
+        };
+        if end <= start || self.read_only_blocked(cx) {
+            return false;
+        }
+        self.write_chord_at(
+            self.focused_track_index,
+            self.cursor_col as u8,
+            start,
+            end - start,
+            chord.degree.min(255) as u8,
+            chord.quality,
+            chord.inversion,
+            chord.base_octave,
+            chord.spread_nanoticks,
+            chord.humanize_timing as u16,
+            chord.humanize_velocity as u16,
+            cx,
+        );
+        true
+    }
+
+    /// Writes a note-on at each hit of a `steps`/`hits` Euclidean rhythm,
+    /// spread evenly over the cursor's bar in the focused column. Each note
+    /// lasts one step. Returns the number of notes written.
+    fn write_euclidean_bar(
+        &mut self,
+        steps: u32,
+        hits: u32,
+        offset: u32,
+        pitch: u8,
+        cx: &mut impl UiNotify,
+    ) -> usize {
+        if self.read_only_blocked(cx) {
+            return 0;
+        }
+        let bar_len = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        let bar_start = self.current_row_nanotick() / bar_len * bar_len;
+        let track = self.focused_track_index;
+        let column = self.cursor_col as u8;
+        let pattern = euclidean_steps(steps, hits, offset);
+        let mut written = 0;
+        for (step, hit) in pattern.iter().enumerate() {
+            if !*hit {
+                continue;
+            }
+            let nanotick = bar_start + bar_len * step as u64 / steps as u64;
+            let duration = bar_len / steps as u64;
+            self.write_note_at(track, column, nanotick, pitch, 100, duration, cx);
+            written += 1;
+        }
+        written
+    }
+
     fn close_jump(&mut self, cx: &mut impl UiNotify) {
         if self.jump_open {
             self.jump_open = false;
@@ -574,81 +1127,278 @@ impl EngineView {
                         self.palette_open = false;
                         self.open_scale_browser(cx);
                     }
-                }
-            }
-            PaletteMode::Plugins => {
-                let filtered = self.filtered_plugin_indices();
-                if filtered.is_empty() {
-                    return;
-                }
-                let selection = self.palette_selection.min(filtered.len() - 1);
-                let index = filtered[selection];
-                let plugin = self.plugins[index].clone();
-
-                if self.bridge.is_some() {
-                    match self.chain_add_mode {
-                        Some(ChainAddMode::Effect) => {
-                            let payload = UiChainCommandPayload {
-                                command_type: UiCommandType::AddDevice as u16,
-                                flags: 0,
-                                track_id: self.focused_track_index as u32,
-                                base_version: self.chain_versions[self.focused_track_index],
-                                device_id: K_CHAIN_DEVICE_ID_AUTO,
-                                device_kind: 4,
-                                insert_index: K_CHAIN_DEVICE_ID_AUTO,
-                                patcher_node_id: 0,
-                                host_slot_index: plugin.index as u32,
-                                bypass: 0,
-                                reserved: [0; 4],
-                            };
-                            self.enqueue_chain_payload(payload);
-                        }
-                        _ => {
-                            let payload = UiCommandPayload {
-                                command_type: UiCommandType::LoadPluginOnTrack as u16,
-                                flags: 0,
-                                track_id: self.focused_track_index as u32,
-                                plugin_index: plugin.index as u32,
-                                note_pitch: 0,
-                                value0: 0,
-                                note_nanotick_lo: 0,
-                                note_nanotick_hi: 0,
-                                note_duration_lo: 0,
-                                note_duration_hi: 0,
-                                base_version: 0,
-                            };
-                            self.enqueue_ui_command(payload);
-                            if self.focused_track_index < self.track_names.len() {
-                                self.track_names[self.focused_track_index] =
-                                    Some(plugin.name.clone());
+                    PaletteCommandId::ReharmonizeTrack => {
+                        self.palette_open = false;
+                        self.open_scale_browser(cx);
+                        self.scale_browser_reharmonize = true;
+                    }
+                    PaletteCommandId::ToggleAutoNameTracks => {
+                        self.auto_name_tracks = !self.auto_name_tracks;
+                        if self.auto_name_tracks {
+                            for track_index in 0..self.track_names.len() {
+                                self.sync_track_name_from_chain(track_index);
                             }
                         }
+                        self.palette_open = false;
+                        let message = if self.auto_name_tracks {
+                            "Auto-name tracks: on"
+                        } else {
+                            "Auto-name tracks: off"
+                        };
+                        self.show_toast(message, cx);
                     }
-                } else {
-                    // No-op until engine reconnects.
-                }
-
-                self.chain_add_mode = None;
-                self.palette_open = false;
-                cx.notify();
-            }
-        }
-    }
-
-    pub fn handle_keystroke(&mut self, keystroke: &gpui::Keystroke, cx: &mut impl UiNotify) {
-        if keystroke.modifiers.control ||
-            keystroke.modifiers.platform ||
-            keystroke.modifiers.function {
-            return;
-        }
-        let key_char = if keystroke.modifiers.alt &&
-            keystroke.key.len() == 1 &&
-            keystroke.key.chars().next().map_or(false, |ch| ch.is_ascii_digit()) {
-            Some(keystroke.key.as_str())
-        } else {
-            keystroke_text(keystroke)
-        };
-        if self.scale_browser_open {
+                    PaletteCommandId::ResyncClipsAndHarmony => {
+                        self.request_full_resync();
+                        self.palette_open = false;
+                        self.show_toast("Resyncing clips and harmony", cx);
+                    }
+                    PaletteCommandId::SetTempo => {
+                        self.open_tempo(cx);
+                    }
+                    PaletteCommandId::JoinNotes => {
+                        self.palette_open = false;
+                        self.join_notes_at_cursor(cx);
+                    }
+                    PaletteCommandId::SetHarmonyRange => {
+                        self.palette_open = false;
+                        if self.selection_bounds().is_none() {
+                            self.show_toast("No selection", cx);
+                            return;
+                        }
+                        self.open_scale_browser(cx);
+                        self.scale_browser_range = true;
+                    }
+                    PaletteCommandId::DuplicateHarmony => {
+                        self.palette_open = false;
+                        self.duplicate_harmony_progression(cx);
+                    }
+                    PaletteCommandId::RotateSelectionDown => {
+                        self.palette_open = false;
+                        self.rotate_selection(1, cx);
+                    }
+                    PaletteCommandId::RotateSelectionUp => {
+                        self.palette_open = false;
+                        self.rotate_selection(-1, cx);
+                    }
+                    PaletteCommandId::CycleVelocityDigitScale => {
+                        self.palette_open = false;
+                        self.cycle_velocity_digit_scale(cx);
+                    }
+                    PaletteCommandId::LegatoColumn => {
+                        self.palette_open = false;
+                        self.legato_column(self.focused_track_index, self.cursor_col as u8, cx);
+                    }
+                    PaletteCommandId::LoopWholeTimeline => {
+                        self.palette_open = false;
+                        self.loop_whole_timeline(cx);
+                    }
+                    PaletteCommandId::ToggleMinimapBarNumbers => {
+                        self.palette_open = false;
+                        self.toggle_minimap_bar_numbers(cx);
+                    }
+                    PaletteCommandId::BakeSelectionToNotes => {
+                        self.palette_open = false;
+                        self.bake_selection_to_notes(cx);
+                    }
+                    PaletteCommandId::SnapLoopToBars => {
+                        self.palette_open = false;
+                        self.snap_loop_to_bars(cx);
+                    }
+                    PaletteCommandId::SwapTrackWithNext => {
+                        self.palette_open = false;
+                        let track = self.focused_track_index;
+                        if track + 1 < TRACK_COUNT {
+                            self.swap_tracks(track, track + 1, cx);
+                            self.focused_track_index = track + 1;
+                        }
+                    }
+                    PaletteCommandId::ToggleRests => {
+                        self.palette_open = false;
+                        self.toggle_rests(cx);
+                    }
+                    PaletteCommandId::TransposeProjectUp => {
+                        self.palette_open = false;
+                        self.transpose_project(1, cx);
+                    }
+                    PaletteCommandId::TransposeProjectDown => {
+                        self.palette_open = false;
+                        self.transpose_project(-1, cx);
+                    }
+                    PaletteCommandId::CollapseHarmonyChanges => {
+                        self.palette_open = false;
+                        self.collapse_harmony_changes(cx);
+                    }
+                    PaletteCommandId::MirrorSelectionToTrack => {
+                        self.open_mirror(cx);
+                    }
+                    PaletteCommandId::ApplyGrooveSwing58
+                    | PaletteCommandId::ApplyGrooveSwing66 => {
+                        self.palette_open = false;
+                        let template = if command.id == PaletteCommandId::ApplyGrooveSwing58 {
+                            GROOVE_TEMPLATES[0]
+                        } else {
+                            GROOVE_TEMPLATES[1]
+                        };
+                        let track = self.focused_track_index;
+                        self.apply_groove(track, &template, cx);
+                    }
+                    PaletteCommandId::SetChordsOneRow => {
+                        self.palette_open = false;
+                        self.set_selection_chord_duration(1, cx);
+                    }
+                    PaletteCommandId::SetChordsFourRows => {
+                        self.palette_open = false;
+                        self.set_selection_chord_duration(4, cx);
+                    }
+                    PaletteCommandId::RandomizeInversions => {
+                        self.palette_open = false;
+                        self.randomize_selection_inversions(cx);
+                    }
+                    PaletteCommandId::FillBarsWithHarmony => {
+                        self.open_bar_harmony(cx);
+                    }
+                    PaletteCommandId::TrimTrackToLoop => {
+                        self.palette_open = false;
+                        let track = self.focused_track_index;
+                        self.trim_to_loop(Some(track), cx);
+                    }
+                    PaletteCommandId::DelayTrack => {
+                        self.palette_open = false;
+                        self.nudge_track_time_offset(1, cx);
+                    }
+                    PaletteCommandId::AdvanceTrack => {
+                        self.palette_open = false;
+                        self.nudge_track_time_offset(-1, cx);
+                    }
+                    PaletteCommandId::WriteSustainedChord => {
+                        self.open_sustain_chord(cx);
+                    }
+                    PaletteCommandId::NameSelectionChord => {
+                        self.palette_open = false;
+                        self.report_selection_chord(cx);
+                    }
+                    PaletteCommandId::ClampTrackDurations => {
+                        self.palette_open = false;
+                        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+                        let max_rows = bar / self.row_nanoticks();
+                        self.clamp_durations(self.focused_track_index, max_rows, cx);
+                    }
+                    PaletteCommandId::StashRegion => {
+                        self.open_stash_prompt(StashPrompt::Stash, cx);
+                    }
+                    PaletteCommandId::SwapStash => {
+                        self.open_stash_prompt(StashPrompt::Swap, cx);
+                    }
+                    PaletteCommandId::FitTrackColumns => {
+                        self.palette_open = false;
+                        self.fit_track_columns(self.focused_track_index, cx);
+                    }
+                    PaletteCommandId::MoveColumnLeft => {
+                        self.palette_open = false;
+                        self.shift_focused_column(-1, cx);
+                    }
+                    PaletteCommandId::MoveColumnRight => {
+                        self.palette_open = false;
+                        self.shift_focused_column(1, cx);
+                    }
+                    PaletteCommandId::WriteEuclideanPattern => {
+                        self.open_euclidean(cx);
+                    }
+                    PaletteCommandId::ReportOutOfScaleNotes => {
+                        self.palette_open = false;
+                        self.report_out_of_scale_notes(cx);
+                    }
+                    PaletteCommandId::TrimAllTracksToLoop => {
+                        self.palette_open = false;
+                        self.trim_to_loop(None, cx);
+                    }
+                    PaletteCommandId::CycleQuantizeStrength => {
+                        let track = self.focused_track_index;
+                        let current = self.track_quantize_strength[track];
+                        let next = if current == 0 {
+                            MAX_QUANTIZE_STRENGTH
+                        } else {
+                            current.saturating_sub(QUANTIZE_STRENGTH_STEP)
+                        };
+                        self.set_track_quantize_strength(track, next);
+                        self.palette_open = false;
+                        self.show_toast(&format!("Input quantize: {next}%"), cx);
+                    }
+                }
+            }
+            PaletteMode::Plugins => {
+                let filtered = self.filtered_plugin_indices();
+                if filtered.is_empty() {
+                    return;
+                }
+                let selection = self.palette_selection.min(filtered.len() - 1);
+                let index = filtered[selection];
+                let plugin = self.plugins[index].clone();
+
+                if self.bridge.is_some() {
+                    match self.chain_add_mode {
+                        Some(ChainAddMode::Effect) => {
+                            let payload = UiChainCommandPayload {
+                                command_type: UiCommandType::AddDevice as u16,
+                                flags: 0,
+                                track_id: self.focused_track_index as u32,
+                                base_version: self.chain_versions[self.focused_track_index],
+                                device_id: K_CHAIN_DEVICE_ID_AUTO,
+                                device_kind: 4,
+                                insert_index: K_CHAIN_DEVICE_ID_AUTO,
+                                patcher_node_id: 0,
+                                host_slot_index: plugin.index as u32,
+                                bypass: 0,
+                                reserved: [0; 4],
+                            };
+                            self.enqueue_chain_payload(payload);
+                        }
+                        _ => {
+                            let payload = UiCommandPayload {
+                                command_type: UiCommandType::LoadPluginOnTrack as u16,
+                                flags: 0,
+                                track_id: self.focused_track_index as u32,
+                                plugin_index: plugin.index as u32,
+                                note_pitch: 0,
+                                value0: 0,
+                                note_nanotick_lo: 0,
+                                note_nanotick_hi: 0,
+                                note_duration_lo: 0,
+                                note_duration_hi: 0,
+                                base_version: 0,
+                            };
+                            self.enqueue_ui_command(payload);
+                            if self.focused_track_index < self.track_names.len() {
+                                self.track_names[self.focused_track_index] =
+                                    Some(plugin.name.clone());
+                            }
+                        }
+                    }
+                } else {
+                    // No-op until engine reconnects.
+                }
+
+                self.chain_add_mode = None;
+                self.palette_open = false;
+                cx.notify();
+            }
+        }
+    }
+
+    pub fn handle_keystroke(&mut self, keystroke: &gpui::Keystroke, cx: &mut impl UiNotify) {
+        if keystroke.modifiers.control ||
+            keystroke.modifiers.platform ||
+            keystroke.modifiers.function {
+            return;
+        }
+        let key_char = if keystroke.modifiers.alt &&
+            keystroke.key.len() == 1 &&
+            keystroke.key.chars().next().map_or(false, |ch| ch.is_ascii_digit()) {
+            Some(keystroke.key.as_str())
+        } else {
+            keystroke_text(keystroke)
+        };
+        if self.scale_browser_open {
             if let Some(key_char) = key_char {
                 self.append_scale_query(key_char, cx);
             }
@@ -663,6 +1413,66 @@ impl EngineView {
             }
             return;
         }
+        if self.tempo_open {
+            if let Some(key_char) = key_char {
+                if is_tempo_char(key_char) {
+                    self.tempo_text.push_str(key_char);
+                    cx.notify();
+                }
+            }
+            return;
+        }
+        if self.euclidean_open {
+            if let Some(key_char) = key_char {
+                if key_char
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '#' || ch == '-' || ch == ',')
+                {
+                    self.euclidean_text.push_str(key_char);
+                    cx.notify();
+                }
+            }
+            return;
+        }
+        if self.sustain_chord_open {
+            if let Some(key_char) = key_char {
+                if key_char.chars().all(|ch| ch.is_ascii_graphic()) {
+                    self.sustain_chord_text.push_str(key_char);
+                    cx.notify();
+                }
+            }
+            return;
+        }
+        if self.bar_harmony_open {
+            if let Some(key_char) = key_char {
+                if key_char
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphabetic() || ch == '#' || ch == ',')
+                {
+                    self.bar_harmony_text.push_str(key_char);
+                    cx.notify();
+                }
+            }
+            return;
+        }
+        if self.mirror_open {
+            if let Some(key_char) = key_char {
+                if key_char.chars().all(|ch| ch.is_ascii_digit()) {
+                    self.mirror_text.push_str(key_char);
+                    cx.notify();
+                }
+            }
+            return;
+        }
+        if self.stash_prompt.is_some() {
+            if let Some(key_char) = key_char {
+                if key_char.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-') {
+                    self.stash_name_text.push_str(key_char);
+                    cx.notify();
+                }
+            }
+            return;
+        }
         if self.preset_save_open {
             if let Some(key_char) = key_char {
                 if key_char.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-') {
@@ -694,8 +1504,20 @@ impl EngineView {
                 }
                 return;
             }
+            if self.velocity_entry && key_char.len() == 1 &&
+                key_char.chars().all(|ch| ch.is_ascii_digit()) {
+                self.push_velocity_digit(key_char, cx);
+                return;
+            }
             if let Some(degree) = degree_for_digit(key_char) {
-                if keystroke.modifiers.alt {
+                let chromatic = keystroke.modifiers.alt != self.chromatic_digits;
+                if self.diatonic_entry && !chromatic {
+                    if degree <= 7 {
+                        self.write_diatonic_note(degree, cx);
+                    }
+                    return;
+                }
+                if chromatic {
                     if let Some(pitch) = pitch_for_key(key_char) {
                         self.write_note(pitch, cx);
                     }
@@ -730,8 +1552,11 @@ impl EngineView {
             self.move_scale_selection(-1, cx);
         } else if self.palette_open {
             self.move_selection(-1, cx);
+        } else if self.velocity_focus {
+            self.nudge_focused_velocity(VELOCITY_FOCUS_STEP, cx);
         } else {
             self.move_cursor_row(-1, cx);
+            self.audition_harmony_change(cx);
             cx.notify(); // Cursor movement needs explicit notify
         }
     }
@@ -741,8 +1566,11 @@ impl EngineView {
             self.move_scale_selection(1, cx);
         } else if self.palette_open {
             self.move_selection(1, cx);
+        } else if self.velocity_focus {
+            self.nudge_focused_velocity(-VELOCITY_FOCUS_STEP, cx);
         } else {
             self.move_cursor_row(1, cx);
+            self.audition_harmony_change(cx);
             cx.notify(); // Cursor movement needs explicit notify
         }
     }
@@ -769,6 +1597,48 @@ impl EngineView {
                 self.jump_text.pop();
                 cx.notify();
             }
+        } else if self.tempo_open {
+            if self.tempo_text.is_empty() {
+                self.close_tempo(cx);
+            } else {
+                self.tempo_text.pop();
+                cx.notify();
+            }
+        } else if self.bar_harmony_open {
+            if self.bar_harmony_text.is_empty() {
+                self.close_bar_harmony(cx);
+            } else {
+                self.bar_harmony_text.pop();
+                cx.notify();
+            }
+        } else if self.euclidean_open {
+            if self.euclidean_text.is_empty() {
+                self.close_euclidean(cx);
+            } else {
+                self.euclidean_text.pop();
+                cx.notify();
+            }
+        } else if self.sustain_chord_open {
+            if self.sustain_chord_text.is_empty() {
+                self.close_sustain_chord(cx);
+            } else {
+                self.sustain_chord_text.pop();
+                cx.notify();
+            }
+        } else if self.mirror_open {
+            if self.mirror_text.is_empty() {
+                self.close_mirror(cx);
+            } else {
+                self.mirror_text.pop();
+                cx.notify();
+            }
+        } else if self.stash_prompt.is_some() {
+            if self.stash_name_text.is_empty() {
+                self.close_stash_prompt(cx);
+            } else {
+                self.stash_name_text.pop();
+                cx.notify();
+            }
         } else if self.preset_save_open {
             if self.preset_save_text.is_empty() {
                 self.preset_save_open = false;
@@ -795,6 +1665,18 @@ impl EngineView {
             self.commit_cell_edit(cx);
         } else if self.jump_open {
             self.confirm_jump(cx);
+        } else if self.tempo_open {
+            self.confirm_tempo(cx);
+        } else if self.mirror_open {
+            self.confirm_mirror(cx);
+        } else if self.bar_harmony_open {
+            self.confirm_bar_harmony(cx);
+        } else if self.euclidean_open {
+            self.confirm_euclidean(cx);
+        } else if self.sustain_chord_open {
+            self.confirm_sustain_chord(cx);
+        } else if self.stash_prompt.is_some() {
+            self.confirm_stash_prompt(cx);
         } else if self.preset_save_open {
             self.commit_preset_save(cx);
         } else if self.scale_browser_open {
@@ -808,9 +1690,6 @@ impl EngineView {
 
     fn move_cursor_row(&mut self, delta: i64, cx: &mut impl UiNotify) {
         let row_nanoticks = self.row_nanoticks() as i64;
-        if row_nanoticks <= 0 {
-            return;
-        }
         let current = self.cursor_nanotick as i64;
         let mut next = current + delta * row_nanoticks;
         if next < 0 {
@@ -827,8 +1706,7 @@ impl EngineView {
     fn move_cursor_or_focus(&mut self, delta: i32, _cx: &mut impl UiNotify) {
         if self.harmony_focus {
             if delta > 0 {
-                self.harmony_focus = false;
-                self.cursor_col = 0;
+                self.leave_harmony_focus();
             }
             self.clear_edit_state();
             return;
@@ -849,16 +1727,30 @@ impl EngineView {
             let next_columns = self.track_columns[self.focused_track_index];
             self.cursor_col = next_columns.saturating_sub(1);
         } else {
-            self.harmony_focus = true;
+            self.enter_harmony_focus();
         }
         self.clear_edit_state();
     }
 
+    fn enter_harmony_focus(&mut self) {
+        if !self.harmony_focus {
+            self.harmony_return_cell = (self.focused_track_index, self.cursor_col);
+        }
+        self.harmony_focus = true;
+    }
+
+    /// Drops harmony focus and puts the cursor back on the note cell it left,
+    /// clamped in case the track lost columns meanwhile.
+    fn leave_harmony_focus(&mut self) {
+        self.harmony_focus = false;
+        let (track, column) = self.harmony_return_cell;
+        self.focused_track_index = track.min(TRACK_COUNT - 1);
+        let columns = self.track_columns[self.focused_track_index];
+        self.cursor_col = column.min(columns.saturating_sub(1));
+    }
+
     fn scroll_rows(&mut self, delta: i64, cx: &mut impl UiNotify) {
         let row_nanoticks = self.row_nanoticks() as i64;
-        if row_nanoticks <= 0 {
-            return;
-        }
         let next = (self.scroll_nanotick_offset + delta * row_nanoticks).max(0);
         if next != self.scroll_nanotick_offset {
             self.scroll_nanotick_offset = next;
@@ -876,35 +1768,168 @@ impl EngineView {
         }
     }
 
-    fn zoom_by(&mut self, delta: i32, cx: &mut impl UiNotify) {
-        let max_index = ZOOM_LEVELS.len().saturating_sub(1) as i32;
-        let next = (self.zoom_index as i32 + delta).clamp(0, max_index) as usize;
-        if next == self.zoom_index {
-            return;
-        }
-        let cursor_view_row = self.cursor_view_row();
-        self.zoom_index = next;
-        let row_nanoticks = self.row_nanoticks() as i64;
-        self.scroll_nanotick_offset =
-            self.cursor_nanotick as i64 - cursor_view_row * row_nanoticks;
-        if self.scroll_nanotick_offset < 0 {
-            self.scroll_nanotick_offset = 0;
+    pub(crate) fn window_layout(&self) -> WindowLayout {
+        WindowLayout {
+            zoom_index: self.zoom_index,
+            scroll_nanotick_offset: self.scroll_nanotick_offset,
+            cursor_nanotick: self.cursor_nanotick,
+            focused_track_index: self.focused_track_index,
+            follow_playhead: self.follow_playhead,
         }
-        self.ensure_cursor_visible();
-        self.follow_playhead = false;
+    }
+
+    pub(crate) fn apply_window_layout(&mut self, layout: &WindowLayout, cx: &mut impl UiNotify) {
+        self.zoom_index = layout.zoom_index.min(ZOOM_LEVELS.len() - 1);
+        self.scroll_nanotick_offset = layout.scroll_nanotick_offset.max(0);
+        self.cursor_nanotick = layout.cursor_nanotick;
+        self.focused_track_index = layout.focused_track_index.min(TRACK_COUNT - 1);
+        self.cursor_col = self
+            .cursor_col
+            .min(self.track_columns[self.focused_track_index].saturating_sub(1));
+        self.follow_playhead = layout.follow_playhead;
         cx.notify();
     }
 
-    pub(crate) fn handle_scroll_wheel(
+    pub(crate) fn project_markers(&self) -> ProjectMarkers {
+        ProjectMarkers {
+            markers: self
+                .markers
+                .iter()
+                .map(|(nanotick, name)| ProjectMarker {
+                    nanotick: *nanotick,
+                    name: name.clone(),
+                })
+                .collect(),
+            ..ProjectMarkers::default()
+        }
+    }
+
+    pub(crate) fn apply_project_markers(
         &mut self,
-        event: &ScrollWheelEvent,
+        markers: &ProjectMarkers,
         cx: &mut impl UiNotify,
     ) {
-        let delta_pixels = event.delta.pixel_delta(px(ROW_HEIGHT));
-        let line_delta = delta_pixels.y / px(ROW_HEIGHT);
-        if line_delta == 0.0 {
-            return;
-        }
+        self.markers = markers
+            .markers
+            .iter()
+            .map(|marker| (marker.nanotick, marker.name.clone()))
+            .collect();
+        self.markers.sort_by_key(|(nanotick, _)| *nanotick);
+        self.markers.dedup_by_key(|(nanotick, _)| *nanotick);
+        cx.notify();
+    }
+
+    /// Drops a numbered marker on the cursor row, keeping markers sorted.
+    fn add_marker_at_cursor(&mut self, cx: &mut impl UiNotify) {
+        let nanotick = self.current_row_nanotick();
+        let index = match self.markers.binary_search_by_key(&nanotick, |(tick, _)| *tick) {
+            Ok(_) => {
+                self.show_toast("Marker already here", cx);
+                return;
+            }
+            Err(index) => index,
+        };
+        let name = format!("M{}", self.markers.len() + 1);
+        self.show_toast(&format!("Added marker {name}"), cx);
+        self.markers.insert(index, (nanotick, name));
+    }
+
+    /// Jumps to the nearest marker after (`forward`) or before the cursor row.
+    fn jump_to_marker(&mut self, forward: bool, cx: &mut impl UiNotify) {
+        let cursor = self.current_row_nanotick();
+        let target = if forward {
+            self.markers.iter().find(|(tick, _)| *tick > cursor)
+        } else {
+            self.markers.iter().rev().find(|(tick, _)| *tick < cursor)
+        };
+        let Some((nanotick, name)) = target.cloned() else {
+            self.show_toast("No marker", cx);
+            return;
+        };
+        self.jump_to_nanotick(nanotick, cx);
+        self.show_toast(&name, cx);
+    }
+
+    /// Writes the last keyjazz pitch on every `stride_rows`-th row of the
+    /// cursor's bar in the focused column. Returns the number of notes written.
+    fn fill_bar_with_note(&mut self, stride_rows: u64, cx: &mut impl UiNotify) -> usize {
+        if self.read_only_blocked(cx) {
+            return 0;
+        }
+        let bar_len = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        let row = self.row_nanoticks();
+        let step = row.saturating_mul(stride_rows.max(1));
+        let bar_start = self.current_row_nanotick() / bar_len * bar_len;
+        let track = self.focused_track_index;
+        let column = self.cursor_col as u8;
+        let pitch = self.keyjazz_pitch;
+        let mut written = 0;
+        let mut nanotick = bar_start;
+        while nanotick < bar_start + bar_len {
+            self.write_note_at(track, column, nanotick, pitch, 100, row, cx);
+            written += 1;
+            nanotick += step;
+        }
+        self.show_toast(&format!("Filled bar with {written} notes"), cx);
+        written
+    }
+
+    /// Marker whose position falls inside `start..end`, if any.
+    pub(crate) fn marker_in_range(&self, start: u64, end: u64) -> Option<&str> {
+        self.markers
+            .iter()
+            .find(|(tick, _)| *tick >= start && *tick < end)
+            .map(|(_, name)| name.as_str())
+    }
+
+    fn zoom_by(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        let max_index = ZOOM_LEVELS.len().saturating_sub(1) as i32;
+        let next = (self.zoom_index as i32 + delta).clamp(0, max_index) as usize;
+        if next == self.zoom_index {
+            return;
+        }
+        let cursor_view_row = self.cursor_view_row();
+        self.zoom_index = next;
+        let row_nanoticks = self.row_nanoticks() as i64;
+        self.scroll_nanotick_offset =
+            self.cursor_nanotick as i64 - cursor_view_row * row_nanoticks;
+        if self.scroll_nanotick_offset < 0 {
+            self.scroll_nanotick_offset = 0;
+        }
+        self.ensure_cursor_visible();
+        self.follow_playhead = false;
+        cx.notify();
+    }
+
+    /// Like `zoom_by`, but keeps the playhead at the same height on screen
+    /// instead of the cursor, so zooming mid-playback does not jump the view.
+    fn zoom_by_at_playhead(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        let max_index = ZOOM_LEVELS.len().saturating_sub(1) as i32;
+        let next = (self.zoom_index as i32 + delta).clamp(0, max_index) as usize;
+        if next == self.zoom_index {
+            return;
+        }
+        let old_row = self.row_nanoticks();
+        self.zoom_index = next;
+        self.scroll_nanotick_offset = playhead_anchored_scroll(
+            self.snapshot.ui_global_nanotick_playhead,
+            self.scroll_nanotick_offset,
+            old_row,
+            self.row_nanoticks(),
+        );
+        cx.notify();
+    }
+
+    pub(crate) fn handle_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        cx: &mut impl UiNotify,
+    ) {
+        let delta_pixels = event.delta.pixel_delta(px(ROW_HEIGHT));
+        let line_delta = delta_pixels.y / px(ROW_HEIGHT);
+        if line_delta == 0.0 {
+            return;
+        }
         let step = if line_delta.abs() < 1.0 {
             line_delta.signum()
         } else {
@@ -943,12 +1968,37 @@ impl EngineView {
     }
 
     fn confirm_jump(&mut self, cx: &mut impl UiNotify) {
+        if let Some(samples) = self.jump_text.trim().strip_prefix('s') {
+            let nanotick = samples
+                .parse::<u64>()
+                .ok()
+                .and_then(|samples| {
+                    samples_to_nanotick(samples, self.tempo_bpm, self.engine_sample_rate())
+                });
+            if let Some(nanotick) = nanotick {
+                self.jump_to_nanotick(nanotick, cx);
+                self.close_jump(cx);
+            } else {
+                self.show_toast("Invalid sample position", cx);
+            }
+            return;
+        }
         if let Some(nanotick) = parse_jump_text(&self.jump_text) {
             self.jump_to_nanotick(nanotick, cx);
             self.close_jump(cx);
+        } else if !self.jump_text.trim().is_empty() {
+            self.show_toast(&format!("Invalid position (max bar {MAX_JUMP_BAR})"), cx);
         }
     }
 
+    fn engine_sample_rate(&self) -> f64 {
+        self.bridge
+            .as_ref()
+            .map(|bridge| bridge.sample_rate())
+            .filter(|rate| *rate > 0.0)
+            .unwrap_or(DEFAULT_SAMPLE_RATE)
+    }
+
     pub(crate) fn show_toast(&mut self, message: &str, cx: &mut impl UiNotify) {
         self.toast_message = Some(message.to_string());
         self.toast_deadline = Some(Instant::now() + Duration::from_millis(1200));
@@ -957,9 +2007,6 @@ impl EngineView {
 
     fn set_loop_from_selection_or_page(&mut self, cx: &mut impl UiNotify) {
         let row_nanoticks = self.row_nanoticks();
-        if row_nanoticks == 0 {
-            return;
-        }
         let (start, end) = if let Some((start, end)) = self.selection_bounds() {
             (start, end.saturating_add(row_nanoticks))
         } else {
@@ -973,6 +2020,27 @@ impl EngineView {
         self.set_loop_range(start, end, cx);
     }
 
+    /// Rounds both ends of the loop (or, without one, the selection) to the
+    /// nearest bar line and applies the result as the loop.
+    fn snap_loop_to_bars(&mut self, cx: &mut impl UiNotify) {
+        let range = self.loop_range.or_else(|| {
+            self.selection_bounds()
+                .map(|(start, end)| (start, end.saturating_add(self.row_nanoticks())))
+        });
+        let Some((start, end)) = range else {
+            self.show_toast("No loop or selection", cx);
+            return;
+        };
+        let (start, end) = snap_range_to_bars(start, end);
+        self.set_loop_range(start, end, cx);
+    }
+
+    /// Loops from the start of the song to the end of its last event.
+    fn loop_whole_timeline(&mut self, cx: &mut impl UiNotify) {
+        let end = self.timeline_end_nanotick();
+        self.set_loop_range(0, end, cx);
+    }
+
     pub fn set_loop_range(&mut self, start: u64, end: u64, cx: &mut impl UiNotify) {
         self.loop_range = Some((start, end));
         if self.bridge.is_none() {
@@ -1340,7 +2408,62 @@ impl EngineView {
         cx.notify();
     }
 
+    /// Serializes the harmony timeline for the system clipboard. Returns None
+    /// (with a toast) when there is nothing to copy.
+    fn harmony_text_for_clipboard(&mut self, cx: &mut impl UiNotify) -> Option<String> {
+        if self.harmony_events.is_empty() {
+            self.show_toast("No harmony to copy", cx);
+            return None;
+        }
+        let text = harmony_chart_text(&self.harmony_events);
+        self.show_toast("Harmony copied", cx);
+        Some(text)
+    }
+
+    /// Chord chart of the harmony timeline for the system clipboard. Returns
+    /// None (with a toast) when there is no harmony.
+    fn chord_chart_for_clipboard(&mut self, cx: &mut impl UiNotify) -> Option<String> {
+        if self.harmony_events.is_empty() {
+            self.show_toast("No harmony to copy", cx);
+            return None;
+        }
+        let text = chord_chart_text(&self.harmony_events);
+        self.show_toast("Chord chart copied", cx);
+        Some(text)
+    }
+
+    /// Step pattern of the first Euclidean node on each track that has one.
+    /// The UI never edits node configs, so every node plays the defaults that
+    /// `patcher_euclidean_pattern` reports for a null config.
+    pub(crate) fn euclidean_node_patterns(&self) -> Vec<(usize, Vec<u8>)> {
+        self.patcher_nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, nodes)| {
+                nodes
+                    .iter()
+                    .any(|node| node.node_type == PATCHER_NODE_EUCLIDEAN)
+            })
+            .map(|(track, _)| (track, euclidean_pattern(None)))
+            .collect()
+    }
+
+    /// ASCII grid of the loaded Euclidean node patterns for the system
+    /// clipboard. Returns None (with a toast) when no track has one.
+    fn euclidean_grid_for_clipboard(&mut self, cx: &mut impl UiNotify) -> Option<String> {
+        let patterns = self.euclidean_node_patterns();
+        if patterns.is_empty() {
+            self.show_toast("No Euclidean nodes to copy", cx);
+            return None;
+        }
+        self.show_toast("Euclidean grid copied", cx);
+        Some(euclidean_grid_text(&patterns))
+    }
+
     fn cut_selection(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
         let Some((start, end)) = self.selection_bounds() else {
             self.show_toast("No selection", cx);
             return;
@@ -1352,7 +2475,97 @@ impl EngineView {
         cx.notify();
     }
 
+    fn open_stash_prompt(&mut self, mode: StashPrompt, cx: &mut impl UiNotify) {
+        self.palette_open = false;
+        if mode == StashPrompt::Stash && self.selection_bounds().is_none() {
+            self.show_toast("No selection", cx);
+            return;
+        }
+        self.edit_active = false;
+        self.edit_text.clear();
+        self.stash_prompt = Some(mode);
+        self.stash_name_text.clear();
+        cx.notify();
+    }
+
+    fn close_stash_prompt(&mut self, cx: &mut impl UiNotify) {
+        if self.stash_prompt.take().is_some() {
+            self.stash_name_text.clear();
+            cx.notify();
+        }
+    }
+
+    fn confirm_stash_prompt(&mut self, cx: &mut impl UiNotify) {
+        let name = self.stash_name_text.trim().to_string();
+        if name.is_empty() {
+            self.show_toast("Take name empty", cx);
+            return;
+        }
+        let Some(mode) = self.stash_prompt else {
+            return;
+        };
+        self.close_stash_prompt(cx);
+        match mode {
+            StashPrompt::Stash => self.stash_region(&name, cx),
+            StashPrompt::Swap => self.swap_stash(&name, cx),
+        }
+    }
+
+    /// Keeps a copy of the selected notes and chords as an alternate take
+    /// named `name`, replacing an earlier take of that name. The tracker
+    /// content is left as is.
+    fn stash_region(&mut self, name: &str, cx: &mut impl UiNotify) {
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return;
+        };
+        let mut mask = self.selection_mask.clone();
+        mask.harmony = false;
+        let content = self.build_clipboard(start, end, &mask, true);
+        self.region_stashes.insert(
+            name.to_string(),
+            RegionStash {
+                start,
+                end,
+                mask,
+                content,
+            },
+        );
+        self.show_toast(&format!("Take '{name}' stashed"), cx);
+    }
+
+    /// Exchanges the take named `name` with what is now in its region, so
+    /// calling it again brings the other take back.
+    fn swap_stash(&mut self, name: &str, cx: &mut impl UiNotify) {
+        let Some(stash) = self.region_stashes.get(name).cloned() else {
+            self.show_toast(&format!("No stashed take '{name}'"), cx);
+            return;
+        };
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let current = self.build_clipboard(stash.start, stash.end, &stash.mask, true);
+        self.delete_range(stash.start, stash.end, &stash.mask, cx);
+        self.paste_clipboard_at(&stash.content, stash.start, cx);
+        self.region_stashes.insert(
+            name.to_string(),
+            RegionStash {
+                content: current,
+                ..stash
+            },
+        );
+        self.show_toast(&format!("Swapped take '{name}'"), cx);
+    }
+
+    fn clear_clipboard(&mut self, cx: &mut impl UiNotify) {
+        self.clipboard = None;
+        self.show_toast("Clipboard cleared", cx);
+    }
+
     fn paste_selection(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
         let Some(clipboard) = self.clipboard.clone() else {
             self.show_toast("Clipboard empty", cx);
             return;
@@ -1374,6 +2587,9 @@ impl EngineView {
     }
 
     fn cut_page(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
         let (start, end) = self.page_range();
         let mut mask = SelectionMask::empty(TRACK_COUNT);
         let columns = self.track_columns[self.focused_track_index].min(MAX_NOTE_COLUMNS);
@@ -1387,6 +2603,9 @@ impl EngineView {
     }
 
     fn paste_page(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
         let Some(clipboard) = self.clipboard.clone() else {
             self.show_toast("Clipboard empty", cx);
             return;
@@ -1401,18 +2620,16 @@ impl EngineView {
         target_start: u64,
         cx: &mut impl UiNotify,
     ) {
+        // Clamp the paste origin so the earliest item never lands before 0;
+        // the whole block shifts later instead of losing leading items.
+        let earliest = clipboard.earliest_offset().unwrap_or(0);
+        let origin = (target_start as i64).max(-earliest);
         for harmony in &clipboard.harmonies {
-            let target = target_start as i64 + harmony.offset;
-            if target < 0 {
-                continue;
-            }
+            let target = origin + harmony.offset;
             self.write_harmony_at(target as u64, harmony.root, harmony.scale_id, cx);
         }
         for note in &clipboard.notes {
-            let target = target_start as i64 + note.offset;
-            if target < 0 {
-                continue;
-            }
+            let target = origin + note.offset;
             self.write_note_at(
                 note.track,
                 note.column,
@@ -1424,10 +2641,7 @@ impl EngineView {
             );
         }
         for chord in &clipboard.chords {
-            let target = target_start as i64 + chord.offset;
-            if target < 0 {
-                continue;
-            }
+            let target = origin + chord.offset;
             self.write_chord_at(
                 chord.track,
                 chord.column,
@@ -1446,2762 +2660,7967 @@ impl EngineView {
         cx.notify();
     }
 
-    fn delete_range(
-        &mut self,
-        start: u64,
-        end: u64,
-        mask: &SelectionMask,
-        cx: &mut impl UiNotify,
-    ) {
-        let notes = self.collect_notes_in_range(start, end, mask, true);
-        let chords = self.collect_chords_in_range(start, end, mask, false);
-        let harmonies = self.collect_harmony_in_range(start, end, mask);
-
-        for (track, note) in notes {
-            self.delete_note_at(track, note.column, note.nanotick, note.pitch, cx);
+    /// Rewrites every selected chord to last `rows` rows at the current zoom,
+    /// keeping its degree, voicing and humanize settings.
+    pub(crate) fn set_selection_chord_duration(&mut self, rows: u64, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
         }
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return;
+        };
+        let duration = rows.max(1).saturating_mul(self.row_nanoticks());
+        let mask = self.selection_mask.clone();
+        let chords = self.collect_chords_in_range(start, end, &mask, true);
         for (track, chord) in chords {
-            if chord.chord_id != 0 {
-                self.delete_chord_at(track, chord.chord_id, chord.nanotick, chord.column, cx);
+            if chord.duration == duration {
+                continue;
             }
+            self.write_chord_at(
+                track,
+                chord.column,
+                chord.nanotick,
+                duration,
+                chord.degree,
+                chord.quality,
+                chord.inversion,
+                chord.base_octave,
+                chord.spread,
+                chord.humanize_timing,
+                chord.humanize_velocity,
+                cx,
+            );
         }
-        for harmony in harmonies {
-            self.delete_harmony_at(harmony.nanotick, cx);
-        }
-        self.pending_chords.retain(|pending| {
-            let track = pending.track_id as usize;
-            let column = pending.column as usize;
-            if track >= mask.tracks.len() ||
-                (mask.tracks[track] & (1u8 << column)) == 0 {
-                return true;
-            }
-            pending.nanotick < start || pending.nanotick > end
-        });
         cx.notify();
     }
 
-    fn toggle_follow_playhead(&mut self, cx: &mut impl UiNotify) {
-        self.follow_playhead = !self.follow_playhead;
+    /// Gives every selected chord an inversion picked by `seeded_inversion`,
+    /// so repeating the command on the same material gives the same voicings.
+    /// Plain degree notes have no inversion and are left alone.
+    fn randomize_selection_inversions(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return;
+        };
+        let mask = self.selection_mask.clone();
+        let chords = self.collect_chords_in_range(start, end, &mask, true);
+        for (track, chord) in chords {
+            if chord.quality == 0 {
+                continue;
+            }
+            let inversion = seeded_inversion(chord.nanotick);
+            if inversion == chord.inversion {
+                continue;
+            }
+            self.write_chord_at(
+                track,
+                chord.column,
+                chord.nanotick,
+                chord.duration,
+                chord.degree,
+                chord.quality,
+                inversion,
+                chord.base_octave,
+                chord.spread,
+                chord.humanize_timing,
+                chord.humanize_velocity,
+                cx,
+            );
+        }
         cx.notify();
     }
 
-    pub fn toggle_harmony_focus(&mut self, cx: &mut impl UiNotify) {
-        self.harmony_focus = !self.harmony_focus;
-        self.clear_edit_state();
-        cx.notify();
+    /// Selected notes that fall outside the harmony scale in force at their
+    /// position, in time order.
+    pub(crate) fn out_of_scale_notes_in_selection(&self) -> Vec<(usize, ClipNote)> {
+        let Some((start, end)) = self.selection_bounds() else {
+            return Vec::new();
+        };
+        let mut notes: Vec<(usize, ClipNote)> = self
+            .collect_notes_in_range(start, end, &self.selection_mask, true)
+            .into_iter()
+            .filter(|(_, note)| {
+                !scale_contains_pitch(
+                    note.pitch,
+                    self.harmony_root_at(note.nanotick),
+                    self.harmony_scale_at(note.nanotick),
+                )
+            })
+            .collect();
+        notes.sort_by_key(|(track, note)| (note.nanotick, *track, note.column));
+        notes
     }
 
-    pub fn move_column(&mut self, delta: i32, cx: &mut impl UiNotify) {
-        let columns = self.track_columns[self.focused_track_index];
-        let max_index = columns.saturating_sub(1) as i32;
-        let next = (self.cursor_col as i32 + delta).clamp(0, max_index);
-        self.cursor_col = next as usize;
-        self.clear_edit_state();
-        cx.notify();
+    /// Sounding pitches of the selected notes and degree chords, the chords
+    /// resolved under the harmony at their position.
+    pub(crate) fn selection_pitches(&self) -> Vec<u8> {
+        let Some((start, end)) = self.selection_bounds() else {
+            return Vec::new();
+        };
+        let mask = &self.selection_mask;
+        let mut pitches: Vec<u8> = self
+            .collect_notes_in_range(start, end, mask, true)
+            .into_iter()
+            .filter(|(_, note)| note.velocity > 0)
+            .map(|(_, note)| note.pitch)
+            .collect();
+        for (_, chord) in self.collect_chords_in_range(start, end, mask, true) {
+            pitches.extend(resolve_chord_pitches(
+                chord.degree,
+                chord.quality,
+                chord.inversion,
+                chord.base_octave,
+                self.harmony_root_at(chord.nanotick),
+                self.harmony_scale_at(chord.nanotick),
+            ));
+        }
+        pitches
     }
 
-    pub fn adjust_columns(&mut self, track: usize, delta: i32, cx: &mut impl UiNotify) {
-        if track >= self.track_columns.len() {
+    fn report_selection_chord(&mut self, cx: &mut impl UiNotify) {
+        if self.selection_bounds().is_none() {
+            self.show_toast("No selection", cx);
             return;
         }
-        let current = self.track_columns[track] as i32;
-        let next = (current + delta).clamp(1, MAX_NOTE_COLUMNS as i32) as usize;
-        self.track_columns[track] = next;
-        if self.focused_track_index == track {
-            self.cursor_col = self.cursor_col.min(next.saturating_sub(1));
+        let pitches = self.selection_pitches();
+        if pitches.is_empty() {
+            self.show_toast("No notes in selection", cx);
+            return;
+        }
+        match chord_name_for_pitches(&pitches) {
+            Some(name) => self.show_toast(&name, cx),
+            None => self.show_toast("No matching chord", cx),
         }
-        self.clear_edit_state();
-        cx.notify();
-    }
-
-    pub fn focus_harmony_row(&mut self, row: usize, cx: &mut impl UiNotify) {
-        let new_row = row.min(VISIBLE_ROWS - 1) as i64;
-        self.cursor_nanotick = self.view_row_nanotick(new_row);
-        self.harmony_focus = true;
-        self.chain_focus = false;
-        self.focused_chain_device_id = None;
-        self.edit_active = false;
-        self.edit_text.clear();
-        cx.notify();
     }
 
-    pub fn focus_note_cell(
-        &mut self,
-        row: usize,
-        track: usize,
-        column: usize,
-        cx: &mut impl UiNotify,
-    ) {
-        let new_row = row.min(VISIBLE_ROWS - 1) as i64;
-        self.cursor_nanotick = self.view_row_nanotick(new_row);
+    /// Toasts the selected notes that clash with the harmony and moves the
+    /// cursor to the first of them.
+    fn report_out_of_scale_notes(&mut self, cx: &mut impl UiNotify) {
+        if self.selection_bounds().is_none() {
+            self.show_toast("No selection", cx);
+            return;
+        }
+        let notes = self.out_of_scale_notes_in_selection();
+        let Some((track, first)) = notes.first().cloned() else {
+            self.show_toast("All selected notes are in scale", cx);
+            return;
+        };
+        let mut names: Vec<String> = notes
+            .iter()
+            .take(4)
+            .map(|(_, note)| pitch_to_note(note.pitch))
+            .collect();
+        if notes.len() > names.len() {
+            names.push("…".to_string());
+        }
+        let message = format!("{} out of scale: {}", notes.len(), names.join(" "));
         self.focused_track_index = track.min(TRACK_COUNT - 1);
-        let max_column = self.track_columns[self.focused_track_index]
-            .saturating_sub(1);
-        self.cursor_col = column.min(max_column);
+        self.cursor_col = first.column as usize;
+        self.cursor_nanotick = self.snap_nanotick_to_row(first.nanotick);
         self.harmony_focus = false;
-        self.chain_focus = false;
-        self.focused_chain_device_id = None;
         self.clear_edit_state();
-        cx.notify();
-    }
-
-    fn chain_selected_device(&self) -> Option<ChainDevice> {
-        let track = self.focused_track_index;
-        let device_id = self.focused_chain_device_id?;
-        self.chain_devices
-            .get(track)
-            .and_then(|devices| devices.iter().find(|d| d.id == device_id))
-            .cloned()
+        self.ensure_cursor_visible();
+        self.show_toast(&message, cx);
     }
 
-    pub(crate) fn select_chain_device(&mut self, device_id: u32, cx: &mut impl UiNotify) {
-        self.chain_focus = true;
-        self.focused_chain_device_id = Some(device_id);
-        self.harmony_focus = false;
-        self.clear_edit_state();
-        self.chain_add_open = false;
+    /// Moves selected notes by whole octaves and steps selected chords'
+    /// base octave, leaving degrees and voicing untouched.
+    fn shift_selection_octave(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return;
+        };
+        let mask = self.selection_mask.clone();
+        let notes = self.collect_notes_in_range(start, end, &mask, true);
+        let chords = self.collect_chords_in_range(start, end, &mask, true);
+        for (track, note) in notes {
+            if note.velocity == 0 && note.duration == 0 {
+                continue;
+            }
+            let pitch = (note.pitch as i32 + delta * 12).clamp(0, 127) as u8;
+            if pitch == note.pitch {
+                continue;
+            }
+            self.write_note_at(
+                track,
+                note.column,
+                note.nanotick,
+                pitch,
+                note.velocity,
+                note.duration,
+                cx,
+            );
+        }
+        for (track, chord) in chords {
+            let base_octave =
+                (chord.base_octave as i32 + delta).clamp(0, MAX_CHORD_BASE_OCTAVE as i32) as u8;
+            if base_octave == chord.base_octave {
+                continue;
+            }
+            self.write_chord_at(
+                track,
+                chord.column,
+                chord.nanotick,
+                chord.duration,
+                chord.degree,
+                chord.quality,
+                chord.inversion,
+                base_octave,
+                chord.spread,
+                chord.humanize_timing,
+                chord.humanize_velocity,
+                cx,
+            );
+        }
         cx.notify();
     }
 
-    pub(crate) fn open_patcher_view(&mut self, cx: &mut impl UiNotify) {
-        self.patcher_open = true;
-        self.patcher_track_id = self.focused_track_index as u32;
-        self.patcher_link_source = None;
-        self.patcher_link_target = None;
-        self.patcher_mouse_pos = None;
-        self.patcher_drag_node = None;
-        self.preset_save_open = false;
-        self.preset_save_text.clear();
-        cx.notify();
-    }
+    fn delete_range(
+        &mut self,
+        start: u64,
+        end: u64,
+        mask: &SelectionMask,
+        cx: &mut impl UiNotify,
+    ) {
+        let notes = self.collect_notes_in_range(start, end, mask, true);
+        let chords = self.collect_chords_in_range(start, end, mask, false);
+        let harmonies = self.collect_harmony_in_range(start, end, mask);
 
-    pub(crate) fn close_patcher_view(&mut self, cx: &mut impl UiNotify) {
-        self.patcher_open = false;
-        self.patcher_link_source = None;
-        self.patcher_link_target = None;
-        self.patcher_mouse_pos = None;
-        self.patcher_drag_node = None;
-        self.preset_save_open = false;
-        self.preset_save_text.clear();
+        for (track, note) in notes {
+            self.delete_note_at(track, note.column, note.nanotick, note.pitch, cx);
+        }
+        for (track, chord) in chords {
+            if chord.chord_id != 0 {
+                self.delete_chord_at(track, chord.chord_id, chord.nanotick, chord.column, cx);
+            }
+        }
+        for harmony in harmonies {
+            self.delete_harmony_at(harmony.nanotick, cx);
+        }
+        self.pending_chords.retain(|pending| {
+            let track = pending.track_id as usize;
+            let column = pending.column as usize;
+            if track >= mask.tracks.len() ||
+                (mask.tracks[track] & (1u8 << column)) == 0 {
+                return true;
+            }
+            pending.nanotick < start || pending.nanotick > end
+        });
         cx.notify();
     }
 
-    fn enqueue_patcher_graph_payload(&mut self, payload: UiPatcherGraphCommandPayload) {
-        self.enqueue_patcher_graph_command(payload);
-    }
-
-    fn enqueue_patcher_config_payload(&mut self, payload: UiPatcherNodeConfigPayload) {
-        self.enqueue_patcher_node_config(payload);
-    }
-
-    fn enqueue_patcher_preset_payload(&mut self, payload: UiPatcherPresetCommandPayload) {
-        self.enqueue_patcher_preset(payload);
+    /// Cyclically shifts the selected notes and chords by `steps` rows,
+    /// wrapping content that leaves the selection back to its other end.
+    fn rotate_selection(&mut self, steps: i64, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return;
+        };
+        let row = self.row_nanoticks();
+        let length = i128::from(end - start + row);
+        let shift = (i128::from(steps) * i128::from(row)).rem_euclid(length);
+        if shift == 0 {
+            return;
+        }
+        let mut mask = self.selection_mask.clone();
+        mask.harmony = false;
+        let mut clipboard = self.build_clipboard(start, end, &mask, true);
+        for note in &mut clipboard.notes {
+            note.offset = ((i128::from(note.offset) + shift) % length) as i64;
+        }
+        for chord in &mut clipboard.chords {
+            chord.offset = ((i128::from(chord.offset) + shift) % length) as i64;
+        }
+        self.delete_range(start, end, &mask, cx);
+        self.paste_clipboard_at(&clipboard, start, cx);
     }
 
-    pub(crate) fn add_patcher_node(&mut self, node_type: u32, cx: &mut impl UiNotify) {
-        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
-        let payload = UiPatcherGraphCommandPayload {
-            command_type: UiCommandType::AddPatcherNode as u16,
-            flags: 0,
-            track_id: track,
-            base_version: self.patcher_versions[track as usize],
-            node_id: 0,
-            node_type,
-            src_node_id: 0,
-            dst_node_id: 0,
-            src_port_id: 0,
-            dst_port_id: 0,
-            edge_kind: 0,
+    /// Rewrites the selected degree notes and chords as plain notes at the
+    /// pitches they currently resolve to, so later harmony edits leave them
+    /// alone. Chord tones spread rightward from the chord's column.
+    fn bake_selection_to_notes(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return;
         };
-        self.enqueue_patcher_graph_payload(payload);
-        cx.notify();
+        let mask = self.selection_mask.clone();
+        let chords = self.collect_chords_in_range(start, end, &mask, true);
+        if chords.is_empty() {
+            self.show_toast("No degree notes in selection", cx);
+            return;
+        }
+        let mut written = 0;
+        let mut dropped = 0;
+        for (track, chord) in chords {
+            let pitches = resolve_chord_pitches(
+                chord.degree,
+                chord.quality,
+                chord.inversion,
+                chord.base_octave,
+                self.harmony_root_at(chord.nanotick),
+                self.harmony_scale_at(chord.nanotick),
+            );
+            if chord.chord_id != 0 {
+                self.delete_chord_at(track, chord.chord_id, chord.nanotick, chord.column, cx);
+            }
+            for (tone, pitch) in pitches.into_iter().enumerate() {
+                let column = chord.column as usize + tone;
+                if column >= MAX_NOTE_COLUMNS {
+                    dropped += 1;
+                    continue;
+                }
+                if self.track_columns[track] <= column {
+                    self.track_columns[track] = column + 1;
+                }
+                self.write_note_at(
+                    track,
+                    column as u8,
+                    chord.nanotick,
+                    pitch,
+                    100,
+                    chord.duration,
+                    cx,
+                );
+                written += 1;
+            }
+        }
+        if dropped > 0 {
+            self.show_toast(&format!("Baked {written} notes ({dropped} past last column)"), cx);
+        } else {
+            self.show_toast(&format!("Baked {written} notes"), cx);
+        }
     }
 
-    pub(crate) fn remove_patcher_node(&mut self, node_id: u32, cx: &mut impl UiNotify) {
-        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
-        let payload = UiPatcherGraphCommandPayload {
-            command_type: UiCommandType::RemovePatcherNode as u16,
-            flags: 0,
-            track_id: track,
-            base_version: self.patcher_versions[track as usize],
-            node_id,
-            node_type: 0,
-            src_node_id: 0,
-            dst_node_id: 0,
-            src_port_id: 0,
-            dst_port_id: 0,
-            edge_kind: 0,
-        };
-        self.enqueue_patcher_graph_payload(payload);
-        cx.notify();
+    /// Re-assigns every note and chord in `from_col` of `track` to `to_col`,
+    /// rewriting each event once the whole clip has loaded. Both columns are
+    /// clamped to the track's column count. Returns the number of events
+    /// moved.
+    pub(crate) fn move_track_column(
+        &mut self,
+        track: usize,
+        from_col: usize,
+        to_col: usize,
+        cx: &mut impl UiNotify,
+    ) -> usize {
+        if track >= TRACK_COUNT || self.read_only_blocked(cx) {
+            return 0;
+        }
+        let last = self.track_columns[track].saturating_sub(1);
+        let (from_col, to_col) = (from_col.min(last), to_col.min(last));
+        if from_col == to_col {
+            return 0;
+        }
+        let edit = WholeClipEdit::MoveTrackColumn { track, from_col, to_col };
+        if !self.whole_clip_ready(edit, cx) {
+            return 0;
+        }
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.tracks[track] = 1u8 << from_col;
+        let mut clipboard = self.build_clipboard(0, u64::MAX, &mask, true);
+        for note in &mut clipboard.notes {
+            note.column = to_col as u8;
+        }
+        for chord in &mut clipboard.chords {
+            chord.column = to_col as u8;
+        }
+        self.delete_range(0, u64::MAX, &mask, cx);
+        self.paste_clipboard_at(&clipboard, 0, cx);
+        let moved = clipboard.notes.len() + clipboard.chords.len();
+        self.show_toast(&format!("Moved {moved} events"), cx);
+        moved
     }
 
-    pub(crate) fn connect_patcher_nodes(&mut self, cx: &mut impl UiNotify) {
-        let Some(src) = self.patcher_link_source else {
-            self.show_toast("Select a source port", cx);
+    /// Moves the focused column's contents one column over and follows them
+    /// with the cursor.
+    fn shift_focused_column(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
             return;
-        };
-        let Some(dst) = self.patcher_link_target else {
-            self.show_toast("Select a target port", cx);
+        }
+        let track = self.focused_track_index;
+        let last = self.track_columns[track].saturating_sub(1) as i32;
+        let target = (self.cursor_col as i32 + delta).clamp(0, last) as usize;
+        if target == self.cursor_col {
+            self.show_toast("No column there", cx);
             return;
-        };
-        if src.node_id == dst.node_id {
-            self.show_toast("Source and target must be different", cx);
+        }
+        self.move_track_column(track, self.cursor_col, target, cx);
+        self.cursor_col = target;
+    }
+
+    /// Exchanges the contents and settings of two tracks. Without an engine
+    /// the clip vectors are swapped in place under one clip-version bump;
+    /// with one, every event is deleted and rewritten on the other track
+    /// once the whole clip has loaded. Device chains and patchers live in the engine and stay put.
+    fn swap_tracks(&mut self, a: usize, b: usize, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
             return;
         }
-        if src.direction != PatcherPortDirection::Output ||
-            dst.direction != PatcherPortDirection::Input ||
-            src.kind != dst.kind {
-            self.show_toast("Invalid patcher connection", cx);
+        if a == b || a >= TRACK_COUNT || b >= TRACK_COUNT {
             return;
         }
-        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
-        let payload = UiPatcherGraphCommandPayload {
-            command_type: UiCommandType::ConnectPatcherNodes as u16,
-            flags: 0,
-            track_id: track,
-            base_version: self.patcher_versions[track as usize],
-            node_id: 0,
-            node_type: 0,
-            src_node_id: src.node_id,
-            dst_node_id: dst.node_id,
-            src_port_id: src.port_id,
-            dst_port_id: dst.port_id,
-            edge_kind: src.kind as u32,
-        };
-        self.enqueue_patcher_graph_payload(payload);
-        self.patcher_link_source = None;
-        self.patcher_link_target = None;
-        cx.notify();
+        if !self.whole_clip_ready(WholeClipEdit::SwapTracks(a, b), cx) {
+            return;
+        }
+        if self.bridge.is_none() {
+            self.clip_notes.swap(a, b);
+            self.clip_chords.swap(a, b);
+            for note in &mut self.pending_notes {
+                if note.track_id as usize == a {
+                    note.track_id = b as u32;
+                } else if note.track_id as usize == b {
+                    note.track_id = a as u32;
+                }
+            }
+            for chord in &mut self.pending_chords {
+                if chord.track_id as usize == a {
+                    chord.track_id = b as u32;
+                } else if chord.track_id as usize == b {
+                    chord.track_id = a as u32;
+                }
+            }
+            self.bump_clip_render_version();
+            self.bump_clip_version();
+        } else {
+            let mut mask = SelectionMask::empty(TRACK_COUNT);
+            mask.tracks[a] = u8::MAX;
+            mask.tracks[b] = u8::MAX;
+            let notes = self.collect_notes_in_range(0, u64::MAX, &mask, true);
+            let chords = self.collect_chords_in_range(0, u64::MAX, &mask, true);
+            self.delete_range(0, u64::MAX, &mask, cx);
+            let other = |track: usize| if track == a { b } else { a };
+            for (track, note) in notes {
+                self.write_note_at(
+                    other(track),
+                    note.column,
+                    note.nanotick,
+                    note.pitch,
+                    note.velocity,
+                    note.duration,
+                    cx,
+                );
+            }
+            for (track, chord) in chords {
+                self.write_chord_at(
+                    other(track),
+                    chord.column,
+                    chord.nanotick,
+                    chord.duration,
+                    chord.degree,
+                    chord.quality,
+                    chord.inversion,
+                    chord.base_octave,
+                    chord.spread,
+                    chord.humanize_timing,
+                    chord.humanize_velocity,
+                    cx,
+                );
+            }
+        }
+        self.track_columns.swap(a, b);
+        self.track_quantize.swap(a, b);
+        self.track_quantize_strength.swap(a, b);
+        self.track_content_filter.swap(a, b);
+        self.track_names.swap(a, b);
+        // Arm state and time offset are applied by the engine per track, so
+        // it has to hear the swapped values.
+        self.track_armed.swap(a, b);
+        self.track_time_offset.swap(a, b);
+        for track in [a, b] {
+            self.send_track_armed(track);
+            self.send_track_time_offset(track);
+        }
+        self.show_toast(&format!("Swapped T{} and T{}", a + 1, b + 1), cx);
+    }
+
+    /// Transposes every track's notes by `semitones`, clamping pitches to the
+    /// MIDI range, and shifts every harmony root with wrap-around. Degree
+    /// chords follow the new roots on their own. The edits are re-issued as
+    /// writes and followed by one full resync.
+    pub(crate) fn transpose_project(&mut self, semitones: i32, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if semitones == 0 {
+            return;
+        }
+        // Harmony is always complete, so the notes must be too or off-screen
+        // notes end up against shifted roots.
+        if !self.whole_clip_ready(WholeClipEdit::TransposeProject(semitones), cx) {
+            return;
+        }
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.tracks.fill(u8::MAX);
+        let notes = self.collect_notes_in_range(0, u64::MAX, &mask, true);
+        for (track, note) in notes {
+            if note.velocity == 0 && note.duration == 0 {
+                continue;
+            }
+            let pitch = (note.pitch as i32 + semitones).clamp(0, 127) as u8;
+            self.delete_note_at(track, note.column, note.nanotick, note.pitch, cx);
+            self.write_note_at(
+                track,
+                note.column,
+                note.nanotick,
+                pitch,
+                note.velocity,
+                note.duration,
+                cx,
+            );
+        }
+        let harmonies: Vec<(u64, u32, u32)> = self
+            .harmony_events
+            .iter()
+            .map(|event| {
+                let root = (event.root as i32 + semitones).rem_euclid(12) as u32;
+                (event.nanotick, root, event.scale_id)
+            })
+            .collect();
+        for (nanotick, root, scale_id) in harmonies {
+            self.write_harmony_at(nanotick, root, scale_id, cx);
+        }
+        if self.bridge.is_some() {
+            self.request_full_resync();
+        }
+        self.show_toast(&format!("Transposed project {semitones:+} semitones"), cx);
     }
 
-    fn send_random_degree_config(&mut self, node_id: u32) {
-        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
-        let mut config = [0u8; 16];
-        let degree: u8 = 8;
-        let velocity: u8 = 100;
-        let duration: u64 = 0;
-        config[0] = degree;
-        config[1] = velocity;
-        config[4..12].copy_from_slice(&duration.to_le_bytes());
-        let payload = UiPatcherNodeConfigPayload {
-            command_type: UiCommandType::SetPatcherNodeConfig as u16,
-            flags: 0,
-            track_id: track,
-            base_version: self.patcher_versions[track as usize],
-            node_id,
-            config_type: PATCHER_NODE_RANDOM_DEGREE,
-            config,
-            reserved: [0; 4],
+    /// Moves every note on `track` by the groove's offset for the step it sits
+    /// in, once the whole clip has loaded. Returns how many notes moved.
+    pub(crate) fn apply_groove(
+        &mut self,
+        track: usize,
+        template: &GrooveTemplate,
+        cx: &mut impl UiNotify,
+    ) -> usize {
+        if self.read_only_blocked(cx) {
+            return 0;
+        }
+        if !self.whole_clip_ready(WholeClipEdit::ApplyGroove(track, *template), cx) {
+            return 0;
+        }
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        if let Some(bits) = mask.tracks.get_mut(track) {
+            *bits = u8::MAX;
+        }
+        let moves: Vec<(ClipNote, u64)> = self
+            .collect_notes_in_range(0, u64::MAX, &mask, true)
+            .into_iter()
+            .filter_map(|(_, note)| {
+                let offset = template.offset_at(note.nanotick);
+                let target = (note.nanotick as i64 + offset).max(0) as u64;
+                (target != note.nanotick).then_some((note, target))
+            })
+            .collect();
+        // Delete everything first so a moved note never lands on one that has
+        // not moved yet.
+        for (note, _) in &moves {
+            self.delete_note_at(track, note.column, note.nanotick, note.pitch, cx);
+        }
+        for (note, target) in &moves {
+            self.write_note_at(
+                track,
+                note.column,
+                *target,
+                note.pitch,
+                note.velocity,
+                note.duration,
+                cx,
+            );
+        }
+        self.show_toast(&format!("{} on T{}", template.name, track + 1), cx);
+        moves.len()
+    }
+
+    /// Deletes the notes and chords outside the loop range on `track`, or on
+    /// every track when `track` is None, once the whole clip has loaded.
+    /// Harmony is left alone.
+    fn trim_to_loop(&mut self, track: Option<usize>, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let Some((start, end)) = self.loop_range.filter(|(start, end)| end > start) else {
+            self.show_toast("Set a loop range first", cx);
+            return;
         };
-        self.enqueue_patcher_config_payload(payload);
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        match track {
+            Some(track) if track < TRACK_COUNT => mask.tracks[track] = u8::MAX,
+            Some(_) => return,
+            None => mask.tracks.fill(u8::MAX),
+        }
+        if !self.whole_clip_ready(WholeClipEdit::TrimToLoop(track), cx) {
+            return;
+        }
+        if start > 0 {
+            self.delete_range(0, start - 1, &mask, cx);
+        }
+        self.delete_range(end, u64::MAX, &mask, cx);
+        let scope = match track {
+            Some(track) => format!("T{}", track + 1),
+            None => "all tracks".to_string(),
+        };
+        self.show_toast(&format!("Trimmed {scope} to loop"), cx);
     }
 
-    pub(crate) fn open_preset_save(&mut self, cx: &mut impl UiNotify) {
-        self.preset_save_open = true;
-        self.preset_save_text.clear();
-        cx.notify();
+    /// Copies the cursor's bar on every track, harmony included, into the
+    /// bar after it.
+    fn duplicate_bar(&mut self, cx: &mut impl UiNotify) {
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        self.duplicate_bar_at(self.current_row_nanotick() / bar * bar, cx);
     }
 
-    pub(crate) fn commit_preset_save(&mut self, cx: &mut impl UiNotify) {
-        if !self.preset_save_open {
+    /// Copies the bar starting at `start` into the bar after it. Everything
+    /// that followed is pushed a bar later first so the copy lands in freshly
+    /// opened time, which needs the whole clip loaded.
+    fn duplicate_bar_at(&mut self, start: u64, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
             return;
         }
-        let name = self.preset_save_text.trim().to_string();
-        self.preset_save_open = false;
-        self.preset_save_text.clear();
-        if name.is_empty() {
-            self.show_toast("Preset name empty", cx);
+        if !self.whole_clip_ready(WholeClipEdit::DuplicateBar(start), cx) {
             return;
         }
-        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
-        let mut payload = UiPatcherPresetCommandPayload::default();
-        payload.command_type = UiCommandType::SavePatcherPreset as u16;
-        payload.track_id = track;
-        let bytes = name.as_bytes();
-        let len = bytes.len().min(payload.name.len());
-        payload.name[..len].copy_from_slice(&bytes[..len]);
-        self.enqueue_patcher_preset_payload(payload);
-        self.show_toast("Saved patcher preset", cx);
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        let next = start + bar;
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.tracks.fill(u8::MAX);
+        mask.harmony = true;
+        let source = self.build_clipboard(start, next - 1, &mask, true);
+        let tail = self.build_clipboard(next, u64::MAX, &mask, true);
+        self.delete_range(next, u64::MAX, &mask, cx);
+        self.paste_clipboard_at(&tail, next + bar, cx);
+        self.paste_clipboard_at(&source, next, cx);
+        self.show_toast(&format!("Duplicated bar {}", start / bar + 1), cx);
+    }
+
+    /// Removes the harmony events in the selection's time range, leaving its
+    /// notes and chords in place.
+    fn delete_selection_harmony(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return;
+        };
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.harmony = true;
+        self.delete_range(start, end, &mask, cx);
     }
 
-    fn enqueue_chain_payload(&mut self, payload: UiChainCommandPayload) {
-        self.enqueue_chain_command(payload);
+    fn toggle_follow_playhead(&mut self, cx: &mut impl UiNotify) {
+        self.follow_playhead = !self.follow_playhead;
+        cx.notify();
     }
 
-    pub(crate) fn toggle_chain_add_menu(&mut self, cx: &mut impl UiNotify) {
-        self.chain_add_open = !self.chain_add_open;
-        self.chain_focus = false;
+    fn toggle_velocity_display(&mut self, cx: &mut impl UiNotify) {
+        self.show_velocity = !self.show_velocity;
         cx.notify();
     }
 
-    pub(crate) fn add_chain_device(
-        &mut self,
-        device_kind: u32,
-        patcher_node_id: u32,
-        host_slot_index: u32,
-        cx: &mut impl UiNotify,
-    ) {
-        let payload = UiChainCommandPayload {
-            command_type: UiCommandType::AddDevice as u16,
-            flags: 0,
-            track_id: self.focused_track_index as u32,
-            base_version: self.chain_versions[self.focused_track_index],
-            device_id: K_CHAIN_DEVICE_ID_AUTO,
-            device_kind,
-            insert_index: K_CHAIN_DEVICE_ID_AUTO,
-            patcher_node_id,
-            host_slot_index,
-            bypass: 0,
-            reserved: [0; 4],
-        };
-        self.enqueue_chain_payload(payload);
-        self.chain_add_open = false;
-        cx.notify();
+    /// Cycles the focused track between showing notes and chords, notes only,
+    /// and chords only.
+    fn cycle_content_filter(&mut self, cx: &mut impl UiNotify) {
+        let track = self.focused_track_index;
+        let next = self.track_content_filter[track].next();
+        self.track_content_filter[track] = next;
+        self.show_toast(&format!("Track {}: {}", track + 1, next.label()), cx);
     }
 
-    fn patcher_candidate_nodes(&self, track: usize, device_kind: u32) -> Vec<PatcherNodeUi> {
-        let nodes = self
-            .patcher_nodes
-            .get(track)
-            .cloned()
-            .unwrap_or_default();
-        let edges = self
-            .patcher_edges
+    /// Whether cells of `kind` are shown on `track` under its content filter.
+    pub(crate) fn cell_kind_visible(&self, track: usize, kind: CellKind) -> bool {
+        self.track_content_filter
             .get(track)
-            .cloned()
-            .unwrap_or_default();
-        let kind = if device_kind == 2 {
-            PatcherPortKind::Audio
-        } else {
-            PatcherPortKind::Event
-        };
-        let mut candidates: Vec<PatcherNodeUi> = nodes
-            .into_iter()
-            .filter(|node| match kind {
-                PatcherPortKind::Event => node_supports_event_device(node.node_type),
-                PatcherPortKind::Audio => node_supports_audio_device(node.node_type),
-                PatcherPortKind::Control => false,
-            })
-            .collect();
-        if candidates.is_empty() {
-            return candidates;
-        }
-        let is_sink = |node_id: u32| {
-            !edges.iter().any(|edge| {
-                edge.kind == kind && edge.src_node_id == node_id
-            })
-        };
-        let sinks: Vec<PatcherNodeUi> =
-            candidates.iter().cloned().filter(|node| is_sink(node.id)).collect();
-        if !sinks.is_empty() {
-            candidates = sinks;
+            .is_none_or(|filter| filter.shows(&kind))
+    }
+
+    /// Latches the master clip LED when the snapshot reports a clipped
+    /// sample. Returns true when the LED just turned on.
+    pub(crate) fn latch_master_clip(&mut self) -> bool {
+        if self.master_clip_latched || !master_peak_clipped(&self.snapshot.ui_master_peak) {
+            return false;
         }
-        candidates
+        self.master_clip_latched = true;
+        true
     }
 
-    fn default_patcher_node_id(&self, track: usize, device_kind: u32) -> Option<u32> {
-        let mut candidates = self.patcher_candidate_nodes(track, device_kind);
-        if candidates.is_empty() {
-            return None;
+    pub(crate) fn clear_master_clip(&mut self, cx: &mut impl UiNotify) {
+        if self.master_clip_latched {
+            self.master_clip_latched = false;
+            cx.notify();
         }
-        if device_kind != 2 {
-            if let Some(node) = candidates
-                .iter()
-                .find(|node| node.node_type == PATCHER_NODE_EVENT_OUT)
-            {
-                return Some(node.id);
-            }
+    }
+
+    fn toggle_duration_display(&mut self, cx: &mut impl UiNotify) {
+        self.show_durations = !self.show_durations;
+        cx.notify();
+    }
+
+    /// Text for a sounding note cell under the current display modes;
+    /// velocity display wins over duration display.
+    pub(crate) fn note_cell_text(&self, pitch: u8, velocity: u8, duration: u64) -> String {
+        if self.show_velocity {
+            velocity_label(velocity)
+        } else if self.show_durations {
+            note_duration_label(pitch, duration, self.row_nanoticks())
+        } else {
+            pitch_to_note(pitch)
         }
-        candidates.sort_by_key(|node| node.id);
-        candidates.first().map(|node| node.id)
     }
 
-    pub(crate) fn add_chain_patcher_device(
-        &mut self,
-        device_kind: u32,
-        cx: &mut impl UiNotify,
-    ) {
-        let track = self.focused_track_index;
-        let patcher_node_id = self
-            .default_patcher_node_id(track, device_kind)
-            .unwrap_or(0);
-        self.add_chain_device(device_kind, patcher_node_id, 0, cx);
+    /// Like `note_cell_text`, but honours a per-note flat spelling override
+    /// for the note at `(track, column, nanotick)`.
+    pub(crate) fn note_cell_text_at(
+        &self,
+        track: usize,
+        column: u8,
+        nanotick: u64,
+        pitch: u8,
+        velocity: u8,
+        duration: u64,
+    ) -> String {
+        if self.show_velocity || !self.flat_spellings.contains(&(track, column, nanotick)) {
+            return self.note_cell_text(pitch, velocity, duration);
+        }
+        let name = pitch_to_flat_note(pitch);
+        if self.show_durations {
+            named_duration_label(&name, duration, self.row_nanoticks())
+        } else {
+            name
+        }
     }
 
-    pub(crate) fn cycle_chain_device_patcher_node(
-        &mut self,
-        device_id: u32,
-        forward: bool,
-        cx: &mut impl UiNotify,
-    ) {
-        let track = self.focused_track_index;
-        let Some(devices) = self.chain_devices.get(track) else {
-            self.show_toast("No chain for track", cx);
+    /// Flips the focused note between its sharp and flat spelling. Natural
+    /// notes have no alternative spelling and are left alone.
+    fn toggle_focused_enharmonic(&mut self, cx: &mut impl UiNotify) {
+        let Some(note) = self.focused_note() else {
             return;
         };
-        let Some(device) = devices.iter().find(|d| d.id == device_id) else {
-            self.show_toast("Device not found", cx);
-            return;
-        };
-        let mut candidates = self.patcher_candidate_nodes(track, device.kind);
-        if candidates.is_empty() {
-            self.show_toast("No patcher nodes for track", cx);
+        if pitch_to_note(note.pitch) == pitch_to_flat_note(note.pitch) {
+            self.show_toast("No enharmonic spelling", cx);
             return;
         }
-        candidates.sort_by_key(|node| node.id);
-        let current_index = candidates
-            .iter()
-            .position(|node| node.id == device.patcher_node_id);
-        let next_index = match (current_index, forward) {
-            (Some(index), true) => (index + 1) % candidates.len(),
-            (Some(index), false) => {
-                if index == 0 {
-                    candidates.len() - 1
-                } else {
-                    index - 1
-                }
-            }
-            (None, true) => 0,
-            (None, false) => candidates.len().saturating_sub(1),
+        let key = (self.focused_track_index, note.column, note.nanotick);
+        if !self.flat_spellings.remove(&key) {
+            self.flat_spellings.insert(key);
+        }
+        self.bump_clip_render_version();
+        cx.notify();
+    }
+
+    fn toggle_resolved_chords(&mut self, cx: &mut impl UiNotify) {
+        self.show_resolved_chords = !self.show_resolved_chords;
+        cx.notify();
+    }
+
+    fn toggle_rests(&mut self, cx: &mut impl UiNotify) {
+        self.show_rests = !self.show_rests;
+        cx.notify();
+    }
+
+    /// Sounding spans `(start, end)` of every note and chord in one column,
+    /// sorted by start. Note-offs are zero-length spans that only mark a
+    /// boundary.
+    pub(crate) fn column_event_spans(&self, track: usize, column: usize) -> Vec<(u64, u64)> {
+        let mut spans = Vec::new();
+        if self.cell_kind_visible(track, CellKind::Note) {
+            if let Some(notes) = self.clip_notes.get(track) {
+                spans.extend(
+                    notes
+                        .iter()
+                        .filter(|note| note.column as usize == column)
+                        .map(|note| (note.nanotick, note.nanotick.saturating_add(note.duration))),
+                );
+            }
+        }
+        if self.cell_kind_visible(track, CellKind::Chord) {
+            if let Some(chords) = self.clip_chords.get(track) {
+                spans.extend(
+                    chords
+                        .iter()
+                        .filter(|chord| chord.column as usize == column)
+                        .map(|chord| {
+                            (chord.nanotick, chord.nanotick.saturating_add(chord.duration))
+                        }),
+                );
+            }
+        }
+        spans.sort_unstable();
+        spans
+    }
+
+    /// Text for a chord or degree cell: its token, or the pitch it resolves to
+    /// under the harmony at its position when resolved display is on.
+    pub(crate) fn chord_cell_text(&self, chord: &ClipChord) -> String {
+        if !self.show_resolved_chords {
+            return chord_token_text(chord);
+        }
+        resolved_chord_text(
+            chord,
+            self.harmony_root_at(chord.nanotick),
+            self.harmony_scale_at(chord.nanotick),
+        )
+    }
+
+    fn toggle_version_overlay(&mut self, cx: &mut impl UiNotify) {
+        self.show_version_overlay = !self.show_version_overlay;
+        cx.notify();
+    }
+
+    /// Solos the cursor column for auditioning, or clears the solo when the
+    /// cursor column is already soloed. The engine has no per-column mute,
+    /// so the solo only dims other columns and gates chord previews.
+    fn toggle_solo_column(&mut self, cx: &mut impl UiNotify) {
+        let target = (self.focused_track_index, self.cursor_col);
+        self.solo_column = if self.solo_column == Some(target) {
+            None
+        } else {
+            Some(target)
         };
-        let next_id = candidates[next_index].id;
-        if next_id == device.patcher_node_id {
-            return;
+        cx.notify();
+    }
+
+    pub(crate) fn column_dimmed(&self, track: usize, column: usize) -> bool {
+        self.solo_column.is_some_and(|solo| solo != (track, column))
+    }
+
+    pub(crate) fn version_overlay_line(&self) -> String {
+        version_overlay_text(
+            self.current_clip_version(),
+            self.snapshot.ui_clip_version,
+            self.clip_version_local,
+            self.queued_commands.len(),
+        )
+    }
+
+    /// Commands still waiting in the queue, counted per command type in
+    /// type-code order.
+    pub(crate) fn queued_command_summary(&self) -> Vec<(String, usize)> {
+        let mut counts: BTreeMap<u16, usize> = BTreeMap::new();
+        for command in &self.queued_commands {
+            *counts.entry(command.command_type()).or_default() += 1;
         }
-        self.set_chain_device_patcher_node_id(device_id, next_id, cx);
+        counts
+            .into_iter()
+            .map(|(command_type, count)| (command_type_name(command_type), count))
+            .collect()
     }
 
-    pub(crate) fn auto_assign_missing_patcher_nodes(&mut self, cx: &mut impl UiNotify) {
-        let mut updates = Vec::new();
-        for track in 0..TRACK_COUNT {
-            let Some(devices) = self.chain_devices.get(track) else { continue };
-            let nodes = self.patcher_nodes.get(track).cloned().unwrap_or_default();
-            let edges = self.patcher_edges.get(track).cloned().unwrap_or_default();
-            if nodes.is_empty() {
-                continue;
-            }
-            let event_out_id = nodes
-                .iter()
-                .find(|node| node.node_type == PATCHER_NODE_EVENT_OUT)
-                .map(|node| node.id);
-            let is_event_sink = |node_id: u32| {
-                !edges.iter().any(|edge| {
-                    edge.kind == PatcherPortKind::Event && edge.src_node_id == node_id
-                })
-            };
-            for device in devices.iter() {
-                if device.kind > 2 {
-                    continue;
-                }
-                let exists = nodes.iter().any(|node| node.id == device.patcher_node_id);
-                if device.kind == 0 {
-                    let sink_ok = exists && is_event_sink(device.patcher_node_id);
-                    if let Some(event_out) = event_out_id {
-                        if !sink_ok || device.patcher_node_id != event_out {
-                            updates.push((device.id, event_out));
-                        }
-                        continue;
-                    }
-                }
-                if exists {
-                    continue;
-                }
-                if let Some(node_id) = self.default_patcher_node_id(track, device.kind) {
-                    updates.push((device.id, node_id));
-                }
-            }
+    fn toggle_minimap_track_dots(&mut self, cx: &mut impl UiNotify) {
+        self.minimap_track_dots = !self.minimap_track_dots;
+        cx.notify();
+    }
+
+    fn toggle_minimap_bar_numbers(&mut self, cx: &mut impl UiNotify) {
+        self.minimap_bar_numbers = !self.minimap_bar_numbers;
+        cx.notify();
+    }
+
+    fn toggle_pitch_ruler(&mut self, cx: &mut impl UiNotify) {
+        self.pitch_ruler_visible = !self.pitch_ruler_visible;
+        cx.notify();
+    }
+
+    pub fn toggle_harmony_focus(&mut self, cx: &mut impl UiNotify) {
+        if self.harmony_focus {
+            self.leave_harmony_focus();
+        } else {
+            self.enter_harmony_focus();
         }
-        for (device_id, node_id) in updates {
-            self.set_chain_device_patcher_node_id(device_id, node_id, cx);
+        self.clear_edit_state();
+        cx.notify();
+    }
+
+    pub fn move_column(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        let columns = self.track_columns[self.focused_track_index];
+        let max_index = columns.saturating_sub(1) as i32;
+        let next = (self.cursor_col as i32 + delta).clamp(0, max_index);
+        self.cursor_col = next as usize;
+        self.clear_edit_state();
+        cx.notify();
+    }
+
+    fn scroll_columns(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        let total: usize = self.track_columns.iter().sum();
+        let max_offset = total.saturating_sub(1) as i64;
+        let next = (self.horizontal_scroll_offset as i64 + delta as i64).clamp(0, max_offset);
+        if next as usize != self.horizontal_scroll_offset {
+            self.horizontal_scroll_offset = next as usize;
+            cx.notify();
         }
     }
 
-    fn delete_selected_chain_device(&mut self, cx: &mut impl UiNotify) {
-        let Some(device) = self.chain_selected_device() else {
-            self.show_toast("No device selected", cx);
+    /// X position of a track column within the scrolled track area, or None
+    /// while it is scrolled out of view.
+    pub(crate) fn column_view_x(&self, track: usize, column: usize) -> Option<f32> {
+        column_x_offset(&self.track_columns, track, column, self.horizontal_scroll_offset)
+    }
+
+    /// Number of a track's columns left in view after horizontal scrolling.
+    pub(crate) fn visible_track_columns(&self, track: usize) -> usize {
+        let columns = self.track_columns.get(track).copied().unwrap_or(0);
+        (0..columns)
+            .filter(|column| self.column_view_x(track, *column).is_some())
+            .count()
+    }
+
+    pub fn adjust_columns(&mut self, track: usize, delta: i32, cx: &mut impl UiNotify) {
+        if track >= self.track_columns.len() {
             return;
-        };
-        let payload = UiChainCommandPayload {
-            command_type: UiCommandType::RemoveDevice as u16,
-            flags: 0,
-            track_id: self.focused_track_index as u32,
-            base_version: self.chain_versions[self.focused_track_index],
-            device_id: device.id,
-            device_kind: device.kind,
-            insert_index: K_CHAIN_DEVICE_ID_AUTO,
-            patcher_node_id: device.patcher_node_id,
-            host_slot_index: device.host_slot_index,
-            bypass: device.bypass as u32,
-            reserved: [0; 4],
-        };
-        self.enqueue_chain_payload(payload);
-        self.focused_chain_device_id = None;
+        }
+        let current = self.track_columns[track] as i32;
+        let next = (current + delta).clamp(1, MAX_NOTE_COLUMNS as i32) as usize;
+        self.track_columns[track] = next;
+        if self.focused_track_index == track {
+            self.cursor_col = self.cursor_col.min(next.saturating_sub(1));
+        }
+        self.clear_edit_state();
         cx.notify();
     }
 
-    fn copy_selected_chain_device(&mut self, cx: &mut impl UiNotify) {
-        let Some(device) = self.chain_selected_device() else {
-            self.show_toast("No device selected", cx);
-            return;
-        };
-        self.chain_clipboard = Some(ChainClipboard {
-            kind: device.kind,
-            patcher_node_id: device.patcher_node_id,
-            host_slot_index: device.host_slot_index,
-            bypass: device.bypass,
-        });
+    /// Sets the track's column count to just past its widest used column,
+    /// hiding trailing empty columns. An empty track keeps one column.
+    /// Waits for the whole clip so off-screen events keep their columns.
+    /// Returns the new count.
+    pub(crate) fn fit_track_columns(&mut self, track: usize, cx: &mut impl UiNotify) -> usize {
+        if track >= self.track_columns.len() {
+            return 0;
+        }
+        if !self.whole_clip_ready(WholeClipEdit::FitTrackColumns(track), cx) {
+            return self.track_columns[track];
+        }
+        let widest = self
+            .clip_notes
+            .get(track)
+            .into_iter()
+            .flatten()
+            .map(|note| note.column)
+            .chain(self.clip_chords.get(track).into_iter().flatten().map(|chord| chord.column))
+            .chain(
+                self.pending_notes
+                    .iter()
+                    .filter(|note| note.track_id as usize == track)
+                    .map(|note| note.column),
+            )
+            .chain(
+                self.pending_chords
+                    .iter()
+                    .filter(|chord| chord.track_id as usize == track)
+                    .map(|chord| chord.column),
+            )
+            .max();
+        let columns = widest.map_or(1, |column| column as usize + 1).min(MAX_NOTE_COLUMNS);
+        let delta = columns as i32 - self.track_columns[track] as i32;
+        self.adjust_columns(track, delta, cx);
+        self.show_toast(&format!("Track uses {columns} column(s)"), cx);
+        columns
+    }
+
+    pub fn focus_harmony_row(&mut self, row: usize, cx: &mut impl UiNotify) {
+        let new_row = row.min(VISIBLE_ROWS - 1) as i64;
+        self.cursor_nanotick = self.view_row_nanotick(new_row);
+        self.enter_harmony_focus();
+        self.chain_focus = false;
+        self.focused_chain_device_id = None;
+        self.edit_active = false;
+        self.edit_text.clear();
         cx.notify();
     }
 
-    fn cut_selected_chain_device(&mut self, cx: &mut impl UiNotify) {
-        self.copy_selected_chain_device(cx);
-        self.delete_selected_chain_device(cx);
+    /// Starts a harmony rubber-band drag at a visible row. Shift extends an
+    /// existing selection from its anchor instead of starting a new one.
+    pub(crate) fn begin_harmony_drag(&mut self, row: usize, extend: bool, cx: &mut impl UiNotify) {
+        self.focus_harmony_row(row, cx);
+        let nanotick = self.cursor_nanotick;
+        if extend && self.selection.is_some() {
+            self.start_selection(nanotick, None, None, true, true, cx);
+            self.harmony_drag_anchor = self.selection_anchor_nanotick;
+        } else {
+            self.harmony_drag_anchor = Some(nanotick);
+        }
     }
 
-    fn paste_chain_device(&mut self, cx: &mut impl UiNotify) {
-        let Some(clipboard) = self.chain_clipboard.clone() else {
-            self.show_toast("Chain clipboard empty", cx);
+    /// Extends an active harmony drag to a visible row, including rows
+    /// reached by dragging across the note grid (see `harmony_drag_move`).
+    pub(crate) fn drag_harmony_selection(&mut self, row: usize, cx: &mut impl UiNotify) {
+        let Some(anchor) = self.harmony_drag_anchor else {
             return;
         };
-        let insert_index = self
-            .chain_selected_device()
-            .map(|device| device.position)
-            .unwrap_or(K_CHAIN_DEVICE_ID_AUTO);
-        let payload = UiChainCommandPayload {
-            command_type: UiCommandType::AddDevice as u16,
-            flags: 0,
-            track_id: self.focused_track_index as u32,
-            base_version: self.chain_versions[self.focused_track_index],
-            device_id: K_CHAIN_DEVICE_ID_AUTO,
-            device_kind: clipboard.kind,
-            insert_index,
-            patcher_node_id: clipboard.patcher_node_id,
-            host_slot_index: clipboard.host_slot_index,
-            bypass: clipboard.bypass as u32,
-            reserved: [0; 4],
-        };
-        self.enqueue_chain_payload(payload);
+        let nanotick = self.view_row_nanotick(row.min(VISIBLE_ROWS - 1) as i64);
+        if self.selection.is_none() || self.selection_anchor_nanotick != Some(anchor) {
+            self.start_selection(anchor, None, None, true, false, cx);
+        }
+        self.selection_mask.harmony = true;
+        self.update_selection_end(nanotick, cx);
     }
 
-    fn duplicate_selected_chain_device(&mut self, cx: &mut impl UiNotify) {
-        let Some(device) = self.chain_selected_device() else {
-            self.show_toast("No device selected", cx);
+    pub(crate) fn end_harmony_drag(&mut self) {
+        self.harmony_drag_anchor = None;
+    }
+
+    /// Mouse movement over a harmony or note cell in a visible row. Extends an
+    /// active harmony drag while the button is held and ends it otherwise.
+    pub(crate) fn harmony_drag_move(&mut self, row: usize, dragging: bool, cx: &mut impl UiNotify) {
+        if self.harmony_drag_anchor.is_none() {
             return;
-        };
-        let payload = UiChainCommandPayload {
-            command_type: UiCommandType::AddDevice as u16,
-            flags: 0,
-            track_id: self.focused_track_index as u32,
-            base_version: self.chain_versions[self.focused_track_index],
-            device_id: K_CHAIN_DEVICE_ID_AUTO,
-            device_kind: device.kind,
-            insert_index: device.position.saturating_add(1),
-            patcher_node_id: device.patcher_node_id,
-            host_slot_index: device.host_slot_index,
-            bypass: device.bypass as u32,
-            reserved: [0; 4],
-        };
-        self.enqueue_chain_payload(payload);
+        }
+        if dragging {
+            self.drag_harmony_selection(row, cx);
+        } else {
+            self.end_harmony_drag();
+        }
     }
 
-    pub(crate) fn toggle_chain_device_bypass(
+    pub fn focus_note_cell(
         &mut self,
-        device_id: u32,
+        row: usize,
+        track: usize,
+        column: usize,
         cx: &mut impl UiNotify,
     ) {
+        let new_row = row.min(VISIBLE_ROWS - 1) as i64;
+        self.cursor_nanotick = self.view_row_nanotick(new_row);
+        self.focused_track_index = track.min(TRACK_COUNT - 1);
+        let max_column = self.track_columns[self.focused_track_index]
+            .saturating_sub(1);
+        self.cursor_col = column.min(max_column);
+        self.harmony_focus = false;
+        self.chain_focus = false;
+        self.focused_chain_device_id = None;
+        self.clear_edit_state();
+        cx.notify();
+    }
+
+    fn chain_selected_device(&self) -> Option<ChainDevice> {
         let track = self.focused_track_index;
-        let devices = match self.chain_devices.get_mut(track) {
-            Some(devices) => devices,
-            None => {
-                self.show_toast("No chain for track", cx);
-                return;
-            }
-        };
-        let Some(device) = devices.iter_mut().find(|d| d.id == device_id) else {
-            self.show_toast("Device not found", cx);
-            return;
-        };
-        device.bypass = !device.bypass;
-        let payload = UiChainCommandPayload {
-            command_type: UiCommandType::UpdateDevice as u16,
-            flags: 0x1,
-            track_id: track as u32,
-            base_version: self.chain_versions[track],
-            device_id: device.id,
-            device_kind: device.kind,
-            insert_index: K_CHAIN_DEVICE_ID_AUTO,
-            patcher_node_id: device.patcher_node_id,
-            host_slot_index: device.host_slot_index,
-            bypass: device.bypass as u32,
-            reserved: [0; 4],
-        };
-        self.enqueue_chain_payload(payload);
-        cx.notify();
+        let device_id = self.focused_chain_device_id?;
+        self.chain_devices
+            .get(track)
+            .and_then(|devices| devices.iter().find(|d| d.id == device_id))
+            .cloned()
     }
 
-    pub(crate) fn set_chain_device_patcher_node_id(
-        &mut self,
-        device_id: u32,
-        patcher_node_id: u32,
-        cx: &mut impl UiNotify,
-    ) {
-        let track = self.focused_track_index;
-        let devices = match self.chain_devices.get_mut(track) {
-            Some(devices) => devices,
-            None => {
-                self.show_toast("No chain for track", cx);
-                return;
-            }
-        };
-        let Some(device) = devices.iter_mut().find(|d| d.id == device_id) else {
-            self.show_toast("Device not found", cx);
-            return;
-        };
-        device.patcher_node_id = patcher_node_id;
-        let payload = UiChainCommandPayload {
-            command_type: UiCommandType::UpdateDevice as u16,
-            flags: 0x2,
-            track_id: track as u32,
-            base_version: self.chain_versions[track],
-            device_id: device.id,
-            device_kind: device.kind,
-            insert_index: K_CHAIN_DEVICE_ID_AUTO,
-            patcher_node_id: device.patcher_node_id,
-            host_slot_index: device.host_slot_index,
-            bypass: device.bypass as u32,
-            reserved: [0; 4],
-        };
-        self.enqueue_chain_payload(payload);
+    pub(crate) fn select_chain_device(&mut self, device_id: u32, cx: &mut impl UiNotify) {
+        self.chain_focus = true;
+        self.focused_chain_device_id = Some(device_id);
+        self.harmony_focus = false;
+        self.clear_edit_state();
+        self.chain_add_open = false;
         cx.notify();
     }
 
-    pub(crate) fn open_vst_editor(&mut self, device_id: u32, cx: &mut impl UiNotify) {
-        let track = self.focused_track_index;
-        let Some(device) = self
-            .chain_devices
-            .get(track)
-            .and_then(|devices| devices.iter().find(|d| d.id == device_id))
-            .cloned()
-        else {
-            self.show_toast("Device not found", cx);
-            return;
-        };
-        if device.kind != 3 && device.kind != 4 {
-            self.show_toast("Editor only available for VSTs", cx);
-            return;
-        }
-        if self.bridge.is_none() {
-            self.show_toast("Engine not connected", cx);
-            return;
-        }
-        let payload = UiCommandPayload {
-            command_type: UiCommandType::OpenPluginEditor as u16,
-            flags: 0,
-            track_id: track as u32,
-            plugin_index: 0,
-            note_pitch: 0,
-            value0: device_id,
-            note_nanotick_lo: 0,
-            note_nanotick_hi: 0,
-            note_duration_lo: 0,
-            note_duration_hi: 0,
-            base_version: 0,
-        };
-        self.enqueue_ui_command(payload);
+    pub(crate) fn open_patcher_view(&mut self, cx: &mut impl UiNotify) {
+        self.patcher_open = true;
+        self.patcher_track_id = self.focused_track_index as u32;
+        self.patcher_link_source = None;
+        self.patcher_link_target = None;
+        self.patcher_mouse_pos = None;
+        self.patcher_drag_node = None;
+        self.preset_save_open = false;
+        self.preset_save_text.clear();
         cx.notify();
     }
 
-    fn start_cell_edit(&mut self, initial: &str, cx: &mut impl UiNotify) {
-        if begin_cell_edit(&mut self.edit_active, &mut self.edit_text, initial) {
-            cx.notify();
-        }
+    pub(crate) fn close_patcher_view(&mut self, cx: &mut impl UiNotify) {
+        self.patcher_open = false;
+        self.patcher_link_source = None;
+        self.patcher_link_target = None;
+        self.patcher_mouse_pos = None;
+        self.patcher_drag_node = None;
+        self.preset_save_open = false;
+        self.preset_save_text.clear();
+        cx.notify();
     }
 
-    fn cancel_cell_edit(&mut self, cx: &mut impl UiNotify) {
-        if cancel_cell_edit_state(&mut self.edit_active, &mut self.edit_text) {
-            cx.notify();
-        }
+    fn enqueue_patcher_graph_payload(&mut self, payload: UiPatcherGraphCommandPayload) {
+        self.enqueue_patcher_graph_command(payload);
     }
 
-    fn commit_cell_edit(&mut self, cx: &mut impl UiNotify) {
-        if let Some(token) = commit_cell_edit_state(&mut self.edit_active, &mut self.edit_text) {
-            if !token.is_empty() {
-                self.apply_cell_token(&token, cx);
-            }
-            cx.notify();
-        }
+    fn enqueue_patcher_config_payload(&mut self, payload: UiPatcherNodeConfigPayload) {
+        self.enqueue_patcher_node_config(payload);
     }
 
-    fn clear_edit_state(&mut self) {
-        if self.edit_active {
-            self.edit_active = false;
-            self.edit_text.clear();
-        }
+    fn enqueue_patcher_preset_payload(&mut self, payload: UiPatcherPresetCommandPayload) {
+        self.enqueue_patcher_preset(payload);
     }
 
-    #[allow(dead_code)]
-    pub fn set_harmony_scale(&mut self, scale_id: u32, cx: &mut impl UiNotify) {
-        self.harmony_scale_id = scale_id;
+    pub(crate) fn add_patcher_node(&mut self, node_type: u32, cx: &mut impl UiNotify) {
+        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
+        let payload = UiPatcherGraphCommandPayload {
+            command_type: UiCommandType::AddPatcherNode as u16,
+            flags: 0,
+            track_id: track,
+            base_version: self.patcher_versions[track as usize],
+            node_id: 0,
+            node_type,
+            src_node_id: 0,
+            dst_node_id: 0,
+            src_port_id: 0,
+            dst_port_id: 0,
+            edge_kind: 0,
+        };
+        self.enqueue_patcher_graph_payload(payload);
         cx.notify();
     }
 
-    fn open_scale_browser(&mut self, cx: &mut impl UiNotify) {
-        self.scale_browser_open = true;
-        self.scale_browser_query.clear();
-        self.scale_browser_selection = 0;
+    pub(crate) fn remove_patcher_node(&mut self, node_id: u32, cx: &mut impl UiNotify) {
+        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
+        let payload = UiPatcherGraphCommandPayload {
+            command_type: UiCommandType::RemovePatcherNode as u16,
+            flags: 0,
+            track_id: track,
+            base_version: self.patcher_versions[track as usize],
+            node_id,
+            node_type: 0,
+            src_node_id: 0,
+            dst_node_id: 0,
+            src_port_id: 0,
+            dst_port_id: 0,
+            edge_kind: 0,
+        };
+        self.enqueue_patcher_graph_payload(payload);
         cx.notify();
     }
 
-    fn close_scale_browser(&mut self, cx: &mut impl UiNotify) {
-        if self.scale_browser_open {
-            self.scale_browser_open = false;
-            cx.notify();
-        }
-    }
-
-    fn append_scale_query(&mut self, value: &str, cx: &mut impl UiNotify) {
-        if value.is_empty() {
+    pub(crate) fn connect_patcher_nodes(&mut self, cx: &mut impl UiNotify) {
+        let Some(src) = self.patcher_link_source else {
+            self.show_toast("Select a source port", cx);
+            return;
+        };
+        let Some(dst) = self.patcher_link_target else {
+            self.show_toast("Select a target port", cx);
+            return;
+        };
+        if src.node_id == dst.node_id {
+            self.show_toast("Source and target must be different", cx);
             return;
         }
-        if value.chars().all(|ch| ch.is_whitespace()) {
+        if src.direction != PatcherPortDirection::Output ||
+            dst.direction != PatcherPortDirection::Input ||
+            src.kind != dst.kind {
+            self.show_toast("Invalid patcher connection", cx);
             return;
         }
-        self.scale_browser_query.push_str(value);
-        self.scale_browser_selection = 0;
+        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
+        let payload = UiPatcherGraphCommandPayload {
+            command_type: UiCommandType::ConnectPatcherNodes as u16,
+            flags: 0,
+            track_id: track,
+            base_version: self.patcher_versions[track as usize],
+            node_id: 0,
+            node_type: 0,
+            src_node_id: src.node_id,
+            dst_node_id: dst.node_id,
+            src_port_id: src.port_id,
+            dst_port_id: dst.port_id,
+            edge_kind: src.kind as u32,
+        };
+        self.enqueue_patcher_graph_payload(payload);
+        self.patcher_link_source = None;
+        self.patcher_link_target = None;
         cx.notify();
     }
 
-    fn backspace_scale_query(&mut self, cx: &mut impl UiNotify) {
-        if self.scale_browser_query.is_empty() {
-            return;
-        }
-        self.scale_browser_query.pop();
-        self.scale_browser_selection = 0;
+    fn send_random_degree_config(&mut self, node_id: u32) {
+        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
+        let mut config = [0u8; 16];
+        let degree: u8 = 8;
+        let velocity: u8 = 100;
+        let duration: u64 = 0;
+        config[0] = degree;
+        config[1] = velocity;
+        config[4..12].copy_from_slice(&duration.to_le_bytes());
+        let payload = UiPatcherNodeConfigPayload {
+            command_type: UiCommandType::SetPatcherNodeConfig as u16,
+            flags: 0,
+            track_id: track,
+            base_version: self.patcher_versions[track as usize],
+            node_id,
+            config_type: PATCHER_NODE_RANDOM_DEGREE,
+            config,
+            reserved: [0; 4],
+        };
+        self.enqueue_patcher_config_payload(payload);
+    }
+
+    pub(crate) fn open_preset_save(&mut self, cx: &mut impl UiNotify) {
+        self.preset_save_open = true;
+        self.preset_save_text.clear();
         cx.notify();
     }
 
-    fn move_scale_selection(&mut self, delta: i32, cx: &mut impl UiNotify) {
-        let filtered = self.filtered_scale_indices();
-        if filtered.is_empty() {
-            self.scale_browser_selection = 0;
-            cx.notify();
-            return;
-        }
-        let max_index = filtered.len().saturating_sub(1) as i32;
-        let next =
-            (self.scale_browser_selection as i32 + delta).clamp(0, max_index);
-        self.scale_browser_selection = next as usize;
-        cx.notify();
-    }
-
-    pub(crate) fn confirm_scale_browser(&mut self, cx: &mut impl UiNotify) {
-        if !self.scale_browser_open {
+    pub(crate) fn commit_preset_save(&mut self, cx: &mut impl UiNotify) {
+        if !self.preset_save_open {
             return;
         }
-        let filtered = self.filtered_scale_indices();
-        if filtered.is_empty() {
+        let name = self.preset_save_text.trim().to_string();
+        self.preset_save_open = false;
+        self.preset_save_text.clear();
+        if name.is_empty() {
+            self.show_toast("Preset name empty", cx);
             return;
         }
-        let selection = self
-            .scale_browser_selection
-            .min(filtered.len().saturating_sub(1));
-        let scale = SCALE_LIBRARY[filtered[selection]];
-        self.write_harmony_scale(scale.id, cx);
-        self.scale_browser_open = false;
-        cx.notify();
+        let track = self.patcher_track_id.min((TRACK_COUNT - 1) as u32);
+        let mut payload = UiPatcherPresetCommandPayload::default();
+        payload.command_type = UiCommandType::SavePatcherPreset as u16;
+        payload.track_id = track;
+        let bytes = name.as_bytes();
+        let len = bytes.len().min(payload.name.len());
+        payload.name[..len].copy_from_slice(&bytes[..len]);
+        self.enqueue_patcher_preset_payload(payload);
+        self.show_toast("Saved patcher preset", cx);
     }
 
-    fn write_harmony(&mut self, root: u32, cx: &mut impl UiNotify) {
-        let nanotick = self.current_row_nanotick();
+    fn enqueue_chain_payload(&mut self, payload: UiChainCommandPayload) {
+        self.enqueue_chain_command(payload);
+    }
 
-        if self.bridge.is_some() {
-            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
-            let payload = UiCommandPayload {
-                command_type: UiCommandType::WriteHarmony as u16,
-                flags: 0,
-                track_id: 0,
-                plugin_index: 0,
-                note_pitch: root,
-                value0: self.harmony_scale_id,
-                note_nanotick_lo: nanotick_lo,
-                note_nanotick_hi: nanotick_hi,
-                note_duration_lo: 0,
-                note_duration_hi: 0,
-                base_version: self.current_harmony_version(),
-            };
-            self.enqueue_ui_command(payload);
-            self.bump_harmony_version();
-        }
+    pub(crate) fn toggle_chain_add_menu(&mut self, cx: &mut impl UiNotify) {
+        self.chain_add_open = !self.chain_add_open;
+        self.chain_focus = false;
         cx.notify();
     }
 
-    fn write_harmony_at(
+    pub(crate) fn add_chain_device(
         &mut self,
-        nanotick: u64,
-        root: u32,
-        scale_id: u32,
+        device_kind: u32,
+        patcher_node_id: u32,
+        host_slot_index: u32,
         cx: &mut impl UiNotify,
     ) {
-        if self.bridge.is_none() {
-            self.harmony_events.retain(|event| event.nanotick != nanotick);
-            self.harmony_events.push(HarmonyEntry {
-                nanotick,
-                root,
-                scale_id,
-            });
-            self.harmony_events.sort_by_key(|event| event.nanotick);
-            self.bump_harmony_version();
-            self.bump_harmony_render_version();
-            cx.notify();
-            return;
-        }
-        let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
-        let payload = UiCommandPayload {
-            command_type: UiCommandType::WriteHarmony as u16,
+        let payload = UiChainCommandPayload {
+            command_type: UiCommandType::AddDevice as u16,
             flags: 0,
-            track_id: 0,
-            plugin_index: 0,
-            note_pitch: root,
-            value0: scale_id,
-            note_nanotick_lo: nanotick_lo,
-            note_nanotick_hi: nanotick_hi,
-            note_duration_lo: 0,
-            note_duration_hi: 0,
-            base_version: self.current_harmony_version(),
+            track_id: self.focused_track_index as u32,
+            base_version: self.chain_versions[self.focused_track_index],
+            device_id: K_CHAIN_DEVICE_ID_AUTO,
+            device_kind,
+            insert_index: K_CHAIN_DEVICE_ID_AUTO,
+            patcher_node_id,
+            host_slot_index,
+            bypass: 0,
+            reserved: [0; 4],
         };
-        self.enqueue_ui_command(payload);
-        self.bump_harmony_version();
-        self.harmony_events.retain(|event| event.nanotick != nanotick);
-        self.harmony_events.push(HarmonyEntry {
-            nanotick,
-            root,
-            scale_id,
-        });
-        self.harmony_events.sort_by_key(|event| event.nanotick);
-        self.bump_harmony_render_version();
+        self.enqueue_chain_payload(payload);
+        self.chain_add_open = false;
         cx.notify();
     }
 
-    fn write_harmony_scale(&mut self, scale_id: u32, cx: &mut impl UiNotify) {
-        let nanotick = self.current_row_nanotick();
-        let root = self.harmony_root_at(nanotick);
-
-        if self.bridge.is_some() {
-            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
-            let payload = UiCommandPayload {
-                command_type: UiCommandType::WriteHarmony as u16,
-                flags: 0,
-                track_id: 0,
-                plugin_index: 0,
-                note_pitch: root,
-                value0: scale_id,
-                note_nanotick_lo: nanotick_lo,
-                note_nanotick_hi: nanotick_hi,
-                note_duration_lo: 0,
-                note_duration_hi: 0,
-                base_version: self.current_harmony_version(),
-            };
-            self.enqueue_ui_command(payload);
-            self.bump_harmony_version();
+    fn patcher_candidate_nodes(&self, track: usize, device_kind: u32) -> Vec<PatcherNodeUi> {
+        let nodes = self
+            .patcher_nodes
+            .get(track)
+            .cloned()
+            .unwrap_or_default();
+        let edges = self
+            .patcher_edges
+            .get(track)
+            .cloned()
+            .unwrap_or_default();
+        let kind = if device_kind == 2 {
+            PatcherPortKind::Audio
+        } else {
+            PatcherPortKind::Event
+        };
+        let mut candidates: Vec<PatcherNodeUi> = nodes
+            .into_iter()
+            .filter(|node| match kind {
+                PatcherPortKind::Event => node_supports_event_device(node.node_type),
+                PatcherPortKind::Audio => node_supports_audio_device(node.node_type),
+                PatcherPortKind::Control => false,
+            })
+            .collect();
+        if candidates.is_empty() {
+            return candidates;
         }
-        self.harmony_scale_id = scale_id;
-        cx.notify();
-    }
-
-    pub(crate) fn harmony_root_at(&self, nanotick: u64) -> u32 {
-        let mut root = 0;
-        for event in &self.harmony_events {
-            if event.nanotick > nanotick {
-                break;
-            }
-            root = event.root;
-            if event.nanotick == nanotick {
-                break;
-            }
+        let is_sink = |node_id: u32| {
+            !edges.iter().any(|edge| {
+                edge.kind == kind && edge.src_node_id == node_id
+            })
+        };
+        let sinks: Vec<PatcherNodeUi> =
+            candidates.iter().cloned().filter(|node| is_sink(node.id)).collect();
+        if !sinks.is_empty() {
+            candidates = sinks;
         }
-        root
+        candidates
     }
 
-    pub(crate) fn harmony_scale_at(&self, nanotick: u64) -> u32 {
-        let mut scale_id = self.harmony_scale_id;
-        for event in &self.harmony_events {
-            if event.nanotick > nanotick {
-                break;
-            }
-            scale_id = event.scale_id;
-            if event.nanotick == nanotick {
-                break;
+    fn default_patcher_node_id(&self, track: usize, device_kind: u32) -> Option<u32> {
+        let mut candidates = self.patcher_candidate_nodes(track, device_kind);
+        if candidates.is_empty() {
+            return None;
+        }
+        if device_kind != 2 {
+            if let Some(node) = candidates
+                .iter()
+                .find(|node| node.node_type == PATCHER_NODE_EVENT_OUT)
+            {
+                return Some(node.id);
             }
         }
-        scale_id
-    }
-
-    pub(crate) fn current_row_nanotick(&self) -> u64 {
-        self.cursor_nanotick
-    }
-
-    fn lines_per_beat(&self) -> u64 {
-        ZOOM_LEVELS[self.zoom_index]
+        candidates.sort_by_key(|node| node.id);
+        candidates.first().map(|node| node.id)
     }
 
-    pub(crate) fn row_nanoticks(&self) -> u64 {
-        NANOTICKS_PER_QUARTER / self.lines_per_beat()
+    pub(crate) fn add_chain_patcher_device(
+        &mut self,
+        device_kind: u32,
+        cx: &mut impl UiNotify,
+    ) {
+        let track = self.focused_track_index;
+        let patcher_node_id = self
+            .default_patcher_node_id(track, device_kind)
+            .unwrap_or(0);
+        self.add_chain_device(device_kind, patcher_node_id, 0, cx);
     }
 
-    pub(crate) fn desired_clip_window_range(&self) -> (u64, u64) {
-        let row_nanoticks = self.row_nanoticks() as i64;
-        if row_nanoticks <= 0 {
-            return (0, 0);
+    pub(crate) fn cycle_chain_device_patcher_node(
+        &mut self,
+        device_id: u32,
+        forward: bool,
+        cx: &mut impl UiNotify,
+    ) {
+        let track = self.focused_track_index;
+        let Some(devices) = self.chain_devices.get(track) else {
+            self.show_toast("No chain for track", cx);
+            return;
+        };
+        let Some(device) = devices.iter().find(|d| d.id == device_id) else {
+            self.show_toast("Device not found", cx);
+            return;
+        };
+        let mut candidates = self.patcher_candidate_nodes(track, device.kind);
+        if candidates.is_empty() {
+            self.show_toast("No patcher nodes for track", cx);
+            return;
         }
-        let visible_start = self.scroll_nanotick_offset.max(0);
-        let visible_end =
-            visible_start + row_nanoticks.saturating_mul(VISIBLE_ROWS as i64);
-        let mut start = visible_start;
-        let mut end = visible_end;
-        if let Some((sel_start, sel_end)) = self.selection_bounds() {
-            start = start.min(sel_start as i64);
-            end = end.max((sel_end + row_nanoticks as u64) as i64);
+        candidates.sort_by_key(|node| node.id);
+        let current_index = candidates
+            .iter()
+            .position(|node| node.id == device.patcher_node_id);
+        let next_index = match (current_index, forward) {
+            (Some(index), true) => (index + 1) % candidates.len(),
+            (Some(index), false) => {
+                if index == 0 {
+                    candidates.len() - 1
+                } else {
+                    index - 1
+                }
+            }
+            (None, true) => 0,
+            (None, false) => candidates.len().saturating_sub(1),
+        };
+        let next_id = candidates[next_index].id;
+        if next_id == device.patcher_node_id {
+            return;
         }
-        let margin = row_nanoticks.saturating_mul(CLIP_WINDOW_MARGIN_ROWS);
-        start = (start - margin).max(0);
-        end = end.saturating_add(margin);
-        (start as u64, end as u64)
+        self.set_chain_device_patcher_node_id(device_id, next_id, cx);
     }
 
-    fn snap_nanotick_to_row(&self, nanotick: u64) -> u64 {
-        let row_nanoticks = self.row_nanoticks();
-        if row_nanoticks == 0 {
-            return nanotick;
+    pub(crate) fn auto_assign_missing_patcher_nodes(&mut self, cx: &mut impl UiNotify) {
+        let mut updates = Vec::new();
+        for track in 0..TRACK_COUNT {
+            let Some(devices) = self.chain_devices.get(track) else { continue };
+            let nodes = self.patcher_nodes.get(track).cloned().unwrap_or_default();
+            let edges = self.patcher_edges.get(track).cloned().unwrap_or_default();
+            if nodes.is_empty() {
+                continue;
+            }
+            let event_out_id = nodes
+                .iter()
+                .find(|node| node.node_type == PATCHER_NODE_EVENT_OUT)
+                .map(|node| node.id);
+            let is_event_sink = |node_id: u32| {
+                !edges.iter().any(|edge| {
+                    edge.kind == PatcherPortKind::Event && edge.src_node_id == node_id
+                })
+            };
+            for device in devices.iter() {
+                if device.kind > 2 {
+                    continue;
+                }
+                let exists = nodes.iter().any(|node| node.id == device.patcher_node_id);
+                if device.kind == 0 {
+                    let sink_ok = exists && is_event_sink(device.patcher_node_id);
+                    if let Some(event_out) = event_out_id {
+                        if !sink_ok || device.patcher_node_id != event_out {
+                            updates.push((device.id, event_out));
+                        }
+                        continue;
+                    }
+                }
+                if exists {
+                    continue;
+                }
+                if let Some(node_id) = self.default_patcher_node_id(track, device.kind) {
+                    updates.push((device.id, node_id));
+                }
+            }
         }
-        (nanotick / row_nanoticks) * row_nanoticks
-    }
-
-    pub fn cursor_view_row(&self) -> i64 {
-        let row_nanoticks = self.row_nanoticks() as i64;
-        if row_nanoticks <= 0 {
-            return 0;
+        for (device_id, node_id) in updates {
+            self.set_chain_device_patcher_node_id(device_id, node_id, cx);
         }
-        (self.cursor_nanotick as i64 - self.scroll_nanotick_offset) / row_nanoticks
     }
 
+    fn delete_selected_chain_device(&mut self, cx: &mut impl UiNotify) {
+        let Some(device) = self.chain_selected_device() else {
+            self.show_toast("No device selected", cx);
+            return;
+        };
+        let payload = UiChainCommandPayload {
+            command_type: UiCommandType::RemoveDevice as u16,
+            flags: 0,
+            track_id: self.focused_track_index as u32,
+            base_version: self.chain_versions[self.focused_track_index],
+            device_id: device.id,
+            device_kind: device.kind,
+            insert_index: K_CHAIN_DEVICE_ID_AUTO,
+            patcher_node_id: device.patcher_node_id,
+            host_slot_index: device.host_slot_index,
+            bypass: device.bypass as u32,
+            reserved: [0; 4],
+        };
+        self.enqueue_chain_payload(payload);
+        self.focused_chain_device_id = None;
+        cx.notify();
+    }
 
-    pub fn view_row_nanotick(&self, row_index: i64) -> u64 {
-        let row_nanoticks = self.row_nanoticks() as i64;
-        let scroll = self.scroll_nanotick_offset.max(0);
-        let row = row_index.max(0);
-        (scroll + row * row_nanoticks) as u64
+    fn copy_selected_chain_device(&mut self, cx: &mut impl UiNotify) {
+        let Some(device) = self.chain_selected_device() else {
+            self.show_toast("No device selected", cx);
+            return;
+        };
+        self.chain_clipboard = Some(ChainClipboard {
+            kind: device.kind,
+            patcher_node_id: device.patcher_node_id,
+            host_slot_index: device.host_slot_index,
+            bypass: device.bypass,
+        });
+        cx.notify();
     }
 
-    #[allow(dead_code)]
-    pub fn nanotick_for_row(&self, absolute_row: i64) -> u64 {
-        let row_nanoticks = self.row_nanoticks();
-        let row = absolute_row.max(0) as u64;
-        row * row_nanoticks
+    fn cut_selected_chain_device(&mut self, cx: &mut impl UiNotify) {
+        self.copy_selected_chain_device(cx);
+        self.delete_selected_chain_device(cx);
     }
 
-    fn ensure_cursor_visible(&mut self) {
-        let view_rows = VISIBLE_ROWS as i64;
-        let row_nanoticks = self.row_nanoticks() as i64;
-        if row_nanoticks <= 0 {
+    fn paste_chain_device(&mut self, cx: &mut impl UiNotify) {
+        let Some(clipboard) = self.chain_clipboard.clone() else {
+            self.show_toast("Chain clipboard empty", cx);
             return;
-        }
-        let cursor_row = self.cursor_view_row();
-        if cursor_row < 0 {
-            self.scroll_nanotick_offset = self.cursor_nanotick as i64;
-        } else if cursor_row >= view_rows {
-            self.scroll_nanotick_offset =
-                self.cursor_nanotick as i64 - (view_rows - 1) * row_nanoticks;
-        }
-        if self.scroll_nanotick_offset < 0 {
-            self.scroll_nanotick_offset = 0;
-        }
+        };
+        let insert_index = self
+            .chain_selected_device()
+            .map(|device| device.position)
+            .unwrap_or(K_CHAIN_DEVICE_ID_AUTO);
+        let payload = UiChainCommandPayload {
+            command_type: UiCommandType::AddDevice as u16,
+            flags: 0,
+            track_id: self.focused_track_index as u32,
+            base_version: self.chain_versions[self.focused_track_index],
+            device_id: K_CHAIN_DEVICE_ID_AUTO,
+            device_kind: clipboard.kind,
+            insert_index,
+            patcher_node_id: clipboard.patcher_node_id,
+            host_slot_index: clipboard.host_slot_index,
+            bypass: clipboard.bypass as u32,
+            reserved: [0; 4],
+        };
+        self.enqueue_chain_payload(payload);
     }
 
-    fn delete_harmony(&mut self, cx: &mut impl UiNotify) {
-        let nanotick = self.current_row_nanotick();
-
-        // Check if there's actually a harmony event to delete
-        let has_harmony = self.harmony_events.iter()
-            .any(|event| event.nanotick == nanotick);
-
-        if self.bridge.is_some() {
-            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
-            let payload = UiCommandPayload {
-                command_type: UiCommandType::DeleteHarmony as u16,
-                flags: 0,
-                track_id: 0,
-                plugin_index: 0,
-                note_pitch: 0,
-                value0: 0,
-                note_nanotick_lo: nanotick_lo,
-                note_nanotick_hi: nanotick_hi,
-                note_duration_lo: 0,
-                note_duration_hi: 0,
-                base_version: self.current_harmony_version(),
-            };
-            self.enqueue_ui_command(payload);
-            self.bump_harmony_version();
-        }
+    fn duplicate_selected_chain_device(&mut self, cx: &mut impl UiNotify) {
+        let Some(device) = self.chain_selected_device() else {
+            self.show_toast("No device selected", cx);
+            return;
+        };
+        let payload = UiChainCommandPayload {
+            command_type: UiCommandType::AddDevice as u16,
+            flags: 0,
+            track_id: self.focused_track_index as u32,
+            base_version: self.chain_versions[self.focused_track_index],
+            device_id: K_CHAIN_DEVICE_ID_AUTO,
+            device_kind: device.kind,
+            insert_index: device.position.saturating_add(1),
+            patcher_node_id: device.patcher_node_id,
+            host_slot_index: device.host_slot_index,
+            bypass: device.bypass as u32,
+            reserved: [0; 4],
+        };
+        self.enqueue_chain_payload(payload);
+    }
 
-        // Move cursor down after deletion if there was something to delete
-        if has_harmony {
-            self.move_cursor_row(1, cx);
-        }
-        if self.bridge.is_none() {
-            self.bump_harmony_version();
-        }
+    pub(crate) fn toggle_chain_device_bypass(
+        &mut self,
+        device_id: u32,
+        cx: &mut impl UiNotify,
+    ) {
+        let track = self.focused_track_index;
+        let devices = match self.chain_devices.get_mut(track) {
+            Some(devices) => devices,
+            None => {
+                self.show_toast("No chain for track", cx);
+                return;
+            }
+        };
+        let Some(device) = devices.iter_mut().find(|d| d.id == device_id) else {
+            self.show_toast("Device not found", cx);
+            return;
+        };
+        device.bypass = !device.bypass;
+        let payload = UiChainCommandPayload {
+            command_type: UiCommandType::UpdateDevice as u16,
+            flags: 0x1,
+            track_id: track as u32,
+            base_version: self.chain_versions[track],
+            device_id: device.id,
+            device_kind: device.kind,
+            insert_index: K_CHAIN_DEVICE_ID_AUTO,
+            patcher_node_id: device.patcher_node_id,
+            host_slot_index: device.host_slot_index,
+            bypass: device.bypass as u32,
+            reserved: [0; 4],
+        };
+        self.enqueue_chain_payload(payload);
         cx.notify();
     }
 
-    fn delete_note(&mut self, cx: &mut impl UiNotify) {
-        let nanotick = self.current_row_nanotick();
-        if let Some(entry) = self.cell_entry_at(
-            nanotick,
-            self.focused_track_index,
-            self.cursor_col,
-        ) {
-            match entry.kind {
-                CellKind::Note => {
-                    let Some(pitch) = entry.note_pitch else {
-                        return;
-                    };
-                    let mut clip_changed = false;
-                    if self.bridge.is_some() {
-                        let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
-                        let payload = UiCommandPayload {
-                            command_type: UiCommandType::DeleteNote as u16,
-                            flags: self.cursor_col as u16,
-                            track_id: self.focused_track_index as u32,
-                            plugin_index: 0,
-                            note_pitch: pitch as u32,
-                            value0: 0,
-                            note_nanotick_lo,
-                            note_nanotick_hi,
-                            note_duration_lo: 0,
-                            note_duration_hi: 0,
-                            base_version: self.current_clip_version(),
-                        };
-                        self.enqueue_ui_command(payload);
-                        self.bump_clip_version();
-                    } else {
-                        return;
-                    }
-                    if let Some(notes) = self.clip_notes.get_mut(self.focused_track_index) {
-                        let before = notes.len();
-                        notes.retain(|note| {
-                            !(note.nanotick == nanotick &&
-                                note.column == self.cursor_col as u8)
-                        });
-                        if notes.len() != before {
-                            clip_changed = true;
-                        }
-                    }
-                    self.pending_notes.retain(|note| {
-                        !(note.track_id == self.focused_track_index as u32 &&
-                            note.nanotick == nanotick &&
-                            note.column == self.cursor_col as u8)
-                    });
-                    self.pending_chords.retain(|chord| {
-                        !(chord.track_id == self.focused_track_index as u32 &&
-                            chord.nanotick == nanotick &&
-                            chord.column == self.cursor_col as u8)
-                    });
-                    if clip_changed {
-                        self.bump_clip_render_version();
-                    }
-                }
-                CellKind::Chord => {
-                    let Some(chord_id) = entry.chord_id else {
-                        return;
-                    };
-                    self.delete_chord_at(
-                        self.focused_track_index,
-                        chord_id,
-                        nanotick,
-                        self.cursor_col as u8,
-                        cx,
-                    );
-                }
+    pub(crate) fn set_chain_device_patcher_node_id(
+        &mut self,
+        device_id: u32,
+        patcher_node_id: u32,
+        cx: &mut impl UiNotify,
+    ) {
+        let track = self.focused_track_index;
+        let devices = match self.chain_devices.get_mut(track) {
+            Some(devices) => devices,
+            None => {
+                self.show_toast("No chain for track", cx);
+                return;
             }
-        }
-        // Always move cursor down to support rapid backspace on empty cells.
-        self.move_cursor_row(1, cx);
+        };
+        let Some(device) = devices.iter_mut().find(|d| d.id == device_id) else {
+            self.show_toast("Device not found", cx);
+            return;
+        };
+        device.patcher_node_id = patcher_node_id;
+        let payload = UiChainCommandPayload {
+            command_type: UiCommandType::UpdateDevice as u16,
+            flags: 0x2,
+            track_id: track as u32,
+            base_version: self.chain_versions[track],
+            device_id: device.id,
+            device_kind: device.kind,
+            insert_index: K_CHAIN_DEVICE_ID_AUTO,
+            patcher_node_id: device.patcher_node_id,
+            host_slot_index: device.host_slot_index,
+            bypass: device.bypass as u32,
+            reserved: [0; 4],
+        };
+        self.enqueue_chain_payload(payload);
         cx.notify();
     }
 
-    fn delete_note_at(
-        &mut self,
-        track: usize,
-        column: u8,
-        nanotick: u64,
-        pitch: u8,
-        cx: &mut impl UiNotify,
-    ) {
-        if track >= TRACK_COUNT {
+    pub(crate) fn open_vst_editor(&mut self, device_id: u32, cx: &mut impl UiNotify) {
+        let track = self.focused_track_index;
+        let Some(device) = self
+            .chain_devices
+            .get(track)
+            .and_then(|devices| devices.iter().find(|d| d.id == device_id))
+            .cloned()
+        else {
+            self.show_toast("Device not found", cx);
+            return;
+        };
+        if device.kind != 3 && device.kind != 4 {
+            self.show_toast("Editor only available for VSTs", cx);
             return;
-        }
-        if self.bridge.is_some() {
-            let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
-            let payload = UiCommandPayload {
-                command_type: UiCommandType::DeleteNote as u16,
-                flags: column as u16,
-                track_id: track as u32,
-                plugin_index: 0,
-                note_pitch: pitch as u32,
-                value0: 0,
-                note_nanotick_lo,
-                note_nanotick_hi,
-                note_duration_lo: 0,
-                note_duration_hi: 0,
-                base_version: self.current_clip_version(),
-            };
-            self.enqueue_ui_command(payload);
-            self.bump_clip_version();
-        }
-        let mut clip_changed = false;
-        if let Some(notes) = self.clip_notes.get_mut(track) {
-            let before = notes.len();
-            notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
-            if notes.len() != before {
-                clip_changed = true;
-            }
-        }
-        self.pending_notes.retain(|note| {
-            !(note.track_id == track as u32 &&
-                note.nanotick == nanotick &&
-                note.column == column)
-        });
-        if clip_changed {
-            self.bump_clip_render_version();
         }
         if self.bridge.is_none() {
-            self.bump_clip_version();
+            self.show_toast("Engine not connected", cx);
+            return;
         }
+        let payload = UiCommandPayload {
+            command_type: UiCommandType::OpenPluginEditor as u16,
+            flags: 0,
+            track_id: track as u32,
+            plugin_index: 0,
+            note_pitch: 0,
+            value0: device_id,
+            note_nanotick_lo: 0,
+            note_nanotick_hi: 0,
+            note_duration_lo: 0,
+            note_duration_hi: 0,
+            base_version: 0,
+        };
+        self.enqueue_ui_command(payload);
         cx.notify();
     }
 
-    fn delete_chord_at(
-        &mut self,
-        track: usize,
-        chord_id: u32,
-        nanotick: u64,
-        column: u8,
-        cx: &mut impl UiNotify,
-    ) {
-        if track >= TRACK_COUNT {
-            return;
-        }
-        if self.bridge.is_some() {
-            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
-            let payload = UiChordCommandPayload {
-                command_type: UiCommandType::DeleteChord as u16,
-                flags: column as u16,
-                track_id: track as u32,
-                base_version: self.current_clip_version(),
-                nanotick_lo,
-                nanotick_hi,
-                duration_lo: 0,
-                duration_hi: 0,
-                degree: 0,
-                quality: 0,
-                inversion: 0,
-                base_octave: 0,
-                humanize_timing: 0,
-                humanize_velocity: 0,
-                reserved: 0,
-                spread_nanoticks: chord_id,
-            };
-            self.enqueue_chord_command(payload);
-            self.bump_clip_version();
+    fn start_cell_edit(&mut self, initial: &str, cx: &mut impl UiNotify) {
+        if begin_cell_edit(&mut self.edit_active, &mut self.edit_text, initial) {
+            cx.notify();
         }
-        let mut clip_changed = false;
-        if let Some(chords) = self.clip_chords.get_mut(track) {
-            let before = chords.len();
-            chords.retain(|chord| chord.chord_id != chord_id);
-            if chords.len() != before {
-                clip_changed = true;
-            }
+    }
+
+    fn cancel_cell_edit(&mut self, cx: &mut impl UiNotify) {
+        if cancel_cell_edit_state(&mut self.edit_active, &mut self.edit_text) {
+            cx.notify();
         }
-        self.pending_chords.retain(|chord| {
-            if chord.track_id != track as u32 {
-                return true;
-            }
-            if chord_id == 0 {
-                !(chord.nanotick == nanotick && chord.column == column)
-            } else {
-                true
+    }
+
+    fn commit_cell_edit(&mut self, cx: &mut impl UiNotify) {
+        if let Some(token) = commit_cell_edit_state(&mut self.edit_active, &mut self.edit_text) {
+            if !token.is_empty() {
+                self.apply_cell_token(&token, cx);
             }
-        });
-        if clip_changed {
-            self.bump_clip_render_version();
+            cx.notify();
         }
-        if self.bridge.is_none() {
-            self.bump_clip_version();
+    }
+
+    fn clear_edit_state(&mut self) {
+        if self.edit_active {
+            self.edit_active = false;
+            self.edit_text.clear();
         }
+    }
+
+    #[allow(dead_code)]
+    pub fn set_harmony_scale(&mut self, scale_id: u32, cx: &mut impl UiNotify) {
+        self.harmony_scale_id = scale_id;
         cx.notify();
     }
 
-    fn delete_harmony_at(&mut self, nanotick: u64, cx: &mut impl UiNotify) {
-        if self.bridge.is_some() {
-            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
-            let payload = UiCommandPayload {
-                command_type: UiCommandType::DeleteHarmony as u16,
-                flags: 0,
-                track_id: 0,
-                plugin_index: 0,
-                note_pitch: 0,
-                value0: 0,
-                note_nanotick_lo: nanotick_lo,
-                note_nanotick_hi: nanotick_hi,
-                note_duration_lo: 0,
-                note_duration_hi: 0,
-                base_version: self.current_harmony_version(),
-            };
-            self.enqueue_ui_command(payload);
-            self.bump_harmony_version();
+    fn open_scale_browser(&mut self, cx: &mut impl UiNotify) {
+        self.scale_browser_open = true;
+        self.scale_browser_query.clear();
+        self.scale_browser_selection = 0;
+        self.scale_browser_reharmonize = false;
+        self.scale_browser_range = false;
+        cx.notify();
+    }
+
+    fn close_scale_browser(&mut self, cx: &mut impl UiNotify) {
+        if self.scale_browser_open {
+            self.scale_browser_open = false;
+            cx.notify();
         }
-        self.harmony_events.retain(|event| event.nanotick != nanotick);
-        if self.bridge.is_none() {
-            self.bump_harmony_version();
+    }
+
+    fn append_scale_query(&mut self, value: &str, cx: &mut impl UiNotify) {
+        if value.is_empty() {
+            return;
         }
-        self.bump_harmony_render_version();
+        if value.chars().all(|ch| ch.is_whitespace()) {
+            return;
+        }
+        self.scale_browser_query.push_str(value);
+        self.scale_browser_selection = 0;
         cx.notify();
     }
 
-    pub fn send_undo(&mut self, cx: &mut impl UiNotify) {
+    fn backspace_scale_query(&mut self, cx: &mut impl UiNotify) {
+        if self.scale_browser_query.is_empty() {
+            return;
+        }
+        self.scale_browser_query.pop();
+        self.scale_browser_selection = 0;
+        cx.notify();
+    }
+
+    fn move_scale_selection(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        let filtered = self.filtered_scale_indices();
+        if filtered.is_empty() {
+            self.scale_browser_selection = 0;
+            cx.notify();
+            return;
+        }
+        let max_index = filtered.len().saturating_sub(1) as i32;
+        let next =
+            (self.scale_browser_selection as i32 + delta).clamp(0, max_index);
+        self.scale_browser_selection = next as usize;
+        cx.notify();
+    }
+
+    pub(crate) fn confirm_scale_browser(&mut self, cx: &mut impl UiNotify) {
+        if !self.scale_browser_open {
+            return;
+        }
+        let filtered = self.filtered_scale_indices();
+        if filtered.is_empty() {
+            return;
+        }
+        let selection = self
+            .scale_browser_selection
+            .min(filtered.len().saturating_sub(1));
+        let scale = SCALE_LIBRARY[filtered[selection]];
+        if self.scale_browser_reharmonize {
+            self.reharmonize_focused_track(scale.id, cx);
+        } else if self.scale_browser_range {
+            if let Some((start, end)) = self.selection_bounds() {
+                let root = self.harmony_root_at(start);
+                self.write_harmony_over_range(start, end, root, scale.id, true, cx);
+            }
+        } else {
+            self.write_harmony_scale(scale.id, cx);
+        }
+        self.scale_browser_open = false;
+        self.scale_browser_reharmonize = false;
+        self.scale_browser_range = false;
+        cx.notify();
+    }
+
+    /// Repeats the harmony events in the selection right after it, shifted by
+    /// the progression's length rounded up to whole bars so relative timing
+    /// and bar alignment are kept.
+    fn duplicate_harmony_progression(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let Some((start, end)) = self.selection_bounds() else {
+            self.show_toast("No selection", cx);
+            return;
+        };
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.harmony = true;
+        let events = self.collect_harmony_in_range(start, end, &mask);
+        let Some(first) = events.first().map(|event| event.nanotick) else {
+            self.show_toast("No harmony in selection", cx);
+            return;
+        };
+        let offset = harmony_progression_offset(first, end, self.row_nanoticks());
+        for event in &events {
+            self.write_harmony_at(event.nanotick + offset, event.root, event.scale_id, cx);
+        }
+        self.show_toast(&format!("Duplicated {} harmony event(s)", events.len()), cx);
+    }
+
+    /// Writes one harmony event at `start`, optionally deleting the events
+    /// inside `start..=end` first so the new root and scale cover the span.
+    pub(crate) fn write_harmony_over_range(
+        &mut self,
+        start: u64,
+        end: u64,
+        root: u32,
+        scale_id: u32,
+        clear_interior: bool,
+        cx: &mut impl UiNotify,
+    ) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if clear_interior {
+            let mut mask = SelectionMask::empty(TRACK_COUNT);
+            mask.harmony = true;
+            for event in self.collect_harmony_in_range(start, end, &mask) {
+                if event.nanotick != start {
+                    self.delete_harmony_at(event.nanotick, cx);
+                }
+            }
+        }
+        self.write_harmony_at(start, root, scale_id, cx);
+    }
+
+    /// Moves the focused track's degree chords into `scale_id` by rewriting the
+    /// harmony over their span; the chords keep their degrees, so the part
+    /// modulates. Roots are preserved, and the harmony after the span is
+    /// restored so the rest of the song is untouched.
+    pub(crate) fn reharmonize_focused_track(&mut self, scale_id: u32, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let track = self.focused_track_index;
+        let Some(chords) = self.clip_chords.get(track).filter(|chords| !chords.is_empty()) else {
+            self.show_toast("No degree chords on track", cx);
+            return;
+        };
+        let start = chords.iter().map(|chord| chord.nanotick).min().unwrap_or(0);
+        let end = chords
+            .iter()
+            .map(|chord| chord.nanotick.saturating_add(chord.duration.max(1)))
+            .max()
+            .unwrap_or(start);
+
+        let mut writes = vec![(start, self.harmony_root_at(start), scale_id)];
+        writes.extend(
+            self.harmony_events
+                .iter()
+                .filter(|event| event.nanotick > start && event.nanotick < end)
+                .map(|event| (event.nanotick, event.root, scale_id)),
+        );
+        if !self.harmony_events.iter().any(|event| event.nanotick == end) {
+            writes.push((end, self.harmony_root_at(end), self.harmony_scale_at(end)));
+        }
+        for (nanotick, root, scale) in writes {
+            self.write_harmony_at(nanotick, root, scale, cx);
+        }
+        let message = format!("Reharmonized track {} to {}", track + 1, harmony_scale_name(scale_id));
+        self.show_toast(&message, cx);
+    }
+
+    fn write_harmony(&mut self, root: u32, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let nanotick = self.current_row_nanotick();
+
         if self.bridge.is_some() {
+            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
             let payload = UiCommandPayload {
-                command_type: UiCommandType::Undo as u16,
+                command_type: UiCommandType::WriteHarmony as u16,
                 flags: 0,
                 track_id: 0,
                 plugin_index: 0,
-                note_pitch: 0,
-                value0: 0,
-                note_nanotick_lo: 0,
-                note_nanotick_hi: 0,
+                note_pitch: root,
+                value0: self.harmony_scale_id,
+                note_nanotick_lo: nanotick_lo,
+                note_nanotick_hi: nanotick_hi,
                 note_duration_lo: 0,
                 note_duration_hi: 0,
-                base_version: self.current_clip_version(),
+                base_version: self.current_harmony_version(),
             };
             self.enqueue_ui_command(payload);
+            self.bump_harmony_version();
         }
         cx.notify();
     }
 
-    pub fn send_redo(&mut self, cx: &mut impl UiNotify) {
+    fn write_harmony_at(
+        &mut self,
+        nanotick: u64,
+        root: u32,
+        scale_id: u32,
+        cx: &mut impl UiNotify,
+    ) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if self.bridge.is_none() {
+            self.harmony_events.retain(|event| event.nanotick != nanotick);
+            self.harmony_events.push(HarmonyEntry {
+                nanotick,
+                root,
+                scale_id,
+            });
+            self.harmony_events.sort_by_key(|event| event.nanotick);
+            self.bump_harmony_version();
+            self.bump_harmony_render_version();
+            cx.notify();
+            return;
+        }
+        let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
+        let payload = UiCommandPayload {
+            command_type: UiCommandType::WriteHarmony as u16,
+            flags: 0,
+            track_id: 0,
+            plugin_index: 0,
+            note_pitch: root,
+            value0: scale_id,
+            note_nanotick_lo: nanotick_lo,
+            note_nanotick_hi: nanotick_hi,
+            note_duration_lo: 0,
+            note_duration_hi: 0,
+            base_version: self.current_harmony_version(),
+        };
+        self.enqueue_ui_command(payload);
+        self.bump_harmony_version();
+        self.harmony_events.retain(|event| event.nanotick != nanotick);
+        self.harmony_events.push(HarmonyEntry {
+            nanotick,
+            root,
+            scale_id,
+        });
+        self.harmony_events.sort_by_key(|event| event.nanotick);
+        self.bump_harmony_render_version();
+        cx.notify();
+    }
+
+    fn write_harmony_scale(&mut self, scale_id: u32, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let nanotick = self.current_row_nanotick();
+        let root = self.harmony_root_at(nanotick);
+
         if self.bridge.is_some() {
+            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
             let payload = UiCommandPayload {
-                command_type: UiCommandType::Redo as u16,
+                command_type: UiCommandType::WriteHarmony as u16,
                 flags: 0,
                 track_id: 0,
                 plugin_index: 0,
-                note_pitch: 0,
-                value0: 0,
-                note_nanotick_lo: 0,
-                note_nanotick_hi: 0,
+                note_pitch: root,
+                value0: scale_id,
+                note_nanotick_lo: nanotick_lo,
+                note_nanotick_hi: nanotick_hi,
                 note_duration_lo: 0,
                 note_duration_hi: 0,
-                base_version: self.current_clip_version(),
+                base_version: self.current_harmony_version(),
             };
             self.enqueue_ui_command(payload);
+            self.bump_harmony_version();
         }
+        self.harmony_scale_id = scale_id;
         cx.notify();
     }
 
-    fn write_degree_note(&mut self, degree: u8, cx: &mut impl UiNotify) {
-        let chord = ParsedChordToken {
-            degree: degree as u32,
-            quality: 0,  // Plain degree note
-            inversion: 0,
-            base_octave: 4,  // Default to octave 4
-            spread_nanoticks: 0,
-            humanize_timing: 0,
-            humanize_velocity: 0,
-            duration: None,
-        };
-        self.send_chord(chord, cx);
-        self.move_cursor_row(EDIT_STEP_ROWS, cx);
+    pub(crate) fn harmony_root_at(&self, nanotick: u64) -> u32 {
+        let mut root = 0;
+        for event in &self.harmony_events {
+            if event.nanotick > nanotick {
+                break;
+            }
+            root = event.root;
+            if event.nanotick == nanotick {
+                break;
+            }
+        }
+        root
     }
 
-    fn write_note(&mut self, pitch: u8, cx: &mut impl UiNotify) {
-        if self.ui_debug {
-            eprintln!("[UI] write_note(pitch={})", pitch);
+    pub(crate) fn harmony_scale_at(&self, nanotick: u64) -> u32 {
+        let mut scale_id = self.harmony_scale_id;
+        for event in &self.harmony_events {
+            if event.nanotick > nanotick {
+                break;
+            }
+            scale_id = event.scale_id;
+            if event.nanotick == nanotick {
+                break;
+            }
         }
-        let nanotick = self.current_row_nanotick();
-        let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
-        let (note_duration_lo, note_duration_hi) = split_u64(0);
+        scale_id
+    }
 
-        // Clear any existing pending notes at this position
-        self.pending_notes.retain(|note| {
-            !(note.track_id == self.focused_track_index as u32 &&
-                note.nanotick == nanotick &&
-                note.column == self.cursor_col as u8)
-        });
-        self.pending_chords.retain(|chord| {
-            !(chord.track_id == self.focused_track_index as u32 &&
-                chord.nanotick == nanotick &&
-                chord.column == self.cursor_col as u8)
-        });
+    pub(crate) fn current_row_nanotick(&self) -> u64 {
+        self.cursor_nanotick
+    }
 
-        if self.bridge.is_some() {
-            let payload = UiCommandPayload {
-                command_type: UiCommandType::WriteNote as u16,
-                flags: self.cursor_col as u16,
-                track_id: self.focused_track_index as u32,
-                plugin_index: 0,
-                note_pitch: pitch as u32,
-                value0: 100,
-                note_nanotick_lo,
-                note_nanotick_hi,
-                note_duration_lo,
-                note_duration_hi,
-                base_version: self.current_clip_version(),
-            };
-            self.enqueue_ui_command(payload);
-            self.bump_clip_version();
-        } else {
-            return;
-        }
-        self.pending_notes.retain(|note| {
-            !(note.track_id == self.focused_track_index as u32 &&
-                note.nanotick == nanotick &&
-                note.column == self.cursor_col as u8)
-        });
-        self.pending_chords.retain(|chord| {
-            !(chord.track_id == self.focused_track_index as u32 &&
-                chord.nanotick == nanotick &&
-                chord.column == self.cursor_col as u8)
-        });
-        let mut clip_changed = false;
-        if let Some(notes) = self.clip_notes.get_mut(self.focused_track_index) {
-            let before = notes.len();
-            notes.retain(|note| {
-                !(note.nanotick == nanotick && note.column == self.cursor_col as u8)
-            });
-            if notes.len() != before {
-                clip_changed = true;
-            }
-        }
-        if let Some(chords) = self.clip_chords.get_mut(self.focused_track_index) {
-            let before = chords.len();
-            chords.retain(|chord| {
-                !(chord.nanotick == nanotick && chord.column == self.cursor_col as u8)
-            });
-            if chords.len() != before {
-                clip_changed = true;
-            }
-        }
-        if clip_changed {
-            self.bump_clip_render_version();
-        }
-        self.pending_notes.push(PendingNote {
-            track_id: self.focused_track_index as u32,
-            nanotick,
-            duration: 0,
-            pitch,
-            velocity: 100,
-            column: self.cursor_col as u8,
-        });
-        self.move_cursor_row(EDIT_STEP_ROWS, cx);
-        cx.notify(); // Show pending note immediately
+    fn lines_per_beat(&self) -> u64 {
+        ZOOM_LEVELS[self.zoom_index]
     }
 
-    fn write_note_at(
-        &mut self,
-        track: usize,
-        column: u8,
-        nanotick: u64,
-        pitch: u8,
-        velocity: u8,
-        duration: u64,
-        cx: &mut impl UiNotify,
-    ) {
-        if track >= TRACK_COUNT || column as usize >= MAX_NOTE_COLUMNS {
-            return;
-        }
-        if self.bridge.is_none() {
-            let mut clip_changed = false;
-            if let Some(notes) = self.clip_notes.get_mut(track) {
-                notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
-                notes.push(ClipNote {
-                    nanotick,
-                    duration,
-                    pitch,
-                    velocity,
-                    column,
-                });
-                notes.sort_by_key(|note| (note.nanotick, note.column));
-                clip_changed = true;
-            }
-            if let Some(chords) = self.clip_chords.get_mut(track) {
-                let before = chords.len();
-                chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
-                if chords.len() != before {
-                    clip_changed = true;
-                }
-            }
-            if clip_changed {
-                self.bump_clip_render_version();
-            }
-            self.bump_clip_version();
-            cx.notify();
-            return;
-        }
-        let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
-        let (note_duration_lo, note_duration_hi) = split_u64(duration);
-        let payload = UiCommandPayload {
-            command_type: UiCommandType::WriteNote as u16,
-            flags: column as u16,
-            track_id: track as u32,
-            plugin_index: 0,
-            note_pitch: pitch as u32,
-            value0: velocity as u32,
-            note_nanotick_lo,
-            note_nanotick_hi,
-            note_duration_lo,
-            note_duration_hi,
-            base_version: self.current_clip_version(),
-        };
-        self.enqueue_ui_command(payload);
-        self.bump_clip_version();
+    /// Length of one tracker row, never less than a nanotick so snapping and
+    /// scrolling stay meaningful even at zoom levels finer than the grid.
+    pub(crate) fn row_nanoticks(&self) -> u64 {
+        (NANOTICKS_PER_QUARTER / self.lines_per_beat().max(1)).max(1)
+    }
 
-        self.pending_notes.retain(|note| {
-            !(note.track_id == track as u32 &&
-                note.nanotick == nanotick &&
-                note.column == column)
-        });
-        self.pending_chords.retain(|chord| {
-            !(chord.track_id == track as u32 &&
-                chord.nanotick == nanotick &&
-                chord.column == column)
-        });
-        let mut clip_changed = false;
-        if let Some(notes) = self.clip_notes.get_mut(track) {
-            let before = notes.len();
-            notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
-            if notes.len() != before {
-                clip_changed = true;
-            }
-        }
-        if let Some(chords) = self.clip_chords.get_mut(track) {
-            let before = chords.len();
-            chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
-            if chords.len() != before {
-                clip_changed = true;
-            }
+    pub(crate) fn desired_clip_window_range(&self) -> (u64, u64) {
+        // Insert mode shifts whole columns, so it keeps the whole clip loaded.
+        if self.insert_mode || !self.pending_whole_clip_edits.is_empty() {
+            return (0, u64::MAX);
         }
-        if clip_changed {
-            self.bump_clip_render_version();
+        let row_nanoticks = self.row_nanoticks() as i64;
+        let visible_start = self.scroll_nanotick_offset.max(0);
+        let visible_end =
+            visible_start + row_nanoticks.saturating_mul(VISIBLE_ROWS as i64);
+        let mut start = visible_start;
+        let mut end = visible_end;
+        if let Some((sel_start, sel_end)) = self.selection_bounds() {
+            start = start.min(sel_start as i64);
+            end = end.max((sel_end + row_nanoticks as u64) as i64);
         }
-        self.pending_notes.push(PendingNote {
-            track_id: track as u32,
-            nanotick,
-            duration,
-            pitch,
-            velocity,
-            column,
-        });
-        cx.notify();
+        let margin = row_nanoticks.saturating_mul(CLIP_WINDOW_MARGIN_ROWS);
+        start = (start - margin).max(0);
+        end = end.saturating_add(margin);
+        (start as u64, end as u64)
     }
 
-    fn note_off_pitch(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u8> {
-        let mut best: Option<(u64, u8)> = None;
+    /// Whether `clip_notes`/`clip_chords` hold every event of every track.
+    /// A track whose window was never requested has only local events.
+    pub(crate) fn whole_clip_loaded(&self) -> bool {
+        self.clip_window.iter().all(|state| {
+            state.request_id == 0 ||
+                (state.window_start == 0 && state.window_end == u64::MAX && state.complete)
+        })
+    }
 
-        for note in self.pending_notes.iter() {
-            if note.track_id as usize != track_index || note.column != column {
-                continue;
-            }
-            if note.nanotick == nanotick && note.velocity > 0 {
-                return Some(note.pitch);
-            }
-            if note.nanotick < nanotick && note.velocity > 0 {
-                if best.map_or(true, |(best_tick, _)| note.nanotick > best_tick) {
-                    best = Some((note.nanotick, note.pitch));
-                }
-            }
+    /// Returns true when `edit` can run now. Otherwise queues it and widens
+    /// the clip window so it runs once the whole clip has loaded.
+    fn whole_clip_ready(&mut self, edit: WholeClipEdit, cx: &mut impl UiNotify) -> bool {
+        if self.whole_clip_loaded() {
+            return true;
         }
+        self.pending_whole_clip_edits.push(edit);
+        self.show_toast("Loading the whole clip", cx);
+        false
+    }
 
-        if let Some(notes) = self.clip_notes.get(track_index) {
-            for note in notes.iter() {
-                if note.column != column {
-                    continue;
+    /// Runs the queued whole-clip edits, in order, once every track has
+    /// loaded.
+    pub(crate) fn resume_whole_clip_edits(&mut self, cx: &mut impl UiNotify) {
+        if self.pending_whole_clip_edits.is_empty() || !self.whole_clip_loaded() {
+            return;
+        }
+        for edit in std::mem::take(&mut self.pending_whole_clip_edits) {
+            match edit {
+                WholeClipEdit::SwapTracks(a, b) => self.swap_tracks(a, b, cx),
+                WholeClipEdit::TransposeProject(semitones) => {
+                    self.transpose_project(semitones, cx)
                 }
-                if note.nanotick == nanotick && note.velocity > 0 {
-                    return Some(note.pitch);
+                WholeClipEdit::TrimToLoop(track) => self.trim_to_loop(track, cx),
+                WholeClipEdit::DuplicateBar(start) => self.duplicate_bar_at(start, cx),
+                WholeClipEdit::FitTrackColumns(track) => {
+                    self.fit_track_columns(track, cx);
                 }
-                if note.nanotick < nanotick && note.velocity > 0 {
-                    if best.map_or(true, |(best_tick, _)| note.nanotick > best_tick) {
-                        best = Some((note.nanotick, note.pitch));
-                    }
+                WholeClipEdit::MoveTrackColumn { track, from_col, to_col } => {
+                    self.move_track_column(track, from_col, to_col, cx);
                 }
-            }
-        }
-
-        if let Some((_, pitch)) = best {
-            return Some(pitch);
-        }
-
-        let mut chord_tick: Option<u64> = None;
-        for chord in self.pending_chords.iter() {
-            if chord.track_id as usize != track_index || chord.column != column {
-                continue;
-            }
-            if chord.nanotick < nanotick &&
-                chord_tick.map_or(true, |best_tick| chord.nanotick > best_tick) {
-                chord_tick = Some(chord.nanotick);
-            }
-        }
-        if let Some(chords) = self.clip_chords.get(track_index) {
-            for chord in chords.iter() {
-                if chord.column != column {
-                    continue;
+                WholeClipEdit::ApplyGroove(track, template) => {
+                    self.apply_groove(track, &template, cx);
                 }
-                if chord.nanotick < nanotick &&
-                    chord_tick.map_or(true, |best_tick| chord.nanotick > best_tick) {
-                    chord_tick = Some(chord.nanotick);
+                WholeClipEdit::ClampDurations { track, max_rows } => {
+                    self.clamp_durations(track, max_rows, cx);
+                }
+                WholeClipEdit::DeleteColumnNoteOffs { track, column } => {
+                    self.delete_column_note_offs(track, column, cx)
+                }
+                WholeClipEdit::LegatoColumn { track, column } => {
+                    self.legato_column(track, column, cx)
                 }
             }
         }
-        if chord_tick.is_some() {
-            return Some(0);
-        }
-        None
     }
 
-    fn next_note_on_nanotick(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u64> {
-        let mut next: Option<u64> = None;
-        for note in self.pending_notes.iter() {
-            if note.track_id as usize != track_index || note.column != column {
-                continue;
-            }
-            if note.nanotick <= nanotick || note.velocity == 0 {
-                continue;
-            }
-            if next.map_or(true, |best| note.nanotick < best) {
-                next = Some(note.nanotick);
-            }
-        }
-        if let Some(notes) = self.clip_notes.get(track_index) {
-            for note in notes.iter() {
-                if note.column != column || note.velocity == 0 {
-                    continue;
-                }
-                if note.nanotick <= nanotick {
-                    continue;
-                }
-                if next.map_or(true, |best| note.nanotick < best) {
-                    next = Some(note.nanotick);
-                }
-            }
-        }
-        next
+    /// Moves the cursor onto the highest-velocity note in the visible rows of
+    /// the focused track. The earliest note wins a tie.
+    fn jump_to_loudest_visible_note(&mut self, cx: &mut impl UiNotify) {
+        let row_nanoticks = self.row_nanoticks();
+        let visible_start = self.scroll_nanotick_offset.max(0) as u64;
+        let visible_end =
+            visible_start.saturating_add(row_nanoticks.saturating_mul(VISIBLE_ROWS as u64));
+        let loudest = self
+            .clip_notes
+            .get(self.focused_track_index)
+            .into_iter()
+            .flatten()
+            .filter(|note| note.velocity > 0)
+            .filter(|note| note.nanotick >= visible_start && note.nanotick < visible_end)
+            .min_by_key(|note| (std::cmp::Reverse(note.velocity), note.nanotick, note.column))
+            .map(|note| (note.nanotick, note.column));
+        let Some((nanotick, column)) = loudest else {
+            self.show_toast("No notes in view", cx);
+            return;
+        };
+        self.cursor_nanotick = self.snap_nanotick_to_row(nanotick);
+        self.cursor_col = column as usize;
+        self.harmony_focus = false;
+        self.clear_edit_state();
+        self.ensure_cursor_visible();
+        cx.notify();
     }
 
-    fn prev_note_on_nanotick(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u64> {
-        let mut prev: Option<u64> = None;
-        for note in self.pending_notes.iter() {
-            if note.track_id as usize != track_index || note.column != column {
-                continue;
-            }
-            if note.nanotick >= nanotick || note.velocity == 0 {
-                continue;
-            }
-            if prev.map_or(true, |best| note.nanotick > best) {
-                prev = Some(note.nanotick);
-            }
-        }
-        if let Some(notes) = self.clip_notes.get(track_index) {
-            for note in notes.iter() {
-                if note.column != column || note.velocity == 0 {
-                    continue;
-                }
-                if note.nanotick >= nanotick {
-                    continue;
-                }
-                if prev.map_or(true, |best| note.nanotick > best) {
-                    prev = Some(note.nanotick);
-                }
-            }
-        }
-        prev
+    /// Moves the cursor to the start of the busiest stretch of the timeline,
+    /// as the minimap sees it.
+    fn jump_to_densest_region(&mut self, cx: &mut impl UiNotify) {
+        let Some(nanotick) = self.densest_bin_start(Self::minimap_segment_count()) else {
+            self.show_toast("Timeline is empty", cx);
+            return;
+        };
+        self.harmony_focus = false;
+        self.clear_edit_state();
+        self.jump_to_nanotick(nanotick, cx);
     }
 
-    fn prev_chord_nanotick(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u64> {
-        let mut prev: Option<u64> = None;
-        for chord in self.pending_chords.iter() {
-            if chord.track_id as usize != track_index {
-                continue;
-            }
-            if chord.column != column {
-                continue;
-            }
-            if chord.nanotick >= nanotick {
-                continue;
-            }
-            if prev.map_or(true, |best| chord.nanotick > best) {
-                prev = Some(chord.nanotick);
-            }
-        }
-        if let Some(chords) = self.clip_chords.get(track_index) {
-            for chord in chords.iter() {
-                if chord.column != column {
-                    continue;
-                }
-                if chord.nanotick >= nanotick {
-                    continue;
-                }
-                if prev.map_or(true, |best| chord.nanotick > best) {
-                    prev = Some(chord.nanotick);
-                }
-            }
-        }
-        prev
+    fn snap_nanotick_to_row(&self, nanotick: u64) -> u64 {
+        let row_nanoticks = self.row_nanoticks();
+        (nanotick / row_nanoticks) * row_nanoticks
     }
 
-    fn next_chord_nanotick(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u64> {
-        let mut next: Option<u64> = None;
-        for chord in self.pending_chords.iter() {
-            if chord.track_id as usize != track_index {
-                continue;
-            }
-            if chord.column != column {
-                continue;
-            }
-            if chord.nanotick <= nanotick {
-                continue;
-            }
-            if next.map_or(true, |best| chord.nanotick < best) {
-                next = Some(chord.nanotick);
-            }
-        }
-        if let Some(chords) = self.clip_chords.get(track_index) {
-            for chord in chords.iter() {
-                if chord.column != column {
-                    continue;
-                }
-                if chord.nanotick <= nanotick {
-                    continue;
-                }
-                if next.map_or(true, |best| chord.nanotick < best) {
-                    next = Some(chord.nanotick);
-                }
-            }
-        }
-        next
+    pub fn cursor_view_row(&self) -> i64 {
+        let row_nanoticks = self.row_nanoticks() as i64;
+        (self.cursor_nanotick as i64 - self.scroll_nanotick_offset) / row_nanoticks
     }
 
-    fn remove_note_offs_in_span(
-        &mut self,
-        track_index: usize,
-        column: u8,
-        nanotick: u64,
-        prev_boundary: Option<u64>,
-        next_boundary: Option<u64>,
-    ) -> bool {
-        let lower = prev_boundary.unwrap_or(0);
-        let upper = next_boundary.unwrap_or(u64::MAX);
-        let in_range = |note_nanotick: u64| {
-            if note_nanotick == nanotick {
-                return false;
-            }
-            note_nanotick > lower && note_nanotick < upper
-        };
 
-        self.pending_notes.retain(|note| {
-            if note.track_id as usize != track_index || note.column != column {
-                return true;
-            }
-            if note.velocity != 0 || note.duration != 0 {
-                return true;
-            }
-            !in_range(note.nanotick)
-        });
+    pub fn view_row_nanotick(&self, row_index: i64) -> u64 {
+        let row_nanoticks = self.row_nanoticks() as i64;
+        let scroll = self.scroll_nanotick_offset.max(0);
+        let row = row_index.max(0);
+        (scroll + row * row_nanoticks) as u64
+    }
 
-        let mut removed = false;
-        if let Some(notes) = self.clip_notes.get_mut(track_index) {
-            let before = notes.len();
-            notes.retain(|note| {
-                if note.column != column {
-                    return true;
-                }
-                if note.velocity != 0 || note.duration != 0 {
-                    return true;
-                }
-                !in_range(note.nanotick)
-            });
-            if notes.len() != before {
-                removed = true;
-            }
-        }
-        removed
+    #[allow(dead_code)]
+    pub fn nanotick_for_row(&self, absolute_row: i64) -> u64 {
+        let row_nanoticks = self.row_nanoticks();
+        let row = absolute_row.max(0) as u64;
+        row * row_nanoticks
     }
 
-    fn write_note_off(&mut self, cx: &mut impl UiNotify) {
-        let nanotick = self.current_row_nanotick();
-        let column = self.cursor_col as u8;
-        let prev_note_on = self.prev_note_on_nanotick(
-            self.focused_track_index,
-            column,
-            nanotick,
-        );
-        let next_note_on = self.next_note_on_nanotick(
-            self.focused_track_index,
-            column,
-            nanotick,
-        );
-        let prev_chord = self.prev_chord_nanotick(self.focused_track_index, column, nanotick);
-        let next_chord = self.next_chord_nanotick(self.focused_track_index, column, nanotick);
-        let (prev_boundary, next_boundary) = (
-            match (prev_note_on, prev_chord) {
-                (Some(a), Some(b)) => Some(a.max(b)),
-                (Some(a), None) => Some(a),
-                (None, Some(b)) => Some(b),
-                (None, None) => None,
-            },
-            match (next_note_on, next_chord) {
-                (Some(a), Some(b)) => Some(a.min(b)),
-                (Some(a), None) => Some(a),
-                (None, Some(b)) => Some(b),
-                (None, None) => None,
-            },
-        );
+    fn ensure_cursor_visible(&mut self) {
+        let view_rows = VISIBLE_ROWS as i64;
+        let row_nanoticks = self.row_nanoticks() as i64;
+        let cursor_row = self.cursor_view_row();
+        if cursor_row < 0 {
+            self.scroll_nanotick_offset = self.cursor_nanotick as i64;
+        } else if cursor_row >= view_rows {
+            self.scroll_nanotick_offset =
+                self.cursor_nanotick as i64 - (view_rows - 1) * row_nanoticks;
+        }
+        if self.scroll_nanotick_offset < 0 {
+            self.scroll_nanotick_offset = 0;
+        }
+    }
 
-        let pitch = self
-            .note_off_pitch(self.focused_track_index, column, nanotick)
-            .unwrap_or(0);
-        if pitch == 0 && prev_boundary.is_none() {
-            self.move_cursor_row(EDIT_STEP_ROWS, cx);
-            cx.notify();
+    fn delete_harmony(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
             return;
         }
-        let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
-        let (note_duration_lo, note_duration_hi) = split_u64(0);
+        let nanotick = self.current_row_nanotick();
 
-        self.pending_notes.retain(|note| {
-            !(note.track_id == self.focused_track_index as u32 &&
-                note.nanotick == nanotick &&
-                note.column == self.cursor_col as u8)
-        });
-        self.pending_chords.retain(|chord| {
-            !(chord.track_id == self.focused_track_index as u32 &&
-                chord.nanotick == nanotick &&
-                chord.column == self.cursor_col as u8)
-        });
+        // Check if there's actually a harmony event to delete
+        let has_harmony = self.harmony_events.iter()
+            .any(|event| event.nanotick == nanotick);
 
         if self.bridge.is_some() {
+            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
             let payload = UiCommandPayload {
-                command_type: UiCommandType::WriteNote as u16,
-                flags: self.cursor_col as u16,
-                track_id: self.focused_track_index as u32,
+                command_type: UiCommandType::DeleteHarmony as u16,
+                flags: 0,
+                track_id: 0,
                 plugin_index: 0,
-                note_pitch: pitch as u32,
+                note_pitch: 0,
                 value0: 0,
-                note_nanotick_lo,
-                note_nanotick_hi,
-                note_duration_lo,
-                note_duration_hi,
-                base_version: self.current_clip_version(),
+                note_nanotick_lo: nanotick_lo,
+                note_nanotick_hi: nanotick_hi,
+                note_duration_lo: 0,
+                note_duration_hi: 0,
+                base_version: self.current_harmony_version(),
             };
             self.enqueue_ui_command(payload);
-            self.bump_clip_version();
-        } else {
+            self.bump_harmony_version();
+        }
+
+        // Move cursor down after deletion if there was something to delete
+        if has_harmony {
+            self.move_cursor_row(1, cx);
+        }
+        if self.bridge.is_none() {
+            self.bump_harmony_version();
+        }
+        cx.notify();
+    }
+
+    fn delete_note(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
             return;
         }
-        self.pending_notes.retain(|note| {
-            !(note.track_id == self.focused_track_index as u32 &&
-                note.nanotick == nanotick &&
-                note.column == self.cursor_col as u8)
-        });
-        self.pending_chords.retain(|chord| {
-            !(chord.track_id == self.focused_track_index as u32 &&
-                chord.nanotick == nanotick &&
-                chord.column == self.cursor_col as u8)
-        });
-        let mut clip_changed = false;
-        if let Some(notes) = self.clip_notes.get_mut(self.focused_track_index) {
-            let before = notes.len();
-            notes.retain(|note| {
-                !(note.nanotick == nanotick && note.column == self.cursor_col as u8)
-            });
-            if notes.len() != before {
-                clip_changed = true;
+        let nanotick = self.current_row_nanotick();
+        if let Some(entry) = self.cell_entry_at(
+            nanotick,
+            self.focused_track_index,
+            self.cursor_col,
+        ) {
+            match entry.kind {
+                CellKind::Note => {
+                    let Some(pitch) = entry.note_pitch else {
+                        return;
+                    };
+                    let mut clip_changed = false;
+                    if self.bridge.is_some() {
+                        let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
+                        let payload = UiCommandPayload {
+                            command_type: UiCommandType::DeleteNote as u16,
+                            flags: self.cursor_col as u16,
+                            track_id: self.focused_track_index as u32,
+                            plugin_index: 0,
+                            note_pitch: pitch as u32,
+                            value0: 0,
+                            note_nanotick_lo,
+                            note_nanotick_hi,
+                            note_duration_lo: 0,
+                            note_duration_hi: 0,
+                            base_version: self.current_clip_version(),
+                        };
+                        self.enqueue_ui_command(payload);
+                        self.bump_clip_version();
+                    } else {
+                        return;
+                    }
+                    if let Some(notes) = self.clip_notes.get_mut(self.focused_track_index) {
+                        let before = notes.len();
+                        notes.retain(|note| {
+                            !(note.nanotick == nanotick &&
+                                note.column == self.cursor_col as u8)
+                        });
+                        if notes.len() != before {
+                            clip_changed = true;
+                        }
+                    }
+                    self.pending_notes.retain(|note| {
+                        !(note.track_id == self.focused_track_index as u32 &&
+                            note.nanotick == nanotick &&
+                            note.column == self.cursor_col as u8)
+                    });
+                    self.pending_chords.retain(|chord| {
+                        !(chord.track_id == self.focused_track_index as u32 &&
+                            chord.nanotick == nanotick &&
+                            chord.column == self.cursor_col as u8)
+                    });
+                    if clip_changed {
+                        self.bump_clip_render_version();
+                    }
+                }
+                CellKind::Chord => {
+                    let Some(chord_id) = entry.chord_id else {
+                        return;
+                    };
+                    self.delete_chord_at(
+                        self.focused_track_index,
+                        chord_id,
+                        nanotick,
+                        self.cursor_col as u8,
+                        cx,
+                    );
+                }
             }
         }
-        if let Some(chords) = self.clip_chords.get_mut(self.focused_track_index) {
-            let before = chords.len();
-            chords.retain(|chord| {
-                !(chord.nanotick == nanotick && chord.column == self.cursor_col as u8)
-            });
-            if chords.len() != before {
-                clip_changed = true;
-            }
+        // Always move cursor down to support rapid backspace on empty cells.
+        self.move_cursor_row(1, cx);
+        cx.notify();
+    }
+
+    /// Merges the note under the cursor into the previous note in its
+    /// column. Both must share a pitch and must not overlap; the joined note
+    /// spans from the first note's start to the second note's end.
+    fn join_notes_at_cursor(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
         }
-        if self.remove_note_offs_in_span(
-            self.focused_track_index,
+        let track = self.focused_track_index;
+        let column = self.cursor_col as u8;
+        let nanotick = self.current_row_nanotick();
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.tracks[track] = 1u8 << column;
+        let mut notes: Vec<ClipNote> = self
+            .collect_notes_in_range(0, u64::MAX, &mask, true)
+            .into_iter()
+            .map(|(_, note)| note)
+            .collect();
+        notes.sort_by_key(|note| note.nanotick);
+        let Some(index) = notes.iter().position(|note| note.nanotick == nanotick) else {
+            self.show_toast("No note at cursor", cx);
+            return;
+        };
+        let second = notes[index].clone();
+        let Some(first) = index.checked_sub(1).map(|prev| notes[prev].clone()) else {
+            self.show_toast("No previous note to join", cx);
+            return;
+        };
+        let is_note_off = |note: &ClipNote| note.velocity == 0 && note.duration == 0;
+        if is_note_off(&first) || is_note_off(&second) || first.pitch != second.pitch {
+            self.show_toast("Join needs two notes of the same pitch", cx);
+            return;
+        }
+        if second.nanotick < first.nanotick.saturating_add(first.duration) {
+            self.show_toast("Notes overlap", cx);
+            return;
+        }
+        let duration = second.nanotick + second.duration - first.nanotick;
+        self.delete_note_at(track, column, second.nanotick, second.pitch, cx);
+        self.write_note_at(
+            track,
             column,
-            nanotick,
-            prev_boundary,
-            next_boundary,
-        ) {
-            clip_changed = true;
+            first.nanotick,
+            first.pitch,
+            first.velocity,
+            duration,
+            cx,
+        );
+        self.show_toast("Notes joined", cx);
+    }
+
+    /// Shortens every note on `track` that sounds longer than `max_rows` rows
+    /// to exactly that length, once the whole clip has loaded. Returns the
+    /// number of notes shortened.
+    pub(crate) fn clamp_durations(
+        &mut self,
+        track: usize,
+        max_rows: u64,
+        cx: &mut impl UiNotify,
+    ) -> usize {
+        if track >= TRACK_COUNT || self.read_only_blocked(cx) {
+            return 0;
         }
-        if clip_changed {
-            self.bump_clip_render_version();
+        if !self.whole_clip_ready(WholeClipEdit::ClampDurations { track, max_rows }, cx) {
+            return 0;
         }
-        self.pending_notes.push(PendingNote {
-            track_id: self.focused_track_index as u32,
-            nanotick,
-            duration: 0,
-            pitch,
-            velocity: 0,
-            column: self.cursor_col as u8,
-        });
-        self.move_cursor_row(EDIT_STEP_ROWS, cx);
+        let max_duration = max_rows.max(1).saturating_mul(self.row_nanoticks());
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.tracks[track] = u8::MAX;
+        let long_notes: Vec<ClipNote> = self
+            .collect_notes_in_range(0, u64::MAX, &mask, true)
+            .into_iter()
+            .map(|(_, note)| note)
+            .filter(|note| note.duration > max_duration)
+            .collect();
+        for note in &long_notes {
+            self.write_note_at(
+                track,
+                note.column,
+                note.nanotick,
+                note.pitch,
+                note.velocity,
+                max_duration,
+                cx,
+            );
+        }
+        self.show_toast(&format!("Shortened {} notes", long_notes.len()), cx);
+        long_notes.len()
     }
 
-    pub fn toggle_play(&mut self, cx: &mut impl UiNotify) {
+    /// Deletes every note-off in `column` of `track`, leaving note-ons alone,
+    /// once the whole clip has loaded.
+    fn delete_column_note_offs(&mut self, track: usize, column: u8, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if !self.whole_clip_ready(WholeClipEdit::DeleteColumnNoteOffs { track, column }, cx) {
+            return;
+        }
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.tracks[track] = 1u8 << column;
+        let note_offs: Vec<ClipNote> = self
+            .collect_notes_in_range(0, u64::MAX, &mask, true)
+            .into_iter()
+            .map(|(_, note)| note)
+            .filter(|note| note.velocity == 0 && note.duration == 0)
+            .collect();
+        for note in &note_offs {
+            self.delete_note_at(track, column, note.nanotick, note.pitch, cx);
+        }
+        self.show_toast(&format!("Removed {} note-offs", note_offs.len()), cx);
+    }
+
+    /// Stretches every note in `column` of `track` to end at the next note-on,
+    /// once the whole clip has loaded. The last note keeps its length;
+    /// note-offs are left alone.
+    fn legato_column(&mut self, track: usize, column: u8, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if !self.whole_clip_ready(WholeClipEdit::LegatoColumn { track, column }, cx) {
+            return;
+        }
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.tracks[track] = 1u8 << column;
+        let mut notes: Vec<ClipNote> = self
+            .collect_notes_in_range(0, u64::MAX, &mask, true)
+            .into_iter()
+            .map(|(_, note)| note)
+            .filter(|note| note.velocity > 0)
+            .collect();
+        notes.sort_by_key(|note| note.nanotick);
+        let mut changed = 0;
+        for pair in notes.windows(2) {
+            let (note, next) = (&pair[0], &pair[1]);
+            let duration = next.nanotick - note.nanotick;
+            if duration == note.duration {
+                continue;
+            }
+            self.write_note_at(
+                track,
+                column,
+                note.nanotick,
+                note.pitch,
+                note.velocity,
+                duration,
+                cx,
+            );
+            changed += 1;
+        }
+        self.show_toast(&format!("Made {changed} notes legato"), cx);
+    }
+
+    fn delete_note_at(
+        &mut self,
+        track: usize,
+        column: u8,
+        nanotick: u64,
+        pitch: u8,
+        cx: &mut impl UiNotify,
+    ) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if track >= TRACK_COUNT {
+            return;
+        }
         if self.bridge.is_some() {
+            let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
             let payload = UiCommandPayload {
-                command_type: UiCommandType::TogglePlay as u16,
-                flags: 0,
-                track_id: 0,
+                command_type: UiCommandType::DeleteNote as u16,
+                flags: column as u16,
+                track_id: track as u32,
                 plugin_index: 0,
-                note_pitch: 0,
+                note_pitch: pitch as u32,
                 value0: 0,
-                note_nanotick_lo: 0,
-                note_nanotick_hi: 0,
+                note_nanotick_lo,
+                note_nanotick_hi,
                 note_duration_lo: 0,
                 note_duration_hi: 0,
-                base_version: 0,
+                base_version: self.current_clip_version(),
             };
             self.enqueue_ui_command(payload);
+            self.bump_clip_version();
+        }
+        let mut clip_changed = false;
+        if let Some(notes) = self.clip_notes.get_mut(track) {
+            let before = notes.len();
+            notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
+            if notes.len() != before {
+                clip_changed = true;
+            }
+        }
+        self.pending_notes.retain(|note| {
+            !(note.track_id == track as u32 &&
+                note.nanotick == nanotick &&
+                note.column == column)
+        });
+        if clip_changed {
+            self.bump_clip_render_version();
+        }
+        if self.bridge.is_none() {
+            self.bump_clip_version();
         }
         cx.notify();
     }
 
-
-    pub fn cell_entry_at(
-        &self,
+    fn delete_chord_at(
+        &mut self,
+        track: usize,
+        chord_id: u32,
         nanotick: u64,
-        track_index: usize,
-        column: usize,
-    ) -> Option<CellEntry> {
-        let entries = self.entries_for_row(nanotick, track_index);
-        entries
-            .iter()
-            .find(|entry| entry.column == column)
-            .cloned()
-    }
-
-    pub fn entries_for_row(&self, nanotick: u64, track_index: usize) -> Vec<CellEntry> {
-        let mut entries: Vec<CellEntry> = Vec::new();
-        if let Some(notes) = self.clip_notes.get(track_index) {
-            for note in notes.iter().filter(|note| note.nanotick == nanotick) {
-                let is_note_off = note.velocity == 0 && note.duration == 0;
-                entries.push(CellEntry {
-                    kind: CellKind::Note,
-                    text: if is_note_off {
-                        String::new()
-                    } else {
-                        pitch_to_note(note.pitch)
-                    },
-                    nanotick,
-                    note_pitch: Some(note.pitch),
-                    chord_id: None,
-                    column: note.column as usize,
-                    note_off: is_note_off,
-                });
-            }
-        }
-        for note in self
-            .pending_notes
-            .iter()
-            .filter(|note| note.track_id as usize == track_index && note.nanotick == nanotick)
-        {
-            let is_note_off = note.velocity == 0 && note.duration == 0;
-            entries.push(CellEntry {
-                kind: CellKind::Note,
-                text: if is_note_off {
-                    String::new()
-                } else {
-                    pitch_to_note(note.pitch)
-                },
-                nanotick,
-                note_pitch: Some(note.pitch),
-                chord_id: None,
-                column: note.column as usize,
-                note_off: is_note_off,
-            });
-        }
-        let mut pending_chord_columns = std::collections::HashSet::new();
-        for chord in self.pending_chords.iter().filter(|chord| {
-            chord.track_id as usize == track_index && chord.nanotick == nanotick
-        }) {
-            pending_chord_columns.insert(chord.column);
-            let temp = ClipChord {
-                chord_id: 0,
-                nanotick,
-                duration: chord.duration,
-                spread: chord.spread,
-                humanize_timing: chord.humanize_timing,
-                humanize_velocity: chord.humanize_velocity,
-                degree: chord.degree,
-                quality: chord.quality,
-                inversion: chord.inversion,
-                base_octave: chord.base_octave,
-                column: chord.column,
-            };
-            entries.push(CellEntry {
-                kind: CellKind::Chord,
-                text: chord_token_text(&temp),
-                nanotick,
-                note_pitch: None,
-                chord_id: Some(0),
-                column: chord.column as usize,
-                note_off: false,
-            });
-        }
-        if let Some(chords) = self.clip_chords.get(track_index) {
-            for chord in chords.iter().filter(|chord| chord.nanotick == nanotick) {
-                if pending_chord_columns.contains(&chord.column) {
-                    continue;
-                }
-                let text = chord_token_text(chord);
-                entries.push(CellEntry {
-                    kind: CellKind::Chord,
-                    text,
-                    nanotick,
-                    note_pitch: None,
-                    chord_id: Some(chord.chord_id),
-                    column: chord.column as usize,
-                    note_off: false,
-                });
-            }
-        }
-        entries.sort_by(|a, b| {
-            match (&a.kind, &b.kind) {
-                (CellKind::Chord, CellKind::Note) => std::cmp::Ordering::Less,
-                (CellKind::Note, CellKind::Chord) => std::cmp::Ordering::Greater,
-                _ => a.text.cmp(&b.text),
-            }
-        });
-        entries
-    }
-
-    fn apply_cell_token(&mut self, token: &str, cx: &mut impl UiNotify) {
-        if let Some(parsed) = parse_chord_token(token) {
-            self.send_chord(parsed, cx);
+        column: u8,
+        cx: &mut impl UiNotify,
+    ) {
+        if self.read_only_blocked(cx) {
             return;
         }
-        if let Some(parsed) = parse_degree_note_token(token) {
-            self.send_chord(parsed, cx);
+        if track >= TRACK_COUNT {
             return;
         }
-        if let Some(note) = parse_note_token(token) {
-            self.write_note(note, cx);
-        }
-    }
-
-    fn send_chord(&mut self, chord: ParsedChordToken, cx: &mut impl UiNotify) {
-        let nanotick = self.current_row_nanotick();
-        let duration = chord.duration.unwrap_or(0);
-        let degree = chord.degree.min(255) as u16;
-        let pending_degree = degree as u8;
-        let column = self.cursor_col as u8;
-
         if self.bridge.is_some() {
             let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
-            let (duration_lo, duration_hi) = split_u64(duration);
             let payload = UiChordCommandPayload {
-                command_type: UiCommandType::WriteChord as u16,
+                command_type: UiCommandType::DeleteChord as u16,
                 flags: column as u16,
-                track_id: self.focused_track_index as u32,
+                track_id: track as u32,
                 base_version: self.current_clip_version(),
                 nanotick_lo,
                 nanotick_hi,
-                duration_lo,
-                duration_hi,
-                degree,
-                quality: chord.quality,
-                inversion: chord.inversion,
-                base_octave: chord.base_octave,
-                humanize_timing: chord.humanize_timing,
-                humanize_velocity: chord.humanize_velocity,
+                duration_lo: 0,
+                duration_hi: 0,
+                degree: 0,
+                quality: 0,
+                inversion: 0,
+                base_octave: 0,
+                humanize_timing: 0,
+                humanize_velocity: 0,
                 reserved: 0,
-                spread_nanoticks: chord.spread_nanoticks,
+                spread_nanoticks: chord_id,
             };
             self.enqueue_chord_command(payload);
             self.bump_clip_version();
-        } else {
-            return;
         }
-        self.pending_notes.retain(|note| {
-            !(note.track_id == self.focused_track_index as u32 &&
-                note.nanotick == nanotick &&
-                note.column == column)
-        });
-        self.pending_chords.retain(|pending| {
-            !(pending.track_id == self.focused_track_index as u32 &&
-                pending.nanotick == nanotick &&
-                pending.column == column)
-        });
         let mut clip_changed = false;
-        if let Some(notes) = self.clip_notes.get_mut(self.focused_track_index) {
-            let before = notes.len();
-            notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
-            if notes.len() != before {
-                clip_changed = true;
-            }
-        }
-        if let Some(chords) = self.clip_chords.get_mut(self.focused_track_index) {
+        if let Some(chords) = self.clip_chords.get_mut(track) {
             let before = chords.len();
-            chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
+            chords.retain(|chord| chord.chord_id != chord_id);
             if chords.len() != before {
                 clip_changed = true;
             }
         }
+        self.pending_chords.retain(|chord| {
+            if chord.track_id != track as u32 {
+                return true;
+            }
+            if chord_id == 0 {
+                !(chord.nanotick == nanotick && chord.column == column)
+            } else {
+                true
+            }
+        });
         if clip_changed {
             self.bump_clip_render_version();
         }
-        self.pending_chords.push(PendingChord {
-            track_id: self.focused_track_index as u32,
-            nanotick,
-            duration,
-            spread: chord.spread_nanoticks,
-            humanize_timing: chord.humanize_timing as u16,
-            humanize_velocity: chord.humanize_velocity as u16,
-            degree: pending_degree,
-            quality: chord.quality,
-            inversion: chord.inversion,
-            base_octave: chord.base_octave,
-            column,
-        });
+        if self.bridge.is_none() {
+            self.bump_clip_version();
+        }
         cx.notify();
     }
 
-    fn write_chord_at(
-        &mut self,
-        track: usize,
-        column: u8,
-        nanotick: u64,
-        duration: u64,
-        degree: u8,
-        quality: u8,
-        inversion: u8,
-        base_octave: u8,
-        spread: u32,
-        humanize_timing: u16,
-        humanize_velocity: u16,
-        cx: &mut impl UiNotify,
-    ) {
-        if track >= TRACK_COUNT || column as usize >= MAX_NOTE_COLUMNS {
+    fn delete_harmony_at(&mut self, nanotick: u64, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
             return;
         }
-        if self.bridge.is_none() {
-            let mut clip_changed = false;
-            if let Some(notes) = self.clip_notes.get_mut(track) {
-                notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
-                clip_changed = true;
-            }
-            if let Some(chords) = self.clip_chords.get_mut(track) {
-                chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
-                chords.push(ClipChord {
-                    chord_id: 0,
-                    nanotick,
-                    duration,
-                    spread,
-                    humanize_timing,
-                    humanize_velocity,
-                    degree,
-                    quality,
-                    inversion,
-                    base_octave,
-                    column,
-                });
-                chords.sort_by_key(|chord| (chord.nanotick, chord.column));
-                clip_changed = true;
-            }
-            if clip_changed {
-                self.bump_clip_render_version();
-            }
-            self.bump_clip_version();
-            cx.notify();
-            return;
+        if self.bridge.is_some() {
+            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
+            let payload = UiCommandPayload {
+                command_type: UiCommandType::DeleteHarmony as u16,
+                flags: 0,
+                track_id: 0,
+                plugin_index: 0,
+                note_pitch: 0,
+                value0: 0,
+                note_nanotick_lo: nanotick_lo,
+                note_nanotick_hi: nanotick_hi,
+                note_duration_lo: 0,
+                note_duration_hi: 0,
+                base_version: self.current_harmony_version(),
+            };
+            self.enqueue_ui_command(payload);
+            self.bump_harmony_version();
         }
-        let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
-        let (duration_lo, duration_hi) = split_u64(duration);
-        let timing = humanize_timing.min(u16::from(u8::MAX)) as u8;
-        let velocity = humanize_velocity.min(u16::from(u8::MAX)) as u8;
-        let payload = UiChordCommandPayload {
-            command_type: UiCommandType::WriteChord as u16,
-            flags: column as u16,
-            track_id: track as u32,
-            base_version: self.current_clip_version(),
-            nanotick_lo,
-            nanotick_hi,
-            duration_lo,
-            duration_hi,
-            degree: degree as u16,
-            quality,
-            inversion,
-            base_octave,
-            humanize_timing: timing,
-            humanize_velocity: velocity,
-            reserved: 0,
-            spread_nanoticks: spread,
-        };
-        self.enqueue_chord_command(payload);
-        self.bump_clip_version();
+        self.harmony_events.retain(|event| event.nanotick != nanotick);
+        if self.bridge.is_none() {
+            self.bump_harmony_version();
+        }
+        self.bump_harmony_render_version();
+        cx.notify();
+    }
 
-        self.pending_notes.retain(|note| {
-            !(note.track_id == track as u32 &&
-                note.nanotick == nanotick &&
-                note.column == column)
-        });
-        self.pending_chords.retain(|pending| {
-            !(pending.track_id == track as u32 &&
-                pending.nanotick == nanotick &&
-                pending.column == column)
-        });
-        let mut clip_changed = false;
-        if let Some(notes) = self.clip_notes.get_mut(track) {
-            let before = notes.len();
-            notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
-            if notes.len() != before {
-                clip_changed = true;
-            }
+    /// Deletes harmony events that repeat the root and scale of the event
+    /// before them, leaving only actual changes. Returns how many were removed.
+    fn collapse_harmony_changes(&mut self, cx: &mut impl UiNotify) -> usize {
+        if self.read_only_blocked(cx) {
+            return 0;
         }
-        if let Some(chords) = self.clip_chords.get_mut(track) {
-            let before = chords.len();
-            chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
-            if chords.len() != before {
-                clip_changed = true;
-            }
+        let redundant: Vec<u64> = self
+            .harmony_events
+            .windows(2)
+            .filter(|pair| pair[0].root == pair[1].root && pair[0].scale_id == pair[1].scale_id)
+            .map(|pair| pair[1].nanotick)
+            .collect();
+        for nanotick in &redundant {
+            self.delete_harmony_at(*nanotick, cx);
         }
-        if clip_changed {
-            self.bump_clip_render_version();
+        let message = match redundant.len() {
+            0 => "No repeated harmony events".to_string(),
+            count => format!("Removed {count} repeated harmony event(s)"),
+        };
+        self.show_toast(&message, cx);
+        redundant.len()
+    }
+
+    pub fn send_undo(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if self.bridge.is_some() {
+            let payload = UiCommandPayload {
+                command_type: UiCommandType::Undo as u16,
+                flags: 0,
+                track_id: 0,
+                plugin_index: 0,
+                note_pitch: 0,
+                value0: 0,
+                note_nanotick_lo: 0,
+                note_nanotick_hi: 0,
+                note_duration_lo: 0,
+                note_duration_hi: 0,
+                base_version: self.current_clip_version(),
+            };
+            self.enqueue_ui_command(payload);
         }
-        self.pending_chords.push(PendingChord {
-            track_id: track as u32,
-            nanotick,
-            duration,
-            spread,
-            humanize_timing,
-            humanize_velocity,
-            degree,
-            quality,
-            inversion,
-            base_octave,
-            column,
-        });
         cx.notify();
     }
 
-}
+    pub fn send_redo(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if self.bridge.is_some() {
+            let payload = UiCommandPayload {
+                command_type: UiCommandType::Redo as u16,
+                flags: 0,
+                track_id: 0,
+                plugin_index: 0,
+                note_pitch: 0,
+                value0: 0,
+                note_nanotick_lo: 0,
+                note_nanotick_hi: 0,
+                note_duration_lo: 0,
+                note_duration_hi: 0,
+                base_version: self.current_clip_version(),
+            };
+            self.enqueue_ui_command(payload);
+        }
+        cx.notify();
+    }
 
-impl UiNotify for Context<'_, EngineView> {
-    fn notify(&mut self) {
-        Context::notify(self);
+    /// Rows the cursor moves after entering a note or chord: down by default,
+    /// up when upward advance is on.
+    fn entry_step_rows(&self) -> i64 {
+        if self.advance_up {
+            -EDIT_STEP_ROWS
+        } else {
+            EDIT_STEP_ROWS
+        }
     }
 
-    fn notify_rows(&mut self, _rows: &[usize]) {
-        // GPUI doesn't support selective row rendering - always full notify
-        Context::notify(self);
+    fn toggle_advance_direction(&mut self, cx: &mut impl UiNotify) {
+        self.advance_up = !self.advance_up;
+        let direction = if self.advance_up { "up" } else { "down" };
+        self.show_toast(&format!("Entry advances {direction}"), cx);
     }
-}
 
-impl Render for EngineView {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        // Debug: log when parent EngineView renders with counter
-        self.render_count += 1;
-        if self.ui_debug {
-            eprintln!("[UI] === EngineView::render #{} ===", self.render_count);
-        }
-        // Note: follow_playhead scroll adjustment moved to update_follow_playhead()
-        // which is called in the async loop, keeping render() pure.
-        if let Some(deadline) = self.toast_deadline {
-            if Instant::now() > deadline {
-                self.toast_deadline = None;
-                self.toast_message = None;
-            }
+    fn write_degree_note(&mut self, degree: u8, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
         }
-        let playhead = format_playhead(self.snapshot.ui_global_nanotick_playhead);
-        let is_playing = self.snapshot.ui_transport_state != 0;
-        let transport_label = if is_playing { "[Stop]" } else { "[Play]" };
-        let follow_label = if self.follow_playhead { "Follow" } else { "Free" };
-        let harmony_label = if self.harmony_focus {
-            format!("Harmony:{}*", harmony_scale_name(self.harmony_scale_id))
-        } else {
-            format!("Harmony:{}", harmony_scale_name(self.harmony_scale_id))
-        };
-        let track_name = self
-            .track_names
-            .get(self.focused_track_index)
-            .and_then(|name| name.clone())
-            .unwrap_or_else(|| "Empty".to_string());
-        let quantize_label = if self
-            .track_quantize
-            .get(self.focused_track_index)
-            .copied()
-            .unwrap_or(true)
-        {
-            "Quantize:On"
-        } else {
-            "Quantize:Off"
+        let chord = ParsedChordToken {
+            degree: degree as u32,
+            quality: 0,  // Plain degree note
+            inversion: 0,
+            base_octave: 4,  // Default to octave 4
+            spread_nanoticks: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            duration: None,
         };
+        self.send_chord(chord, cx);
+        self.move_cursor_row(self.entry_step_rows(), cx);
+    }
 
-        let mut root = div()
-            .relative()
-            .flex()
-            .flex_col()
-            .gap_2()
-            .bg(rgb(0x0f161c))
-            .text_color(rgb(0xe6eef5))
-            .font_family("Menlo")  // Use Menlo for better monospace on macOS
-            .w_full()
-            .h_full()
-            .p_3()
-            .child(
-                div()
-                    .flex()
-                    .items_center()
-                    .gap_0()
-                    .child(
-                        div()
-                            .w(px(TIME_COLUMN_WIDTH))
-                            .text_sm()
-                            .child(playhead.clone()),
-                    )
-                    .child(
-                        div()
-                            .text_sm()
-                            .text_color(rgb(0x93a1ad))
-                            .bg(rgb(0x18222b))
-                            .px_2()
-                            .py_1()
-                            .border_1()
-                            .border_color(rgb(0x253240))
-                            .on_mouse_down(
-                                MouseButton::Left,
-                                cx.listener(|view, _, _, cx| {
-                                    view.toggle_play(cx);
-                                }),
-                            )
-                            .child(transport_label),
-                    ),
-            )
-            ;
+    fn toggle_chromatic_digits(&mut self, cx: &mut impl UiNotify) {
+        self.chromatic_digits = !self.chromatic_digits;
+        let mode = if self.chromatic_digits { "chromatic" } else { "degrees" };
+        self.show_toast(&format!("Digits enter {mode}"), cx);
+    }
 
-        if self.patcher_open {
-            root = root.child(self.render_patcher_view(cx));
-        } else {
-            root = root.child(self.render_tracker_grid(cx));
-        }
+    fn toggle_diatonic_entry(&mut self, cx: &mut impl UiNotify) {
+        self.diatonic_entry = !self.diatonic_entry;
+        let state = if self.diatonic_entry { "on" } else { "off" };
+        self.show_toast(&format!("Diatonic entry {state}"), cx);
+    }
 
-        root
-            .child(self.render_device_chain_strip(cx))
-            .child(
-                div()
-                    .text_sm()
-                    .text_color(rgb(0x93a1ad))
-                    .child(format!(
-                        "[Track {}:{} {}] [{}] [BPM 120] [{}] [View: {}] [{}]",
-                        self.focused_track_index,
-                        self.cursor_col + 1,
-                        track_name,
-                        playhead,
-                        quantize_label,
-                        follow_label,
-                        harmony_label
-                    )),
-            )
-            .child(self.render_palette(cx))
-            .child(self.render_scale_browser(cx))
-            .child(self.render_jump_overlay(cx))
-            .child(self.render_preset_save_overlay(cx))
-            .child(self.render_debug_overlay(cx))
-            .child(self.render_toast(cx))
+    /// Absolute pitch of scale tone `degree` (1-7) under the harmony at the
+    /// cursor, in the same octave degree notes default to.
+    pub(crate) fn diatonic_pitch(&self, degree: u8) -> u8 {
+        let nanotick = self.current_row_nanotick();
+        resolve_degree_pitch(
+            degree,
+            4,
+            self.harmony_root_at(nanotick),
+            self.harmony_scale_at(nanotick),
+        )
     }
-}
 
-impl EngineView {
-    pub(crate) fn harmony_label_at_nanotick(&self, nanotick: u64) -> Option<String> {
-        let event = self
-            .harmony_events
-            .iter()
-            .find(|event| event.nanotick == nanotick)?;
-        let root = harmony_root_name(event.root);
-        let scale = harmony_scale_name(event.scale_id);
-        Some(format!("{root}:{scale}"))
+    fn write_diatonic_note(&mut self, degree: u8, cx: &mut impl UiNotify) {
+        let pitch = self.diatonic_pitch(degree);
+        self.write_note(pitch, cx);
     }
-}
 
-pub(crate) fn format_playhead(nanoticks: u64) -> String {
-    let total_beats = nanoticks / NANOTICKS_PER_QUARTER;
-    let bar = total_beats / BEATS_PER_BAR + 1;
-    let beat = total_beats % BEATS_PER_BAR + 1;
-    let tick = (nanoticks % NANOTICKS_PER_QUARTER) / 10_000;
-    format!("{}:{}:{}", bar, beat, tick)
-}
-
-fn parse_jump_text(text: &str) -> Option<u64> {
-    if text.trim().is_empty() {
-        return None;
-    }
-    let parts: Vec<&str> = text.split(':').collect();
-    let bar = parts.get(0)?.parse::<u64>().ok()?;
-    if bar == 0 {
-        return None;
-    }
-    let beat = match parts.get(1) {
-        Some(part) => part.parse::<u64>().ok()?,
-        None => 1,
-    };
-    if beat == 0 {
-        return None;
+    pub fn set_track_quantize_strength(&mut self, track: usize, strength: u8) {
+        if let Some(slot) = self.track_quantize_strength.get_mut(track) {
+            *slot = strength.min(MAX_QUANTIZE_STRENGTH);
+        }
     }
-    let tick = match parts.get(2) {
-        Some(part) => part.parse::<u64>().ok()?,
-        None => 0,
-    };
-    let total_beats = (bar - 1) * BEATS_PER_BAR + (beat - 1);
-    Some(total_beats * NANOTICKS_PER_QUARTER + tick * 10_000)
-}
 
-fn is_jump_char(value: &str) -> bool {
-    value.len() == 1 && value.chars().all(|ch| ch.is_ascii_digit() || ch == ':')
-}
-
-fn pitch_for_key(key: &str) -> Option<u8> {
-    let k = key.to_lowercase();
-    let pitch = match k.as_str() {
-        "z" => 48,  // C-3
-        "s" => 49,  // C#3
-        "x" => 50,  // D-3
-        "d" => 51,  // D#3
-        "c" => 52,  // E-3
-        "v" => 53,  // F-3
-        "g" => 54,  // F#3
-        "b" => 55,  // G-3
-        "h" => 56,  // G#3
-        "n" => 57,  // A-3
-        "j" => 58,  // A#3
-        "m" => 59,  // B-3
-        "q" => 60,  // C-4
-        "2" => 61,  // C#4
-        "w" => 62,  // D-4
-        "3" => 63,  // D#4
-        "e" => 64,  // E-4
-        "r" => 65,  // F-4
-        "5" => 66,  // F#4
-        "t" => 67,  // G-4
-        "6" => 68,  // G#4
-        "y" => 69,  // A-4
-        "7" => 70,  // A#4
-        "u" => 71,  // B-4
-        _ => return None,
-    };
-    Some(pitch)
-}
+    /// Nanotick for a note entered at the cursor. With quantize on, the
+    /// cursor position moves the track's strength percentage of the way to
+    /// the nearest row.
+    pub(crate) fn entry_nanotick(&self) -> u64 {
+        let nanotick = self.current_row_nanotick();
+        let track = self.focused_track_index;
+        if !self.track_quantize.get(track).copied().unwrap_or(false) {
+            return nanotick;
+        }
+        let strength = self
+            .track_quantize_strength
+            .get(track)
+            .copied()
+            .unwrap_or(MAX_QUANTIZE_STRENGTH);
+        quantize_toward_row(nanotick, self.row_nanoticks(), strength)
+    }
 
-fn pitch_for_letter_key(key: &str) -> Option<u8> {
-    if key.chars().all(|ch| ch.is_ascii_alphabetic()) {
-        return pitch_for_key(key);
+    fn toggle_read_only(&mut self, cx: &mut impl UiNotify) {
+        self.read_only = !self.read_only;
+        let state = if self.read_only { "on" } else { "off" };
+        self.show_toast(&format!("Read-only {state}"), cx);
     }
-    None
-}
 
-fn degree_for_digit(key: &str) -> Option<u8> {
-    if key.len() != 1 {
-        return None;
+    /// True (with a toast) when read-only mode should swallow an edit.
+    fn read_only_blocked(&mut self, cx: &mut impl UiNotify) -> bool {
+        if self.read_only {
+            self.show_toast("Read-only mode", cx);
+        }
+        self.read_only
     }
-    let ch = key.chars().next()?;
-    if !ch.is_ascii_digit() {
-        return None;
+
+    fn toggle_insert_mode(&mut self, cx: &mut impl UiNotify) {
+        self.insert_mode = !self.insert_mode;
+        cx.notify();
     }
-    let digit = ch.to_digit(10)? as u8;
-    if digit == 0 {
-        return None;
+
+    fn toggle_velocity_entry(&mut self, cx: &mut impl UiNotify) {
+        self.velocity_entry = !self.velocity_entry;
+        self.velocity_digits.clear();
+        let state = if self.velocity_entry { "on" } else { "off" };
+        self.show_toast(
+            &format!("Velocity entry {state} ({})", self.velocity_digit_scale.label()),
+            cx,
+        );
     }
-    Some(digit)
-}
 
-fn harmony_root_for_key(key: &str) -> Option<u32> {
-    let mut sharp = false;
-    let mut k = key.to_string();
-    if k.len() == 1 && k.chars().next().unwrap().is_ascii_uppercase() {
-        sharp = true;
-        k = k.to_lowercase();
+    /// Switches Up/Down between moving the cursor and stepping the focused
+    /// note's velocity.
+    fn toggle_velocity_focus(&mut self, cx: &mut impl UiNotify) {
+        self.velocity_focus = !self.velocity_focus;
+        let state = if self.velocity_focus { "on" } else { "off" };
+        self.show_toast(&format!("Velocity focus {state}"), cx);
     }
-    let base = match k.as_str() {
-        "c" => 0,
-        "d" => 2,
-        "e" => 4,
-        "f" => 5,
-        "g" => 7,
-        "a" => 9,
-        "b" => 11,
-        _ => return None,
-    };
-    let root = if sharp { (base + 1) % 12 } else { base };
-    Some(root)
-}
 
-#[allow(dead_code)]
-fn harmony_scale_id_for_key(key: &str) -> Option<u32> {
-    for scale in SCALE_LIBRARY {
-        if scale.key == key {
-            return Some(scale.id);
+    /// Rewrites the focused note with its velocity moved by `delta`, kept
+    /// within 1..=127.
+    fn nudge_focused_velocity(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        let Some(note) = self.focused_note() else {
+            self.show_toast("No note to set velocity", cx);
+            return;
+        };
+        let velocity = (note.velocity as i32 + delta).clamp(1, 127) as u8;
+        if velocity == note.velocity {
+            return;
         }
+        self.write_note_at(
+            self.focused_track_index,
+            note.column,
+            note.nanotick,
+            note.pitch,
+            velocity,
+            note.duration,
+            cx,
+        );
+        self.show_toast(&format!("Velocity {velocity}"), cx);
     }
-    None
-}
-
-pub(crate) fn pitch_to_note(pitch: u8) -> String {
-    const NAMES: [&str; 12] = [
-        "C-", "C#", "D-", "D#", "E-", "F-", "F#", "G-", "G#", "A-", "A#", "B-",
-    ];
-    let name = NAMES[(pitch as usize) % 12];
-    let octave = (pitch / 12) as i8 - 1;
-    format!("{name}{octave}")
-}
 
-pub(crate) fn chord_token_text(chord: &ClipChord) -> String {
-    if chord.quality == 0 {
-        return format!("{}-{}", chord.degree, chord.base_octave);
-    }
-    let mut text = format!("@{}", chord.degree);
-    if chord.quality == 2 {
-        text.push_str("^7");
-    }
-    if chord.inversion > 0 {
-        text.push_str(&format!("/{}", chord.inversion));
-    }
-    if chord.base_octave != 0 {
-        text.push_str(&format!("-{}", chord.base_octave));
-    }
-    if chord.spread > 0 {
-        text.push_str(&format!("~{}", chord.spread));
-    }
-    if chord.humanize_timing > 0 {
-        text.push_str(&format!("h{}", chord.humanize_timing));
+    fn cycle_velocity_digit_scale(&mut self, cx: &mut impl UiNotify) {
+        self.velocity_digit_scale = self.velocity_digit_scale.next();
+        self.velocity_digits.clear();
+        self.show_toast(
+            &format!("Velocity digits: {}", self.velocity_digit_scale.label()),
+            cx,
+        );
     }
-    text
-}
-
-struct ParsedChordToken {
-    degree: u32,
-    quality: u8,
-    inversion: u8,
-    base_octave: u8,
-    spread_nanoticks: u32,
-    humanize_timing: u8,
-    humanize_velocity: u8,
-    duration: Option<u64>,
-}
 
-fn parse_chord_token(token: &str) -> Option<ParsedChordToken> {
-    let token = token.trim();
-    let mut chars = token.chars().peekable();
-    if chars.next()? != '@' {
-        return None;
+    /// Collects a typed digit and, once the number is complete, rewrites the
+    /// focused note with the resulting velocity.
+    fn push_velocity_digit(&mut self, digit: &str, cx: &mut impl UiNotify) {
+        let Some(note) = self.focused_note() else {
+            self.velocity_digits.clear();
+            self.show_toast("No note to set velocity", cx);
+            return;
+        };
+        self.velocity_digits.push_str(digit);
+        let Some(velocity) = velocity_from_digits(&self.velocity_digits, self.velocity_digit_scale)
+        else {
+            cx.notify();
+            return;
+        };
+        self.velocity_digits.clear();
+        self.write_note_at(
+            self.focused_track_index,
+            note.column,
+            note.nanotick,
+            note.pitch,
+            velocity,
+            note.duration,
+            cx,
+        );
+        self.show_toast(&format!("Velocity {velocity}"), cx);
     }
-    let mut degree_str = String::new();
-    while let Some(&ch) = chars.peek() {
-        if ch.is_ascii_digit() {
-            degree_str.push(ch);
-            chars.next();
-        } else {
-            break;
+
+    /// Pushes everything at or after `nanotick` in one column a row later,
+    /// latest first so moved events never land on ones still to be moved.
+    /// Returns false, without shifting, while the whole clip is still loading.
+    fn insert_row_in_column(
+        &mut self,
+        track: usize,
+        column: u8,
+        nanotick: u64,
+        cx: &mut impl UiNotify,
+    ) -> bool {
+        if !self.whole_clip_loaded() {
+            self.show_toast("Loading the whole clip", cx);
+            return false;
+        }
+        if track >= TRACK_COUNT || column as usize >= MAX_NOTE_COLUMNS {
+            return true;
+        }
+        let row_nanoticks = self.row_nanoticks();
+        let mut mask = SelectionMask::empty(TRACK_COUNT);
+        mask.tracks[track] = 1u8 << column;
+        let mut notes = self.collect_notes_in_range(nanotick, u64::MAX, &mask, true);
+        let mut chords = self.collect_chords_in_range(nanotick, u64::MAX, &mask, true);
+        notes.sort_by_key(|(_, note)| std::cmp::Reverse(note.nanotick));
+        chords.sort_by_key(|(_, chord)| std::cmp::Reverse(chord.nanotick));
+        for (_, note) in notes {
+            self.delete_note_at(track, column, note.nanotick, note.pitch, cx);
+            self.write_note_at(
+                track,
+                column,
+                note.nanotick + row_nanoticks,
+                note.pitch,
+                note.velocity,
+                note.duration,
+                cx,
+            );
+        }
+        for (_, chord) in chords {
+            self.delete_chord_at(track, chord.chord_id, chord.nanotick, column, cx);
+            self.write_chord_at(
+                track,
+                column,
+                chord.nanotick + row_nanoticks,
+                chord.duration,
+                chord.degree,
+                chord.quality,
+                chord.inversion,
+                chord.base_octave,
+                chord.spread,
+                chord.humanize_timing,
+                chord.humanize_velocity,
+                cx,
+            );
         }
+        true
     }
-    let degree: u32 = degree_str.parse().ok()?;
-    let mut quality: u8 = 1;
-    let mut inversion: u8 = 0;
-    let mut base_octave: u8 = 4;
-    let mut spread: u32 = 0;
-    let mut humanize: u8 = 0;
-    let rest: Vec<char> = chars.collect();
-    let mut index = 0;
-    while index < rest.len() {
-        match rest[index] {
-            '^' => {
-                index += 1;
-                let mut number = String::new();
-                while index < rest.len() && rest[index].is_ascii_digit() {
-                    number.push(rest[index]);
-                    index += 1;
-                }
-                if number == "7" {
-                    quality = 2;
-                }
-            }
-            '/' => {
-                index += 1;
-                let mut number = String::new();
-                while index < rest.len() && rest[index].is_ascii_digit() {
-                    number.push(rest[index]);
-                    index += 1;
-                }
-                if let Ok(value) = number.parse::<u8>() {
-                    inversion = value;
-                }
-            }
-            '-' => {
-                index += 1;
-                let mut number = String::new();
-                while index < rest.len() && rest[index].is_ascii_digit() {
-                    number.push(rest[index]);
-                    index += 1;
-                }
-                if let Ok(value) = number.parse::<u8>() {
-                    base_octave = value;
-                }
-            }
-            '~' => {
-                index += 1;
-                let mut number = String::new();
-                while index < rest.len() && rest[index].is_ascii_digit() {
-                    number.push(rest[index]);
-                    index += 1;
-                }
-                if let Ok(value) = number.parse::<u32>() {
-                    spread = value;
-                }
-            }
-            'h' => {
-                index += 1;
-                let mut number = String::new();
-                while index < rest.len() && rest[index].is_ascii_digit() {
-                    number.push(rest[index]);
-                    index += 1;
-                }
-                if let Ok(value) = number.parse::<u8>() {
-                    humanize = value;
-                }
+
+    fn write_note(&mut self, pitch: u8, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if self.ui_debug {
+            eprintln!("[UI] write_note(pitch={})", pitch);
+        }
+        self.keyjazz_pitch = pitch;
+        let nanotick = self.entry_nanotick();
+        if self.insert_mode &&
+            !self.insert_row_in_column(
+                self.focused_track_index,
+                self.cursor_col as u8,
+                nanotick,
+                cx,
+            )
+        {
+            return;
+        }
+        let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
+        let (note_duration_lo, note_duration_hi) = split_u64(0);
+
+        // Clear any existing pending notes at this position
+        self.pending_notes.retain(|note| {
+            !(note.track_id == self.focused_track_index as u32 &&
+                note.nanotick == nanotick &&
+                note.column == self.cursor_col as u8)
+        });
+        self.pending_chords.retain(|chord| {
+            !(chord.track_id == self.focused_track_index as u32 &&
+                chord.nanotick == nanotick &&
+                chord.column == self.cursor_col as u8)
+        });
+
+        if self.bridge.is_some() {
+            let payload = UiCommandPayload {
+                command_type: UiCommandType::WriteNote as u16,
+                flags: self.cursor_col as u16,
+                track_id: self.focused_track_index as u32,
+                plugin_index: 0,
+                note_pitch: pitch as u32,
+                value0: 100,
+                note_nanotick_lo,
+                note_nanotick_hi,
+                note_duration_lo,
+                note_duration_hi,
+                base_version: self.current_clip_version(),
+            };
+            self.enqueue_ui_command(payload);
+            self.bump_clip_version();
+        } else {
+            return;
+        }
+        self.pending_notes.retain(|note| {
+            !(note.track_id == self.focused_track_index as u32 &&
+                note.nanotick == nanotick &&
+                note.column == self.cursor_col as u8)
+        });
+        self.pending_chords.retain(|chord| {
+            !(chord.track_id == self.focused_track_index as u32 &&
+                chord.nanotick == nanotick &&
+                chord.column == self.cursor_col as u8)
+        });
+        let mut clip_changed = false;
+        if let Some(notes) = self.clip_notes.get_mut(self.focused_track_index) {
+            let before = notes.len();
+            notes.retain(|note| {
+                !(note.nanotick == nanotick && note.column == self.cursor_col as u8)
+            });
+            if notes.len() != before {
+                clip_changed = true;
             }
-            _ => {
-                index += 1;
+        }
+        if let Some(chords) = self.clip_chords.get_mut(self.focused_track_index) {
+            let before = chords.len();
+            chords.retain(|chord| {
+                !(chord.nanotick == nanotick && chord.column == self.cursor_col as u8)
+            });
+            if chords.len() != before {
+                clip_changed = true;
             }
         }
+        if clip_changed {
+            self.bump_clip_render_version();
+        }
+        self.pending_notes.push(PendingNote {
+            track_id: self.focused_track_index as u32,
+            nanotick,
+            duration: 0,
+            pitch,
+            velocity: 100,
+            column: self.cursor_col as u8,
+        });
+        self.move_cursor_row(self.entry_step_rows(), cx);
+        cx.notify(); // Show pending note immediately
     }
-    Some(ParsedChordToken {
-        degree,
-        quality,
-        inversion,
-        base_octave,
-        spread_nanoticks: spread,
-        humanize_timing: humanize,
-        humanize_velocity: humanize,
-        duration: None,
-    })
-}
 
-fn parse_degree_note_token(token: &str) -> Option<ParsedChordToken> {
-    let token = token.trim();
-    let mut parts = token.splitn(2, '-');
-    let degree_str = parts.next()?;
-    let octave_and_mods = parts.next();
-    if degree_str.is_empty() {
-        return None;
-    }
-    if !degree_str.chars().all(|c| c.is_ascii_digit()) {
-        return None;
-    }
-    let degree: u32 = degree_str.parse().ok()?;
-    let mut oct_chars = octave_and_mods.unwrap_or("").chars().peekable();
-    let mut octave_str = String::new();
-    while let Some(&ch) = oct_chars.peek() {
-        if ch.is_ascii_digit() {
-            octave_str.push(ch);
-            oct_chars.next();
-        } else {
-            break;
+    fn write_note_at(
+        &mut self,
+        track: usize,
+        column: u8,
+        nanotick: u64,
+        pitch: u8,
+        velocity: u8,
+        duration: u64,
+        cx: &mut impl UiNotify,
+    ) {
+        if self.read_only_blocked(cx) {
+            return;
         }
-    }
-    let base_octave: u8 = if octave_str.is_empty() {
-        4
-    } else {
-        octave_str.parse().ok()?
-    };
-    let rest: Vec<char> = oct_chars.collect();
-    let mut spread: u32 = 0;
-    let mut humanize: u8 = 0;
-    let mut index = 0;
-    while index < rest.len() {
-        match rest[index] {
-            '~' => {
-                index += 1;
-                let mut number = String::new();
-                while index < rest.len() && rest[index].is_ascii_digit() {
-                    number.push(rest[index]);
-                    index += 1;
-                }
-                if let Ok(value) = number.parse::<u32>() {
-                    spread = value;
-                }
+        if track >= TRACK_COUNT || column as usize >= MAX_NOTE_COLUMNS {
+            return;
+        }
+        if self.bridge.is_none() {
+            let mut clip_changed = false;
+            if let Some(notes) = self.clip_notes.get_mut(track) {
+                notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
+                notes.push(ClipNote {
+                    nanotick,
+                    duration,
+                    pitch,
+                    velocity,
+                    column,
+                });
+                notes.sort_by_key(|note| (note.nanotick, note.column));
+                clip_changed = true;
             }
-            'h' => {
-                index += 1;
-                let mut number = String::new();
-                while index < rest.len() && rest[index].is_ascii_digit() {
-                    number.push(rest[index]);
-                    index += 1;
-                }
-                if let Ok(value) = number.parse::<u8>() {
-                    humanize = value;
+            if let Some(chords) = self.clip_chords.get_mut(track) {
+                let before = chords.len();
+                chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
+                if chords.len() != before {
+                    clip_changed = true;
                 }
             }
-            _ => {
-                index += 1;
+            if clip_changed {
+                self.bump_clip_render_version();
+            }
+            self.bump_clip_version();
+            cx.notify();
+            return;
+        }
+        let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
+        let (note_duration_lo, note_duration_hi) = split_u64(duration);
+        let payload = UiCommandPayload {
+            command_type: UiCommandType::WriteNote as u16,
+            flags: column as u16,
+            track_id: track as u32,
+            plugin_index: 0,
+            note_pitch: pitch as u32,
+            value0: velocity as u32,
+            note_nanotick_lo,
+            note_nanotick_hi,
+            note_duration_lo,
+            note_duration_hi,
+            base_version: self.current_clip_version(),
+        };
+        self.enqueue_ui_command(payload);
+        self.bump_clip_version();
+
+        self.pending_notes.retain(|note| {
+            !(note.track_id == track as u32 &&
+                note.nanotick == nanotick &&
+                note.column == column)
+        });
+        self.pending_chords.retain(|chord| {
+            !(chord.track_id == track as u32 &&
+                chord.nanotick == nanotick &&
+                chord.column == column)
+        });
+        let mut clip_changed = false;
+        if let Some(notes) = self.clip_notes.get_mut(track) {
+            let before = notes.len();
+            notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
+            if notes.len() != before {
+                clip_changed = true;
+            }
+        }
+        if let Some(chords) = self.clip_chords.get_mut(track) {
+            let before = chords.len();
+            chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
+            if chords.len() != before {
+                clip_changed = true;
             }
         }
-    }
-    Some(ParsedChordToken {
-        degree,
-        quality: 0,
-        inversion: 0,
-        base_octave,
-        spread_nanoticks: spread,
-        humanize_timing: humanize,
-        humanize_velocity: humanize,
-        duration: None,
-    })
-}
+        if clip_changed {
+            self.bump_clip_render_version();
+        }
+        self.pending_notes.push(PendingNote {
+            track_id: track as u32,
+            nanotick,
+            duration,
+            pitch,
+            velocity,
+            column,
+        });
+        cx.notify();
+    }
+
+    fn note_off_pitch(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u8> {
+        let mut best: Option<(u64, u8)> = None;
+
+        for note in self.pending_notes.iter() {
+            if note.track_id as usize != track_index || note.column != column {
+                continue;
+            }
+            if note.nanotick == nanotick && note.velocity > 0 {
+                return Some(note.pitch);
+            }
+            if note.nanotick < nanotick && note.velocity > 0 {
+                if best.map_or(true, |(best_tick, _)| note.nanotick > best_tick) {
+                    best = Some((note.nanotick, note.pitch));
+                }
+            }
+        }
+
+        if let Some(notes) = self.clip_notes.get(track_index) {
+            for note in notes.iter() {
+                if note.column != column {
+                    continue;
+                }
+                if note.nanotick == nanotick && note.velocity > 0 {
+                    return Some(note.pitch);
+                }
+                if note.nanotick < nanotick && note.velocity > 0 {
+                    if best.map_or(true, |(best_tick, _)| note.nanotick > best_tick) {
+                        best = Some((note.nanotick, note.pitch));
+                    }
+                }
+            }
+        }
+
+        if let Some((_, pitch)) = best {
+            return Some(pitch);
+        }
+
+        let mut chord_tick: Option<u64> = None;
+        for chord in self.pending_chords.iter() {
+            if chord.track_id as usize != track_index || chord.column != column {
+                continue;
+            }
+            if chord.nanotick < nanotick &&
+                chord_tick.map_or(true, |best_tick| chord.nanotick > best_tick) {
+                chord_tick = Some(chord.nanotick);
+            }
+        }
+        if let Some(chords) = self.clip_chords.get(track_index) {
+            for chord in chords.iter() {
+                if chord.column != column {
+                    continue;
+                }
+                if chord.nanotick < nanotick &&
+                    chord_tick.map_or(true, |best_tick| chord.nanotick > best_tick) {
+                    chord_tick = Some(chord.nanotick);
+                }
+            }
+        }
+        if chord_tick.is_some() {
+            return Some(0);
+        }
+        None
+    }
+
+    fn next_note_on_nanotick(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u64> {
+        let mut next: Option<u64> = None;
+        for note in self.pending_notes.iter() {
+            if note.track_id as usize != track_index || note.column != column {
+                continue;
+            }
+            if note.nanotick <= nanotick || note.velocity == 0 {
+                continue;
+            }
+            if next.map_or(true, |best| note.nanotick < best) {
+                next = Some(note.nanotick);
+            }
+        }
+        if let Some(notes) = self.clip_notes.get(track_index) {
+            for note in notes.iter() {
+                if note.column != column || note.velocity == 0 {
+                    continue;
+                }
+                if note.nanotick <= nanotick {
+                    continue;
+                }
+                if next.map_or(true, |best| note.nanotick < best) {
+                    next = Some(note.nanotick);
+                }
+            }
+        }
+        next
+    }
+
+    fn prev_note_on_nanotick(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u64> {
+        let mut prev: Option<u64> = None;
+        for note in self.pending_notes.iter() {
+            if note.track_id as usize != track_index || note.column != column {
+                continue;
+            }
+            if note.nanotick >= nanotick || note.velocity == 0 {
+                continue;
+            }
+            if prev.map_or(true, |best| note.nanotick > best) {
+                prev = Some(note.nanotick);
+            }
+        }
+        if let Some(notes) = self.clip_notes.get(track_index) {
+            for note in notes.iter() {
+                if note.column != column || note.velocity == 0 {
+                    continue;
+                }
+                if note.nanotick >= nanotick {
+                    continue;
+                }
+                if prev.map_or(true, |best| note.nanotick > best) {
+                    prev = Some(note.nanotick);
+                }
+            }
+        }
+        prev
+    }
+
+    fn prev_chord_nanotick(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u64> {
+        let mut prev: Option<u64> = None;
+        for chord in self.pending_chords.iter() {
+            if chord.track_id as usize != track_index {
+                continue;
+            }
+            if chord.column != column {
+                continue;
+            }
+            if chord.nanotick >= nanotick {
+                continue;
+            }
+            if prev.map_or(true, |best| chord.nanotick > best) {
+                prev = Some(chord.nanotick);
+            }
+        }
+        if let Some(chords) = self.clip_chords.get(track_index) {
+            for chord in chords.iter() {
+                if chord.column != column {
+                    continue;
+                }
+                if chord.nanotick >= nanotick {
+                    continue;
+                }
+                if prev.map_or(true, |best| chord.nanotick > best) {
+                    prev = Some(chord.nanotick);
+                }
+            }
+        }
+        prev
+    }
+
+    fn next_chord_nanotick(&self, track_index: usize, column: u8, nanotick: u64) -> Option<u64> {
+        let mut next: Option<u64> = None;
+        for chord in self.pending_chords.iter() {
+            if chord.track_id as usize != track_index {
+                continue;
+            }
+            if chord.column != column {
+                continue;
+            }
+            if chord.nanotick <= nanotick {
+                continue;
+            }
+            if next.map_or(true, |best| chord.nanotick < best) {
+                next = Some(chord.nanotick);
+            }
+        }
+        if let Some(chords) = self.clip_chords.get(track_index) {
+            for chord in chords.iter() {
+                if chord.column != column {
+                    continue;
+                }
+                if chord.nanotick <= nanotick {
+                    continue;
+                }
+                if next.map_or(true, |best| chord.nanotick < best) {
+                    next = Some(chord.nanotick);
+                }
+            }
+        }
+        next
+    }
+
+    fn remove_note_offs_in_span(
+        &mut self,
+        track_index: usize,
+        column: u8,
+        nanotick: u64,
+        prev_boundary: Option<u64>,
+        next_boundary: Option<u64>,
+    ) -> bool {
+        let lower = prev_boundary.unwrap_or(0);
+        let upper = next_boundary.unwrap_or(u64::MAX);
+        let in_range = |note_nanotick: u64| {
+            if note_nanotick == nanotick {
+                return false;
+            }
+            note_nanotick > lower && note_nanotick < upper
+        };
+
+        self.pending_notes.retain(|note| {
+            if note.track_id as usize != track_index || note.column != column {
+                return true;
+            }
+            if note.velocity != 0 || note.duration != 0 {
+                return true;
+            }
+            !in_range(note.nanotick)
+        });
+
+        let mut removed = false;
+        if let Some(notes) = self.clip_notes.get_mut(track_index) {
+            let before = notes.len();
+            notes.retain(|note| {
+                if note.column != column {
+                    return true;
+                }
+                if note.velocity != 0 || note.duration != 0 {
+                    return true;
+                }
+                !in_range(note.nanotick)
+            });
+            if notes.len() != before {
+                removed = true;
+            }
+        }
+        removed
+    }
+
+    fn write_note_off(&mut self, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let nanotick = self.current_row_nanotick();
+        let column = self.cursor_col as u8;
+        let prev_note_on = self.prev_note_on_nanotick(
+            self.focused_track_index,
+            column,
+            nanotick,
+        );
+        let next_note_on = self.next_note_on_nanotick(
+            self.focused_track_index,
+            column,
+            nanotick,
+        );
+        let prev_chord = self.prev_chord_nanotick(self.focused_track_index, column, nanotick);
+        let next_chord = self.next_chord_nanotick(self.focused_track_index, column, nanotick);
+        let (prev_boundary, next_boundary) = (
+            match (prev_note_on, prev_chord) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (Some(a), None) => Some(a),
+                (None, Some(b)) => Some(b),
+                (None, None) => None,
+            },
+            match (next_note_on, next_chord) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (Some(a), None) => Some(a),
+                (None, Some(b)) => Some(b),
+                (None, None) => None,
+            },
+        );
+
+        let pitch = self
+            .note_off_pitch(self.focused_track_index, column, nanotick)
+            .unwrap_or(0);
+        if pitch == 0 && prev_boundary.is_none() {
+            self.move_cursor_row(self.entry_step_rows(), cx);
+            cx.notify();
+            return;
+        }
+        let (note_nanotick_lo, note_nanotick_hi) = split_u64(nanotick);
+        let (note_duration_lo, note_duration_hi) = split_u64(0);
+
+        self.pending_notes.retain(|note| {
+            !(note.track_id == self.focused_track_index as u32 &&
+                note.nanotick == nanotick &&
+                note.column == self.cursor_col as u8)
+        });
+        self.pending_chords.retain(|chord| {
+            !(chord.track_id == self.focused_track_index as u32 &&
+                chord.nanotick == nanotick &&
+                chord.column == self.cursor_col as u8)
+        });
+
+        if self.bridge.is_some() {
+            let payload = UiCommandPayload {
+                command_type: UiCommandType::WriteNote as u16,
+                flags: self.cursor_col as u16,
+                track_id: self.focused_track_index as u32,
+                plugin_index: 0,
+                note_pitch: pitch as u32,
+                value0: 0,
+                note_nanotick_lo,
+                note_nanotick_hi,
+                note_duration_lo,
+                note_duration_hi,
+                base_version: self.current_clip_version(),
+            };
+            self.enqueue_ui_command(payload);
+            self.bump_clip_version();
+        } else {
+            return;
+        }
+        self.pending_notes.retain(|note| {
+            !(note.track_id == self.focused_track_index as u32 &&
+                note.nanotick == nanotick &&
+                note.column == self.cursor_col as u8)
+        });
+        self.pending_chords.retain(|chord| {
+            !(chord.track_id == self.focused_track_index as u32 &&
+                chord.nanotick == nanotick &&
+                chord.column == self.cursor_col as u8)
+        });
+        let mut clip_changed = false;
+        if let Some(notes) = self.clip_notes.get_mut(self.focused_track_index) {
+            let before = notes.len();
+            notes.retain(|note| {
+                !(note.nanotick == nanotick && note.column == self.cursor_col as u8)
+            });
+            if notes.len() != before {
+                clip_changed = true;
+            }
+        }
+        if let Some(chords) = self.clip_chords.get_mut(self.focused_track_index) {
+            let before = chords.len();
+            chords.retain(|chord| {
+                !(chord.nanotick == nanotick && chord.column == self.cursor_col as u8)
+            });
+            if chords.len() != before {
+                clip_changed = true;
+            }
+        }
+        if self.remove_note_offs_in_span(
+            self.focused_track_index,
+            column,
+            nanotick,
+            prev_boundary,
+            next_boundary,
+        ) {
+            clip_changed = true;
+        }
+        if clip_changed {
+            self.bump_clip_render_version();
+        }
+        self.pending_notes.push(PendingNote {
+            track_id: self.focused_track_index as u32,
+            nanotick,
+            duration: 0,
+            pitch,
+            velocity: 0,
+            column: self.cursor_col as u8,
+        });
+        self.move_cursor_row(self.entry_step_rows(), cx);
+    }
+
+    pub fn toggle_play(&mut self, cx: &mut impl UiNotify) {
+        if self.bridge.is_some() {
+            for payload in self.play_command_sequence() {
+                self.enqueue_ui_command(payload);
+            }
+        }
+        cx.notify();
+    }
+
+    /// Commands sent for a transport toggle. Starting playback with count-in
+    /// enabled tells the engine how many bars to click before the transport
+    /// moves.
+    pub(crate) fn play_command_sequence(&self) -> Vec<UiCommandPayload> {
+        let mut sequence = Vec::with_capacity(2);
+        let is_playing = self.snapshot.ui_transport_state != 0;
+        if !is_playing && self.count_in_bars > 0 {
+            sequence.push(transport_command(UiCommandType::SetCountIn, self.count_in_bars));
+        }
+        sequence.push(transport_command(UiCommandType::TogglePlay, 0));
+        sequence
+    }
+
+    /// Arms or disarms `track` for recording and tells the engine, which will
+    /// route live input to armed tracks.
+    pub(crate) fn toggle_track_armed(&mut self, track: usize, cx: &mut impl UiNotify) {
+        let Some(armed) = self.track_armed.get_mut(track) else {
+            return;
+        };
+        *armed = !*armed;
+        self.send_track_armed(track);
+        cx.notify();
+    }
+
+    fn send_track_armed(&mut self, track: usize) {
+        let armed = self.track_armed[track];
+        let mut payload = transport_command(UiCommandType::SetTrackArmed, armed as u32);
+        payload.track_id = track as u32;
+        self.enqueue_ui_command(payload);
+    }
+
+    /// Sets how far `track` plays late (positive) or early (negative). The
+    /// engine applies it at playback; notes stay where they are in the grid.
+    pub(crate) fn set_track_time_offset(
+        &mut self,
+        track: usize,
+        offset: i64,
+        cx: &mut impl UiNotify,
+    ) {
+        let offset = offset.clamp(-MAX_TRACK_TIME_OFFSET, MAX_TRACK_TIME_OFFSET);
+        let Some(current) = self.track_time_offset.get_mut(track) else {
+            return;
+        };
+        if *current == offset {
+            return;
+        }
+        *current = offset;
+        self.send_track_time_offset(track);
+        cx.notify();
+    }
+
+    fn send_track_time_offset(&mut self, track: usize) {
+        let (offset_lo, offset_hi) = split_u64(self.track_time_offset[track] as u64);
+        let mut payload = transport_command(UiCommandType::SetTrackTimeOffset, 0);
+        payload.track_id = track as u32;
+        payload.note_nanotick_lo = offset_lo;
+        payload.note_nanotick_hi = offset_hi;
+        self.enqueue_ui_command(payload);
+    }
+
+    fn nudge_track_time_offset(&mut self, steps: i64, cx: &mut impl UiNotify) {
+        let track = self.focused_track_index;
+        let Some(current) = self.track_time_offset.get(track).copied() else {
+            return;
+        };
+        self.set_track_time_offset(track, current + steps * TRACK_TIME_OFFSET_STEP, cx);
+        let offset = self.track_time_offset[track];
+        let label = self
+            .track_time_offset_label(track)
+            .unwrap_or_else(|| "on grid".to_string());
+        if offset == current {
+            self.show_toast(&format!("Track {} offset at limit ({label})", track + 1), cx);
+        } else {
+            self.show_toast(&format!("Track {} offset {label}", track + 1), cx);
+        }
+    }
+
+    /// Header text for a track's time offset in milliseconds at the current
+    /// tempo, or `None` when the track plays on the grid.
+    pub(crate) fn track_time_offset_label(&self, track: usize) -> Option<String> {
+        let offset = self.track_time_offset.get(track).copied().unwrap_or(0);
+        if offset == 0 {
+            return None;
+        }
+        let ms = offset as f64 * 60_000.0 / (self.tempo_bpm * NANOTICKS_PER_QUARTER as f64);
+        Some(format!("{ms:+.1}ms"))
+    }
+
+    fn toggle_count_in(&mut self, cx: &mut impl UiNotify) {
+        self.count_in_bars = (self.count_in_bars + 1) % (MAX_COUNT_IN_BARS + 1);
+        if self.count_in_bars == 0 && self.bridge.is_some() {
+            self.enqueue_ui_command(transport_command(UiCommandType::SetCountIn, 0));
+        }
+        cx.notify();
+    }
+
+
+    pub fn cell_entry_at(
+        &self,
+        nanotick: u64,
+        track_index: usize,
+        column: usize,
+    ) -> Option<CellEntry> {
+        let entries = self.entries_for_row(nanotick, track_index);
+        entries
+            .iter()
+            .find(|entry| entry.column == column)
+            .cloned()
+    }
+
+    pub fn entries_for_row(&self, nanotick: u64, track_index: usize) -> Vec<CellEntry> {
+        let mut entries: Vec<CellEntry> = Vec::new();
+        if let Some(notes) = self.clip_notes.get(track_index) {
+            for note in notes.iter().filter(|note| note.nanotick == nanotick) {
+                let is_note_off = note.velocity == 0 && note.duration == 0;
+                entries.push(CellEntry {
+                    kind: CellKind::Note,
+                    text: if is_note_off {
+                        String::new()
+                    } else {
+                        pitch_to_note(note.pitch)
+                    },
+                    nanotick,
+                    note_pitch: Some(note.pitch),
+                    chord_id: None,
+                    column: note.column as usize,
+                    note_off: is_note_off,
+                    pending: false,
+                });
+            }
+        }
+        for note in self
+            .pending_notes
+            .iter()
+            .filter(|note| note.track_id as usize == track_index && note.nanotick == nanotick)
+        {
+            let is_note_off = note.velocity == 0 && note.duration == 0;
+            entries.push(CellEntry {
+                kind: CellKind::Note,
+                text: if is_note_off {
+                    String::new()
+                } else {
+                    pitch_to_note(note.pitch)
+                },
+                nanotick,
+                note_pitch: Some(note.pitch),
+                chord_id: None,
+                column: note.column as usize,
+                note_off: is_note_off,
+                pending: true,
+            });
+        }
+        let mut pending_chord_columns = std::collections::HashSet::new();
+        for chord in self.pending_chords.iter().filter(|chord| {
+            chord.track_id as usize == track_index && chord.nanotick == nanotick
+        }) {
+            pending_chord_columns.insert(chord.column);
+            let temp = ClipChord {
+                chord_id: 0,
+                nanotick,
+                duration: chord.duration,
+                spread: chord.spread,
+                humanize_timing: chord.humanize_timing,
+                humanize_velocity: chord.humanize_velocity,
+                degree: chord.degree,
+                quality: chord.quality,
+                inversion: chord.inversion,
+                base_octave: chord.base_octave,
+                column: chord.column,
+            };
+            entries.push(CellEntry {
+                kind: CellKind::Chord,
+                text: chord_token_text(&temp),
+                nanotick,
+                note_pitch: None,
+                chord_id: Some(0),
+                column: chord.column as usize,
+                note_off: false,
+                pending: true,
+            });
+        }
+        if let Some(chords) = self.clip_chords.get(track_index) {
+            for chord in chords.iter().filter(|chord| chord.nanotick == nanotick) {
+                if pending_chord_columns.contains(&chord.column) {
+                    continue;
+                }
+                let text = chord_token_text(chord);
+                entries.push(CellEntry {
+                    kind: CellKind::Chord,
+                    text,
+                    nanotick,
+                    note_pitch: None,
+                    chord_id: Some(chord.chord_id),
+                    column: chord.column as usize,
+                    note_off: false,
+                    pending: false,
+                });
+            }
+        }
+        if let Some(filter) = self.track_content_filter.get(track_index) {
+            entries.retain(|entry| filter.shows(&entry.kind));
+        }
+        entries.sort_by(|a, b| {
+            match (&a.kind, &b.kind) {
+                (CellKind::Chord, CellKind::Note) => std::cmp::Ordering::Less,
+                (CellKind::Note, CellKind::Chord) => std::cmp::Ordering::Greater,
+                _ => a.text.cmp(&b.text),
+            }
+        });
+        entries
+    }
+
+    fn apply_cell_token(&mut self, token: &str, cx: &mut impl UiNotify) {
+        if let Some(parsed) = parse_chord_token(token) {
+            self.send_chord(parsed, cx);
+            return;
+        }
+        if let Some(parsed) = parse_degree_note_token(token) {
+            self.send_chord(parsed, cx);
+            return;
+        }
+        if let Some(note) = parse_note_token(token) {
+            self.write_note(note, cx);
+        }
+    }
+
+    fn send_chord(&mut self, chord: ParsedChordToken, cx: &mut impl UiNotify) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        let nanotick = self.current_row_nanotick();
+        if self.insert_mode &&
+            !self.insert_row_in_column(
+                self.focused_track_index,
+                self.cursor_col as u8,
+                nanotick,
+                cx,
+            )
+        {
+            return;
+        }
+        let duration = chord.duration.unwrap_or(0);
+        let degree = chord.degree.min(255) as u16;
+        let pending_degree = degree as u8;
+        let column = self.cursor_col as u8;
+
+        if self.bridge.is_some() {
+            let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
+            let (duration_lo, duration_hi) = split_u64(duration);
+            let payload = UiChordCommandPayload {
+                command_type: UiCommandType::WriteChord as u16,
+                flags: column as u16,
+                track_id: self.focused_track_index as u32,
+                base_version: self.current_clip_version(),
+                nanotick_lo,
+                nanotick_hi,
+                duration_lo,
+                duration_hi,
+                degree,
+                quality: chord.quality,
+                inversion: chord.inversion,
+                base_octave: chord.base_octave,
+                humanize_timing: chord.humanize_timing,
+                humanize_velocity: chord.humanize_velocity,
+                reserved: 0,
+                spread_nanoticks: chord.spread_nanoticks,
+            };
+            self.enqueue_chord_command(payload);
+            self.bump_clip_version();
+        } else {
+            return;
+        }
+        self.pending_notes.retain(|note| {
+            !(note.track_id == self.focused_track_index as u32 &&
+                note.nanotick == nanotick &&
+                note.column == column)
+        });
+        self.pending_chords.retain(|pending| {
+            !(pending.track_id == self.focused_track_index as u32 &&
+                pending.nanotick == nanotick &&
+                pending.column == column)
+        });
+        let mut clip_changed = false;
+        if let Some(notes) = self.clip_notes.get_mut(self.focused_track_index) {
+            let before = notes.len();
+            notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
+            if notes.len() != before {
+                clip_changed = true;
+            }
+        }
+        if let Some(chords) = self.clip_chords.get_mut(self.focused_track_index) {
+            let before = chords.len();
+            chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
+            if chords.len() != before {
+                clip_changed = true;
+            }
+        }
+        if clip_changed {
+            self.bump_clip_render_version();
+        }
+        self.pending_chords.push(PendingChord {
+            track_id: self.focused_track_index as u32,
+            nanotick,
+            duration,
+            spread: chord.spread_nanoticks,
+            humanize_timing: chord.humanize_timing as u16,
+            humanize_velocity: chord.humanize_velocity as u16,
+            degree: pending_degree,
+            quality: chord.quality,
+            inversion: chord.inversion,
+            base_octave: chord.base_octave,
+            column,
+        });
+        cx.notify();
+    }
+
+    fn focused_chord(&self) -> Option<ClipChord> {
+        let track_index = self.focused_track_index;
+        let nanotick = self.current_row_nanotick();
+        let column = self.cursor_col as u8;
+        if let Some(chord) = self.pending_chords.iter().find(|chord| {
+            chord.track_id as usize == track_index &&
+                chord.nanotick == nanotick &&
+                chord.column == column
+        }) {
+            return Some(ClipChord {
+                chord_id: 0,
+                nanotick,
+                duration: chord.duration,
+                spread: chord.spread,
+                humanize_timing: chord.humanize_timing,
+                humanize_velocity: chord.humanize_velocity,
+                degree: chord.degree,
+                quality: chord.quality,
+                inversion: chord.inversion,
+                base_octave: chord.base_octave,
+                column: chord.column,
+            });
+        }
+        self.clip_chords
+            .get(track_index)?
+            .iter()
+            .find(|chord| chord.nanotick == nanotick && chord.column == column)
+            .cloned()
+    }
+
+    /// Note (not a note-off) under the cursor, preferring an unconfirmed write.
+    fn focused_note(&self) -> Option<ClipNote> {
+        let track_index = self.focused_track_index;
+        let nanotick = self.current_row_nanotick();
+        let column = self.cursor_col as u8;
+        if let Some(note) = self.pending_notes.iter().find(|note| {
+            note.track_id as usize == track_index &&
+                note.nanotick == nanotick &&
+                note.column == column &&
+                note.velocity > 0
+        }) {
+            return Some(ClipNote {
+                nanotick,
+                duration: note.duration,
+                pitch: note.pitch,
+                velocity: note.velocity,
+                column: note.column,
+            });
+        }
+        self.clip_notes
+            .get(track_index)?
+            .iter()
+            .find(|note| note.nanotick == nanotick && note.column == column && note.velocity > 0)
+            .cloned()
+    }
+
+    /// Builds the preview command for the chord under the cursor. The engine
+    /// resolves pitches against the harmony active at the chord's nanotick.
+    pub(crate) fn preview_chord_payload(&self) -> Option<UiChordCommandPayload> {
+        let chord = self.focused_chord()?;
+        if self.column_dimmed(self.focused_track_index, chord.column as usize) {
+            return None;
+        }
+        let (nanotick_lo, nanotick_hi) = split_u64(chord.nanotick);
+        Some(UiChordCommandPayload {
+            command_type: UiCommandType::PreviewChord as u16,
+            flags: chord.column as u16,
+            track_id: self.focused_track_index as u32,
+            base_version: 0,
+            nanotick_lo,
+            nanotick_hi,
+            duration_lo: 0,
+            duration_hi: 0,
+            degree: chord.degree as u16,
+            quality: chord.quality,
+            inversion: chord.inversion,
+            base_octave: chord.base_octave,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            reserved: 0,
+            spread_nanoticks: 0,
+        })
+    }
+
+    /// Builds the command that asks the engine to play the triad implied by
+    /// the harmony root and scale at the cursor.
+    pub(crate) fn preview_harmony_payload(&self) -> UiCommandPayload {
+        let nanotick = self.current_row_nanotick();
+        let mut payload = transport_command(
+            UiCommandType::PreviewHarmony,
+            self.harmony_scale_at(nanotick),
+        );
+        payload.track_id = self.focused_track_index as u32;
+        payload.note_pitch = self.harmony_root_at(nanotick);
+        payload
+    }
+
+    /// Toggles harmony audition. While it is on, stepping the cursor onto a
+    /// harmony change in the harmony lane plays the new harmony.
+    fn toggle_harmony_audition(&mut self, cx: &mut impl UiNotify) {
+        self.harmony_audition = !self.harmony_audition;
+        if self.harmony_audition && self.bridge.is_some() {
+            let payload = self.preview_harmony_payload();
+            self.enqueue_ui_command(payload);
+        }
+        let state = if self.harmony_audition { "on" } else { "off" };
+        self.show_toast(&format!("Harmony audition {state}"), cx);
+    }
+
+    fn audition_harmony_change(&mut self, cx: &mut impl UiNotify) {
+        if !self.harmony_audition || !self.harmony_focus || self.bridge.is_none() {
+            return;
+        }
+        let start = self.current_row_nanotick();
+        let end = start.saturating_add(self.row_nanoticks());
+        if !self
+            .harmony_events
+            .iter()
+            .any(|event| event.nanotick >= start && event.nanotick < end)
+        {
+            return;
+        }
+        let payload = self.preview_harmony_payload();
+        self.enqueue_ui_command(payload);
+        cx.notify();
+    }
+
+    fn preview_focused_chord(&mut self, cx: &mut impl UiNotify) {
+        if self.bridge.is_none() {
+            return;
+        }
+        let Some(payload) = self.preview_chord_payload() else {
+            return;
+        };
+        self.enqueue_chord_command(payload);
+        cx.notify();
+    }
+
+    /// Steps the chord under the cursor by one octave, keeping its degree,
+    /// voicing and timing.
+    fn bump_focused_chord_octave(&mut self, delta: i32, cx: &mut impl UiNotify) {
+        let Some(chord) = self.focused_chord() else {
+            return;
+        };
+        let base_octave =
+            (chord.base_octave as i32 + delta).clamp(0, MAX_CHORD_BASE_OCTAVE as i32) as u8;
+        if base_octave == chord.base_octave {
+            return;
+        }
+        self.write_chord_at(
+            self.focused_track_index,
+            chord.column,
+            chord.nanotick,
+            chord.duration,
+            chord.degree,
+            chord.quality,
+            chord.inversion,
+            base_octave,
+            chord.spread,
+            chord.humanize_timing,
+            chord.humanize_velocity,
+            cx,
+        );
+    }
+
+    fn write_chord_at(
+        &mut self,
+        track: usize,
+        column: u8,
+        nanotick: u64,
+        duration: u64,
+        degree: u8,
+        quality: u8,
+        inversion: u8,
+        base_octave: u8,
+        spread: u32,
+        humanize_timing: u16,
+        humanize_velocity: u16,
+        cx: &mut impl UiNotify,
+    ) {
+        if self.read_only_blocked(cx) {
+            return;
+        }
+        if track >= TRACK_COUNT || column as usize >= MAX_NOTE_COLUMNS {
+            return;
+        }
+        if self.bridge.is_none() {
+            let mut clip_changed = false;
+            if let Some(notes) = self.clip_notes.get_mut(track) {
+                notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
+                clip_changed = true;
+            }
+            if let Some(chords) = self.clip_chords.get_mut(track) {
+                chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
+                chords.push(ClipChord {
+                    chord_id: 0,
+                    nanotick,
+                    duration,
+                    spread,
+                    humanize_timing,
+                    humanize_velocity,
+                    degree,
+                    quality,
+                    inversion,
+                    base_octave,
+                    column,
+                });
+                chords.sort_by_key(|chord| (chord.nanotick, chord.column));
+                clip_changed = true;
+            }
+            if clip_changed {
+                self.bump_clip_render_version();
+            }
+            self.bump_clip_version();
+            cx.notify();
+            return;
+        }
+        let (nanotick_lo, nanotick_hi) = split_u64(nanotick);
+        let (duration_lo, duration_hi) = split_u64(duration);
+        let timing = humanize_timing.min(u16::from(u8::MAX)) as u8;
+        let velocity = humanize_velocity.min(u16::from(u8::MAX)) as u8;
+        let payload = UiChordCommandPayload {
+            command_type: UiCommandType::WriteChord as u16,
+            flags: column as u16,
+            track_id: track as u32,
+            base_version: self.current_clip_version(),
+            nanotick_lo,
+            nanotick_hi,
+            duration_lo,
+            duration_hi,
+            degree: degree as u16,
+            quality,
+            inversion,
+            base_octave,
+            humanize_timing: timing,
+            humanize_velocity: velocity,
+            reserved: 0,
+            spread_nanoticks: spread,
+        };
+        self.enqueue_chord_command(payload);
+        self.bump_clip_version();
+
+        self.pending_notes.retain(|note| {
+            !(note.track_id == track as u32 &&
+                note.nanotick == nanotick &&
+                note.column == column)
+        });
+        self.pending_chords.retain(|pending| {
+            !(pending.track_id == track as u32 &&
+                pending.nanotick == nanotick &&
+                pending.column == column)
+        });
+        let mut clip_changed = false;
+        if let Some(notes) = self.clip_notes.get_mut(track) {
+            let before = notes.len();
+            notes.retain(|note| !(note.nanotick == nanotick && note.column == column));
+            if notes.len() != before {
+                clip_changed = true;
+            }
+        }
+        if let Some(chords) = self.clip_chords.get_mut(track) {
+            let before = chords.len();
+            chords.retain(|chord| !(chord.nanotick == nanotick && chord.column == column));
+            if chords.len() != before {
+                clip_changed = true;
+            }
+        }
+        if clip_changed {
+            self.bump_clip_render_version();
+        }
+        self.pending_chords.push(PendingChord {
+            track_id: track as u32,
+            nanotick,
+            duration,
+            spread,
+            humanize_timing,
+            humanize_velocity,
+            degree,
+            quality,
+            inversion,
+            base_octave,
+            column,
+        });
+        cx.notify();
+    }
+
+}
+
+impl UiNotify for Context<'_, EngineView> {
+    fn notify(&mut self) {
+        Context::notify(self);
+    }
+
+    fn notify_rows(&mut self, _rows: &[usize]) {
+        // GPUI doesn't support selective row rendering - always full notify
+        Context::notify(self);
+    }
+}
+
+impl Render for EngineView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // Debug: log when parent EngineView renders with counter
+        self.render_count += 1;
+        self.last_render_versions = self.render_versions();
+        if self.ui_debug {
+            eprintln!("[UI] === EngineView::render #{} ===", self.render_count);
+        }
+        // Note: follow_playhead scroll adjustment moved to update_follow_playhead()
+        // which is called in the async loop, keeping render() pure.
+        if let Some(deadline) = self.toast_deadline {
+            if Instant::now() > deadline {
+                self.toast_deadline = None;
+                self.toast_message = None;
+            }
+        }
+        let playhead = format_playhead(self.snapshot.ui_global_nanotick_playhead);
+        let is_playing = self.snapshot.ui_transport_state != 0;
+        let transport_label = if is_playing { "[Stop]" } else { "[Play]" };
+        let follow_label = if self.follow_playhead { "Follow" } else { "Free" };
+        let entry_mode_label = if self.read_only {
+            "RO"
+        } else if self.velocity_focus {
+            "VEL"
+        } else if self.insert_mode {
+            "INS"
+        } else {
+            "OVR"
+        };
+        let count_in_label = if self.count_in_bars > 0 {
+            format!(" [Count-in:{}]", self.count_in_bars)
+        } else {
+            String::new()
+        };
+        let digits_label = if self.chromatic_digits { "Digits:Chr" } else { "Digits:Deg" };
+        let harmony_label = if self.harmony_focus {
+            format!("Harmony:{}*", harmony_scale_name(self.harmony_scale_id))
+        } else {
+            format!("Harmony:{}", harmony_scale_name(self.harmony_scale_id))
+        };
+        let track_name = self
+            .track_names
+            .get(self.focused_track_index)
+            .and_then(|name| name.clone())
+            .unwrap_or_else(|| "Empty".to_string());
+        let quantize_strength = self
+            .track_quantize_strength
+            .get(self.focused_track_index)
+            .copied()
+            .unwrap_or(MAX_QUANTIZE_STRENGTH);
+        let quantize_label = if !self
+            .track_quantize
+            .get(self.focused_track_index)
+            .copied()
+            .unwrap_or(true)
+        {
+            "Quantize:Off".to_string()
+        } else if quantize_strength < MAX_QUANTIZE_STRENGTH {
+            format!("Quantize:{quantize_strength}%")
+        } else {
+            "Quantize:On".to_string()
+        };
+
+        let mut root = div()
+            .relative()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .bg(rgb(0x0f161c))
+            .text_color(rgb(0xe6eef5))
+            .font_family("Menlo")  // Use Menlo for better monospace on macOS
+            .w_full()
+            .h_full()
+            .p_3()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_0()
+                    .child(
+                        div()
+                            .w(px(TIME_COLUMN_WIDTH))
+                            .text_sm()
+                            .child(playhead.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0x93a1ad))
+                            .bg(rgb(0x18222b))
+                            .px_2()
+                            .py_1()
+                            .border_1()
+                            .border_color(rgb(0x253240))
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.toggle_play(cx);
+                                }),
+                            )
+                            .child(transport_label),
+                    )
+                    .child(self.render_master_meter(cx)),
+            )
+            ;
+
+        if self.patcher_open {
+            root = root.child(self.render_patcher_view(cx));
+        } else {
+            root = root.child(self.render_tracker_grid(cx));
+        }
+
+        root
+            .child(self.render_device_chain_strip(cx))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(rgb(0x93a1ad))
+                    .child(format!(
+                        "[Track {}:{} {}] [{}] [BPM {}] [{}] [View: {}] [{}] [{}] [{}]{}",
+                        self.focused_track_index,
+                        self.cursor_col + 1,
+                        track_name,
+                        playhead,
+                        self.tempo_bpm,
+                        quantize_label,
+                        follow_label,
+                        harmony_label,
+                        entry_mode_label,
+                        digits_label,
+                        count_in_label
+                    )),
+            )
+            .child(self.render_palette(cx))
+            .child(self.render_scale_browser(cx))
+            .child(self.render_jump_overlay(cx))
+            .child(self.render_tempo_overlay(cx))
+            .child(self.render_mirror_overlay(cx))
+            .child(self.render_bar_harmony_overlay(cx))
+            .child(self.render_euclidean_overlay(cx))
+            .child(self.render_sustain_chord_overlay(cx))
+            .child(self.render_stash_prompt_overlay(cx))
+            .child(self.render_preset_save_overlay(cx))
+            .child(self.render_debug_overlay(cx))
+            .child(self.render_version_overlay(cx))
+            .child(self.render_toast(cx))
+    }
+}
+
+impl EngineView {
+    pub(crate) fn harmony_label_at_nanotick(&self, nanotick: u64) -> Option<String> {
+        let event = self
+            .harmony_events
+            .iter()
+            .find(|event| event.nanotick == nanotick)?;
+        let root = harmony_root_name(event.root);
+        let scale = harmony_scale_name(event.scale_id);
+        Some(format!("{root}:{scale}"))
+    }
+
+    /// Root and scale carried forward to a row without its own harmony
+    /// event. None when the row holds an event, which shows its own label.
+    pub(crate) fn inherited_harmony_label(&self, nanotick: u64) -> Option<String> {
+        if self.harmony_events.iter().any(|event| event.nanotick == nanotick) {
+            return None;
+        }
+        let root = harmony_root_name(self.harmony_root_at(nanotick));
+        let scale = harmony_scale_name(self.harmony_scale_at(nanotick));
+        Some(format!("{root}:{scale}"))
+    }
+
+    fn toggle_inherited_harmony(&mut self, cx: &mut impl UiNotify) {
+        self.show_inherited_harmony = !self.show_inherited_harmony;
+        cx.notify();
+    }
+}
+
+/// Whether any master channel peaked above full scale.
+fn master_peak_clipped(peaks: &[f32]) -> bool {
+    peaks.iter().any(|peak| *peak > 1.0)
+}
+
+fn quantize_toward_row(nanotick: u64, row_nanoticks: u64, strength: u8) -> u64 {
+    let nearest = (nanotick + row_nanoticks / 2) / row_nanoticks * row_nanoticks;
+    let strength = strength.min(MAX_QUANTIZE_STRENGTH) as i128;
+    let delta = (nearest as i128 - nanotick as i128) * strength / 100;
+    (nanotick as i128 + delta) as u64
+}
+
+/// Scroll offset that keeps `playhead` at the same on-screen height when the
+/// row size changes from `old_row` to `new_row`, clamped to the clip start.
+pub(crate) fn playhead_anchored_scroll(
+    playhead: u64,
+    scroll: i64,
+    old_row: u64,
+    new_row: u64,
+) -> i64 {
+    let offset = playhead as i128 - scroll as i128;
+    let scaled = offset * new_row as i128 / old_row.max(1) as i128;
+    (playhead as i128 - scaled).max(0) as i64
+}
+
+fn follow_band_is_valid(lower: f32, upper: f32) -> bool {
+    lower > 0.0 && upper < 1.0 && lower < upper
+}
+
+/// Parses a `lower,upper` follow band such as `0.4,0.6`.
+fn parse_follow_band(text: &str) -> Option<(f32, f32)> {
+    let (lower, upper) = text.split_once(',')?;
+    let lower = lower.trim().parse::<f32>().ok()?;
+    let upper = upper.trim().parse::<f32>().ok()?;
+    follow_band_is_valid(lower, upper).then_some((lower, upper))
+}
+
+fn transport_command(command: UiCommandType, value0: u32) -> UiCommandPayload {
+    UiCommandPayload {
+        command_type: command as u16,
+        flags: 0,
+        track_id: 0,
+        plugin_index: 0,
+        note_pitch: 0,
+        value0,
+        note_nanotick_lo: 0,
+        note_nanotick_hi: 0,
+        note_duration_lo: 0,
+        note_duration_hi: 0,
+        base_version: 0,
+    }
+}
+
+pub(crate) fn should_notify(previous: RenderVersions, current: RenderVersions) -> bool {
+    previous != current
+}
+
+pub(crate) fn format_playhead(nanoticks: u64) -> String {
+    let total_beats = nanoticks / NANOTICKS_PER_QUARTER;
+    let bar = total_beats / BEATS_PER_BAR + 1;
+    let beat = total_beats % BEATS_PER_BAR + 1;
+    let tick = (nanoticks % NANOTICKS_PER_QUARTER) / 10_000;
+    format!("{}:{}:{}", bar, beat, tick)
+}
+
+fn parse_jump_text(text: &str) -> Option<u64> {
+    if text.trim().is_empty() {
+        return None;
+    }
+    let parts: Vec<&str> = text.split(':').collect();
+    let bar = parts.get(0)?.parse::<u64>().ok()?;
+    if bar == 0 || bar > MAX_JUMP_BAR {
+        return None;
+    }
+    let beat = match parts.get(1) {
+        Some(part) => part.parse::<u64>().ok()?,
+        None => 1,
+    };
+    if beat == 0 {
+        return None;
+    }
+    let tick = match parts.get(2) {
+        Some(part) => part.parse::<u64>().ok()?,
+        None => 0,
+    };
+    let total_beats = ((bar - 1) * BEATS_PER_BAR).checked_add(beat - 1)?;
+    let nanotick = total_beats
+        .checked_mul(NANOTICKS_PER_QUARTER)?
+        .checked_add(tick.checked_mul(10_000)?)?;
+    if nanotick >= MAX_JUMP_BAR * BEATS_PER_BAR * NANOTICKS_PER_QUARTER {
+        return None;
+    }
+    Some(nanotick)
+}
+
+fn format_bar_beat(nanotick: u64) -> String {
+    let total_beats = nanotick / NANOTICKS_PER_QUARTER;
+    let bar = total_beats / BEATS_PER_BAR + 1;
+    let beat = total_beats % BEATS_PER_BAR + 1;
+    let tick = (nanotick % NANOTICKS_PER_QUARTER) / 10_000;
+    if tick == 0 {
+        format!("{bar}:{beat}")
+    } else {
+        format!("{bar}:{beat}:{tick}")
+    }
+}
+
+/// One `bar:beat root scale` line per harmony event, in timeline order.
+pub(crate) fn harmony_chart_text(events: &[HarmonyEntry]) -> String {
+    let mut sorted: Vec<&HarmonyEntry> = events.iter().collect();
+    sorted.sort_by_key(|event| event.nanotick);
+    let mut text = String::new();
+    for event in sorted {
+        text.push_str(&format!(
+            "{} {} {}\n",
+            format_bar_beat(event.nanotick),
+            harmony_root_name(event.root),
+            harmony_scale_name(event.scale_id)
+        ));
+    }
+    text
+}
+
+/// One `bar: chord` line per harmony event, in timeline order, naming the
+/// chord each root and scale implies. Changes off the bar line are written
+/// as `bar.beat`.
+pub(crate) fn chord_chart_text(events: &[HarmonyEntry]) -> String {
+    let mut sorted: Vec<&HarmonyEntry> = events.iter().collect();
+    sorted.sort_by_key(|event| event.nanotick);
+    let mut text = String::new();
+    for event in sorted {
+        let beats = event.nanotick / NANOTICKS_PER_QUARTER;
+        let bar = beats / BEATS_PER_BAR + 1;
+        let beat = beats % BEATS_PER_BAR + 1;
+        let position = if beat == 1 && event.nanotick % NANOTICKS_PER_QUARTER == 0 {
+            bar.to_string()
+        } else {
+            format!("{bar}.{beat}")
+        };
+        text.push_str(&format!(
+            "{position}: {}\n",
+            implied_chord_name(event.root, event.scale_id)
+        ));
+    }
+    text
+}
+
+/// Parses a comma-separated list of root names such as `C,G,A,F`.
+pub(crate) fn parse_root_list(text: &str) -> Option<Vec<u32>> {
+    let roots = text
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(harmony_root_from_name)
+        .collect::<Option<Vec<u32>>>()?;
+    (!roots.is_empty()).then_some(roots)
+}
+
+/// Parses `steps,hits[,offset[,pitch]]` for the Euclidean prompt. Steps run
+/// 1..=`EUCLIDEAN_MAX_STEPS`, hits may not exceed steps, and the pitch is a
+/// note name such as `C-4`.
+pub(crate) fn parse_euclidean_spec(text: &str) -> Option<(u32, u32, u32, Option<u8>)> {
+    let mut parts = text.split(',').map(str::trim);
+    let steps: u32 = parts.next()?.parse().ok()?;
+    let hits: u32 = parts.next()?.parse().ok()?;
+    let offset: u32 = match parts.next() {
+        Some(part) if !part.is_empty() => part.parse().ok()?,
+        _ => 0,
+    };
+    let pitch = match parts.next() {
+        Some(part) if !part.is_empty() => Some(parse_note_token(part)?),
+        _ => None,
+    };
+    if parts.next().is_some() || steps == 0 || steps > EUCLIDEAN_MAX_STEPS || hits > steps {
+        return None;
+    }
+    Some((steps, hits, offset, pitch))
+}
+
+/// Renders Euclidean step patterns as an ASCII grid, one `T<n>` row per
+/// track with `x` for a hit and `.` for a rest. Patterns use the layout
+/// written by `patcher_euclidean_pattern`.
+pub(crate) fn euclidean_grid_text(patterns: &[(usize, Vec<u8>)]) -> String {
+    let mut text = String::new();
+    for (track, pattern) in patterns {
+        let steps: String = pattern
+            .iter()
+            .map(|step| if *step != 0 { 'x' } else { '.' })
+            .collect();
+        text.push_str(&format!("T{:<2} {}\n", track + 1, steps));
+    }
+    text
+}
+
+fn is_jump_char(value: &str) -> bool {
+    value.len() == 1 && value.chars().all(|ch| ch.is_ascii_digit() || ch == ':' || ch == 's')
+}
+
+/// Nanotick reached after `samples` at a steady `tempo_bpm`, or None past
+/// the furthest position the jump box accepts.
+fn samples_to_nanotick(samples: u64, tempo_bpm: f64, sample_rate: f64) -> Option<u64> {
+    if sample_rate <= 0.0 {
+        return None;
+    }
+    let quarters = samples as f64 / sample_rate * tempo_bpm / 60.0;
+    let nanotick = (quarters * NANOTICKS_PER_QUARTER as f64).round();
+    let limit = MAX_JUMP_BAR * BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+    (nanotick.is_finite() && nanotick < limit as f64).then_some(nanotick as u64)
+}
+
+fn is_tempo_char(value: &str) -> bool {
+    value.len() == 1 && value.chars().all(|ch| ch.is_ascii_digit() || ch == '.')
+}
+
+fn parse_tempo_text(text: &str) -> Option<f64> {
+    let bpm = text.trim().parse::<f64>().ok()?;
+    (MIN_TEMPO_BPM..=MAX_TEMPO_BPM).contains(&bpm).then_some(bpm)
+}
+
+fn pitch_for_key(key: &str) -> Option<u8> {
+    let k = key.to_lowercase();
+    let pitch = match k.as_str() {
+        "z" => 48,  // C-3
+        "s" => 49,  // C#3
+        "x" => 50,  // D-3
+        "d" => 51,  // D#3
+        "c" => 52,  // E-3
+        "v" => 53,  // F-3
+        "g" => 54,  // F#3
+        "b" => 55,  // G-3
+        "h" => 56,  // G#3
+        "n" => 57,  // A-3
+        "j" => 58,  // A#3
+        "m" => 59,  // B-3
+        "q" => 60,  // C-4
+        "2" => 61,  // C#4
+        "w" => 62,  // D-4
+        "3" => 63,  // D#4
+        "e" => 64,  // E-4
+        "r" => 65,  // F-4
+        "5" => 66,  // F#4
+        "t" => 67,  // G-4
+        "6" => 68,  // G#4
+        "y" => 69,  // A-4
+        "7" => 70,  // A#4
+        "u" => 71,  // B-4
+        _ => return None,
+    };
+    Some(pitch)
+}
+
+fn pitch_for_letter_key(key: &str) -> Option<u8> {
+    if key.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        return pitch_for_key(key);
+    }
+    None
+}
+
+fn degree_for_digit(key: &str) -> Option<u8> {
+    if key.len() != 1 {
+        return None;
+    }
+    let ch = key.chars().next()?;
+    if !ch.is_ascii_digit() {
+        return None;
+    }
+    let digit = ch.to_digit(10)? as u8;
+    if digit == 0 {
+        return None;
+    }
+    Some(digit)
+}
+
+fn harmony_root_for_key(key: &str) -> Option<u32> {
+    let mut sharp = false;
+    let mut k = key.to_string();
+    if k.len() == 1 && k.chars().next().unwrap().is_ascii_uppercase() {
+        sharp = true;
+        k = k.to_lowercase();
+    }
+    let base = match k.as_str() {
+        "c" => 0,
+        "d" => 2,
+        "e" => 4,
+        "f" => 5,
+        "g" => 7,
+        "a" => 9,
+        "b" => 11,
+        _ => return None,
+    };
+    let root = if sharp { (base + 1) % 12 } else { base };
+    Some(root)
+}
+
+#[allow(dead_code)]
+fn harmony_scale_id_for_key(key: &str) -> Option<u32> {
+    for scale in SCALE_LIBRARY {
+        if scale.key == key {
+            return Some(scale.id);
+        }
+    }
+    None
+}
+
+pub(crate) fn pitch_to_note(pitch: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C-", "C#", "D-", "D#", "E-", "F-", "F#", "G-", "G#", "A-", "A#", "B-",
+    ];
+    let name = NAMES[(pitch as usize) % 12];
+    let octave = (pitch / 12) as i8 - 1;
+    format!("{name}{octave}")
+}
+
+/// `pitch_to_note` with black keys spelled as flats, e.g. `Db4`.
+pub(crate) fn pitch_to_flat_note(pitch: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C-", "Db", "D-", "Eb", "E-", "F-", "Gb", "G-", "Ab", "A-", "Bb", "B-",
+    ];
+    let name = NAMES[(pitch as usize) % 12];
+    let octave = (pitch / 12) as i8 - 1;
+    format!("{name}{octave}")
+}
+
+/// Distance to shift a harmony progression starting at `first` so its copy
+/// follows the selection ending on the row at `end`: the span rounded up to
+/// whole bars.
+pub(crate) fn harmony_progression_offset(first: u64, end: u64, row_nanoticks: u64) -> u64 {
+    let bar = NANOTICKS_PER_QUARTER * BEATS_PER_BAR;
+    let span = end.saturating_add(row_nanoticks).saturating_sub(first).max(1);
+    span.div_ceil(bar) * bar
+}
+
+/// Whether a write sent at `sent_at` has waited past `COMMAND_ACK_TIMEOUT`
+/// for its confirming diff.
+pub(crate) fn command_ack_overdue(sent_at: Instant, now: Instant) -> bool {
+    now.saturating_duration_since(sent_at) >= COMMAND_ACK_TIMEOUT
+}
+
+/// Clip sync state for the version overlay: the version edits are based on,
+/// the engine's snapshot version, the optimistic local version, and the
+/// number of commands still waiting for the engine.
+fn version_overlay_text(current: u32, engine: u32, local: u32, queued: usize) -> String {
+    format!("clip v{current} engine v{engine} local v{local} queued {queued}")
+}
+
+fn command_type_name(command_type: u16) -> String {
+    UiCommandType::from_u16(command_type)
+        .map(|kind| format!("{kind:?}"))
+        .unwrap_or_else(|| format!("#{command_type}"))
+}
+
+/// One-line form of `queued_command_summary` for the debug overlay.
+pub(crate) fn queue_summary_text(summary: &[(String, usize)]) -> String {
+    if summary.is_empty() {
+        return "queue empty".to_string();
+    }
+    let parts: Vec<String> = summary
+        .iter()
+        .map(|(name, count)| format!("{name} {count}"))
+        .collect();
+    format!("queue {}", parts.join(", "))
+}
+
+/// Numeric velocity label shown in note cells when velocity display is on.
+pub(crate) fn velocity_label(velocity: u8) -> String {
+    format!("v{:03}", velocity.clamp(1, 127))
+}
+
+/// Pseudo-random inversion in 0..=3 seeded by a chord's position. Uses the
+/// same LCG as the engine's humanize jitter, taking high bits since the low
+/// bits of row-aligned nanoticks barely change.
+pub(crate) fn seeded_inversion(nanotick: u64) -> u8 {
+    let seed = (nanotick ^ (nanotick >> 32)) as u32;
+    let x = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+    ((x >> 16) % 4) as u8
+}
+
+/// Rounds a range to the nearest bar lines, keeping at least one bar.
+pub(crate) fn snap_range_to_bars(start: u64, end: u64) -> (u64, u64) {
+    let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+    let round = |tick: u64| tick.saturating_add(bar / 2) / bar * bar;
+    let start = round(start);
+    let end = round(end).max(start.saturating_add(bar));
+    (start, end)
+}
+
+/// Whether an empty cell starting at `row_start` is a rest: it lies between
+/// two events of its column and no event starts at or still sounds over it. Cells
+/// before the first event or after the last one stay plain empty cells.
+pub(crate) fn is_rest_cell(spans: &[(u64, u64)], row_start: u64) -> bool {
+    let after_event = spans.iter().any(|(start, _)| *start < row_start);
+    let before_event = spans.iter().any(|(start, _)| *start > row_start);
+    let occupied = spans
+        .iter()
+        .any(|(start, end)| *start == row_start || (*start < row_start && row_start < *end));
+    after_event && before_event && !occupied
+}
+
+/// X offset of `column` in `track` from the left of the track area when the
+/// first `scroll_columns` columns (counted across all tracks) are scrolled
+/// away. None for columns scrolled out of view.
+pub(crate) fn column_x_offset(
+    track_columns: &[usize],
+    track: usize,
+    column: usize,
+    scroll_columns: usize,
+) -> Option<f32> {
+    let index = track_columns.iter().take(track).sum::<usize>() + column;
+    let visible = index.checked_sub(scroll_columns)?;
+    Some(visible as f32 * COLUMN_WIDTH)
+}
+
+/// Velocity for digits typed in velocity entry mode, or None while more
+/// digits are expected. Scaled entry takes two digits and stretches 00-99
+/// over 1..=127; direct entry completes at three digits or as soon as
+/// another digit would pass 127.
+pub(crate) fn velocity_from_digits(digits: &str, scale: VelocityDigitScale) -> Option<u8> {
+    let value: u32 = digits.parse().ok()?;
+    let velocity = match scale {
+        VelocityDigitScale::Scaled => {
+            if digits.len() < 2 {
+                return None;
+            }
+            (value.min(99) * 127 + 49) / 99
+        }
+        VelocityDigitScale::Direct => {
+            if digits.len() < 3 && value * 10 <= 127 {
+                return None;
+            }
+            value
+        }
+    };
+    Some(velocity.clamp(1, 127) as u8)
+}
+
+/// Note label with its length in rows appended, e.g. `C-4:4`. Partial rows
+/// round up, and lengths past two digits collapse to `**` so the text still
+/// fits a note column.
+pub(crate) fn note_duration_label(pitch: u8, duration: u64, row_nanoticks: u64) -> String {
+    named_duration_label(&pitch_to_note(pitch), duration, row_nanoticks)
+}
+
+fn named_duration_label(name: &str, duration: u64, row_nanoticks: u64) -> String {
+    let rows = duration.div_ceil(row_nanoticks);
+    if rows > 99 {
+        format!("{name}:**")
+    } else {
+        format!("{name}:{rows}")
+    }
+}
+
+/// Labels for the pitch ruler, highest first, covering the two keyjazz
+/// octaves that start at `base_octave`.
+pub(crate) fn pitch_ruler_labels(base_octave: u8) -> Vec<String> {
+    let low = (base_octave as u16 + 1) * 12;
+    (low..low + 24)
+        .rev()
+        .filter(|pitch| *pitch <= 127)
+        .map(|pitch| pitch_to_note(pitch as u8))
+        .collect()
+}
+
+pub(crate) fn chord_token_text(chord: &ClipChord) -> String {
+    if chord.quality == 0 {
+        return format!("{}-{}", chord.degree, chord.base_octave);
+    }
+    let mut text = format!("@{}", chord.degree);
+    text.push_str(&chord_quality_suffix(chord.quality));
+    if chord.inversion > 0 {
+        text.push_str(&format!("/{}", chord.inversion));
+    }
+    if chord.base_octave != 0 {
+        text.push_str(&format!("-{}", chord.base_octave));
+    }
+    if chord.spread > 0 {
+        text.push_str(&format!("~{}", chord.spread));
+    }
+    if chord.humanize_timing > 0 {
+        text.push_str(&format!("h{}", chord.humanize_timing));
+    }
+    text
+}
+
+/// Resolved display for a chord cell: the note name of its degree under
+/// `root`/`scale_id`, with chords marked by `@` and their quality/inversion.
+pub(crate) fn resolved_chord_text(chord: &ClipChord, root: u32, scale_id: u32) -> String {
+    let pitch = resolve_degree_pitch(chord.degree, chord.base_octave, root, scale_id);
+    if chord.quality == 0 {
+        return pitch_to_note(pitch);
+    }
+    let mut text = format!("@{}", pitch_to_note(pitch));
+    text.push_str(&chord_quality_suffix(chord.quality));
+    if chord.inversion > 0 {
+        text.push_str(&format!("/{}", chord.inversion));
+    }
+    text
+}
+
+/// Token suffix for a chord quality: triads (1) need none, sevenths (2) use
+/// `^7`, and any other quality is spelled explicitly as `q<n>` so the token
+/// re-parses to the same value.
+fn chord_quality_suffix(quality: u8) -> String {
+    match quality {
+        CHORD_QUALITY_TRIAD => String::new(),
+        CHORD_QUALITY_SEVENTH => "^7".to_string(),
+        other => format!("q{other}"),
+    }
+}
+
+struct ParsedChordToken {
+    degree: u32,
+    quality: u8,
+    inversion: u8,
+    base_octave: u8,
+    spread_nanoticks: u32,
+    humanize_timing: u8,
+    humanize_velocity: u8,
+    duration: Option<u64>,
+}
+
+fn parse_chord_token(token: &str) -> Option<ParsedChordToken> {
+    let token = token.trim();
+    let mut chars = token.chars().peekable();
+    if chars.next()? != '@' {
+        return None;
+    }
+    let mut degree_str = String::new();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_ascii_digit() {
+            degree_str.push(ch);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    let degree: u32 = degree_str.parse().ok()?;
+    let mut quality: u8 = CHORD_QUALITY_TRIAD;
+    let mut inversion: u8 = 0;
+    let mut base_octave: u8 = 4;
+    let mut spread: u32 = 0;
+    let mut humanize: u8 = 0;
+    let rest: Vec<char> = chars.collect();
+    let mut index = 0;
+    while index < rest.len() {
+        match rest[index] {
+            '^' => {
+                index += 1;
+                let mut number = String::new();
+                while index < rest.len() && rest[index].is_ascii_digit() {
+                    number.push(rest[index]);
+                    index += 1;
+                }
+                if number == "7" {
+                    quality = CHORD_QUALITY_SEVENTH;
+                }
+            }
+            'q' => {
+                index += 1;
+                let mut number = String::new();
+                while index < rest.len() && rest[index].is_ascii_digit() {
+                    number.push(rest[index]);
+                    index += 1;
+                }
+                if let Ok(value) = number.parse::<u8>() {
+                    quality = value;
+                }
+            }
+            '/' => {
+                index += 1;
+                let mut number = String::new();
+                while index < rest.len() && rest[index].is_ascii_digit() {
+                    number.push(rest[index]);
+                    index += 1;
+                }
+                if let Ok(value) = number.parse::<u8>() {
+                    inversion = value;
+                }
+            }
+            '-' => {
+                index += 1;
+                let mut number = String::new();
+                while index < rest.len() && rest[index].is_ascii_digit() {
+                    number.push(rest[index]);
+                    index += 1;
+                }
+                if let Ok(value) = number.parse::<u8>() {
+                    base_octave = value;
+                }
+            }
+            '~' => {
+                index += 1;
+                let mut number = String::new();
+                while index < rest.len() && rest[index].is_ascii_digit() {
+                    number.push(rest[index]);
+                    index += 1;
+                }
+                if let Ok(value) = number.parse::<u32>() {
+                    spread = value;
+                }
+            }
+            'h' => {
+                index += 1;
+                let mut number = String::new();
+                while index < rest.len() && rest[index].is_ascii_digit() {
+                    number.push(rest[index]);
+                    index += 1;
+                }
+                if let Ok(value) = number.parse::<u8>() {
+                    humanize = value;
+                }
+            }
+            _ => {
+                index += 1;
+            }
+        }
+    }
+    Some(ParsedChordToken {
+        degree,
+        quality,
+        inversion,
+        base_octave,
+        spread_nanoticks: spread,
+        humanize_timing: humanize,
+        humanize_velocity: humanize,
+        duration: None,
+    })
+}
+
+fn parse_degree_note_token(token: &str) -> Option<ParsedChordToken> {
+    let token = token.trim();
+    let mut parts = token.splitn(2, '-');
+    let degree_str = parts.next()?;
+    let octave_and_mods = parts.next();
+    if degree_str.is_empty() {
+        return None;
+    }
+    if !degree_str.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let degree: u32 = degree_str.parse().ok()?;
+    let mut oct_chars = octave_and_mods.unwrap_or("").chars().peekable();
+    let mut octave_str = String::new();
+    while let Some(&ch) = oct_chars.peek() {
+        if ch.is_ascii_digit() {
+            octave_str.push(ch);
+            oct_chars.next();
+        } else {
+            break;
+        }
+    }
+    let base_octave: u8 = if octave_str.is_empty() {
+        4
+    } else {
+        octave_str.parse().ok()?
+    };
+    let rest: Vec<char> = oct_chars.collect();
+    let mut spread: u32 = 0;
+    let mut humanize: u8 = 0;
+    let mut index = 0;
+    while index < rest.len() {
+        match rest[index] {
+            '~' => {
+                index += 1;
+                let mut number = String::new();
+                while index < rest.len() && rest[index].is_ascii_digit() {
+                    number.push(rest[index]);
+                    index += 1;
+                }
+                if let Ok(value) = number.parse::<u32>() {
+                    spread = value;
+                }
+            }
+            'h' => {
+                index += 1;
+                let mut number = String::new();
+                while index < rest.len() && rest[index].is_ascii_digit() {
+                    number.push(rest[index]);
+                    index += 1;
+                }
+                if let Ok(value) = number.parse::<u8>() {
+                    humanize = value;
+                }
+            }
+            _ => {
+                index += 1;
+            }
+        }
+    }
+    Some(ParsedChordToken {
+        degree,
+        quality: 0,
+        inversion: 0,
+        base_octave,
+        spread_nanoticks: spread,
+        humanize_timing: humanize,
+        humanize_velocity: humanize,
+        duration: None,
+    })
+}
+
+fn parse_note_token(token: &str) -> Option<u8> {
+    let token = token.trim();
+    if token.len() < 2 {
+        return None;
+    }
+    let mut chars = token.chars();
+    let letter = chars.next()?.to_ascii_uppercase();
+    let mut accidental: i8 = 0;
+    let mut next = chars.next()?;
+    if next == '#' {
+        accidental = 1;
+        next = chars.next()?;
+    } else if next == 'b' || next == 'B' {
+        accidental = -1;
+        next = chars.next()?;
+    } else if next == '-' {
+        next = chars.next()?;
+    }
+    let mut octave_str = String::new();
+    octave_str.push(next);
+    octave_str.extend(chars);
+    let octave: i8 = octave_str.parse().ok()?;
+    let base = match letter {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let mut pitch = (octave + 1) as i16 * 12 + base as i16 + accidental as i16;
+    if pitch < 0 {
+        pitch = 0;
+    }
+    if pitch > 127 {
+        pitch = 127;
+    }
+    Some(pitch as u8)
+}
+
+fn keystroke_text(keystroke: &gpui::Keystroke) -> Option<&str> {
+    if let Some(key_char) = keystroke.key_char.as_ref() {
+        return Some(key_char);
+    }
+    if keystroke.key.len() == 1 {
+        return Some(keystroke.key.as_str());
+    }
+    None
+}
+
+fn begin_cell_edit(edit_active: &mut bool, edit_text: &mut String, initial: &str) -> bool {
+    *edit_active = true;
+    edit_text.clear();
+    edit_text.push_str(initial);
+    true
+}
+
+fn cancel_cell_edit_state(edit_active: &mut bool, edit_text: &mut String) -> bool {
+    if !*edit_active {
+        return false;
+    }
+    *edit_active = false;
+    edit_text.clear();
+    true
+}
+
+fn commit_cell_edit_state(edit_active: &mut bool, edit_text: &mut String) -> Option<String> {
+    if !*edit_active {
+        return None;
+    }
+    let token = edit_text.trim().to_string();
+    *edit_active = false;
+    edit_text.clear();
+    Some(token)
+}
+
+fn is_cell_edit_start(key: &str) -> bool {
+    key == "@"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note_token() {
+        assert_eq!(parse_note_token("C-4"), Some(60));
+        assert_eq!(parse_note_token("D#5"), Some(75));
+        assert_eq!(parse_note_token("Bb3"), Some(58));
+        assert_eq!(parse_note_token("Q-4"), None);
+    }
+
+    #[test]
+    fn test_parse_degree_note_token() {
+        let parsed = parse_degree_note_token("24-4~120h8").expect("degree token");
+        assert_eq!(parsed.degree, 24);
+        assert_eq!(parsed.base_octave, 4);
+        assert_eq!(parsed.quality, 0);
+        assert_eq!(parsed.spread_nanoticks, 120);
+        assert_eq!(parsed.humanize_timing, 8);
+    }
+
+    #[test]
+    fn test_chord_token_quality_round_trip() {
+        for quality in [0u8, 1, 2, 3, 7] {
+            let chord = ClipChord {
+                chord_id: 1,
+                nanotick: 0,
+                duration: 0,
+                spread: 0,
+                humanize_timing: 0,
+                humanize_velocity: 0,
+                degree: 5,
+                quality,
+                inversion: if quality == 0 { 0 } else { 1 },
+                base_octave: 3,
+                column: 0,
+            };
+            let text = chord_token_text(&chord);
+            let parsed = parse_chord_token(&text)
+                .or_else(|| parse_degree_note_token(&text))
+                .unwrap_or_else(|| panic!("token {text} did not parse"));
+            assert_eq!(parsed.quality, quality, "token {text}");
+            assert_eq!(parsed.degree, 5);
+            assert_eq!(parsed.inversion, chord.inversion);
+            assert_eq!(parsed.base_octave, 3);
+        }
+    }
+
+    #[test]
+    fn test_parse_chord_token() {
+        let parsed = parse_chord_token("@3^7/1-5~240h6").expect("chord token");
+        assert_eq!(parsed.degree, 3);
+        assert_eq!(parsed.quality, 2);
+        assert_eq!(parsed.inversion, 1);
+        assert_eq!(parsed.base_octave, 5);
+        assert_eq!(parsed.spread_nanoticks, 240);
+        assert_eq!(parsed.humanize_timing, 6);
+    }
+
+    #[test]
+    fn test_cell_edit_flow() {
+        let mut active = false;
+        let mut text = String::new();
+        assert!(begin_cell_edit(&mut active, &mut text, "A"));
+        assert!(active);
+        assert_eq!(text, "A");
+
+        let token = commit_cell_edit_state(&mut active, &mut text);
+        assert_eq!(token, Some("A".to_string()));
+        assert!(!active);
+        assert!(text.is_empty());
+
+        assert!(begin_cell_edit(&mut active, &mut text, " @3 "));
+        assert!(cancel_cell_edit_state(&mut active, &mut text));
+        assert!(!active);
+        assert!(text.is_empty());
+    }
+
+    #[test]
+    fn test_should_notify_compares_render_versions() {
+        let previous = RenderVersions { snapshot: 4, clip: 2, harmony: 1 };
+        assert!(!should_notify(previous, previous));
+        assert!(should_notify(previous, RenderVersions { snapshot: 5, ..previous }));
+        assert!(should_notify(previous, RenderVersions { clip: 3, ..previous }));
+        assert!(should_notify(previous, RenderVersions { harmony: 2, ..previous }));
+
+        let mut view = EngineView::new_for_tests();
+        view.last_render_versions = view.render_versions();
+        assert!(!view.needs_render());
+        view.bump_clip_render_version();
+        assert!(view.needs_render());
+        view.last_render_versions = view.render_versions();
+        view.snapshot.version += 2;
+        view.acknowledge_snapshot_version();
+        assert!(!view.needs_render());
+    }
+
+    #[test]
+    fn test_chain_plugin_load_names_track() {
+        use daw_bridge::layout::UiChainDiffPayload;
+
+        let mut view = EngineView::new_for_tests();
+        view.plugins = vec![PluginEntry {
+            index: 7,
+            name: "Surge XT".to_string(),
+            vendor: "Surge Synth Team".to_string(),
+            is_instrument: true,
+        }];
+        view.apply_chain_diff(UiChainDiffPayload {
+            diff_type: UiDiffType::ChainSnapshot as u16,
+            track_id: 2,
+            chain_version: 1,
+            device_id: 10,
+            device_kind: 3,
+            host_slot_index: 7,
+            ..Default::default()
+        });
+        assert_eq!(view.track_names[2].as_deref(), Some("Surge XT"));
+
+        // An empty chain snapshot means the plugin was removed.
+        view.apply_chain_diff(UiChainDiffPayload {
+            diff_type: UiDiffType::ChainSnapshot as u16,
+            track_id: 2,
+            chain_version: 2,
+            device_id: K_CHAIN_DEVICE_ID_AUTO,
+            ..Default::default()
+        });
+        assert_eq!(view.track_names[2], None);
+
+        view.auto_name_tracks = false;
+        view.apply_chain_diff(UiChainDiffPayload {
+            diff_type: UiDiffType::ChainSnapshot as u16,
+            track_id: 2,
+            chain_version: 3,
+            device_id: 11,
+            device_kind: 3,
+            host_slot_index: 7,
+            ..Default::default()
+        });
+        assert_eq!(view.track_names[2], None);
+    }
+
+    #[test]
+    fn test_euclidean_grid_text() {
+        let patterns = vec![
+            (0, vec![1, 0, 0, 1, 0, 0, 1, 0]),
+            (11, vec![1, 0, 1, 0]),
+        ];
+        assert_eq!(euclidean_grid_text(&patterns), "T1  x..x..x.\nT12 x.x.\n");
+        assert_eq!(euclidean_grid_text(&[]), "");
+    }
+
+    #[test]
+    fn test_euclidean_grid_for_clipboard_lists_node_tracks() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        assert_eq!(view.euclidean_grid_for_clipboard(&mut TestNotify), None);
+
+        view.patcher_nodes[2].push(PatcherNodeUi {
+            id: 1,
+            node_type: PATCHER_NODE_EUCLIDEAN,
+            ..Default::default()
+        });
+        view.patcher_nodes[4].push(PatcherNodeUi {
+            id: 2,
+            node_type: PATCHER_NODE_LFO,
+            ..Default::default()
+        });
+        let text = view
+            .euclidean_grid_for_clipboard(&mut TestNotify)
+            .expect("grid text");
+        // Node defaults: five hits over sixteen steps.
+        let (label, steps) = text.trim_end().split_once(' ').expect("track label");
+        assert_eq!(label, "T3");
+        let steps = steps.trim_start();
+        assert_eq!(steps.len(), 16);
+        assert_eq!(steps.matches('x').count(), 5);
+        assert_eq!(text.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_unacknowledged_command_triggers_resync() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let enqueued = Instant::now();
+        assert!(!command_ack_overdue(enqueued, enqueued));
+        assert!(!command_ack_overdue(enqueued, enqueued + COMMAND_ACK_TIMEOUT / 2));
+        assert!(command_ack_overdue(enqueued, enqueued + COMMAND_ACK_TIMEOUT));
+
+        let mut view = EngineView::new_for_tests();
+        view.command_sent_at.insert((0, 0, 0), enqueued);
+        assert!(!view.check_command_acks(enqueued + Duration::from_millis(10), &mut TestNotify));
+        assert!(!view.clip_resync_pending);
+
+        assert!(view.check_command_acks(enqueued + COMMAND_ACK_TIMEOUT, &mut TestNotify));
+        assert!(view.clip_resync_pending);
+        assert!(view.harmony_resync_pending);
+        assert!(view.command_sent_at.is_empty());
+        assert!(view.toast_message.is_some());
+    }
+
+    #[test]
+    fn test_unsent_write_is_never_overdue() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let start = Instant::now();
+        view.enqueue_ui_command(UiCommandPayload {
+            command_type: UiCommandType::WriteNote as u16,
+            flags: 0,
+            track_id: 1,
+            plugin_index: 0,
+            note_pitch: 62,
+            value0: 100,
+            note_nanotick_lo: 0,
+            note_nanotick_hi: 0,
+            note_duration_lo: 0,
+            note_duration_hi: 0,
+            base_version: 0,
+        });
+        // No bridge, so the write stays queued and its timer never starts.
+        view.flush_queued_commands();
+        assert_eq!(view.queued_commands.len(), 1);
+        assert!(view.command_sent_at.is_empty());
+        assert!(!view.check_command_acks(start + COMMAND_ACK_TIMEOUT * 10, &mut TestNotify));
+        assert!(!view.clip_resync_pending);
+        assert!(view.toast_message.is_none());
+    }
+
+    #[test]
+    fn test_master_clip_latches_until_cleared() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.snapshot.ui_master_peak = [0.8, 1.0];
+        assert!(!view.latch_master_clip());
+        assert!(!view.master_clip_latched);
+
+        view.snapshot.ui_master_peak = [0.5, 1.2];
+        assert!(view.latch_master_clip());
+        assert!(view.master_clip_latched);
+
+        view.snapshot.ui_master_peak = [0.1, 0.1];
+        assert!(!view.latch_master_clip());
+        assert!(view.master_clip_latched);
+
+        view.clear_master_clip(&mut TestNotify);
+        assert!(!view.master_clip_latched);
+        assert!(!view.latch_master_clip());
+    }
+
+    #[test]
+    fn test_note_duration_label() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let row = NANOTICKS_PER_QUARTER / 4;
+        assert_eq!(note_duration_label(60, 4 * row, row), "C-4:4");
+        assert_eq!(note_duration_label(61, row / 2, row), "C#4:1");
+        assert_eq!(note_duration_label(60, 0, row), "C-4:0");
+        assert_eq!(note_duration_label(60, 150 * row, row), "C-4:**");
+
+        let mut view = EngineView::new_for_tests();
+        view.toggle_duration_display(&mut TestNotify);
+        assert!(view.show_durations);
+        assert_eq!(
+            view.note_cell_text(60, 100, 2 * view.row_nanoticks()),
+            "C-4:2"
+        );
+        view.show_velocity = true;
+        assert_eq!(view.note_cell_text(60, 100, view.row_nanoticks()), "v100");
+    }
+
+    #[test]
+    fn test_resolved_chord_display_toggle() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.write_harmony_at(0, 2, 1, &mut TestNotify);
+        let chord = ClipChord {
+            chord_id: 1,
+            nanotick: 0,
+            duration: NANOTICKS_PER_QUARTER,
+            spread: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            degree: 3,
+            quality: CHORD_QUALITY_SEVENTH,
+            inversion: 1,
+            base_octave: 4,
+            column: 0,
+        };
+        assert_eq!(view.chord_cell_text(&chord), "@3^7/1-4");
+        view.toggle_resolved_chords(&mut TestNotify);
+        assert_eq!(view.chord_cell_text(&chord), "@F#4^7/1");
+        let degree_note = ClipChord { quality: 0, ..chord.clone() };
+        assert_eq!(view.chord_cell_text(&degree_note), "F#4");
+        view.toggle_resolved_chords(&mut TestNotify);
+        assert_eq!(view.chord_cell_text(&degree_note), "3-4");
+    }
+
+    #[test]
+    fn test_loop_whole_timeline_matches_timeline_end() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        view.write_note_at(track, 0, 5 * row, 60, 100, row, &mut TestNotify);
+        view.write_harmony_at(40 * row, 2, 1, &mut TestNotify);
+
+        view.toggle_palette(&mut TestNotify);
+        view.palette_query = "loop whole".to_string();
+        view.confirm_palette(&mut TestNotify);
+
+        let end = view.timeline_end_nanotick();
+        assert_eq!(end, 41 * row);
+        assert_eq!(view.loop_range, Some((0, end)));
+    }
+
+    #[test]
+    fn test_delete_selection_harmony_keeps_notes() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        for index in [0u64, 2, 6] {
+            view.write_harmony_at(index * row, 2, 1, &mut TestNotify);
+        }
+        view.write_note_at(track, 0, 2 * row, 60, 100, row, &mut TestNotify);
+        view.selection = Some(SelectionRange {
+            start: row,
+            end: 4 * row,
+        });
+        view.selection_mask.tracks[track] = 1;
+        view.selection_mask.harmony = true;
+
+        view.delete_selection_harmony(&mut TestNotify);
+        let harmony: Vec<u64> = view.harmony_events.iter().map(|event| event.nanotick).collect();
+        assert_eq!(harmony, vec![0, 6 * row]);
+        assert_eq!(view.clip_notes[track].len(), 1);
+        assert_eq!(view.clip_notes[track][0].nanotick, 2 * row);
+    }
+
+    #[test]
+    fn test_minimap_bar_ticks_positions() {
+        use crate::ui::render_minimap::minimap_bar_ticks;
+
+        let bar = NANOTICKS_PER_QUARTER * BEATS_PER_BAR;
+        let ticks = minimap_bar_ticks(4 * bar, 400.0);
+        let ys: Vec<f32> = ticks.iter().map(|(_, y, _)| *y).collect();
+        assert_eq!(ys, vec![0.0, 100.0, 200.0, 300.0]);
+        assert!(ticks.iter().all(|(_, _, labelled)| *labelled));
+
+        // 1000 bars in 500px: every 8th bar gets a tick, every 6th tick a number.
+        let ticks = minimap_bar_ticks(1000 * bar, 500.0);
+        assert_eq!(ticks.len(), 125);
+        assert_eq!(ticks[1].0, 8);
+        assert!((ticks[1].1 - 4.0).abs() < 1e-3);
+        let labelled: Vec<u64> = ticks
+            .iter()
+            .filter(|(_, _, labelled)| *labelled)
+            .map(|(index, _, _)| *index)
+            .take(3)
+            .collect();
+        assert_eq!(labelled, vec![0, 48, 96]);
+    }
+
+    #[test]
+    fn test_bake_selection_to_notes() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        view.write_harmony_at(0, 0, 1, &mut TestNotify);
+        view.write_chord_at(track, 0, 0, row, 1, 0, 0, 4, 0, 0, 0, &mut TestNotify);
+        view.write_chord_at(
+            track,
+            0,
+            2 * row,
+            row,
+            1,
+            CHORD_QUALITY_TRIAD,
+            0,
+            4,
+            0,
+            0,
+            0,
+            &mut TestNotify,
+        );
+        view.selection = Some(SelectionRange {
+            start: 0,
+            end: 3 * row,
+        });
+        view.selection_mask.tracks[track] = 1;
+
+        view.bake_selection_to_notes(&mut TestNotify);
+        assert!(view.clip_chords[track].is_empty());
+        let notes: Vec<(u64, u8, u8)> = view.clip_notes[track]
+            .iter()
+            .map(|note| (note.nanotick, note.column, note.pitch))
+            .collect();
+        assert_eq!(
+            notes,
+            vec![(0, 0, 60), (2 * row, 0, 60), (2 * row, 1, 64), (2 * row, 2, 67)]
+        );
+        assert!(view.track_columns[track] >= 3);
+
+        view.write_harmony_at(0, 2, 1, &mut TestNotify);
+        assert_eq!(view.clip_notes[track][0].pitch, 60);
+    }
+
+    #[test]
+    fn test_column_x_offset_under_scroll() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let columns = [3, 1, 2];
+        assert_eq!(column_x_offset(&columns, 0, 0, 0), Some(0.0));
+        assert_eq!(column_x_offset(&columns, 2, 1, 0), Some(5.0 * COLUMN_WIDTH));
+        assert_eq!(column_x_offset(&columns, 0, 1, 2), None);
+        assert_eq!(column_x_offset(&columns, 0, 2, 2), Some(0.0));
+        assert_eq!(column_x_offset(&columns, 1, 0, 2), Some(COLUMN_WIDTH));
+        assert_eq!(column_x_offset(&columns, 2, 0, 4), Some(0.0));
+
+        let mut view = EngineView::new_for_tests();
+        view.track_columns = vec![1; TRACK_COUNT];
+        view.track_columns[0] = 3;
+        view.scroll_columns(2, &mut TestNotify);
+        assert_eq!(view.visible_track_columns(0), 1);
+        assert_eq!(view.column_view_x(1, 0), Some(COLUMN_WIDTH));
+        view.scroll_columns(100, &mut TestNotify);
+        assert_eq!(view.horizontal_scroll_offset, TRACK_COUNT + 1);
+        view.scroll_columns(-100, &mut TestNotify);
+        assert_eq!(view.horizontal_scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_snap_loop_to_bars() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let bar = NANOTICKS_PER_QUARTER * BEATS_PER_BAR;
+        let beat = NANOTICKS_PER_QUARTER;
+        let mut view = EngineView::new_for_tests();
+        view.set_loop_range(bar + beat, 3 * bar + 3 * beat, &mut TestNotify);
+        view.snap_loop_to_bars(&mut TestNotify);
+        assert_eq!(view.loop_range, Some((bar, 4 * bar)));
+
+        view.set_loop_range(2 * bar - beat, 2 * bar + beat, &mut TestNotify);
+        view.snap_loop_to_bars(&mut TestNotify);
+        assert_eq!(view.loop_range, Some((2 * bar, 3 * bar)));
+
+        view.loop_range = None;
+        view.selection = Some(SelectionRange {
+            start: 3 * beat,
+            end: bar + beat,
+        });
+        view.snap_loop_to_bars(&mut TestNotify);
+        assert_eq!(view.loop_range, Some((bar, 2 * bar)));
+    }
+
+    #[test]
+    fn test_swap_tracks_exchanges_contents() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.write_note_at(0, 0, 0, 60, 100, row, &mut TestNotify);
+        view.write_note_at(0, 1, row, 62, 100, row, &mut TestNotify);
+        view.write_note_at(2, 0, 4 * row, 48, 90, row, &mut TestNotify);
+        view.write_chord_at(2, 0, 8 * row, row, 1, 1, 0, 4, 0, 0, 0, &mut TestNotify);
+        view.track_columns[0] = 2;
+        view.track_columns[2] = 1;
+        view.track_names[2] = Some("Bass".to_string());
+        let version = view.current_clip_version();
+
+        view.swap_tracks(0, 2, &mut TestNotify);
+        let pitches = |view: &EngineView, track: usize| -> Vec<u8> {
+            view.clip_notes[track].iter().map(|note| note.pitch).collect()
+        };
+        assert_eq!(pitches(&view, 0), vec![48]);
+        assert_eq!(pitches(&view, 2), vec![60, 62]);
+        assert_eq!(view.clip_chords[0].len(), 1);
+        assert!(view.clip_chords[2].is_empty());
+        assert_eq!((view.track_columns[0], view.track_columns[2]), (1, 2));
+        assert_eq!(view.track_names[0].as_deref(), Some("Bass"));
+        assert_eq!(view.current_clip_version(), version + 1);
+    }
+
+    /// Marks every track as showing an engine window of `[0, end)`.
+    fn load_clip_window(view: &mut EngineView, end: u64) {
+        for state in &mut view.clip_window {
+            *state = ClipWindowState {
+                request_id: 1,
+                window_end: end,
+                complete: true,
+                ..ClipWindowState::default()
+            };
+        }
+    }
+
+    /// Delivers every track's whole clip as one page, with `notes` on
+    /// `track` as `(nanotick, column, pitch, duration)`.
+    fn load_whole_clip(view: &mut EngineView, track: usize, notes: &[(u64, u8, u8, u64)]) {
+        use daw_bridge::layout::{UiClipNote, UiClipWindowSnapshot};
+
+        for track_id in 0..TRACK_COUNT {
+            let mut snapshot = UiClipWindowSnapshot {
+                track_id: track_id as u32,
+                window_end_nanotick: u64::MAX,
+                request_id: 2,
+                flags: UI_CLIP_WINDOW_FLAG_COMPLETE,
+                ..UiClipWindowSnapshot::default()
+            };
+            if track_id == track {
+                for (index, (nanotick, column, pitch, duration)) in notes.iter().enumerate() {
+                    snapshot.notes[index] = UiClipNote {
+                        t_on: *nanotick,
+                        t_off: nanotick + duration,
+                        note_id: 0,
+                        pitch: *pitch,
+                        velocity: 100,
+                        column: *column,
+                        reserved: 0,
+                    };
+                }
+                snapshot.note_count = notes.len() as u32;
+            }
+            view.apply_clip_window_page(snapshot, true);
+        }
+    }
+
+    #[test]
+    fn test_swap_tracks_waits_for_the_whole_clip() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.write_note_at(0, 0, 0, 60, 100, row, &mut TestNotify);
+        load_clip_window(&mut view, 16 * row);
+
+        view.swap_tracks(0, 1, &mut TestNotify);
+        assert_eq!(view.clip_notes[0].len(), 1);
+        assert!(view.clip_notes[1].is_empty());
+        assert_eq!(view.desired_clip_window_range(), (0, u64::MAX));
+
+        // The whole clip includes a note far past the on-screen window.
+        load_whole_clip(&mut view, 0, &[(0, 0, 60, row), (400 * row, 0, 64, row)]);
+        view.resume_whole_clip_edits(&mut TestNotify);
+        assert!(view.pending_whole_clip_edits.is_empty());
+        assert!(view.clip_notes[0].is_empty());
+        let moved: Vec<(u64, u8)> = view.clip_notes[1]
+            .iter()
+            .map(|note| (note.nanotick, note.pitch))
+            .collect();
+        assert_eq!(moved, vec![(0, 60), (400 * row, 64)]);
+        assert_ne!(view.desired_clip_window_range(), (0, u64::MAX));
+    }
+
+    #[test]
+    fn test_swap_tracks_resends_arm_and_time_offset() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.toggle_track_armed(1, &mut TestNotify);
+        view.set_track_time_offset(1, -4000, &mut TestNotify);
+        view.queued_commands.clear();
+
+        view.swap_tracks(1, 5, &mut TestNotify);
+        assert!(!view.track_armed[1]);
+        assert!(view.track_armed[5]);
+        assert_eq!(view.track_time_offset[1], 0);
+        assert_eq!(view.track_time_offset[5], -4000);
+
+        let sent: Vec<(u16, u32, u32, i64)> = view
+            .queued_commands
+            .iter()
+            .filter_map(|entry| match entry {
+                QueuedCommand::Ui(payload) => Some((
+                    payload.command_type,
+                    payload.track_id,
+                    payload.value0,
+                    (((payload.note_nanotick_hi as u64) << 32)
+                        | payload.note_nanotick_lo as u64) as i64,
+                )),
+                _ => None,
+            })
+            .collect();
+        let armed = UiCommandType::SetTrackArmed as u16;
+        let offset = UiCommandType::SetTrackTimeOffset as u16;
+        assert!(sent.contains(&(armed, 1, 0, 0)));
+        assert!(sent.contains(&(armed, 5, 1, 0)));
+        assert!(sent.contains(&(offset, 1, 0, 0)));
+        assert!(sent.contains(&(offset, 5, 0, -4000)));
+    }
+
+    #[test]
+    fn test_read_only_blocks_edits() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.toggle_read_only(&mut TestNotify);
+        view.write_note(60, &mut TestNotify);
+        view.write_note_at(0, 0, row, 62, 100, row, &mut TestNotify);
+        view.write_harmony_at(0, 2, 1, &mut TestNotify);
+        assert!(view.queued_commands.is_empty());
+        assert!(view.pending_notes.is_empty());
+        assert!(view.clip_notes.iter().all(|notes| notes.is_empty()));
+        assert!(view.harmony_events.is_empty());
+        assert_eq!(view.toast_message.as_deref(), Some("Read-only mode"));
+
+        view.move_cursor_row(2, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 2 * row);
+
+        view.toggle_read_only(&mut TestNotify);
+        view.write_note_at(0, 0, row, 62, 100, row, &mut TestNotify);
+        assert_eq!(view.clip_notes[0].len(), 1);
+    }
+
+    #[test]
+    fn test_read_only_blocks_bake_once() {
+        struct TestNotify(usize);
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {
+                self.0 += 1;
+            }
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        view.write_harmony_at(0, 0, 1, &mut TestNotify(0));
+        view.write_chord_at(
+            track,
+            0,
+            0,
+            row,
+            1,
+            CHORD_QUALITY_TRIAD,
+            0,
+            4,
+            0,
+            0,
+            0,
+            &mut TestNotify(0),
+        );
+        view.selection = Some(SelectionRange { start: 0, end: row });
+        view.selection_mask.tracks[track] = 1;
+        let columns = view.track_columns[track];
+        view.toggle_read_only(&mut TestNotify(0));
+
+        let mut notify = TestNotify(0);
+        view.bake_selection_to_notes(&mut notify);
+        assert_eq!(notify.0, 1);
+        assert_eq!(view.toast_message.as_deref(), Some("Read-only mode"));
+        assert_eq!(view.track_columns[track], columns);
+        assert_eq!(view.clip_chords[track].len(), 1);
+        assert!(view.clip_notes[track].is_empty());
+    }
+
+    #[test]
+    fn test_queued_command_summary_counts_by_type() {
+        let ui = |command_type: UiCommandType| {
+            QueuedCommand::Ui(UiCommandPayload {
+                command_type: command_type as u16,
+                flags: 0,
+                track_id: 0,
+                plugin_index: 0,
+                note_pitch: 0,
+                value0: 0,
+                note_nanotick_lo: 0,
+                note_nanotick_hi: 0,
+                note_duration_lo: 0,
+                note_duration_hi: 0,
+                base_version: 0,
+            })
+        };
+        let mut view = EngineView::new_for_tests();
+        assert_eq!(queue_summary_text(&view.queued_command_summary()), "queue empty");
+
+        view.queued_commands.push_back(ui(UiCommandType::WriteNote));
+        view.queued_commands.push_back(ui(UiCommandType::DeleteNote));
+        view.queued_commands.push_back(QueuedCommand::Chord(UiChordCommandPayload {
+            command_type: UiCommandType::WriteChord as u16,
+            ..Default::default()
+        }));
+        view.queued_commands.push_back(ui(UiCommandType::WriteNote));
+        view.queued_commands.push_back(ui(UiCommandType::SetLoopRange));
+
+        let summary = view.queued_command_summary();
+        assert_eq!(
+            summary,
+            vec![
+                ("WriteNote".to_string(), 2),
+                ("DeleteNote".to_string(), 1),
+                ("WriteChord".to_string(), 1),
+                ("SetLoopRange".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            queue_summary_text(&summary),
+            "queue WriteNote 2, DeleteNote 1, WriteChord 1, SetLoopRange 1"
+        );
+    }
+
+    #[test]
+    fn test_diatonic_entry_writes_scale_tones() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.write_harmony_at(0, 0, 1, &mut TestNotify);
+        view.toggle_diatonic_entry(&mut TestNotify);
+        assert!(view.diatonic_entry);
+        assert_eq!(pitch_to_note(view.diatonic_pitch(1)), "C-4");
+        assert_eq!(pitch_to_note(view.diatonic_pitch(3)), "E-4");
+        assert_eq!(pitch_to_note(view.diatonic_pitch(7)), "B-4");
+
+        view.write_harmony_at(0, 9, 2, &mut TestNotify);
+        assert_eq!(pitch_to_note(view.diatonic_pitch(3)), "C-5");
+        view.write_diatonic_note(3, &mut TestNotify);
+        assert_eq!(view.keyjazz_pitch, 72);
+    }
+
+    #[test]
+    fn test_chromatic_digits_write_keyjazz_pitches() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let two = gpui::Keystroke::parse("2").unwrap();
+        view.handle_keystroke(&two, &mut TestNotify);
+        assert_ne!(view.keyjazz_pitch, 61);
+
+        view.toggle_chromatic_digits(&mut TestNotify);
+        view.handle_keystroke(&two, &mut TestNotify);
+        assert_eq!(view.keyjazz_pitch, 61);
+    }
+
+    #[test]
+    fn test_rest_cells_between_column_events() {
+        let spans = vec![(0, 240), (960, 960), (1920, 2400)];
+        // Before the first event and after the last one are plain empty cells.
+        assert!(!is_rest_cell(&spans, 2880));
+        assert!(!is_rest_cell(&[(480, 720)], 0));
+        // A cell still covered by a sounding note is not a rest.
+        assert!(!is_rest_cell(&spans, 120));
+        assert!(!is_rest_cell(&spans, 2000));
+        // Gaps after a note ends or after a note-off are rests.
+        assert!(is_rest_cell(&spans, 480));
+        assert!(is_rest_cell(&spans, 1440));
+        // An event's own row is never classified as a rest.
+        assert!(!is_rest_cell(&spans, 960));
+
+        let mut view = EngineView::new_for_tests();
+        view.clip_notes[0] = vec![
+            ClipNote { nanotick: 0, duration: 240, pitch: 60, velocity: 100, column: 0 },
+            ClipNote { nanotick: 960, duration: 240, pitch: 62, velocity: 100, column: 0 },
+            ClipNote { nanotick: 480, duration: 240, pitch: 64, velocity: 100, column: 1 },
+        ];
+        let spans = view.column_event_spans(0, 0);
+        assert_eq!(spans, vec![(0, 240), (960, 1200)]);
+        assert!(is_rest_cell(&spans, 480));
+        assert!(!is_rest_cell(&view.column_event_spans(0, 1), 240));
+    }
+
+    #[test]
+    fn test_transpose_project_waits_for_the_whole_clip() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.harmony_events = vec![HarmonyEntry { nanotick: 0, root: 2, scale_id: 1 }];
+        load_clip_window(&mut view, 16 * row);
+
+        view.transpose_project(2, &mut TestNotify);
+        assert_eq!(view.harmony_events[0].root, 2);
+
+        load_whole_clip(&mut view, 3, &[(0, 0, 60, row), (400 * row, 0, 62, row)]);
+        view.resume_whole_clip_edits(&mut TestNotify);
+        assert_eq!(view.harmony_events[0].root, 4);
+        let pitches: Vec<u8> = view.clip_notes[3].iter().map(|note| note.pitch).collect();
+        assert_eq!(pitches, vec![62, 64]);
+    }
+
+    #[test]
+    fn test_transpose_project_moves_notes_and_roots() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let mut cx = TestNotify;
+        view.clip_notes[0] = vec![
+            ClipNote { nanotick: 0, duration: 240, pitch: 60, velocity: 100, column: 0 },
+            ClipNote { nanotick: 480, duration: 240, pitch: 125, velocity: 90, column: 1 },
+        ];
+        view.clip_notes[2] = vec![
+            ClipNote { nanotick: 960, duration: 120, pitch: 40, velocity: 80, column: 0 },
+        ];
+        view.harmony_events = vec![
+            HarmonyEntry { nanotick: 0, root: 2, scale_id: 1 },
+            HarmonyEntry { nanotick: 960, root: 10, scale_id: 2 },
+        ];
+
+        view.transpose_project(3, &mut cx);
+
+        let pitches = |view: &EngineView, track: usize| {
+            let mut notes: Vec<_> = view.clip_notes[track]
+                .iter()
+                .map(|note| (note.nanotick, note.pitch, note.velocity))
+                .collect();
+            notes.sort();
+            notes
+        };
+        assert_eq!(pitches(&view, 0), vec![(0, 63, 100), (480, 127, 90)]);
+        assert_eq!(pitches(&view, 2), vec![(960, 43, 80)]);
+        let roots: Vec<_> = view
+            .harmony_events
+            .iter()
+            .map(|event| (event.nanotick, event.root, event.scale_id))
+            .collect();
+        assert_eq!(roots, vec![(0, 5, 1), (960, 1, 2)]);
+
+        view.transpose_project(-5, &mut cx);
+        assert_eq!(pitches(&view, 0), vec![(0, 58, 100), (480, 122, 90)]);
+        assert_eq!(view.harmony_events[0].root, 0);
+        assert_eq!(view.harmony_events[1].root, 8);
+    }
+
+    #[test]
+    fn test_velocity_focus_up_raises_velocity_in_place() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let mut cx = TestNotify;
+        let row = view.row_nanoticks();
+        view.cursor_nanotick = row * 2;
+        let track = view.focused_track_index;
+        view.clip_notes[track] = vec![ClipNote {
+            nanotick: row * 2,
+            duration: row,
+            pitch: 60,
+            velocity: 100,
+            column: 0,
+        }];
+
+        view.toggle_velocity_focus(&mut cx);
+        view.action_palette_up(&mut cx);
+        assert_eq!(view.cursor_nanotick, row * 2);
+        assert_eq!(view.clip_notes[track][0].velocity, 100 + VELOCITY_FOCUS_STEP as u8);
+        assert_eq!(view.clip_notes[track][0].pitch, 60);
+
+        view.action_palette_down(&mut cx);
+        view.action_palette_down(&mut cx);
+        assert_eq!(view.cursor_nanotick, row * 2);
+        assert_eq!(view.clip_notes[track][0].velocity, 100 - VELOCITY_FOCUS_STEP as u8);
+
+        view.toggle_velocity_focus(&mut cx);
+        view.action_palette_down(&mut cx);
+        assert_eq!(view.cursor_nanotick, row * 3);
+    }
+
+    #[test]
+    fn test_toggle_track_armed_enqueues_command() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.toggle_track_armed(2, &mut TestNotify);
+        assert!(view.track_armed[2]);
+        assert!(!view.track_armed[0]);
+        let Some(QueuedCommand::Ui(payload)) = view.queued_commands.back() else {
+            panic!("expected a queued SetTrackArmed command");
+        };
+        assert_eq!(payload.command_type, UiCommandType::SetTrackArmed as u16);
+        assert_eq!(payload.track_id, 2);
+        assert_eq!(payload.value0, 1);
+
+        view.toggle_track_armed(2, &mut TestNotify);
+        assert!(!view.track_armed[2]);
+        let Some(QueuedCommand::Ui(payload)) = view.queued_commands.back() else {
+            panic!("expected a queued SetTrackArmed command");
+        };
+        assert_eq!(payload.value0, 0);
+        assert_eq!(view.queued_commands.len(), 2);
+    }
+
+    #[test]
+    fn test_track_time_offset_enqueues_signed_command() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let step = NANOTICKS_PER_QUARTER as i64 / 96;
+        view.focused_track_index = 3;
+        view.nudge_track_time_offset(-2, &mut TestNotify);
+        assert_eq!(view.track_time_offset[3], -2 * step);
+        assert!(view.clip_notes[3].is_empty());
+        let Some(QueuedCommand::Ui(payload)) = view.queued_commands.back() else {
+            panic!("expected a queued SetTrackTimeOffset command");
+        };
+        assert_eq!(payload.command_type, UiCommandType::SetTrackTimeOffset as u16);
+        assert_eq!(payload.track_id, 3);
+        let sent = ((payload.note_nanotick_hi as u64) << 32) | payload.note_nanotick_lo as u64;
+        assert_eq!(sent as i64, -2 * step);
+        assert!(view.track_time_offset_label(3).unwrap().starts_with('-'));
+
+        view.set_track_time_offset(3, 10 * NANOTICKS_PER_QUARTER as i64, &mut TestNotify);
+        assert_eq!(view.track_time_offset[3], NANOTICKS_PER_QUARTER as i64);
+        let queued = view.queued_commands.len();
+        view.set_track_time_offset(3, NANOTICKS_PER_QUARTER as i64, &mut TestNotify);
+        assert_eq!(view.queued_commands.len(), queued);
+        assert_eq!(view.track_time_offset_label(0), None);
+    }
+
+    #[test]
+    fn test_collapse_harmony_changes_removes_repeats() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        view.harmony_events = vec![
+            HarmonyEntry { nanotick: 0, root: 2, scale_id: 1 },
+            HarmonyEntry { nanotick: bar, root: 2, scale_id: 1 },
+            HarmonyEntry { nanotick: bar * 2, root: 7, scale_id: 1 },
+        ];
+
+        assert_eq!(view.collapse_harmony_changes(&mut TestNotify), 1);
+        let ticks: Vec<u64> = view.harmony_events.iter().map(|event| event.nanotick).collect();
+        assert_eq!(ticks, vec![0, bar * 2]);
+        assert_eq!(view.collapse_harmony_changes(&mut TestNotify), 0);
+    }
+
+    #[test]
+    fn test_jump_to_loudest_visible_note() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        view.clip_notes[track] = vec![
+            ClipNote { nanotick: row, duration: row, pitch: 60, velocity: 70, column: 0 },
+            ClipNote { nanotick: row * 5, duration: row, pitch: 64, velocity: 118, column: 1 },
+            ClipNote { nanotick: row * 9, duration: row, pitch: 67, velocity: 90, column: 0 },
+            ClipNote {
+                nanotick: row * (VISIBLE_ROWS as u64 + 4),
+                duration: row,
+                pitch: 72,
+                velocity: 127,
+                column: 0,
+            },
+        ];
+        view.clip_notes[track + 1] = vec![ClipNote {
+            nanotick: row * 3,
+            duration: row,
+            pitch: 48,
+            velocity: 125,
+            column: 0,
+        }];
+
+        view.jump_to_loudest_visible_note(&mut TestNotify);
+        assert_eq!(view.cursor_nanotick, row * 5);
+        assert_eq!(view.cursor_col, 1);
+
+        view.clip_notes[track].clear();
+        view.cursor_nanotick = 0;
+        view.jump_to_loudest_visible_note(&mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 0);
+    }
+
+    #[test]
+    fn test_mirror_selection_to_track() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.clip_notes[0] = vec![
+            ClipNote { nanotick: 0, duration: row, pitch: 60, velocity: 100, column: 0 },
+            ClipNote { nanotick: row * 2, duration: row, pitch: 67, velocity: 90, column: 1 },
+            ClipNote { nanotick: row * 20, duration: row, pitch: 72, velocity: 80, column: 0 },
+        ];
+        view.selection = Some(SelectionRange {
+            start: 0,
+            end: row * 4,
+        });
+        view.selection_mask = SelectionMask::empty(TRACK_COUNT);
+        view.selection_mask.tracks[0] = 0b11;
+
+        view.mirror_text = "4".to_string();
+        view.mirror_open = true;
+        view.confirm_mirror(&mut TestNotify);
+        assert!(!view.mirror_open);
+
+        let mut mirrored: Vec<_> = view.clip_notes[3]
+            .iter()
+            .map(|note| (note.nanotick, note.column, note.pitch, note.velocity))
+            .collect();
+        mirrored.sort();
+        assert_eq!(mirrored, vec![(0, 0, 60, 100), (row * 2, 1, 67, 90)]);
+        assert!(view.track_columns[3] >= 2);
+        assert_eq!(view.clip_notes[0].len(), 3);
+    }
+
+    #[test]
+    fn test_advance_up_moves_cursor_up_after_entry() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.cursor_nanotick = row * 8;
+        view.write_degree_note(1, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, row * 9);
+
+        view.toggle_advance_direction(&mut TestNotify);
+        view.write_degree_note(3, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, row * 8);
+        view.write_degree_note(5, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, row * 7);
+    }
+
+    #[test]
+    fn test_apply_groove_shifts_notes_by_template() {
+        use crate::groove::GROOVE_STEP_NANOTICKS;
+
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let step = GROOVE_STEP_NANOTICKS;
+        let template = GrooveTemplate {
+            name: "Test",
+            offsets: &[0, 25, -10],
+        };
+        let mut view = EngineView::new_for_tests();
+        view.clip_notes[1] = (0..4)
+            .map(|index| ClipNote {
+                nanotick: index * step,
+                duration: step / 2,
+                pitch: 60 + index as u8,
+                velocity: 100,
+                column: 0,
+            })
+            .collect();
+
+        assert_eq!(view.apply_groove(1, &template, &mut TestNotify), 2);
+        let mut notes: Vec<(u8, u64)> = view.clip_notes[1]
+            .iter()
+            .map(|note| (note.pitch, note.nanotick))
+            .collect();
+        notes.sort();
+        assert_eq!(
+            notes,
+            vec![
+                (60, 0),
+                (61, step + step / 4),
+                (62, 2 * step - step / 10),
+                (63, 3 * step),
+            ]
+        );
+        assert!(view.clip_notes[0].is_empty());
+    }
+
+    #[test]
+    fn test_preview_harmony_payload_carries_root_and_scale() {
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.focused_track_index = 3;
+        view.harmony_events = vec![
+            HarmonyEntry { nanotick: 0, root: 2, scale_id: 1 },
+            HarmonyEntry { nanotick: row * 4, root: 9, scale_id: 3 },
+        ];
+
+        view.cursor_nanotick = row * 2;
+        let payload = view.preview_harmony_payload();
+        assert_eq!(payload.command_type, UiCommandType::PreviewHarmony as u16);
+        assert_eq!(payload.track_id, 3);
+        assert_eq!(payload.note_pitch, 2);
+        assert_eq!(payload.value0, 1);
+
+        view.cursor_nanotick = row * 5;
+        let payload = view.preview_harmony_payload();
+        assert_eq!(payload.note_pitch, 9);
+        assert_eq!(payload.value0, 3);
+    }
+
+    #[test]
+    fn test_set_selection_chord_duration() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let chord = |nanotick: u64, duration: u64, degree: u8| ClipChord {
+            chord_id: 0,
+            nanotick,
+            duration,
+            spread: 0,
+            humanize_timing: 3,
+            humanize_velocity: 0,
+            degree,
+            quality: 2,
+            inversion: 1,
+            base_octave: 4,
+            column: 0,
+        };
+        view.clip_chords[0] = vec![
+            chord(0, row, 1),
+            chord(row * 2, row * 7, 4),
+            chord(row * 12, row, 5),
+        ];
+        view.selection = Some(SelectionRange {
+            start: 0,
+            end: row * 4,
+        });
+        view.selection_mask.tracks[0] = 1;
+
+        view.set_selection_chord_duration(3, &mut TestNotify);
+        let chords: Vec<_> = view.clip_chords[0]
+            .iter()
+            .map(|chord| (chord.nanotick, chord.duration, chord.degree, chord.inversion))
+            .collect();
+        assert_eq!(
+            chords,
+            vec![(0, row * 3, 1, 1), (row * 2, row * 3, 4, 1), (row * 12, row, 5, 1)]
+        );
+        assert!(view.clip_chords[0].iter().all(|chord| chord.humanize_timing == 3));
+    }
+
+    #[test]
+    fn test_inherited_harmony_label_carries_forward() {
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.harmony_events = vec![
+            HarmonyEntry { nanotick: 0, root: 2, scale_id: 1 },
+            HarmonyEntry { nanotick: row * 8, root: 7, scale_id: 2 },
+        ];
+
+        let expected = |root: u32, scale_id: u32| {
+            Some(format!("{}:{}", harmony_root_name(root), harmony_scale_name(scale_id)))
+        };
+        assert_eq!(view.inherited_harmony_label(row * 3), expected(2, 1));
+        assert_eq!(view.inherited_harmony_label(row * 12), expected(7, 2));
+        assert_eq!(view.inherited_harmony_label(row * 8), None);
+        assert_eq!(view.inherited_harmony_label(0), None);
+    }
+
+    #[test]
+    fn test_randomize_inversions_is_stable_and_in_range() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let build = || {
+            let mut view = EngineView::new_for_tests();
+            let row = view.row_nanoticks();
+            view.clip_chords[0] = (0..16)
+                .map(|index| ClipChord {
+                    chord_id: 0,
+                    nanotick: index * row,
+                    duration: row,
+                    spread: 0,
+                    humanize_timing: 0,
+                    humanize_velocity: 0,
+                    degree: 1 + (index % 7) as u8,
+                    quality: CHORD_QUALITY_TRIAD,
+                    inversion: 0,
+                    base_octave: 4,
+                    column: 0,
+                })
+                .collect();
+            view.selection = Some(SelectionRange {
+                start: 0,
+                end: row * 16,
+            });
+            view.selection_mask.tracks[0] = 1;
+            view.randomize_selection_inversions(&mut TestNotify);
+            view.clip_chords[0]
+                .iter()
+                .map(|chord| (chord.nanotick, chord.inversion))
+                .collect::<Vec<_>>()
+        };
+
+        let first = build();
+        assert_eq!(first, build());
+        assert_eq!(first.len(), 16);
+        assert!(first.iter().all(|(_, inversion)| *inversion <= 3));
+        let distinct: HashSet<u8> = first.iter().map(|(_, inversion)| *inversion).collect();
+        assert!(distinct.len() > 1);
+        for (nanotick, inversion) in first {
+            assert_eq!(inversion, seeded_inversion(nanotick));
+        }
+    }
+
+    #[test]
+    fn test_minimap_jump_snaps_to_nearest_bar() {
+        use crate::ui::render_minimap::minimap_jump_target;
+
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        let clicked = 3 * bar + bar / 3;
+        assert_eq!(minimap_jump_target(clicked, false), clicked);
+        assert_eq!(minimap_jump_target(clicked, true), 3 * bar);
+        assert_eq!(minimap_jump_target(3 * bar + bar * 3 / 4, true), 4 * bar);
+        assert_eq!(minimap_jump_target(bar / 2 - 1, true), 0);
+        assert_eq!(minimap_jump_target(5 * bar, true), 5 * bar);
+    }
+
+    #[test]
+    fn test_write_bar_harmony_cycles_roots() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        view.harmony_scale_id = 2;
+        view.selection = Some(SelectionRange {
+            start: bar + bar / 2,
+            end: 4 * bar + bar / 4,
+        });
+        let roots = parse_root_list("A, F,c").unwrap();
+        assert_eq!(roots, vec![9, 5, 0]);
+        let (start, bars) = view.bar_harmony_span(roots.len());
+        assert_eq!((start, bars), (bar, 4));
+
+        assert_eq!(view.write_bar_harmony(start, bars, &roots, &mut TestNotify), 4);
+        let events: Vec<_> = view
+            .harmony_events
+            .iter()
+            .map(|event| (event.nanotick, event.root, event.scale_id))
+            .collect();
+        assert_eq!(
+            events,
+            vec![(bar, 9, 2), (2 * bar, 5, 2), (3 * bar, 0, 2), (4 * bar, 9, 2)]
+        );
+        assert!(parse_root_list("C,H").is_none());
+        assert!(parse_root_list(",").is_none());
+    }
+
+    #[test]
+    fn test_track_state_dot_color_priority() {
+        use crate::ui::render_minimap::track_state_dot_color;
+
+        let idle = track_state_dot_color(false, false, false);
+        let armed = track_state_dot_color(true, false, false);
+        let soloed = track_state_dot_color(false, true, false);
+        let muted = track_state_dot_color(false, false, true);
+        let distinct: HashSet<u32> = [idle, armed, soloed, muted].into_iter().collect();
+        assert_eq!(distinct.len(), 4);
+        assert_eq!(track_state_dot_color(true, true, true), armed);
+        assert_eq!(track_state_dot_color(false, true, true), soloed);
+    }
+
+    #[test]
+    fn test_trim_to_loop_keeps_only_loop_content() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let note = |nanotick: u64| ClipNote {
+            nanotick,
+            duration: row,
+            pitch: 60,
+            velocity: 100,
+            column: 0,
+        };
+        view.clip_notes[0] =
+            vec![note(0), note(row * 4), note(row * 7), note(row * 8), note(row * 12)];
+        view.clip_notes[1] = vec![note(0), note(row * 12)];
+        view.clip_chords[0] = vec![ClipChord {
+            chord_id: 5,
+            nanotick: row * 10,
+            duration: row,
+            spread: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            degree: 1,
+            quality: CHORD_QUALITY_TRIAD,
+            inversion: 0,
+            base_octave: 4,
+            column: 1,
+        }];
+        view.focused_track_index = 0;
+
+        view.trim_to_loop(Some(0), &mut TestNotify);
+        assert_eq!(view.clip_notes[0].len(), 5);
+
+        view.loop_range = Some((row * 4, row * 8));
+        view.trim_to_loop(Some(0), &mut TestNotify);
+        let kept: Vec<u64> = view.clip_notes[0].iter().map(|note| note.nanotick).collect();
+        assert_eq!(kept, vec![row * 4, row * 7]);
+        assert!(view.clip_chords[0].is_empty());
+        assert_eq!(view.clip_notes[1].len(), 2);
+
+        view.trim_to_loop(None, &mut TestNotify);
+        assert!(view.clip_notes[1].is_empty());
+        assert_eq!(view.clip_notes[0].len(), 2);
+    }
+
+    #[test]
+    fn test_enharmonic_override_changes_only_that_note() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let mut cx = TestNotify;
+        let row = view.row_nanoticks();
+        view.clip_notes[0].push(ClipNote {
+            nanotick: 0,
+            duration: row,
+            pitch: 61,
+            velocity: 100,
+            column: 0,
+        });
+        view.clip_notes[0].push(ClipNote {
+            nanotick: row,
+            duration: row,
+            pitch: 61,
+            velocity: 100,
+            column: 0,
+        });
+        view.toggle_focused_enharmonic(&mut cx);
+        assert_eq!(view.note_cell_text_at(0, 0, 0, 61, 100, row), "Db4");
+        assert_eq!(view.note_cell_text_at(0, 0, row, 61, 100, row), "C#4");
+        assert_eq!(view.clip_notes[0][0].pitch, 61);
+
+        view.toggle_focused_enharmonic(&mut cx);
+        assert_eq!(view.note_cell_text_at(0, 0, 0, 61, 100, row), "C#4");
+    }
+
+    #[test]
+    fn test_out_of_scale_notes_are_flagged() {
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.harmony_events = vec![
+            HarmonyEntry { nanotick: 0, root: 0, scale_id: 1 },
+            HarmonyEntry { nanotick: row * 4, root: 2, scale_id: 2 },
+        ];
+        let note = |nanotick: u64, pitch: u8| ClipNote {
+            nanotick,
+            duration: row,
+            pitch,
+            velocity: 100,
+            column: 0,
+        };
+        // C major for four rows, then D minor: E, G and F fit, while F#
+        // and C# fall outside whichever scale is in force.
+        view.clip_notes[0] = vec![
+            note(0, 64),
+            note(row, 66),
+            note(row * 2, 67),
+            note(row * 3, 61),
+            note(row * 4, 65),
+            note(row * 5, 66),
+        ];
+        view.selection = Some(SelectionRange {
+            start: 0,
+            end: row * 5,
+        });
+        view.selection_mask.tracks[0] = 1;
+
+        let flagged: Vec<_> = view
+            .out_of_scale_notes_in_selection()
+            .iter()
+            .map(|(track, note)| (*track, note.nanotick, note.pitch))
+            .collect();
+        assert_eq!(flagged, vec![(0, row, 66), (0, row * 3, 61), (0, row * 5, 66)]);
+    }
+
+    #[test]
+    fn test_harmony_focus_toggle_restores_note_cell() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let mut cx = TestNotify;
+        view.track_columns[3] = 4;
+        view.focused_track_index = 3;
+        view.cursor_col = 2;
+
+        view.toggle_harmony_focus(&mut cx);
+        assert!(view.harmony_focus);
+        view.toggle_harmony_focus(&mut cx);
+        assert!(!view.harmony_focus);
+        assert_eq!((view.focused_track_index, view.cursor_col), (3, 2));
+
+        view.toggle_harmony_focus(&mut cx);
+        view.move_cursor_or_focus(1, &mut cx);
+        assert!(!view.harmony_focus);
+        assert_eq!((view.focused_track_index, view.cursor_col), (3, 2));
+    }
+
+    #[test]
+    fn test_euclidean_pattern_writes_hits_at_expected_rows() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        let rows_per_step = bar / 8 / row;
+        view.cursor_nanotick = bar + row;
+
+        assert_eq!(parse_euclidean_spec("8,3,0,C-4"), Some((8, 3, 0, Some(60))));
+        assert_eq!(view.write_euclidean_bar(8, 3, 0, 60, &mut TestNotify), 3);
+        let rows: Vec<u64> = view.clip_notes[0]
+            .iter()
+            .map(|note| (note.nanotick - bar) / row)
+            .collect();
+        // Bjorklund's 3-in-8 as the patcher node lays it out: .x..x..x
+        assert_eq!(rows, vec![rows_per_step, rows_per_step * 4, rows_per_step * 7]);
+        assert!(view.clip_notes[0].iter().all(|note| note.pitch == 60));
+    }
+
+    #[test]
+    fn test_move_track_column_empties_source() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.track_columns[1] = 3;
+        view.clip_notes[1] = vec![
+            ClipNote { nanotick: 0, duration: row, pitch: 60, velocity: 100, column: 0 },
+            ClipNote { nanotick: row * 2, duration: 0, pitch: 60, velocity: 0, column: 0 },
+            ClipNote { nanotick: row, duration: row, pitch: 67, velocity: 90, column: 1 },
+        ];
+        view.clip_chords[1] = vec![ClipChord {
+            chord_id: 9,
+            nanotick: row * 4,
+            duration: row,
+            spread: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            degree: 1,
+            quality: CHORD_QUALITY_TRIAD,
+            inversion: 0,
+            base_octave: 4,
+            column: 0,
+        }];
+
+        assert_eq!(view.move_track_column(1, 0, 7, &mut TestNotify), 3);
+        assert!(view.clip_notes[1].iter().all(|note| note.column != 0));
+        assert!(view.clip_chords[1].iter().all(|chord| chord.column != 0));
+        let mut moved: Vec<_> = view.clip_notes[1]
+            .iter()
+            .filter(|note| note.column == 2)
+            .map(|note| (note.nanotick, note.pitch, note.velocity))
+            .collect();
+        moved.sort();
+        assert_eq!(moved, vec![(0, 60, 100), (row * 2, 60, 0)]);
+        assert_eq!(view.clip_chords[1].len(), 1);
+        assert_eq!(view.clip_chords[1][0].column, 2);
+        assert!(view.clip_notes[1].iter().any(|note| note.column == 1 && note.pitch == 67));
+    }
+
+    #[test]
+    fn test_chord_chart_names_implied_chords() {
+        assert_eq!(implied_chord_name(0, 1), "Cmaj7");
+        assert_eq!(implied_chord_name(9, 2), "Am7");
+        assert_eq!(implied_chord_name(7, 4), "G7");
+        assert_eq!(implied_chord_name(2, 3), "Dm7");
+        assert_eq!(implied_chord_name(5, 99), "F");
+
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        let events = vec![
+            HarmonyEntry { nanotick: bar, root: 7, scale_id: 4 },
+            HarmonyEntry { nanotick: 0, root: 0, scale_id: 1 },
+            HarmonyEntry { nanotick: bar + 2 * NANOTICKS_PER_QUARTER, root: 9, scale_id: 2 },
+        ];
+        assert_eq!(chord_chart_text(&events), "1: Cmaj7\n2: G7\n2.3: Am7\n");
+    }
+
+    #[test]
+    fn test_duplicate_bar_clones_bar_and_pushes_tail() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        let note = |nanotick: u64, pitch: u8| ClipNote {
+            nanotick,
+            duration: row,
+            pitch,
+            velocity: 100,
+            column: 0,
+        };
+        view.clip_notes[0] = vec![note(0, 40), note(bar + row, 60), note(bar * 2, 72)];
+        view.clip_notes[2] = vec![note(bar + row * 2, 50)];
+        view.harmony_events = vec![HarmonyEntry { nanotick: bar, root: 7, scale_id: 1 }];
+        view.cursor_nanotick = bar + row * 3;
+
+        view.duplicate_bar(&mut TestNotify);
+
+        let positions = |notes: &[ClipNote]| {
+            let mut positions: Vec<_> =
+                notes.iter().map(|note| (note.nanotick, note.pitch)).collect();
+            positions.sort();
+            positions
+        };
+        assert_eq!(
+            positions(&view.clip_notes[0]),
+            vec![(0, 40), (bar + row, 60), (bar * 2 + row, 60), (bar * 3, 72)]
+        );
+        assert_eq!(
+            positions(&view.clip_notes[2]),
+            vec![(bar + row * 2, 50), (bar * 2 + row * 2, 50)]
+        );
+        let harmony: Vec<_> = view.harmony_events.iter().map(|event| event.nanotick).collect();
+        assert_eq!(harmony, vec![bar, bar * 2]);
+    }
+
+    #[test]
+    fn test_fit_track_columns_to_widest_used() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.track_columns[1] = 6;
+        for column in [0u8, 2] {
+            view.clip_notes[1].push(ClipNote {
+                nanotick: row * column as u64,
+                duration: row,
+                pitch: 60,
+                velocity: 100,
+                column,
+            });
+        }
+
+        assert_eq!(view.fit_track_columns(1, &mut TestNotify), 3);
+        assert_eq!(view.track_columns[1], 3);
+        assert_eq!(view.fit_track_columns(4, &mut TestNotify), 1);
+        assert_eq!(view.track_columns[4], 1);
+    }
+
+    #[test]
+    fn test_swap_stash_twice_restores_content() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let mut cx = TestNotify;
+        let row = view.row_nanoticks();
+        let note = |nanotick: u64, pitch: u8| ClipNote {
+            nanotick,
+            duration: row,
+            pitch,
+            velocity: 100,
+            column: 0,
+        };
+        let contents = |view: &EngineView| {
+            let mut notes: Vec<_> = view.clip_notes[0]
+                .iter()
+                .map(|note| (note.nanotick, note.pitch))
+                .collect();
+            notes.sort();
+            notes
+        };
+        view.clip_notes[0] = vec![note(0, 60), note(row * 2, 64), note(row * 8, 48)];
+        view.selection = Some(SelectionRange {
+            start: 0,
+            end: row * 3,
+        });
+        view.selection_mask.tracks[0] = 1;
+        view.stash_region("a", &mut cx);
+
+        view.clip_notes[0] = vec![note(row, 67), note(row * 8, 48)];
+        let original = contents(&view);
+
+        view.swap_stash("a", &mut cx);
+        assert_eq!(contents(&view), vec![(0, 60), (row * 2, 64), (row * 8, 48)]);
+        view.swap_stash("a", &mut cx);
+        assert_eq!(contents(&view), original);
+    }
+
+    #[test]
+    fn test_named_stashes_are_kept_apart() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let mut cx = TestNotify;
+        let row = view.row_nanoticks();
+        let note = |nanotick: u64, pitch: u8| ClipNote {
+            nanotick,
+            duration: row,
+            pitch,
+            velocity: 100,
+            column: 0,
+        };
+        let pitches = |view: &EngineView| {
+            let mut pitches: Vec<_> = view.clip_notes[0].iter().map(|note| note.pitch).collect();
+            pitches.sort();
+            pitches
+        };
+        view.selection = Some(SelectionRange {
+            start: 0,
+            end: row * 3,
+        });
+        view.selection_mask.tracks[0] = 1;
+        view.clip_notes[0] = vec![note(0, 60)];
+        view.open_stash_prompt(StashPrompt::Stash, &mut cx);
+        view.stash_name_text.push_str("verse");
+        view.confirm_stash_prompt(&mut cx);
+        assert!(view.stash_prompt.is_none());
+        view.clip_notes[0] = vec![note(row, 62)];
+        view.stash_region("chorus", &mut cx);
+        assert_eq!(view.region_stashes.len(), 2);
+
+        view.clip_notes[0] = vec![note(row * 2, 64)];
+        view.swap_stash("verse", &mut cx);
+        assert_eq!(pitches(&view), vec![60]);
+        view.swap_stash("chorus", &mut cx);
+        assert_eq!(pitches(&view), vec![62]);
+        view.swap_stash("missing", &mut cx);
+        assert_eq!(pitches(&view), vec![62]);
+    }
+
+    #[test]
+    fn test_clamp_durations_truncates_long_notes() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.clip_notes[2] = vec![
+            ClipNote { nanotick: 0, duration: row * 40, pitch: 48, velocity: 90, column: 0 },
+            ClipNote { nanotick: row * 4, duration: row * 2, pitch: 55, velocity: 90, column: 1 },
+        ];
+
+        assert_eq!(view.clamp_durations(2, 8, &mut TestNotify), 1);
+        let durations: Vec<_> = view.clip_notes[2]
+            .iter()
+            .map(|note| (note.pitch, note.duration))
+            .collect();
+        assert!(durations.contains(&(48, row * 8)));
+        assert!(durations.contains(&(55, row * 2)));
+        assert_eq!(durations.len(), 2);
+    }
+
+    #[test]
+    fn test_selection_chord_is_named() {
+        assert_eq!(chord_name_for_pitches(&[60, 64, 67]), Some("C maj".to_string()));
+        assert_eq!(chord_name_for_pitches(&[64, 67, 72]), Some("C maj".to_string()));
+        assert_eq!(chord_name_for_pitches(&[57, 60, 64]), Some("A min".to_string()));
+        assert_eq!(chord_name_for_pitches(&[55, 59, 62, 65]), Some("G 7".to_string()));
+        assert_eq!(chord_name_for_pitches(&[60, 61, 62]), None);
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        for (column, pitch) in [(0u8, 48u8), (1, 64), (2, 67), (3, 60)] {
+            view.clip_notes[0].push(ClipNote {
+                nanotick: 0,
+                duration: row,
+                pitch,
+                velocity: 100,
+                column,
+            });
+        }
+        view.track_columns[0] = 4;
+        view.selection = Some(SelectionRange { start: 0, end: 0 });
+        view.selection_mask.tracks[0] = 0b1111;
+        assert_eq!(
+            chord_name_for_pitches(&view.selection_pitches()),
+            Some("C maj".to_string())
+        );
+    }
+
+    #[test]
+    fn test_playhead_anchored_zoom_scroll() {
+        // Playhead eight rows below the top stays eight rows down.
+        assert_eq!(playhead_anchored_scroll(2_000, 1_200, 100, 50), 1_600);
+        assert_eq!(playhead_anchored_scroll(2_000, 1_200, 100, 200), 400);
+        // Zooming out near the start clamps instead of scrolling before 0.
+        assert_eq!(playhead_anchored_scroll(500, 0, 100, 400), 0);
+
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.snapshot.ui_global_nanotick_playhead = 100 * row;
+        view.scroll_nanotick_offset = (90 * row) as i64;
+        view.zoom_by_at_playhead(-1, &mut TestNotify);
+        assert_ne!(view.row_nanoticks(), row);
+        let playhead_offset = (100 * row) as i64 - view.scroll_nanotick_offset;
+        assert_eq!(playhead_offset / view.row_nanoticks() as i64, 10);
+    }
+
+    #[test]
+    fn test_sustained_chord_spans_selection() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        view.cursor_col = 1;
+        view.selection = Some(SelectionRange {
+            start: 12 * row,
+            end: 4 * row,
+        });
+        view.open_sustain_chord(&mut TestNotify);
+        assert!(view.sustain_chord_open);
+        view.sustain_chord_text = "@5^7".to_string();
+        view.confirm_sustain_chord(&mut TestNotify);
+        assert!(!view.sustain_chord_open);
+
+        let chords = &view.clip_chords[track];
+        assert_eq!(chords.len(), 1);
+        assert_eq!(chords[0].nanotick, 4 * row);
+        assert_eq!(chords[0].duration, 8 * row);
+        assert_eq!(chords[0].column, 1);
+        assert_eq!(chords[0].degree, 5);
+        assert_eq!(chords[0].quality, CHORD_QUALITY_SEVENTH);
+    }
+
+    /// Reads back the `bar:beat root scale` lines `harmony_chart_text` writes.
+    fn parse_harmony_chart(text: &str) -> Option<Vec<HarmonyEntry>> {
+        let mut events = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let nanotick = parse_jump_text(parts.next()?)?;
+            let root = harmony_root_from_name(parts.next()?)?;
+            let scale_id = crate::harmony::harmony_scale_id_from_name(parts.next()?)?;
+            if parts.next().is_some() {
+                return None;
+            }
+            events.push(HarmonyEntry {
+                nanotick,
+                root,
+                scale_id,
+            });
+        }
+        Some(events)
+    }
+
+    #[test]
+    fn test_harmony_chart_text_round_trip() {
+        let quarter = NANOTICKS_PER_QUARTER;
+        let events = vec![
+            HarmonyEntry { nanotick: quarter * 4, root: 7, scale_id: 4 },
+            HarmonyEntry { nanotick: 0, root: 0, scale_id: 1 },
+            HarmonyEntry { nanotick: quarter * 6 + 250_000, root: 10, scale_id: 2 },
+        ];
+        let text = harmony_chart_text(&events);
+        assert_eq!(text, "1:1 C maj\n2:1 G mix\n2:3:25 A# min\n");
+
+        let parsed = parse_harmony_chart(&text).expect("chart parses");
+        let mut expected = events.clone();
+        expected.sort_by_key(|event| event.nanotick);
+        assert_eq!(parsed.len(), expected.len());
+        for (parsed, expected) in parsed.iter().zip(expected.iter()) {
+            assert_eq!(parsed.nanotick, expected.nanotick);
+            assert_eq!(parsed.root, expected.root);
+            assert_eq!(parsed.scale_id, expected.scale_id);
+        }
+
+        assert!(parse_harmony_chart("1:1 H maj").is_none());
+        assert!(parse_harmony_chart("1:1 C maj extra").is_none());
+    }
+
+    #[test]
+    fn test_paste_at_zero_keeps_leading_items() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let quarter = NANOTICKS_PER_QUARTER;
+        let mut view = EngineView::new_for_tests();
+        let clipboard = ClipboardData {
+            notes: vec![
+                ClipboardNote {
+                    track: 0,
+                    column: 0,
+                    offset: -2 * quarter as i64,
+                    pitch: 60,
+                    velocity: 100,
+                    duration: quarter,
+                },
+                ClipboardNote {
+                    track: 0,
+                    column: 0,
+                    offset: 0,
+                    pitch: 62,
+                    velocity: 100,
+                    duration: quarter,
+                },
+            ],
+            chords: Vec::new(),
+            harmonies: vec![ClipboardHarmony {
+                offset: -(quarter as i64),
+                root: 2,
+                scale_id: 1,
+            }],
+        };
+        let mut cx = TestNotify;
+        view.paste_clipboard_at(&clipboard, 0, &mut cx);
+
+        let mut ticks: Vec<(u64, u8)> = view.clip_notes[0]
+            .iter()
+            .map(|note| (note.nanotick, note.pitch))
+            .collect();
+        ticks.sort();
+        assert_eq!(ticks, vec![(0, 60), (2 * quarter, 62)]);
+        assert_eq!(view.harmony_events.len(), 1);
+        assert_eq!(view.harmony_events[0].nanotick, quarter);
+
+        // Pasting far enough in keeps the original spacing around the target.
+        let mut view = EngineView::new_for_tests();
+        view.paste_clipboard_at(&clipboard, 4 * quarter, &mut cx);
+        let mut ticks: Vec<u64> = view.clip_notes[0].iter().map(|note| note.nanotick).collect();
+        ticks.sort();
+        assert_eq!(ticks, vec![2 * quarter, 4 * quarter]);
+    }
+
+    #[test]
+    fn test_request_full_resync_clears_optimistic_state() {
+        use crate::state::{PendingChord, PendingNote};
+
+        let mut view = EngineView::new_for_tests();
+        view.pending_notes.push(PendingNote {
+            track_id: 0,
+            nanotick: 0,
+            duration: NANOTICKS_PER_QUARTER,
+            pitch: 60,
+            velocity: 100,
+            column: 0,
+        });
+        view.pending_chords.push(PendingChord {
+            track_id: 0,
+            nanotick: 0,
+            duration: NANOTICKS_PER_QUARTER,
+            spread: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            degree: 1,
+            quality: 0,
+            inversion: 0,
+            base_octave: 4,
+            column: 0,
+        });
+        view.clip_version_local = 9;
+        view.harmony_version_local = 4;
+        view.last_render_versions = view.render_versions();
+
+        view.request_full_resync();
+
+        assert!(view.clip_resync_pending);
+        assert!(view.harmony_resync_pending);
+        assert!(view.pending_notes.is_empty());
+        assert!(view.pending_chords.is_empty());
+        assert_eq!(view.clip_version_local, 0);
+        assert_eq!(view.harmony_version_local, 0);
+        assert!(view.needs_render());
+    }
+
+    #[test]
+    fn test_reharmonize_track_keeps_degrees() {
+        use crate::harmony::resolve_degree_pitch;
+
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let bar = NANOTICKS_PER_QUARTER * BEATS_PER_BAR;
+        let mut view = EngineView::new_for_tests();
+        view.harmony_events = vec![HarmonyEntry { nanotick: 0, root: 0, scale_id: 1 }];
+        view.focused_track_index = 1;
+        for (index, degree) in [1u8, 3, 6].into_iter().enumerate() {
+            view.clip_chords[1].push(ClipChord {
+                chord_id: index as u32,
+                nanotick: bar + index as u64 * NANOTICKS_PER_QUARTER,
+                duration: NANOTICKS_PER_QUARTER,
+                spread: 0,
+                humanize_timing: 0,
+                humanize_velocity: 0,
+                degree,
+                quality: 0,
+                inversion: 0,
+                base_octave: 4,
+                column: 0,
+            });
+        }
+        let resolved = |view: &EngineView| -> Vec<u8> {
+            view.clip_chords[1]
+                .iter()
+                .map(|chord| {
+                    resolve_degree_pitch(
+                        chord.degree,
+                        chord.base_octave,
+                        view.harmony_root_at(chord.nanotick),
+                        view.harmony_scale_at(chord.nanotick),
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(resolved(&view), vec![60, 64, 69]);
+
+        view.reharmonize_focused_track(2, &mut TestNotify);
+
+        let degrees: Vec<u8> = view.clip_chords[1].iter().map(|chord| chord.degree).collect();
+        assert_eq!(degrees, vec![1, 3, 6]);
+        assert_eq!(resolved(&view), vec![60, 63, 68]);
+        assert_eq!(view.harmony_scale_at(0), 1);
+        let span_end = bar + 3 * NANOTICKS_PER_QUARTER;
+        assert_eq!(view.harmony_scale_at(span_end), 1);
+        assert_eq!(view.harmony_root_at(span_end), 0);
+    }
+
+    #[test]
+    fn test_preview_chord_payload_carries_focused_chord() {
+        let mut view = EngineView::new_for_tests();
+        view.focused_track_index = 2;
+        view.cursor_col = 1;
+        view.cursor_nanotick = NANOTICKS_PER_QUARTER * 3;
+        assert!(view.preview_chord_payload().is_none());
+
+        view.clip_chords[2].push(ClipChord {
+            chord_id: 9,
+            nanotick: NANOTICKS_PER_QUARTER * 3,
+            duration: NANOTICKS_PER_QUARTER,
+            spread: 40,
+            humanize_timing: 5,
+            humanize_velocity: 7,
+            degree: 5,
+            quality: 2,
+            inversion: 1,
+            base_octave: 3,
+            column: 1,
+        });
+        let payload = view.preview_chord_payload().expect("focused chord");
+        assert_eq!(payload.command_type, UiCommandType::PreviewChord as u16);
+        assert_eq!(payload.track_id, 2);
+        assert_eq!(payload.flags, 1);
+        let nanotick = (payload.nanotick_lo as u64) | ((payload.nanotick_hi as u64) << 32);
+        assert_eq!(nanotick, NANOTICKS_PER_QUARTER * 3);
+        assert_eq!(payload.degree, 5);
+        assert_eq!(payload.quality, 2);
+        assert_eq!(payload.inversion, 1);
+        assert_eq!(payload.base_octave, 3);
+
+        view.cursor_col = 0;
+        assert!(view.preview_chord_payload().is_none());
+    }
+
+    #[test]
+    fn test_solo_column_toggle_dims_other_columns() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.focused_track_index = 1;
+        view.cursor_col = 2;
+        assert!(!view.column_dimmed(1, 0));
+
+        view.toggle_solo_column(&mut TestNotify);
+        assert_eq!(view.solo_column, Some((1, 2)));
+        assert!(!view.column_dimmed(1, 2));
+        assert!(view.column_dimmed(1, 0));
+        assert!(view.column_dimmed(0, 2));
+
+        view.clip_chords[1].push(ClipChord {
+            chord_id: 1,
+            nanotick: 0,
+            duration: 0,
+            spread: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            degree: 1,
+            quality: 0,
+            inversion: 0,
+            base_octave: 4,
+            column: 0,
+        });
+        view.cursor_col = 0;
+        assert!(view.preview_chord_payload().is_none());
+
+        view.cursor_col = 2;
+        view.toggle_solo_column(&mut TestNotify);
+        assert_eq!(view.solo_column, None);
+        assert!(!view.column_dimmed(1, 0));
+        view.cursor_col = 0;
+        assert!(view.preview_chord_payload().is_some());
+    }
+
+    #[test]
+    fn test_window_layout_round_trip() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.zoom_index = 4;
+        view.scroll_nanotick_offset = NANOTICKS_PER_QUARTER as i64 * 16;
+        view.cursor_nanotick = NANOTICKS_PER_QUARTER * 18;
+        view.focused_track_index = 3;
+        view.follow_playhead = false;
+        let layout = view.window_layout();
+        let json = serde_json::to_string(&layout).unwrap();
+        let parsed: WindowLayout = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, layout);
+
+        let mut restored = EngineView::new_for_tests();
+        restored.apply_window_layout(&parsed, &mut TestNotify);
+        assert_eq!(restored.window_layout(), layout);
+
+        let partial: WindowLayout = serde_json::from_str(r#"{"zoom_index":99}"#).unwrap();
+        restored.apply_window_layout(&partial, &mut TestNotify);
+        assert_eq!(restored.zoom_index, ZOOM_LEVELS.len() - 1);
+        assert!(restored.follow_playhead);
+    }
+
+    #[test]
+    fn test_fill_bar_with_note_writes_every_row() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let bar = NANOTICKS_PER_QUARTER * BEATS_PER_BAR;
+        let row = view.row_nanoticks();
+        let rows_per_bar = (bar / row) as usize;
+        view.keyjazz_pitch = 42;
+        view.cursor_col = 1;
+        view.cursor_nanotick = bar + 3 * row;
+
+        assert_eq!(view.fill_bar_with_note(1, &mut TestNotify), rows_per_bar);
+        let notes = &view.clip_notes[view.focused_track_index];
+        assert_eq!(notes.len(), rows_per_bar);
+        assert_eq!(notes[0].nanotick, bar);
+        assert!(notes.iter().all(|note| note.pitch == 42 && note.column == 1));
+        assert!(notes.iter().all(|note| note.nanotick < 2 * bar));
+
+        view.clip_notes[view.focused_track_index].clear();
+        assert_eq!(view.fill_bar_with_note(2, &mut TestNotify), rows_per_bar.div_ceil(2));
+    }
+
+    #[test]
+    fn test_rotate_selection_wraps_last_row() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        for (index, pitch) in [(0u64, 60u8), (1, 62), (3, 67)] {
+            view.write_note_at(track, 0, index * row, pitch, 100, row, &mut TestNotify);
+        }
+        view.selection = Some(SelectionRange {
+            start: 0,
+            end: 3 * row,
+        });
+        view.selection_mask.tracks[track] = 1;
+
+        view.rotate_selection(1, &mut TestNotify);
+        let notes: Vec<(u64, u8)> = view.clip_notes[track]
+            .iter()
+            .map(|note| (note.nanotick, note.pitch))
+            .collect();
+        assert_eq!(notes, vec![(0, 67), (row, 60), (2 * row, 62)]);
+
+        view.rotate_selection(-1, &mut TestNotify);
+        let notes: Vec<(u64, u8)> = view.clip_notes[track]
+            .iter()
+            .map(|note| (note.nanotick, note.pitch))
+            .collect();
+        assert_eq!(notes, vec![(0, 60), (row, 62), (3 * row, 67)]);
+    }
+
+    #[test]
+    fn test_velocity_from_digits() {
+        let scaled = VelocityDigitScale::Scaled;
+        assert_eq!(velocity_from_digits("6", scaled), None);
+        assert_eq!(velocity_from_digits("64", scaled), Some(82));
+        assert_eq!(velocity_from_digits("99", scaled), Some(127));
+        assert_eq!(velocity_from_digits("00", scaled), Some(1));
+
+        let direct = VelocityDigitScale::Direct;
+        assert_eq!(velocity_from_digits("64", direct), Some(64));
+        assert_eq!(velocity_from_digits("12", direct), None);
+        assert_eq!(velocity_from_digits("127", direct), Some(127));
+        assert_eq!(velocity_from_digits("120", direct), Some(120));
+        assert_eq!(velocity_from_digits("000", direct), Some(1));
+    }
+
+    #[test]
+    fn test_velocity_entry_rewrites_focused_note() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        view.write_note_at(track, 0, 0, 60, 100, row, &mut TestNotify);
+        view.velocity_entry = true;
+        view.push_velocity_digit("6", &mut TestNotify);
+        assert_eq!(view.clip_notes[track][0].velocity, 100);
+        view.push_velocity_digit("4", &mut TestNotify);
+        let note = &view.clip_notes[track][0];
+        assert_eq!((note.pitch, note.velocity, note.duration), (60, 82, row));
+        assert!(view.velocity_digits.is_empty());
+    }
+
+    #[test]
+    fn test_legato_column_reaches_an_off_screen_note() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        load_clip_window(&mut view, 16 * row);
+        view.legato_column(2, 0, &mut TestNotify);
+        assert_eq!(view.pending_whole_clip_edits.len(), 1);
+
+        load_whole_clip(&mut view, 2, &[(0, 0, 60, row), (400 * row, 0, 62, row)]);
+        view.resume_whole_clip_edits(&mut TestNotify);
+        let first = view.clip_notes[2].iter().find(|note| note.nanotick == 0).unwrap();
+        assert_eq!(first.duration, 400 * row);
+    }
+
+    #[test]
+    fn test_legato_focused_column_fills_gaps() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        for (index, pitch) in [(0u64, 60u8), (2, 62), (3, 64), (7, 65)] {
+            view.write_note_at(track, 0, index * row, pitch, 100, 0, &mut TestNotify);
+        }
+        view.write_note_at(track, 1, row, 48, 100, 0, &mut TestNotify);
+
+        view.legato_column(track, 0, &mut TestNotify);
+        let column: Vec<(u64, u64)> = view.clip_notes[track]
+            .iter()
+            .filter(|note| note.column == 0)
+            .map(|note| (note.nanotick, note.duration))
+            .collect();
+        assert_eq!(
+            column,
+            vec![(0, 2 * row), (2 * row, row), (3 * row, 4 * row), (7 * row, 0)]
+        );
+        let other = view.clip_notes[track].iter().find(|note| note.column == 1).unwrap();
+        assert_eq!(other.duration, 0);
+    }
+
+    #[test]
+    fn test_delete_column_note_offs() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let track = view.focused_track_index;
+        view.write_note_at(track, 0, 0, 60, 100, row, &mut TestNotify);
+        view.write_note_at(track, 0, row, 60, 0, 0, &mut TestNotify);
+        view.write_note_at(track, 0, 2 * row, 62, 100, 0, &mut TestNotify);
+        view.write_note_at(track, 0, 3 * row, 62, 0, 0, &mut TestNotify);
+        view.write_note_at(track, 1, row, 48, 0, 0, &mut TestNotify);
+
+        view.delete_column_note_offs(track, 0, &mut TestNotify);
+        let column: Vec<(u64, u8)> = view.clip_notes[track]
+            .iter()
+            .filter(|note| note.column == 0)
+            .map(|note| (note.nanotick, note.velocity))
+            .collect();
+        assert_eq!(column, vec![(0, 100), (2 * row, 100)]);
+        assert!(view.clip_notes[track].iter().any(|note| note.column == 1));
+    }
+
+    #[test]
+    fn test_project_markers_round_trip() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.markers = vec![(2 * row, "M1".to_string()), (6 * row, "Chorus".to_string())];
+        let markers = view.project_markers();
+        let json = serde_json::to_string(&markers).unwrap();
+        let parsed: ProjectMarkers = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, markers);
+
+        let mut restored = EngineView::new_for_tests();
+        restored.apply_project_markers(&parsed, &mut TestNotify);
+        assert_eq!(restored.markers, view.markers);
+
+        let unsorted: ProjectMarkers = serde_json::from_str(
+            r#"{"markers":[{"nanotick":9,"name":"B"},{"nanotick":3,"name":"A"},{"nanotick":9,"name":"C"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(unsorted.schema_version, 1);
+        restored.apply_project_markers(&unsorted, &mut TestNotify);
+        assert_eq!(
+            restored.markers,
+            vec![(3, "A".to_string()), (9, "B".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_markers_add_and_jump_in_order() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        for tick in [8 * row, 2 * row, 5 * row, 5 * row] {
+            view.cursor_nanotick = tick;
+            view.add_marker_at_cursor(&mut TestNotify);
+        }
+        let ticks: Vec<u64> = view.markers.iter().map(|(tick, _)| *tick).collect();
+        assert_eq!(ticks, vec![2 * row, 5 * row, 8 * row]);
+        assert_eq!(view.marker_in_range(5 * row, 6 * row), Some("M3"));
+
+        view.cursor_nanotick = 0;
+        view.jump_to_marker(true, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 2 * row);
+        view.jump_to_marker(true, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 5 * row);
+        view.jump_to_marker(true, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 8 * row);
+        view.jump_to_marker(true, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 8 * row);
+        view.jump_to_marker(false, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 5 * row);
+        view.jump_to_marker(false, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 2 * row);
+        view.jump_to_marker(false, &mut TestNotify);
+        assert_eq!(view.cursor_nanotick, 2 * row);
+    }
+
+    #[test]
+    fn test_count_in_prefixes_play_sequence() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let commands: Vec<u16> = view
+            .play_command_sequence()
+            .iter()
+            .map(|payload| payload.command_type)
+            .collect();
+        assert_eq!(commands, vec![UiCommandType::TogglePlay as u16]);
+
+        view.toggle_count_in(&mut TestNotify);
+        assert_eq!(view.count_in_bars, 1);
+        let sequence = view.play_command_sequence();
+        assert_eq!(sequence.len(), 2);
+        assert_eq!(sequence[0].command_type, UiCommandType::SetCountIn as u16);
+        assert_eq!(sequence[0].value0, 1);
+        assert_eq!(sequence[1].command_type, UiCommandType::TogglePlay as u16);
+
+        view.toggle_count_in(&mut TestNotify);
+        assert_eq!(view.play_command_sequence()[0].value0, 2);
+
+        view.snapshot.ui_transport_state = 1;
+        assert_eq!(view.play_command_sequence().len(), 1);
+
+        view.toggle_count_in(&mut TestNotify);
+        assert_eq!(view.count_in_bars, 0);
+    }
+
+    #[test]
+    fn test_bump_focused_chord_octave_preserves_fields() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.cursor_col = 1;
+        view.clip_chords[0].push(ClipChord {
+            chord_id: 0,
+            nanotick: 0,
+            duration: NANOTICKS_PER_QUARTER,
+            spread: 120,
+            humanize_timing: 3,
+            humanize_velocity: 4,
+            degree: 5,
+            quality: 2,
+            inversion: 1,
+            base_octave: 8,
+            column: 1,
+        });
+
+        view.bump_focused_chord_octave(1, &mut TestNotify);
+        view.bump_focused_chord_octave(1, &mut TestNotify);
+        let chord = view.focused_chord().expect("chord under cursor");
+        assert_eq!(chord.base_octave, MAX_CHORD_BASE_OCTAVE);
+        assert_eq!(chord.degree, 5);
+        assert_eq!(chord.quality, 2);
+        assert_eq!(chord.inversion, 1);
+        assert_eq!(chord.duration, NANOTICKS_PER_QUARTER);
+        assert_eq!(chord.spread, 120);
+        assert_eq!(chord.humanize_timing, 3);
+        assert_eq!(chord.humanize_velocity, 4);
+
+        view.bump_focused_chord_octave(-1, &mut TestNotify);
+        assert_eq!(view.clip_chords[0].len(), 1);
+        assert_eq!(view.clip_chords[0][0].base_octave, 8);
+    }
+
+    #[test]
+    fn test_shift_selection_octave_moves_notes_and_chords() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.clip_notes[0].push(ClipNote {
+            nanotick: 0,
+            duration: NANOTICKS_PER_QUARTER,
+            pitch: 60,
+            velocity: 90,
+            column: 0,
+        });
+        view.clip_notes[0].push(ClipNote {
+            nanotick: NANOTICKS_PER_QUARTER,
+            duration: NANOTICKS_PER_QUARTER,
+            pitch: 120,
+            velocity: 90,
+            column: 0,
+        });
+        view.clip_chords[0].push(ClipChord {
+            chord_id: 4,
+            nanotick: 0,
+            duration: NANOTICKS_PER_QUARTER,
+            spread: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            degree: 5,
+            quality: 1,
+            inversion: 2,
+            base_octave: 4,
+            column: 1,
+        });
+        view.selection = Some(SelectionRange {
+            start: 0,
+            end: NANOTICKS_PER_QUARTER,
+        });
+        view.selection_mask = SelectionMask::empty(TRACK_COUNT);
+        view.selection_mask.tracks[0] = 0b11;
+
+        view.shift_selection_octave(1, &mut TestNotify);
+
+        let pitches: Vec<u8> = view.clip_notes[0].iter().map(|note| note.pitch).collect();
+        assert_eq!(pitches, vec![72, 127]);
+        let chord = &view.clip_chords[0][0];
+        assert_eq!(chord.base_octave, 5);
+        assert_eq!((chord.degree, chord.quality, chord.inversion), (5, 1, 2));
+
+        view.shift_selection_octave(-1, &mut TestNotify);
+        assert_eq!(view.clip_notes[0][0].pitch, 60);
+        assert_eq!(view.clip_chords[0][0].base_octave, 4);
+    }
+
+    #[test]
+    fn test_jump_to_playhead_keeps_follow_off() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.follow_playhead = false;
+        view.snapshot.ui_transport_state = 1;
+        view.snapshot.ui_global_nanotick_playhead = 100 * row;
+        view.jump_to_playhead(&mut TestNotify);
+        assert_eq!(
+            view.scroll_nanotick_offset,
+            ((100 - VISIBLE_ROWS as u64 / 2) * row) as i64
+        );
+        assert!(!view.follow_playhead);
+        assert!(!view.update_follow_playhead());
+
+        view.snapshot.ui_global_nanotick_playhead = row;
+        view.jump_to_playhead(&mut TestNotify);
+        assert_eq!(view.scroll_nanotick_offset, 0);
+    }
+
+    #[test]
+    fn test_follow_playhead_custom_band() {
+        let mut view = EngineView::new_for_tests();
+        assert!(!view.set_follow_playhead_band(0.6, 0.4));
+        assert!(!view.set_follow_playhead_band(0.0, 0.5));
+        assert!(!view.set_follow_playhead_band(0.5, 1.0));
+        assert_eq!(view.follow_playhead_lower, FOLLOW_PLAYHEAD_LOWER);
+        assert!(view.set_follow_playhead_band(0.4, 0.6));
+        assert_eq!(parse_follow_band("0.4, 0.6"), Some((0.4, 0.6)));
+        assert_eq!(parse_follow_band("0.9,0.1"), None);
+
+        let row = view.row_nanoticks();
+        view.follow_playhead = true;
+        view.snapshot.ui_transport_state = 1;
+        // Row 10 of 32 sits inside the default band but below 0.4 * 32.
+        view.snapshot.ui_global_nanotick_playhead = 10 * row;
+        assert!(view.update_follow_playhead());
+        assert_eq!(view.scroll_nanotick_offset, 0);
+
+        view.scroll_nanotick_offset = 0;
+        view.snapshot.ui_global_nanotick_playhead = 16 * row;
+        assert!(!view.update_follow_playhead());
+
+        view.snapshot.ui_global_nanotick_playhead = 21 * row;
+        assert!(view.update_follow_playhead());
+        assert_eq!(view.scroll_nanotick_offset, (5 * row) as i64);
+
+        let mut wide = EngineView::new_for_tests();
+        wide.follow_playhead = true;
+        wide.snapshot.ui_transport_state = 1;
+        wide.snapshot.ui_global_nanotick_playhead = 21 * row;
+        assert!(!wide.update_follow_playhead());
+    }
+
+    #[test]
+    fn test_join_notes_at_cursor_merges_adjacent_notes() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        for start in [0, 2 * row] {
+            view.clip_notes[0].push(ClipNote {
+                nanotick: start,
+                duration: 2 * row,
+                pitch: 64,
+                velocity: 100,
+                column: 0,
+            });
+        }
+        view.cursor_nanotick = 2 * row;
+        view.join_notes_at_cursor(&mut TestNotify);
+
+        assert_eq!(view.clip_notes[0].len(), 1);
+        let joined = &view.clip_notes[0][0];
+        assert_eq!(joined.nanotick, 0);
+        assert_eq!(joined.duration, 4 * row);
+        assert_eq!(joined.pitch, 64);
+
+        view.clip_notes[0].push(ClipNote {
+            nanotick: 4 * row,
+            duration: row,
+            pitch: 65,
+            velocity: 100,
+            column: 0,
+        });
+        view.cursor_nanotick = 4 * row;
+        view.join_notes_at_cursor(&mut TestNotify);
+        assert_eq!(view.clip_notes[0].len(), 2);
+    }
+
+    #[test]
+    fn test_harmony_drag_sets_mask_and_bounds() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.begin_harmony_drag(2, false, &mut TestNotify);
+        assert!(view.selection.is_none());
+
+        view.drag_harmony_selection(6, &mut TestNotify);
+        assert!(view.selection_mask.harmony);
+        assert!(view.selection_mask.tracks.iter().all(|mask| *mask == 0));
+        assert_eq!(view.selection_bounds(), Some((2 * row, 6 * row)));
+
+        view.drag_harmony_selection(4, &mut TestNotify);
+        assert_eq!(view.selection_bounds(), Some((2 * row, 4 * row)));
+        view.end_harmony_drag();
+        view.drag_harmony_selection(12, &mut TestNotify);
+        assert_eq!(view.selection_bounds(), Some((2 * row, 4 * row)));
+
+        view.begin_harmony_drag(9, true, &mut TestNotify);
+        assert_eq!(view.selection_bounds(), Some((2 * row, 9 * row)));
+        view.drag_harmony_selection(11, &mut TestNotify);
+        assert!(view.selection_mask.harmony);
+        assert_eq!(view.selection_bounds(), Some((2 * row, 11 * row)));
+    }
+
+    #[test]
+    fn test_harmony_drag_continues_across_note_cells() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.focused_track_index = 1;
+        view.cursor_col = 0;
+        view.begin_harmony_drag(3, false, &mut TestNotify);
+
+        // Moves over note cells report only their row, like harmony cells.
+        view.harmony_drag_move(7, true, &mut TestNotify);
+        assert!(view.selection_mask.harmony);
+        assert!(view.selection_mask.tracks.iter().all(|mask| *mask == 0));
+        assert_eq!(view.selection_bounds(), Some((3 * row, 7 * row)));
+        assert!(view.harmony_focus);
+
+        // A move without the button held ends the drag.
+        view.harmony_drag_move(9, false, &mut TestNotify);
+        assert!(view.harmony_drag_anchor.is_none());
+        view.harmony_drag_move(12, true, &mut TestNotify);
+        assert_eq!(view.selection_bounds(), Some((3 * row, 7 * row)));
+    }
+
+    #[test]
+    fn test_clear_clipboard_empties_paste() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.clipboard = Some(ClipboardData {
+            notes: Vec::new(),
+            chords: Vec::new(),
+            harmonies: Vec::new(),
+        });
+        view.clear_clipboard(&mut TestNotify);
+        assert!(view.clipboard.is_none());
+        assert_eq!(view.toast_message.as_deref(), Some("Clipboard cleared"));
+
+        view.paste_selection(&mut TestNotify);
+        assert_eq!(view.toast_message.as_deref(), Some("Clipboard empty"));
+    }
+
+    #[test]
+    fn test_cell_entry_pending_flag() {
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.clip_notes[0].push(ClipNote {
+            nanotick: 0,
+            duration: row,
+            pitch: 60,
+            velocity: 100,
+            column: 0,
+        });
+        view.pending_notes.push(PendingNote {
+            track_id: 0,
+            nanotick: 0,
+            duration: row,
+            pitch: 62,
+            velocity: 100,
+            column: 1,
+        });
+        let clip = view.cell_entry_at(0, 0, 0).expect("clip note entry");
+        assert!(!clip.pending);
+        let pending = view.cell_entry_at(0, 0, 1).expect("pending note entry");
+        assert!(pending.pending);
+        assert_eq!(pending.note_pitch, Some(62));
+    }
+
+    #[test]
+    fn test_content_filter_hides_cell_kinds() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
 
-fn parse_note_token(token: &str) -> Option<u8> {
-    let token = token.trim();
-    if token.len() < 2 {
-        return None;
-    }
-    let mut chars = token.chars();
-    let letter = chars.next()?.to_ascii_uppercase();
-    let mut accidental: i8 = 0;
-    let mut next = chars.next()?;
-    if next == '#' {
-        accidental = 1;
-        next = chars.next()?;
-    } else if next == 'b' || next == 'B' {
-        accidental = -1;
-        next = chars.next()?;
-    } else if next == '-' {
-        next = chars.next()?;
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.clip_notes[0].push(ClipNote {
+            nanotick: 0,
+            duration: row,
+            pitch: 60,
+            velocity: 100,
+            column: 0,
+        });
+        view.clip_chords[0].push(ClipChord {
+            chord_id: 3,
+            nanotick: 0,
+            duration: row,
+            spread: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            degree: 1,
+            quality: 1,
+            inversion: 0,
+            base_octave: 4,
+            column: 1,
+        });
+        assert_eq!(view.entries_for_row(0, 0).len(), 2);
+
+        view.cycle_content_filter(&mut TestNotify);
+        let entries = view.entries_for_row(0, 0);
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(entries[0].kind, CellKind::Note));
+        assert!(!view.cell_kind_visible(0, CellKind::Chord));
+        assert!(view.cell_kind_visible(1, CellKind::Chord));
+
+        view.cycle_content_filter(&mut TestNotify);
+        let entries = view.entries_for_row(0, 0);
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(entries[0].kind, CellKind::Chord));
+        assert!(view.cell_entry_at(0, 0, 0).is_none());
+
+        view.cycle_content_filter(&mut TestNotify);
+        assert_eq!(view.track_content_filter[0], CellContentFilter::All);
+        assert_eq!(view.entries_for_row(0, 0).len(), 2);
     }
-    let mut octave_str = String::new();
-    octave_str.push(next);
-    octave_str.extend(chars);
-    let octave: i8 = octave_str.parse().ok()?;
-    let base = match letter {
-        'C' => 0,
-        'D' => 2,
-        'E' => 4,
-        'F' => 5,
-        'G' => 7,
-        'A' => 9,
-        'B' => 11,
-        _ => return None,
-    };
-    let mut pitch = (octave + 1) as i16 * 12 + base as i16 + accidental as i16;
-    if pitch < 0 {
-        pitch = 0;
+
+    #[test]
+    fn test_quantize_strength_moves_partway_to_row() {
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.track_quantize[0] = true;
+        view.cursor_nanotick = 3 * row + row / 4;
+
+        view.set_track_quantize_strength(0, 50);
+        assert_eq!(view.entry_nanotick(), 3 * row + row / 8);
+
+        view.set_track_quantize_strength(0, 100);
+        assert_eq!(view.entry_nanotick(), 3 * row);
+
+        view.set_track_quantize_strength(0, 0);
+        assert_eq!(view.entry_nanotick(), 3 * row + row / 4);
+
+        view.set_track_quantize_strength(0, 50);
+        view.track_quantize[0] = false;
+        assert_eq!(view.entry_nanotick(), 3 * row + row / 4);
+
+        assert_eq!(quantize_toward_row(3 * row - row / 4, row, 50), 3 * row - row / 8);
     }
-    if pitch > 127 {
-        pitch = 127;
+
+    #[test]
+    fn test_palette_set_tempo_opens_overlay() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        view.toggle_palette(&mut TestNotify);
+        view.palette_query = "tempo".to_string();
+        view.confirm_palette(&mut TestNotify);
+        assert!(view.tempo_open);
+        assert!(!view.palette_open);
+
+        view.tempo_text = "96.5".to_string();
+        view.action_palette_confirm(&mut TestNotify);
+        assert!(!view.tempo_open);
+        assert_eq!(view.tempo_bpm, 96.5);
+
+        assert_eq!(parse_tempo_text("400"), None);
+        assert_eq!(parse_tempo_text("abc"), None);
     }
-    Some(pitch as u8)
-}
 
-fn keystroke_text(keystroke: &gpui::Keystroke) -> Option<&str> {
-    if let Some(key_char) = keystroke.key_char.as_ref() {
-        return Some(key_char);
+    #[test]
+    fn test_harmony_over_range_clears_interior() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        for (index, nanotick) in [0, 2 * row, 3 * row, 6 * row].into_iter().enumerate() {
+            view.harmony_events.push(HarmonyEntry {
+                nanotick,
+                root: index as u32,
+                scale_id: 1,
+            });
+        }
+        view.selection = Some(SelectionRange {
+            start: 4 * row,
+            end: row,
+        });
+        view.toggle_palette(&mut TestNotify);
+        view.palette_query = "harmony over".to_string();
+        view.confirm_palette(&mut TestNotify);
+        assert!(view.scale_browser_open);
+        assert!(view.scale_browser_range);
+        view.close_scale_browser(&mut TestNotify);
+
+        view.write_harmony_over_range(row, 4 * row, 7, 2, true, &mut TestNotify);
+        let events: Vec<(u64, u32, u32)> = view
+            .harmony_events
+            .iter()
+            .map(|event| (event.nanotick, event.root, event.scale_id))
+            .collect();
+        assert_eq!(events, vec![(0, 0, 1), (row, 7, 2), (6 * row, 3, 1)]);
+
+        view.write_harmony_over_range(0, 6 * row, 5, 1, false, &mut TestNotify);
+        assert_eq!(view.harmony_events.len(), 3);
+        assert_eq!(view.harmony_events[0].root, 5);
     }
-    if keystroke.key.len() == 1 {
-        return Some(keystroke.key.as_str());
+
+    #[test]
+    fn test_row_nanoticks_never_zero() {
+        let mut view = EngineView::new_for_tests();
+        for zoom_index in 0..ZOOM_LEVELS.len() {
+            view.zoom_index = zoom_index;
+            assert!(view.row_nanoticks() >= 1, "zoom index {zoom_index}");
+        }
     }
-    None
-}
 
-fn begin_cell_edit(edit_active: &mut bool, edit_text: &mut String, initial: &str) -> bool {
-    *edit_active = true;
-    edit_text.clear();
-    edit_text.push_str(initial);
-    true
-}
+    #[test]
+    fn test_duplicate_harmony_progression() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
 
-fn cancel_cell_edit_state(edit_active: &mut bool, edit_text: &mut String) -> bool {
-    if !*edit_active {
-        return false;
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        let beat = NANOTICKS_PER_QUARTER;
+        let bar = beat * BEATS_PER_BAR;
+        view.harmony_events.push(HarmonyEntry { nanotick: 0, root: 0, scale_id: 1 });
+        view.harmony_events.push(HarmonyEntry { nanotick: 2 * beat, root: 7, scale_id: 2 });
+        view.harmony_events.push(HarmonyEntry { nanotick: 3 * bar, root: 5, scale_id: 1 });
+        view.selection = Some(SelectionRange { start: 0, end: bar - row });
+
+        view.toggle_palette(&mut TestNotify);
+        view.palette_query = "duplicate harmony".to_string();
+        view.confirm_palette(&mut TestNotify);
+
+        let events: Vec<(u64, u32, u32)> = view
+            .harmony_events
+            .iter()
+            .map(|event| (event.nanotick, event.root, event.scale_id))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (0, 0, 1),
+                (2 * beat, 7, 2),
+                (bar, 0, 1),
+                (bar + 2 * beat, 7, 2),
+                (3 * bar, 5, 1),
+            ]
+        );
+        assert_eq!(harmony_progression_offset(beat, bar + beat, row), 2 * bar);
     }
-    *edit_active = false;
-    edit_text.clear();
-    true
-}
 
-fn commit_cell_edit_state(edit_active: &mut bool, edit_text: &mut String) -> Option<String> {
-    if !*edit_active {
-        return None;
-    }
-    let token = edit_text.trim().to_string();
-    *edit_active = false;
-    edit_text.clear();
-    Some(token)
-}
+    #[test]
+    fn test_insert_mode_waits_for_the_whole_clip() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
 
-fn is_cell_edit_start(key: &str) -> bool {
-    key == "@"
-}
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.track_quantize[0] = false;
+        load_clip_window(&mut view, 16 * row);
+        view.toggle_insert_mode(&mut TestNotify);
+        assert_eq!(view.desired_clip_window_range(), (0, u64::MAX));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        view.write_note(64, &mut TestNotify);
+        assert!(view.pending_notes.is_empty());
+        assert_eq!(view.toast_message.as_deref(), Some("Loading the whole clip"));
+
+        load_whole_clip(&mut view, 0, &[(row, 0, 60, row), (400 * row, 0, 62, row)]);
+        view.write_note(64, &mut TestNotify);
+        let positions: Vec<u64> = view.clip_notes[0].iter().map(|note| note.nanotick).collect();
+        assert!(positions.contains(&(2 * row)));
+        assert!(positions.contains(&(401 * row)));
+    }
 
     #[test]
-    fn test_parse_note_token() {
-        assert_eq!(parse_note_token("C-4"), Some(60));
-        assert_eq!(parse_note_token("D#5"), Some(75));
-        assert_eq!(parse_note_token("Bb3"), Some(58));
-        assert_eq!(parse_note_token("Q-4"), None);
+    fn test_insert_mode_shifts_column_down() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = EngineView::new_for_tests();
+        let row = view.row_nanoticks();
+        view.track_quantize[0] = false;
+        view.clip_notes[0].push(ClipNote {
+            nanotick: 2 * row,
+            duration: row,
+            pitch: 60,
+            velocity: 100,
+            column: 0,
+        });
+        view.clip_notes[0].push(ClipNote {
+            nanotick: row,
+            duration: row,
+            pitch: 55,
+            velocity: 100,
+            column: 0,
+        });
+        view.cursor_nanotick = 2 * row;
+        view.toggle_insert_mode(&mut TestNotify);
+        view.write_note(64, &mut TestNotify);
+
+        let mut positions: Vec<(u64, u8)> = view.clip_notes[0]
+            .iter()
+            .map(|note| (note.nanotick, note.pitch))
+            .collect();
+        positions.sort();
+        assert_eq!(positions, vec![(row, 55), (3 * row, 60)]);
+
+        view.toggle_insert_mode(&mut TestNotify);
+        view.cursor_nanotick = row;
+        view.write_note(64, &mut TestNotify);
+        let unchanged: Vec<u64> = view.clip_notes[0].iter().map(|note| note.nanotick).collect();
+        assert!(unchanged.contains(&row) && unchanged.contains(&(3 * row)));
     }
 
     #[test]
-    fn test_parse_degree_note_token() {
-        let parsed = parse_degree_note_token("24-4~120h8").expect("degree token");
-        assert_eq!(parsed.degree, 24);
-        assert_eq!(parsed.base_octave, 4);
-        assert_eq!(parsed.quality, 0);
-        assert_eq!(parsed.spread_nanoticks, 120);
-        assert_eq!(parsed.humanize_timing, 8);
+    fn test_version_overlay_text() {
+        assert_eq!(
+            version_overlay_text(7, 5, 7, 2),
+            "clip v7 engine v5 local v7 queued 2"
+        );
+        let mut view = EngineView::new_for_tests();
+        view.snapshot.ui_clip_version = 12;
+        assert_eq!(view.version_overlay_line(), "clip v12 engine v12 local v0 queued 0");
+        view.bump_clip_version();
+        assert_eq!(view.version_overlay_line(), "clip v13 engine v12 local v13 queued 0");
     }
 
     #[test]
-    fn test_parse_chord_token() {
-        let parsed = parse_chord_token("@3^7/1-5~240h6").expect("chord token");
-        assert_eq!(parsed.degree, 3);
-        assert_eq!(parsed.quality, 2);
-        assert_eq!(parsed.inversion, 1);
-        assert_eq!(parsed.base_octave, 5);
-        assert_eq!(parsed.spread_nanoticks, 240);
-        assert_eq!(parsed.humanize_timing, 6);
+    fn test_velocity_label_format() {
+        assert_eq!(velocity_label(100), "v100");
+        assert_eq!(velocity_label(7), "v007");
+        assert_eq!(velocity_label(127), "v127");
+        assert_eq!(velocity_label(200), "v127");
+        assert_eq!(velocity_label(0), "v001");
     }
 
     #[test]
-    fn test_cell_edit_flow() {
-        let mut active = false;
-        let mut text = String::new();
-        assert!(begin_cell_edit(&mut active, &mut text, "A"));
-        assert!(active);
-        assert_eq!(text, "A");
+    fn test_pitch_ruler_labels_follow_octave_base() {
+        let labels = pitch_ruler_labels(KEYJAZZ_BASE_OCTAVE);
+        assert_eq!(labels.len(), 24);
+        assert_eq!(labels.first().map(String::as_str), Some("B-4"));
+        assert_eq!(labels[11], "C-4");
+        assert_eq!(labels[12], "B-3");
+        assert_eq!(labels.last().map(String::as_str), Some("C-3"));
 
-        let token = commit_cell_edit_state(&mut active, &mut text);
-        assert_eq!(token, Some("A".to_string()));
-        assert!(!active);
-        assert!(text.is_empty());
+        let shifted = pitch_ruler_labels(5);
+        assert_eq!(shifted.first().map(String::as_str), Some("B-6"));
+        assert_eq!(shifted.last().map(String::as_str), Some("C-5"));
 
-        assert!(begin_cell_edit(&mut active, &mut text, " @3 "));
-        assert!(cancel_cell_edit_state(&mut active, &mut text));
-        assert!(!active);
-        assert!(text.is_empty());
+        // Pitches past the MIDI range are dropped.
+        let top = pitch_ruler_labels(9);
+        assert_eq!(top.first().map(String::as_str), Some("G-9"));
+        assert_eq!(top.len(), 8);
     }
 
     #[test]
@@ -4544,6 +10963,29 @@ mod tests {
         assert_eq!(parse_jump_text("3:2:5"), Some(expected));
     }
 
+    #[test]
+    fn test_parse_jump_text_rejects_absurd_positions() {
+        assert_eq!(parse_jump_text("999999999"), None);
+        assert_eq!(parse_jump_text("18446744073709551615"), None);
+        assert_eq!(parse_jump_text("1:18446744073709551615"), None);
+        assert_eq!(parse_jump_text("1:1:18446744073709551615"), None);
+        let last_bar = (MAX_JUMP_BAR - 1) * BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+        assert_eq!(parse_jump_text(&MAX_JUMP_BAR.to_string()), Some(last_bar));
+        assert_eq!(parse_jump_text(&(MAX_JUMP_BAR + 1).to_string()), None);
+    }
+
+    #[test]
+    fn test_samples_to_nanotick() {
+        // 120 BPM at 48 kHz: one quarter every 24000 samples.
+        assert_eq!(samples_to_nanotick(0, 120.0, 48_000.0), Some(0));
+        assert_eq!(samples_to_nanotick(24_000, 120.0, 48_000.0), Some(NANOTICKS_PER_QUARTER));
+        assert_eq!(samples_to_nanotick(12_000, 120.0, 48_000.0), Some(NANOTICKS_PER_QUARTER / 2));
+        assert_eq!(samples_to_nanotick(44_100, 60.0, 44_100.0), Some(NANOTICKS_PER_QUARTER));
+        assert_eq!(samples_to_nanotick(1, 120.0, 48_000.0), Some(40));
+        assert_eq!(samples_to_nanotick(u64::MAX, 120.0, 48_000.0), None);
+        assert_eq!(samples_to_nanotick(100, 120.0, 0.0), None);
+    }
+
     #[test]
     fn test_backspace_moves_cursor_down() {
         struct TestNotify;
@@ -4587,6 +11029,7 @@ mod tests {
             ui_harmony_offset: 0,
             ui_harmony_bytes: 0,
             ui_track_peak_rms: [0.0; K_UI_MAX_TRACKS],
+            ui_master_peak: [0.0; K_UI_MASTER_METER_CHANNELS],
         };
         if let Some(state) = engine_view.clip_window.get_mut(0) {
             state.window_start = 0;
@@ -4618,23 +11061,66 @@ mod tests {
             diff_type: UiDiffType::AddNote as u16,
             flags: 0,
             track_id: 0,
-            clip_version: 1,
+            clip_version: 1,
+            note_nanotick_lo: 0,
+            note_nanotick_hi: 0,
+            note_duration_lo: 240000,
+            note_duration_hi: 0,
+            note_pitch: 62,  // D-4
+            note_velocity: 100,
+            note_column: 0,
+        };
+
+        engine_view.apply_diff(diff);
+
+        // Should have only 1 note (D-4), not 2
+        assert_eq!(engine_view.clip_notes[0].len(), 1,
+                   "Should have replaced C-4 with D-4");
+        assert_eq!(engine_view.clip_notes[0][0].pitch, 62,
+                   "Note should be D-4");
+    }
+
+    #[test]
+    fn test_apply_diff_rejects_out_of_range_note() {
+        use daw_bridge::layout::{UiDiffPayload, UiDiffType};
+
+        let mut view = EngineView::new_for_tests();
+        if let Some(state) = view.clip_window.get_mut(0) {
+            state.window_start = 0;
+            state.window_end = NANOTICKS_PER_QUARTER * 4;
+        }
+        view.clip_notes[0].push(ClipNote {
+            nanotick: 0,
+            duration: 240000,
+            pitch: 60,
+            velocity: 100,
+            column: 0,
+        });
+        let diff = UiDiffPayload {
+            diff_type: UiDiffType::AddNote as u16,
+            flags: 0,
+            track_id: 0,
+            clip_version: 3,
             note_nanotick_lo: 0,
             note_nanotick_hi: 0,
             note_duration_lo: 240000,
             note_duration_hi: 0,
-            note_pitch: 62,  // D-4
+            note_pitch: 200,
             note_velocity: 100,
             note_column: 0,
         };
+        view.apply_diff(diff);
+        view.apply_diff(UiDiffPayload {
+            clip_version: 4,
+            note_pitch: 64,
+            note_velocity: 300,
+            ..diff
+        });
 
-        engine_view.apply_diff(diff);
-
-        // Should have only 1 note (D-4), not 2
-        assert_eq!(engine_view.clip_notes[0].len(), 1,
-                   "Should have replaced C-4 with D-4");
-        assert_eq!(engine_view.clip_notes[0][0].pitch, 62,
-                   "Note should be D-4");
+        assert_eq!(view.clip_notes[0].len(), 1);
+        assert_eq!(view.clip_notes[0][0].pitch, 60);
+        assert_eq!(view.clip_notes[0][0].velocity, 100);
+        assert_eq!(view.clip_version_local, 4);
     }
 
     #[test]
@@ -4680,14 +11166,14 @@ mod tests {
         let mut view = super::EngineView::new_state();
         view.zoom_index = 0; // zoomed out -> aggregate
 
-        view.clip_notes[0].push(super::ClipNote {
+        view.clip_notes[0].push(ClipNote {
             nanotick: 0,
             duration: 240000,
             pitch: 60,
             velocity: 100,
             column: 0,
         });
-        view.clip_notes[0].push(super::ClipNote {
+        view.clip_notes[0].push(ClipNote {
             nanotick: 240000,
             duration: 240000,
             pitch: 60,
@@ -4706,6 +11192,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_semantic_zoom_aggregate_mixed_note_and_chord() {
+        let mut view = super::EngineView::new_state();
+        view.zoom_index = 0;
+        view.clip_notes[0].push(ClipNote {
+            nanotick: 0,
+            duration: 240000,
+            pitch: 60,
+            velocity: 100,
+            column: 0,
+        });
+        view.clip_chords[0].push(ClipChord {
+            chord_id: 1,
+            nanotick: 240000,
+            duration: 240000,
+            spread: 0,
+            humanize_timing: 0,
+            humanize_velocity: 0,
+            degree: 1,
+            quality: CHORD_QUALITY_TRIAD,
+            inversion: 0,
+            base_octave: 4,
+            column: 0,
+        });
+
+        let aggregates = view.aggregate_cells_in_range(0, view.row_nanoticks(), 0, 1);
+        assert_eq!(
+            view.aggregate_cell_label(&aggregates[0]),
+            Some("[1N/1C]".to_string())
+        );
+    }
+
     #[test]
     fn test_semantic_zoom_default_no_aggregate() {
         let view = super::EngineView::new_state();
@@ -4821,21 +11339,21 @@ mod tests {
         let (page_start, page_end) = view.page_range();
         let outside = page_end + row;
 
-        view.clip_notes[0].push(super::ClipNote {
+        view.clip_notes[0].push(ClipNote {
             nanotick: page_start,
             duration: row,
             pitch: 60,
             velocity: 100,
             column: 0,
         });
-        view.clip_notes[0].push(super::ClipNote {
+        view.clip_notes[0].push(ClipNote {
             nanotick: page_start + row,
             duration: row,
             pitch: 62,
             velocity: 100,
             column: 1,
         });
-        view.clip_notes[0].push(super::ClipNote {
+        view.clip_notes[0].push(ClipNote {
             nanotick: outside,
             duration: row,
             pitch: 64,
@@ -4891,7 +11409,7 @@ mod tests {
         let mut view = super::EngineView::new_state();
         let row = view.row_nanoticks();
 
-        view.clip_notes[0].push(super::ClipNote {
+        view.clip_notes[0].push(ClipNote {
             nanotick: 0,
             duration: row,
             pitch: 60,
@@ -4924,7 +11442,7 @@ mod tests {
             root: 0,
             scale_id: 1,
         });
-        view.clip_notes[0].push(super::ClipNote {
+        view.clip_notes[0].push(ClipNote {
             nanotick: row * 20,
             duration: row,
             pitch: 64,
@@ -4937,6 +11455,35 @@ mod tests {
         let total: usize = bins.iter().sum();
         assert_eq!(total, 4);
     }
+
+    #[test]
+    fn test_jump_to_densest_region() {
+        struct TestNotify;
+        impl super::UiNotify for TestNotify {
+            fn notify(&mut self) {}
+        }
+
+        let mut view = super::EngineView::new_state();
+        let row = view.row_nanoticks();
+        let note = |nanotick: u64, column: u8| ClipNote {
+            nanotick,
+            duration: row,
+            pitch: 60,
+            velocity: 100,
+            column,
+        };
+        // The timeline ends a row after the last event, so eight bins are
+        // four rows each and rows 12-15 hold the most events.
+        view.clip_notes[0] = vec![note(0, 0), note(row * 13, 0), note(row * 31, 0)];
+        view.clip_notes[1] = vec![note(row * 14, 0), note(row * 14, 1), note(row * 15, 0)];
+        assert_eq!(view.densest_bin_start(8), Some(row * 12));
+
+        view.jump_to_densest_region(&mut TestNotify);
+        assert_eq!(view.cursor_nanotick, row * 14);
+
+        let mut empty = super::EngineView::new_state();
+        assert_eq!(empty.densest_bin_start(8), None);
+    }
 }
 
 
@@ -4966,12 +11513,25 @@ pub fn run_app() -> Result<()> {
             .unwrap();
 
         let view = window.update(cx, |_, _, cx| cx.entity()).unwrap();
+        if let Some(layout) = load_window_layout() {
+            view.update(cx, |view, cx| view.apply_window_layout(&layout, cx));
+        }
+        if let Some(markers) = load_project_markers() {
+            view.update(cx, |view, cx| view.apply_project_markers(&markers, cx));
+        }
 
         let _ = cx.on_window_closed({
+            let view = view.clone();
             let engine_supervisor = engine_supervisor.clone();
             let shutting_down = shutting_down.clone();
-            move |_cx| {
+            move |cx| {
                 shutting_down.store(true, Ordering::Relaxed);
+                if let Err(err) = save_window_layout(&view.read(cx).window_layout()) {
+                    eprintln!("daw-app: failed to save layout: {err:#}");
+                }
+                if let Err(err) = save_project_markers(&view.read(cx).project_markers()) {
+                    eprintln!("daw-app: failed to save markers: {err:#}");
+                }
                 if let Ok(mut supervisor) = engine_supervisor.lock() {
                     stop_engine_process(&mut supervisor);
                 }
@@ -5004,8 +11564,22 @@ pub fn run_app() -> Result<()> {
                         view.cancel_cell_edit(cx);
                     } else if view.jump_open {
                         view.close_jump(cx);
+                    } else if view.tempo_open {
+                        view.close_tempo(cx);
+                    } else if view.mirror_open {
+                        view.close_mirror(cx);
+                    } else if view.bar_harmony_open {
+                        view.close_bar_harmony(cx);
+                    } else if view.euclidean_open {
+                        view.close_euclidean(cx);
+                    } else if view.sustain_chord_open {
+                        view.close_sustain_chord(cx);
+                    } else if view.stash_prompt.is_some() {
+                        view.close_stash_prompt(cx);
                     } else if view.scale_browser_open {
                         view.close_scale_browser(cx);
+                    } else if view.velocity_focus && !view.palette_open {
+                        view.toggle_velocity_focus(cx);
                     } else {
                         view.close_palette(cx);
                     }
@@ -5093,6 +11667,27 @@ pub fn run_app() -> Result<()> {
                 view.update(cx, |view, cx| view.zoom_by(1, cx));
             }
         });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &PlayheadZoomIn, cx| {
+                view.update(cx, |view, cx| view.zoom_by_at_playhead(-1, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &PlayheadZoomOut, cx| {
+                view.update(cx, |view, cx| view.zoom_by_at_playhead(1, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &CopyEuclideanGrid, cx| {
+                let text = view.update(cx, |view, cx| view.euclidean_grid_for_clipboard(cx));
+                if let Some(text) = text {
+                    cx.write_to_clipboard(ClipboardItem::new_string(text));
+                }
+            }
+        });
         cx.on_action({
             let view = view.clone();
             move |_: &ScrollUp, cx| {
@@ -5169,6 +11764,232 @@ pub fn run_app() -> Result<()> {
                 view.update(cx, |view, cx| view.toggle_harmony_focus(cx));
             }
         });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleVelocityDisplay, cx| {
+                view.update(cx, |view, cx| view.toggle_velocity_display(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleVersionOverlay, cx| {
+                view.update(cx, |view, cx| view.toggle_version_overlay(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &PreviewChord, cx| {
+                view.update(cx, |view, cx| view.preview_focused_chord(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleSoloColumn, cx| {
+                view.update(cx, |view, cx| view.toggle_solo_column(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleCountIn, cx| {
+                view.update(cx, |view, cx| view.toggle_count_in(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ShiftOctaveUp, cx| {
+                view.update(cx, |view, cx| view.shift_selection_octave(1, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ShiftOctaveDown, cx| {
+                view.update(cx, |view, cx| view.shift_selection_octave(-1, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ClearClipboard, cx| {
+                view.update(cx, |view, cx| view.clear_clipboard(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleInsertMode, cx| {
+                view.update(cx, |view, cx| view.toggle_insert_mode(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ChordOctaveUp, cx| {
+                view.update(cx, |view, cx| view.bump_focused_chord_octave(1, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ChordOctaveDown, cx| {
+                view.update(cx, |view, cx| view.bump_focused_chord_octave(-1, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleDurationDisplay, cx| {
+                view.update(cx, |view, cx| view.toggle_duration_display(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleResolvedChords, cx| {
+                view.update(cx, |view, cx| view.toggle_resolved_chords(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &DeleteSelectionHarmony, cx| {
+                view.update(cx, |view, cx| view.delete_selection_harmony(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ScrollColumnsLeft, cx| {
+                view.update(cx, |view, cx| view.scroll_columns(-1, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ScrollColumnsRight, cx| {
+                view.update(cx, |view, cx| view.scroll_columns(1, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleReadOnly, cx| {
+                view.update(cx, |view, cx| view.toggle_read_only(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleDiatonicEntry, cx| {
+                view.update(cx, |view, cx| view.toggle_diatonic_entry(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &DeleteColumnNoteOffs, cx| {
+                view.update(cx, |view, cx| {
+                    let (track, column) = (view.focused_track_index, view.cursor_col as u8);
+                    view.delete_column_note_offs(track, column, cx);
+                });
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleVelocityFocus, cx| {
+                view.update(cx, |view, cx| view.toggle_velocity_focus(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleTrackArmed, cx| {
+                view.update(cx, |view, cx| {
+                    let track = view.focused_track_index;
+                    view.toggle_track_armed(track, cx);
+                });
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &JumpToLoudestNote, cx| {
+                view.update(cx, |view, cx| view.jump_to_loudest_visible_note(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleAdvanceDirection, cx| {
+                view.update(cx, |view, cx| view.toggle_advance_direction(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleHarmonyAudition, cx| {
+                view.update(cx, |view, cx| view.toggle_harmony_audition(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleInheritedHarmony, cx| {
+                view.update(cx, |view, cx| view.toggle_inherited_harmony(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleMinimapTrackDots, cx| {
+                view.update(cx, |view, cx| view.toggle_minimap_track_dots(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleEnharmonicSpelling, cx| {
+                view.update(cx, |view, cx| view.toggle_focused_enharmonic(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &JumpToPlayhead, cx| {
+                view.update(cx, |view, cx| view.jump_to_playhead(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &CycleContentFilter, cx| {
+                view.update(cx, |view, cx| view.cycle_content_filter(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &AddMarker, cx| {
+                view.update(cx, |view, cx| view.add_marker_at_cursor(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &NextMarker, cx| {
+                view.update(cx, |view, cx| view.jump_to_marker(true, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &PrevMarker, cx| {
+                view.update(cx, |view, cx| view.jump_to_marker(false, cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &FillBar, cx| {
+                view.update(cx, |view, cx| {
+                    view.fill_bar_with_note(1, cx);
+                });
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &FillBarAlternate, cx| {
+                view.update(cx, |view, cx| {
+                    view.fill_bar_with_note(2, cx);
+                });
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleVelocityEntry, cx| {
+                view.update(cx, |view, cx| view.toggle_velocity_entry(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &TogglePitchRuler, cx| {
+                view.update(cx, |view, cx| view.toggle_pitch_ruler(cx));
+            }
+        });
         cx.on_action({
             let view = view.clone();
             move |_: &Undo, cx| {
@@ -5198,6 +12019,42 @@ pub fn run_app() -> Result<()> {
                 });
             }
         });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &CopyHarmonyText, cx| {
+                let text = view.update(cx, |view, cx| view.harmony_text_for_clipboard(cx));
+                if let Some(text) = text {
+                    cx.write_to_clipboard(ClipboardItem::new_string(text));
+                }
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &CopyChordChart, cx| {
+                let text = view.update(cx, |view, cx| view.chord_chart_for_clipboard(cx));
+                if let Some(text) = text {
+                    cx.write_to_clipboard(ClipboardItem::new_string(text));
+                }
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &DuplicateBar, cx| {
+                view.update(cx, |view, cx| view.duplicate_bar(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &JumpToDensestRegion, cx| {
+                view.update(cx, |view, cx| view.jump_to_densest_region(cx));
+            }
+        });
+        cx.on_action({
+            let view = view.clone();
+            move |_: &ToggleChromaticDigits, cx| {
+                view.update(cx, |view, cx| view.toggle_chromatic_digits(cx));
+            }
+        });
         cx.on_action({
             let view = view.clone();
             move |_: &CopySelection, cx| {
@@ -5420,6 +12277,7 @@ pub fn run_app() -> Result<()> {
                                 if view.snapshot.version != snapshot.version {
                                     let old_snapshot = view.snapshot;
                                     view.snapshot = snapshot;
+                                    let clip_latched = view.latch_master_clip();
                                     // Update scroll position before render if following playhead
                                     let scroll_changed = view.update_follow_playhead();
 
@@ -5429,16 +12287,20 @@ pub fn run_app() -> Result<()> {
                                     let transport_changed = old_snapshot.ui_transport_state
                                         != snapshot.ui_transport_state;
 
-                                    if scroll_changed || transport_changed || playhead_changed {
+                                    if scroll_changed || transport_changed || playhead_changed || clip_latched {
                                         // Visual change - notify
                                         if view.ui_debug {
                                             eprintln!("[UI] async loop: scroll_changed={} transport_changed={} playhead_changed={}, notify",
                                                 scroll_changed, transport_changed, playhead_changed);
                                         }
                                         cx.notify();
+                                    } else if view.show_version_overlay {
+                                        // The version overlay shows the snapshot clip version.
+                                        cx.notify();
                                     } else {
                                         // Nothing visually meaningful changed (just version tick or clip version)
                                         // Skip notify - pending notes are already shown locally
+                                        view.acknowledge_snapshot_version();
                                         if view.ui_debug {
                                             eprintln!("[UI] async loop: no visual change, skipping notify");
                                         }
@@ -5596,6 +12458,9 @@ pub fn run_app() -> Result<()> {
                             }
                             let needs_patcher_fix =
                                 !chain_diffs.is_empty() || !patcher_diffs.is_empty();
+                            // Chain and patcher state is not versioned for rendering,
+                            // so those diffs always repaint.
+                            let force_notify = needs_patcher_fix;
                             if !chain_diffs.is_empty() ||
                                 !chain_errors.is_empty() ||
                                 !patcher_diffs.is_empty() ||
@@ -5634,9 +12499,22 @@ pub fn run_app() -> Result<()> {
                                     if needs_patcher_fix {
                                         view.auto_assign_missing_patcher_nodes(cx);
                                     }
-                                    cx.notify();
+                                    if force_notify || view.needs_render() {
+                                        cx.notify();
+                                    }
                                 });
                             }
+                            let _ = window.update(&mut async_cx, |view, _, cx| {
+                                view.check_command_acks(Instant::now(), cx);
+                            });
+                            // Pick up resyncs requested from the view itself.
+                            let (view_clip_resync, view_harmony_resync) = window
+                                .update(&mut async_cx, |view, _, _| {
+                                    (view.clip_resync_pending, view.harmony_resync_pending)
+                                })
+                                .unwrap_or((false, false));
+                            needs_clip_resync |= view_clip_resync;
+                            needs_harmony_resync |= view_harmony_resync;
                             if let Some(snapshot) = current_snapshot.as_ref() {
                                 let track_count = snapshot.ui_track_count.min(TRACK_COUNT as u32) as usize;
                                 let desired_range = window
@@ -5707,7 +12585,10 @@ pub fn run_app() -> Result<()> {
                                                 let reset = window_snapshot.cursor_event_index == 0;
                                                 let _ = window.update(&mut async_cx, |view, _, cx| {
                                                     view.apply_clip_window_page(window_snapshot, reset);
-                                                    cx.notify();
+                                                    view.resume_whole_clip_edits(cx);
+                                                    if view.needs_render() {
+                                                        cx.notify();
+                                                    }
                                                 });
                                                 state.cursor_event_index = window_snapshot.next_event_index;
                                                 state.complete =
@@ -5761,7 +12642,9 @@ pub fn run_app() -> Result<()> {
                                         view.apply_harmony_snapshot(harmony_snapshot);
                                         view.harmony_resync_pending = false;
                                         view.rebase_harmony_queue(new_version);
-                                        cx.notify();
+                                        if view.needs_render() {
+                                            cx.notify();
+                                        }
                                     });
                                 }
                             } else if !have_harmony_snapshot && last_harmony_version == 0 {
@@ -5777,7 +12660,9 @@ pub fn run_app() -> Result<()> {
                                     let _ = window.update(&mut async_cx, |view, _, cx| {
                                         view.apply_harmony_snapshot(harmony_snapshot);
                                         view.rebase_harmony_queue(new_version);
-                                        cx.notify();
+                                        if view.needs_render() {
+                                            cx.notify();
+                                        }
                                     });
                                 }
                             }
@@ -1,3 +1,5 @@
+use crate::selection::SelectionMask;
+
 #[derive(Clone, Debug)]
 pub struct ClipboardNote {
     pub track: usize,
@@ -36,3 +38,23 @@ pub struct ClipboardData {
     pub chords: Vec<ClipboardChord>,
     pub harmonies: Vec<ClipboardHarmony>,
 }
+
+/// An alternate take of a region, kept aside for comping. `start..=end` and
+/// `mask` are where it came from and where a swap puts it back.
+#[derive(Clone, Debug)]
+pub struct RegionStash {
+    pub start: u64,
+    pub end: u64,
+    pub mask: SelectionMask,
+    pub content: ClipboardData,
+}
+
+impl ClipboardData {
+    /// Smallest offset across all items, or None for an empty clipboard.
+    pub fn earliest_offset(&self) -> Option<i64> {
+        let notes = self.notes.iter().map(|note| note.offset);
+        let chords = self.chords.iter().map(|chord| chord.offset);
+        let harmonies = self.harmonies.iter().map(|harmony| harmony.offset);
+        notes.chain(chords).chain(harmonies).min()
+    }
+}
@@ -7,7 +7,12 @@ use daw_bridge::layout::{
     UiPatcherGraphErrorPayload, UI_CLIP_WINDOW_FLAG_COMPLETE,
 };
 
-use crate::app::{ChainDevice, EngineView, PatcherEdgeUi, PatcherNodeUi, PatcherPortKind, TRACK_COUNT};
+use std::time::Instant;
+
+use crate::app::{
+    command_ack_overdue, ChainDevice, EngineView, PatcherEdgeUi, PatcherNodeUi, PatcherPortKind,
+    UiNotify, TRACK_COUNT,
+};
 use crate::engine::bridge::{
     bump_ui_enqueued, bump_ui_send_fail, bump_ui_sent, log_ui_send_fail,
 };
@@ -77,6 +82,49 @@ impl EngineView {
         bump_ui_enqueued();
     }
 
+    /// Key under which a sent note or chord write waits for its confirming
+    /// diff. Only writes are tracked since the engine always answers them
+    /// with a diff.
+    fn ack_key(entry: &QueuedCommand) -> Option<(u32, u64, u8)> {
+        match entry {
+            QueuedCommand::Ui(payload)
+                if payload.command_type == UiCommandType::WriteNote as u16 =>
+            {
+                Self::note_key(payload)
+            }
+            QueuedCommand::Chord(payload)
+                if payload.command_type == UiCommandType::WriteChord as u16 =>
+            {
+                Self::chord_key(payload)
+            }
+            _ => None,
+        }
+    }
+
+    /// Flags a full resync when a note or chord write has gone unconfirmed
+    /// past the ack timeout after it was sent, so optimistic state from a stuck engine does not
+    /// linger. Only writes are tracked since the engine always answers them
+    /// with a diff. Returns whether a resync was requested.
+    pub(crate) fn check_command_acks(&mut self, now: Instant, cx: &mut impl UiNotify) -> bool {
+        if self.clip_resync_pending {
+            // A resync is already rebuilding the clip state.
+            self.command_sent_at.clear();
+            return false;
+        }
+        let overdue = self
+            .command_sent_at
+            .values()
+            .filter(|sent_at| command_ack_overdue(**sent_at, now))
+            .count();
+        if overdue == 0 {
+            return false;
+        }
+        eprintln!("daw-app: {overdue} edit(s) unacknowledged by engine, resyncing");
+        self.request_full_resync();
+        self.show_toast("Engine did not confirm edits; resyncing", cx);
+        true
+    }
+
     pub(crate) fn enqueue_chain_command(&mut self, payload: UiChainCommandPayload) {
         self.queued_commands.push_back(QueuedCommand::Chain(payload));
         bump_ui_enqueued();
@@ -110,6 +158,7 @@ impl EngineView {
         let Some(bridge) = &self.bridge else {
             return;
         };
+        let retry = self.ui_send_retry;
         while let Some(entry) = self.queued_commands.front() {
             if self.clip_resync_pending || self.harmony_resync_pending {
                 let should_pause = match entry {
@@ -144,18 +193,23 @@ impl EngineView {
                 }
             }
             let sent = match entry {
-                QueuedCommand::Ui(payload) => bridge.try_send_ui_command(*payload),
-                QueuedCommand::Chord(payload) => bridge.try_send_ui_chord_command(*payload),
-                QueuedCommand::Chain(payload) => bridge.try_send_ui_chain_command(*payload),
+                QueuedCommand::Ui(payload) => bridge.try_send_ui_command(*payload, retry),
+                QueuedCommand::Chord(payload) => bridge.try_send_ui_chord_command(*payload, retry),
+                QueuedCommand::Chain(payload) => bridge.try_send_ui_chain_command(*payload, retry),
                 QueuedCommand::PatcherGraph(payload) =>
-                    bridge.try_send_ui_patcher_graph_command(*payload),
+                    bridge.try_send_ui_patcher_graph_command(*payload, retry),
                 QueuedCommand::PatcherConfig(payload) =>
-                    bridge.try_send_ui_patcher_node_config(*payload),
+                    bridge.try_send_ui_patcher_node_config(*payload, retry),
                 QueuedCommand::PatcherPreset(payload) =>
-                    bridge.try_send_ui_patcher_preset(*payload),
+                    bridge.try_send_ui_patcher_preset(*payload, retry),
             };
             if sent {
                 bump_ui_sent();
+                // The ack timer starts here: writes held back by a full ring,
+                // a pending resync or a missing bridge are not overdue.
+                if let Some(key) = Self::ack_key(entry) {
+                    self.command_sent_at.entry(key).or_insert_with(Instant::now);
+                }
                 self.queued_commands.pop_front();
             } else {
                 bump_ui_send_fail();
@@ -196,6 +250,21 @@ impl EngineView {
         self.clip_version_local = next;
     }
 
+    /// Drops optimistic clip and harmony state and flags both for resync so
+    /// the next snapshots rebuild the view from the engine. Queued commands
+    /// are kept; they are rebased once the resync lands.
+    pub(crate) fn request_full_resync(&mut self) {
+        self.clip_resync_pending = true;
+        self.harmony_resync_pending = true;
+        self.command_sent_at.clear();
+        self.pending_notes.clear();
+        self.pending_chords.clear();
+        self.clip_version_local = 0;
+        self.harmony_version_local = 0;
+        self.bump_clip_render_version();
+        self.bump_harmony_render_version();
+    }
+
     pub(crate) fn rebase_harmony_queue(&mut self, base_version: u32) {
         let mut next = base_version;
         for entry in self.queued_commands.iter_mut() {
@@ -332,6 +401,7 @@ impl EngineView {
             self.chain_devices[track_index].clear();
         }
         if diff.device_id == K_CHAIN_DEVICE_ID_AUTO {
+            self.sync_track_name_from_chain(track_index);
             return;
         }
         let device = ChainDevice {
@@ -349,6 +419,29 @@ impl EngineView {
         } else {
             devices.push(device);
         }
+        self.sync_track_name_from_chain(track_index);
+    }
+
+    /// Names the track after the plugin hosted in its chain (instrument first),
+    /// clearing the name once no plugin device remains.
+    pub(crate) fn sync_track_name_from_chain(&mut self, track_index: usize) {
+        if !self.auto_name_tracks || track_index >= self.track_names.len() {
+            return;
+        }
+        let Some(devices) = self.chain_devices.get(track_index) else {
+            return;
+        };
+        let plugin_device = devices
+            .iter()
+            .filter(|device| device.kind == 3 || device.kind == 4)
+            .min_by_key(|device| (device.kind, device.position));
+        let name = plugin_device.and_then(|device| {
+            self.plugins
+                .iter()
+                .find(|plugin| plugin.index as u32 == device.host_slot_index)
+                .map(|plugin| plugin.name.clone())
+        });
+        self.track_names[track_index] = name;
     }
 
     pub fn apply_patcher_graph_diff(&mut self, diff: UiPatcherGraphDiffPayload) {
@@ -442,6 +535,25 @@ impl EngineView {
         }
         let nanotick =
             (diff.note_nanotick_lo as u64) | ((diff.note_nanotick_hi as u64) << 32);
+        self.command_sent_at
+            .remove(&(diff.track_id, nanotick, diff.note_column.min(255) as u8));
+        // Out-of-range MIDI values point at a protocol bug; reject the diff
+        // instead of clamping so it shows up rather than silently rewriting
+        // the note. The version still advances since the engine applied it.
+        if diff.note_pitch > 127 || diff.note_velocity > 127 {
+            eprintln!(
+                "daw-app: Rejected note diff with pitch {} velocity {} (track {}, tick {}, version {})",
+                diff.note_pitch,
+                diff.note_velocity,
+                diff.track_id,
+                nanotick,
+                diff.clip_version
+            );
+            if self.clip_version_local < diff.clip_version {
+                self.clip_version_local = diff.clip_version;
+            }
+            return;
+        }
         if !self.clip_window_contains(track_index, nanotick) {
             if self.clip_version_local < diff.clip_version {
                 self.clip_version_local = diff.clip_version;
@@ -450,8 +562,8 @@ impl EngineView {
         }
         let duration =
             (diff.note_duration_lo as u64) | ((diff.note_duration_hi as u64) << 32);
-        let pitch = diff.note_pitch.min(127) as u8;
-        let velocity = diff.note_velocity.min(127) as u8;
+        let pitch = diff.note_pitch as u8;
+        let velocity = diff.note_velocity as u8;
         let column = diff.note_column.min(255) as u8;
 
         match diff.diff_type {
@@ -559,6 +671,8 @@ impl EngineView {
             return;
         }
         let nanotick = (diff.nanotick_lo as u64) | ((diff.nanotick_hi as u64) << 32);
+        let (spread, column) = unpack_chord_spread(diff.spread_nanoticks);
+        self.command_sent_at.remove(&(diff.track_id, nanotick, column));
         if !self.clip_window_contains(track_index, nanotick) {
             if self.clip_version_local < diff.clip_version {
                 self.clip_version_local = diff.clip_version;
@@ -567,7 +681,6 @@ impl EngineView {
         }
         let duration = (diff.duration_lo as u64) | ((diff.duration_hi as u64) << 32);
         let (degree, quality, inversion, base_octave) = unpack_chord_packed(diff.packed);
-        let (spread, column) = unpack_chord_spread(diff.spread_nanoticks);
         let humanize_timing = (diff.flags & 0xff) as u16;
         let humanize_velocity = ((diff.flags >> 8) & 0xff) as u16;
 
@@ -206,6 +206,14 @@ impl EngineBridge {
         self.reader.read_snapshot()
     }
 
+    /// Engine sample rate, fixed when the engine creates the segment.
+    pub fn sample_rate(&self) -> f64 {
+        if self.header.is_null() {
+            return 0.0;
+        }
+        unsafe { (*self.header).sample_rate }
+    }
+
     fn ring_ui_view(&self) -> Option<RingView> {
         if let Some(ring) = self.ring_ui {
             return Some(ring);
@@ -410,7 +418,7 @@ impl EngineBridge {
         ok
     }
 
-    pub fn try_send_ui_command(&self, payload: UiCommandPayload) -> bool {
+    pub fn try_send_ui_command(&self, payload: UiCommandPayload, retry: Duration) -> bool {
         let Some(ring) = self.ring_ui_view() else {
             eprintln!(
                 "daw-app: UI ring unavailable; cmd {} track {}",
@@ -435,7 +443,7 @@ impl EngineBridge {
             )
         };
         entry.payload[..payload_bytes.len()].copy_from_slice(payload_bytes);
-        let ok = ring_write(&ring, entry);
+        let ok = ring_write_with_retry(&ring, entry, retry);
         if ok {
             if payload.command_type == UiCommandType::LoadPluginOnTrack as u16 ||
                 payload.command_type == UiCommandType::TogglePlay as u16 {
@@ -458,7 +466,11 @@ impl EngineBridge {
         ok
     }
 
-    pub fn try_send_ui_chain_command(&self, payload: UiChainCommandPayload) -> bool {
+    pub fn try_send_ui_chain_command(
+        &self,
+        payload: UiChainCommandPayload,
+        retry: Duration,
+    ) -> bool {
         let Some(ring) = self.ring_ui_view() else {
             return false;
         };
@@ -478,10 +490,14 @@ impl EngineBridge {
             )
         };
         entry.payload[..payload_bytes.len()].copy_from_slice(payload_bytes);
-        ring_write(&ring, entry)
+        ring_write_with_retry(&ring, entry, retry)
     }
 
-    pub fn try_send_ui_chord_command(&self, payload: UiChordCommandPayload) -> bool {
+    pub fn try_send_ui_chord_command(
+        &self,
+        payload: UiChordCommandPayload,
+        retry: Duration,
+    ) -> bool {
         let Some(ring) = self.ring_ui_view() else {
             return false;
         };
@@ -501,12 +517,13 @@ impl EngineBridge {
             )
         };
         entry.payload[..payload_bytes.len()].copy_from_slice(payload_bytes);
-        ring_write(&ring, entry)
+        ring_write_with_retry(&ring, entry, retry)
     }
 
     pub fn try_send_ui_patcher_graph_command(
         &self,
         payload: UiPatcherGraphCommandPayload,
+        retry: Duration,
     ) -> bool {
         let Some(ring) = self.ring_ui_view() else {
             return false;
@@ -527,12 +544,13 @@ impl EngineBridge {
             )
         };
         entry.payload[..payload_bytes.len()].copy_from_slice(payload_bytes);
-        ring_write(&ring, entry)
+        ring_write_with_retry(&ring, entry, retry)
     }
 
     pub fn try_send_ui_patcher_node_config(
         &self,
         payload: UiPatcherNodeConfigPayload,
+        retry: Duration,
     ) -> bool {
         let Some(ring) = self.ring_ui_view() else {
             return false;
@@ -553,12 +571,13 @@ impl EngineBridge {
             )
         };
         entry.payload[..payload_bytes.len()].copy_from_slice(payload_bytes);
-        ring_write(&ring, entry)
+        ring_write_with_retry(&ring, entry, retry)
     }
 
     pub fn try_send_ui_patcher_preset(
         &self,
         payload: UiPatcherPresetCommandPayload,
+        retry: Duration,
     ) -> bool {
         let Some(ring) = self.ring_ui_view() else {
             return false;
@@ -579,7 +598,7 @@ impl EngineBridge {
             )
         };
         entry.payload[..payload_bytes.len()].copy_from_slice(payload_bytes);
-        ring_write(&ring, entry)
+        ring_write_with_retry(&ring, entry, retry)
     }
 
     pub fn pop_ui_event(&self) -> Option<EventEntry> {
@@ -716,7 +735,8 @@ fn ring_write(ring: &RingView, entry: EventEntry) -> bool {
     true
 }
 
-#[allow(dead_code)]
+/// Retries a full ring until `timeout` passes, yielding at first and then
+/// sleeping briefly. A zero timeout makes exactly one attempt.
 fn ring_write_with_retry(ring: &RingView, entry: EventEntry, timeout: Duration) -> bool {
     let start = Instant::now();
     let mut spins = 0_u32;
@@ -895,3 +915,55 @@ pub fn decode_chord_diff(entry: &EventEntry) -> Option<UiChordDiffPayload> {
     payload_bytes.copy_from_slice(&entry.payload[..payload_bytes.len()]);
     Some(payload)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[test]
+    fn test_ring_write_retry_outlasts_a_briefly_full_ring() {
+        #[repr(C, align(64))]
+        #[derive(Clone, Copy)]
+        struct Block([u8; 64]);
+
+        // Header at offset 64 so `ring_view` accepts it, then four entries.
+        let mut buffer = vec![Block([0; 64]); 8];
+        let base = buffer.as_mut_ptr() as *mut u8;
+        unsafe {
+            std::ptr::write(
+                base.add(64) as *mut RingHeader,
+                RingHeader {
+                    capacity: 4,
+                    entry_size: std::mem::size_of::<EventEntry>() as u32,
+                    read_index: AtomicU32::new(0),
+                    write_index: AtomicU32::new(0),
+                    reserved: [0; 12],
+                },
+            );
+        }
+        let ring = ring_view(base, 64).expect("ring");
+        let entry = EventEntry {
+            sample_time: 0,
+            block_id: 0,
+            event_type: 0,
+            size: 0,
+            flags: 0,
+            payload: [0; 40],
+        };
+        for _ in 0..3 {
+            assert!(ring_write_with_retry(&ring, entry, Duration::ZERO));
+        }
+        assert!(!ring_write_with_retry(&ring, entry, Duration::ZERO));
+
+        let address = base as usize;
+        let reader = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(2));
+            let ring = ring_view(address as *mut u8, 64).expect("ring");
+            ring_pop(&ring).is_some()
+        });
+        assert!(ring_write_with_retry(&ring, entry, Duration::from_secs(1)));
+        assert!(reader.join().unwrap());
+        drop(buffer);
+    }
+}
@@ -0,0 +1,62 @@
+use patcher_rust::{patcher_euclidean_pattern, PatcherEuclideanConfig};
+
+/// Longest pattern the UI will render into notes, matching the patcher node's
+/// fixed step buffer.
+pub const EUCLIDEAN_MAX_STEPS: u32 = patcher_rust::EUCLIDEAN_MAX_STEPS as u32;
+
+/// Node config for a plain `steps`/`hits` rhythm with the node's Bjorklund
+/// distribution and no rotation.
+pub fn euclidean_config(steps: u32, hits: u32, offset: u32) -> PatcherEuclideanConfig {
+    PatcherEuclideanConfig {
+        steps,
+        hits,
+        offset,
+        rotation: 0,
+        duration_ticks: 0,
+        degree: 1,
+        octave_offset: 0,
+        velocity: 100,
+        base_octave: 4,
+        distribution: patcher_rust::EUCLIDEAN_DISTRIBUTION_BJORKLUND,
+        swing: 0,
+        seed: 0,
+        humanize_timing: 0,
+        humanize_velocity: 0,
+        accent_velocity: 0,
+        loop_advance: 0,
+        loop_random: 0,
+        _pad0: [0u8; 2],
+        accent_mask: 0,
+    }
+}
+
+/// One bar of step flags (1 = hit) as the patcher node plays them, from
+/// `patcher_euclidean_pattern`. `None` reports the node defaults.
+pub fn euclidean_pattern(config: Option<&PatcherEuclideanConfig>) -> Vec<u8> {
+    let config = config.map_or(core::ptr::null(), |config| config as *const _);
+    // SAFETY: `config` is null or borrowed for the call, and `pattern` is
+    // valid for the capacity passed.
+    unsafe {
+        let steps = patcher_euclidean_pattern(config, core::ptr::null_mut(), 0);
+        let mut pattern = vec![0u8; steps as usize];
+        patcher_euclidean_pattern(config, pattern.as_mut_ptr(), steps);
+        pattern
+    }
+}
+
+/// Hit flags for one bar of a `steps`/`hits` rhythm with `offset` applied so
+/// index 0 is the bar's first step.
+pub fn euclidean_steps(steps: u32, hits: u32, offset: u32) -> Vec<bool> {
+    // The node treats zero steps or hits as "use the default", so handle the
+    // empty rhythms here.
+    if steps == 0 {
+        return Vec::new();
+    }
+    if hits == 0 {
+        return vec![false; steps as usize];
+    }
+    euclidean_pattern(Some(&euclidean_config(steps, hits.min(steps), offset)))
+        .into_iter()
+        .map(|step| step != 0)
+        .collect()
+}
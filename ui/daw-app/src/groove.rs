@@ -0,0 +1,38 @@
+use crate::app::NANOTICKS_PER_QUARTER;
+
+/// Grooves work on sixteenth-note steps regardless of the tracker zoom.
+pub const GROOVE_STEP_NANOTICKS: u64 = NANOTICKS_PER_QUARTER / 4;
+
+/// Per-step timing feel. Offsets are in percent of a step and repeat over
+/// the length of the template.
+#[derive(Clone, Copy, Debug)]
+pub struct GrooveTemplate {
+    pub name: &'static str,
+    pub offsets: &'static [i32],
+}
+
+/// MPC-style swing: the swing amount is where the second sixteenth of each
+/// pair lands inside the eighth, so 58% delays it by 16% of a step.
+pub const GROOVE_TEMPLATES: &[GrooveTemplate] = &[
+    GrooveTemplate {
+        name: "MPC Swing 58%",
+        offsets: &[0, 16],
+    },
+    GrooveTemplate {
+        name: "MPC Swing 66%",
+        offsets: &[0, 32],
+    },
+];
+
+impl GrooveTemplate {
+    /// Nanotick offset for an event at `nanotick`, taken from the step it
+    /// falls in.
+    pub fn offset_at(&self, nanotick: u64) -> i64 {
+        if self.offsets.is_empty() {
+            return 0;
+        }
+        let step = nanotick / GROOVE_STEP_NANOTICKS;
+        let percent = self.offsets[(step % self.offsets.len() as u64) as usize];
+        GROOVE_STEP_NANOTICKS as i64 * percent as i64 / 100
+    }
+}
@@ -3,13 +3,15 @@ pub struct ScaleInfo {
     pub id: u32,
     pub name: &'static str,
     pub key: &'static str,
+    /// Semitones above the root, matching the engine's scale registry.
+    pub steps: &'static [u8],
 }
 
 pub const SCALE_LIBRARY: &[ScaleInfo] = &[
-    ScaleInfo { id: 1, name: "maj", key: "1" },
-    ScaleInfo { id: 2, name: "min", key: "2" },
-    ScaleInfo { id: 3, name: "dor", key: "3" },
-    ScaleInfo { id: 4, name: "mix", key: "4" },
+    ScaleInfo { id: 1, name: "maj", key: "1", steps: &[0, 2, 4, 5, 7, 9, 11] },
+    ScaleInfo { id: 2, name: "min", key: "2", steps: &[0, 2, 3, 5, 7, 8, 10] },
+    ScaleInfo { id: 3, name: "dor", key: "3", steps: &[0, 2, 3, 5, 7, 9, 10] },
+    ScaleInfo { id: 4, name: "mix", key: "4", steps: &[0, 2, 4, 5, 7, 9, 10] },
 ];
 
 pub fn harmony_root_name(root: u32) -> &'static str {
@@ -30,6 +32,18 @@ pub fn harmony_root_name(root: u32) -> &'static str {
     }
 }
 
+pub fn harmony_root_from_name(name: &str) -> Option<u32> {
+    (0..12).find(|root| harmony_root_name(*root).eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+pub fn harmony_scale_id_from_name(name: &str) -> Option<u32> {
+    SCALE_LIBRARY
+        .iter()
+        .find(|scale| scale.name.eq_ignore_ascii_case(name))
+        .map(|scale| scale.id)
+}
+
 pub fn harmony_scale_name(scale_id: u32) -> &'static str {
     for scale in SCALE_LIBRARY {
         if scale.id == scale_id {
@@ -38,3 +52,113 @@ pub fn harmony_scale_name(scale_id: u32) -> &'static str {
     }
     "chr"
 }
+
+/// Name of the seventh chord built on the scale's first degree, e.g. `Cmaj7`
+/// for C major or `Gm7` for G dorian. An unknown scale gives just the root.
+pub fn implied_chord_name(root: u32, scale_id: u32) -> String {
+    let root_name = harmony_root_name(root);
+    let Some(scale) = SCALE_LIBRARY.iter().find(|scale| scale.id == scale_id) else {
+        return root_name.to_string();
+    };
+    let suffix = match (scale.steps[2], scale.steps[4], scale.steps[6]) {
+        (4, 7, 11) => "maj7",
+        (4, 7, 10) => "7",
+        (3, 7, 10) => "m7",
+        (3, 7, 11) => "mMaj7",
+        (3, 6, 10) => "m7b5",
+        _ => "",
+    };
+    format!("{root_name}{suffix}")
+}
+
+/// Chord shapes recognized by `chord_name_for_pitches`, as semitones above
+/// the root.
+const CHORD_SHAPES: &[(&str, &[u8])] = &[
+    ("maj", &[0, 4, 7]),
+    ("min", &[0, 3, 7]),
+    ("dim", &[0, 3, 6]),
+    ("aug", &[0, 4, 8]),
+    ("7", &[0, 4, 7, 10]),
+    ("maj7", &[0, 4, 7, 11]),
+    ("min7", &[0, 3, 7, 10]),
+    ("m7b5", &[0, 3, 6, 10]),
+    ("dim7", &[0, 3, 6, 9]),
+];
+
+/// Names the chord formed by the distinct pitch classes of `pitches`, e.g.
+/// `C maj` for C, E and G in any octave or inversion. Symmetric shapes such
+/// as aug and dim7 are named after their lowest note.
+pub fn chord_name_for_pitches(pitches: &[u8]) -> Option<String> {
+    let mut sorted = pitches.to_vec();
+    sorted.sort_unstable();
+    let mut classes: Vec<u8> = sorted.iter().map(|pitch| pitch % 12).collect();
+    let mut roots = classes.clone();
+    roots.dedup();
+    classes.sort_unstable();
+    classes.dedup();
+    for root in roots {
+        let mut intervals: Vec<u8> =
+            classes.iter().map(|class| (class + 12 - root) % 12).collect();
+        intervals.sort_unstable();
+        if let Some((name, _)) = CHORD_SHAPES.iter().find(|(_, shape)| *shape == intervals) {
+            return Some(format!("{} {name}", harmony_root_name(root as u32)));
+        }
+    }
+    None
+}
+
+/// Whether `pitch` is a tone of the scale on `root`. An unknown scale is
+/// treated as chromatic, so every pitch fits.
+pub fn scale_contains_pitch(pitch: u8, root: u32, scale_id: u32) -> bool {
+    let Some(scale) = SCALE_LIBRARY.iter().find(|scale| scale.id == scale_id) else {
+        return true;
+    };
+    let interval = (pitch as u32 + 12 - root % 12) % 12;
+    scale.steps.contains(&(interval as u8))
+}
+
+/// MIDI pitch for a 1-based scale degree, mirroring the engine's
+/// `resolveDegree`: degrees past the scale length wrap into higher octaves,
+/// and an unknown scale resolves to the root.
+pub fn resolve_degree_pitch(degree: u8, base_octave: u8, root: u32, scale_id: u32) -> u8 {
+    let root_pitch = (base_octave as i32 + 1) * 12 + (root % 12) as i32;
+    let Some(scale) = SCALE_LIBRARY.iter().find(|scale| scale.id == scale_id) else {
+        return root_pitch.clamp(0, 127) as u8;
+    };
+    let index = degree.max(1) as usize - 1;
+    let steps = scale.steps;
+    let octave = (index / steps.len()) as i32;
+    let pitch = root_pitch + octave * 12 + steps[index % steps.len()] as i32;
+    pitch.clamp(0, 127) as u8
+}
+
+/// Sounding pitches of a chord cell, lowest first, mirroring the engine's
+/// `resolveChordPitches`: quality 0 is a single degree, 1 a triad and
+/// anything higher a seventh, and each inversion lifts the lowest tone an
+/// octave.
+pub fn resolve_chord_pitches(
+    degree: u8,
+    quality: u8,
+    inversion: u8,
+    base_octave: u8,
+    root: u32,
+    scale_id: u32,
+) -> Vec<u8> {
+    let count = match quality {
+        0 => 1,
+        1 => 3,
+        _ => 4,
+    };
+    let mut pitches: Vec<u8> = (0..count)
+        .map(|tone| {
+            resolve_degree_pitch(degree.saturating_add(tone * 2), base_octave, root, scale_id)
+        })
+        .collect();
+    pitches.sort_unstable();
+    for _ in 0..inversion {
+        let lowest = pitches.remove(0);
+        pitches.push(lowest.saturating_add(12).min(127));
+        pitches.sort_unstable();
+    }
+    pitches
+}
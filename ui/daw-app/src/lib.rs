@@ -17,13 +17,17 @@ pub use crate::engine::supervisor::{default_engine_path, spawn_engine_process, s
 mod app;
 mod clipboard;
 mod commands;
+mod euclidean;
+mod groove;
 mod harmony;
 mod palette;
 mod plugins;
+mod project_markers;
 mod scale_browser;
 mod selection;
 mod state;
 mod util;
+mod window_layout;
 mod engine;
 mod tracker;
 mod ui;
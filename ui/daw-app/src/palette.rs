@@ -8,6 +8,48 @@ pub enum PaletteMode {
 pub enum PaletteCommandId {
     LoadPlugin,
     SetHarmonyScale,
+    ToggleAutoNameTracks,
+    ResyncClipsAndHarmony,
+    ReharmonizeTrack,
+    JoinNotes,
+    CycleQuantizeStrength,
+    SetTempo,
+    SetHarmonyRange,
+    DuplicateHarmony,
+    RotateSelectionDown,
+    RotateSelectionUp,
+    CycleVelocityDigitScale,
+    LegatoColumn,
+    LoopWholeTimeline,
+    ToggleMinimapBarNumbers,
+    BakeSelectionToNotes,
+    SnapLoopToBars,
+    SwapTrackWithNext,
+    ToggleRests,
+    TransposeProjectUp,
+    TransposeProjectDown,
+    CollapseHarmonyChanges,
+    MirrorSelectionToTrack,
+    ApplyGrooveSwing58,
+    ApplyGrooveSwing66,
+    SetChordsOneRow,
+    SetChordsFourRows,
+    RandomizeInversions,
+    FillBarsWithHarmony,
+    TrimTrackToLoop,
+    TrimAllTracksToLoop,
+    ReportOutOfScaleNotes,
+    WriteEuclideanPattern,
+    MoveColumnLeft,
+    MoveColumnRight,
+    FitTrackColumns,
+    StashRegion,
+    SwapStash,
+    ClampTrackDurations,
+    NameSelectionChord,
+    WriteSustainedChord,
+    DelayTrack,
+    AdvanceTrack,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -28,4 +70,214 @@ pub const PALETTE_COMMANDS: &[PaletteCommand] = &[
         label: "Set Harmony Scale…",
         hint: "Cmd+Shift+S",
     },
+    PaletteCommand {
+        id: PaletteCommandId::ToggleAutoNameTracks,
+        label: "Toggle Auto-Name Tracks",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::ResyncClipsAndHarmony,
+        label: "Resync Clips and Harmony",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::ReharmonizeTrack,
+        label: "Reharmonize Track to Scale…",
+        hint: "Enter",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::JoinNotes,
+        label: "Join Note with Previous",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::CycleQuantizeStrength,
+        label: "Cycle Input Quantize Strength",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::SetTempo,
+        label: "Set Tempo (BPM)…",
+        hint: "Enter",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::SetHarmonyRange,
+        label: "Set Harmony over Selection…",
+        hint: "Enter",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::DuplicateHarmony,
+        label: "Duplicate Harmony Progression",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::RotateSelectionDown,
+        label: "Rotate Selection Down",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::RotateSelectionUp,
+        label: "Rotate Selection Up",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::CycleVelocityDigitScale,
+        label: "Cycle Velocity Digit Scaling",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::LegatoColumn,
+        label: "Make Column Legato",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::LoopWholeTimeline,
+        label: "Loop Whole Timeline",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::ToggleMinimapBarNumbers,
+        label: "Toggle Minimap Bar Numbers",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::BakeSelectionToNotes,
+        label: "Bake Selected Degrees to Notes",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::SnapLoopToBars,
+        label: "Snap Loop to Bars",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::SwapTrackWithNext,
+        label: "Swap Track with Next",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::ToggleRests,
+        label: "Toggle Rest Markers",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::TransposeProjectUp,
+        label: "Transpose Project Up a Semitone",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::TransposeProjectDown,
+        label: "Transpose Project Down a Semitone",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::CollapseHarmonyChanges,
+        label: "Collapse Harmony to Changes",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::MirrorSelectionToTrack,
+        label: "Mirror Selection to Track…",
+        hint: "Enter",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::ApplyGrooveSwing58,
+        label: "Apply Groove: MPC Swing 58%",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::ApplyGrooveSwing66,
+        label: "Apply Groove: MPC Swing 66%",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::SetChordsOneRow,
+        label: "Set Selected Chords to 1 Row",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::SetChordsFourRows,
+        label: "Set Selected Chords to 4 Rows",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::RandomizeInversions,
+        label: "Randomize Selected Chord Inversions",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::FillBarsWithHarmony,
+        label: "Write Harmony on Each Bar…",
+        hint: "Enter",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::TrimTrackToLoop,
+        label: "Trim Track to Loop",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::TrimAllTracksToLoop,
+        label: "Trim All Tracks to Loop",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::ReportOutOfScaleNotes,
+        label: "Find Out-of-Scale Notes in Selection",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::WriteEuclideanPattern,
+        label: "Write Euclidean Pattern into Bar…",
+        hint: "Enter",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::MoveColumnLeft,
+        label: "Move Column Contents Left",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::MoveColumnRight,
+        label: "Move Column Contents Right",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::FitTrackColumns,
+        label: "Fit Track Columns to Content",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::StashRegion,
+        label: "Stash Selection as Named Take",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::SwapStash,
+        label: "Swap Region with Named Take",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::ClampTrackDurations,
+        label: "Clamp Track Note Lengths to One Bar",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::NameSelectionChord,
+        label: "Name Chord in Selection",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::WriteSustainedChord,
+        label: "Write Chord Across Selection",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::DelayTrack,
+        label: "Delay Track Playback",
+        hint: "",
+    },
+    PaletteCommand {
+        id: PaletteCommandId::AdvanceTrack,
+        label: "Play Track Early",
+        hint: "",
+    },
 ];
@@ -0,0 +1,66 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context as AnyhowContext, Result};
+use serde::{Deserialize, Serialize};
+
+pub const PROJECT_MARKERS_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProjectMarker {
+    pub nanotick: u64,
+    pub name: String,
+}
+
+/// Markers belong to the project, not to the window, so they are kept in
+/// the project directory next to `project.json` using the same field names.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectMarkers {
+    pub schema_version: u32,
+    pub markers: Vec<ProjectMarker>,
+}
+
+impl Default for ProjectMarkers {
+    fn default() -> Self {
+        Self {
+            schema_version: PROJECT_MARKERS_SCHEMA_VERSION,
+            markers: Vec::new(),
+        }
+    }
+}
+
+fn default_project_markers_path() -> PathBuf {
+    if let Ok(dir) = std::env::var("DAW_PROJECT_DIR") {
+        return PathBuf::from(dir).join("markers.json");
+    }
+    PathBuf::from("build/project/markers.json")
+}
+
+pub fn load_project_markers() -> Option<ProjectMarkers> {
+    let path = default_project_markers_path();
+    let json = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<ProjectMarkers>(&json) {
+        Ok(markers) => Some(markers),
+        Err(err) => {
+            eprintln!(
+                "daw-app: ignoring markers file {}: {err}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+pub fn save_project_markers(markers: &ProjectMarkers) -> Result<()> {
+    let path = default_project_markers_path();
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(markers)
+        .context("failed to serialize markers")?;
+    fs::write(&path, json)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
@@ -13,6 +13,19 @@ pub enum QueuedCommand {
     PatcherPreset(UiPatcherPresetCommandPayload),
 }
 
+impl QueuedCommand {
+    pub fn command_type(&self) -> u16 {
+        match self {
+            QueuedCommand::Ui(payload) => payload.command_type,
+            QueuedCommand::Chord(payload) => payload.command_type,
+            QueuedCommand::Chain(payload) => payload.command_type,
+            QueuedCommand::PatcherGraph(payload) => payload.command_type,
+            QueuedCommand::PatcherConfig(payload) => payload.command_type,
+            QueuedCommand::PatcherPreset(payload) => payload.command_type,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PendingNote {
     pub track_id: u32,
@@ -75,6 +88,67 @@ pub enum CellKind {
     Chord,
 }
 
+/// Which cell kinds a track shows; hidden kinds render as empty cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CellContentFilter {
+    #[default]
+    All,
+    NotesOnly,
+    ChordsOnly,
+}
+
+impl CellContentFilter {
+    pub fn next(self) -> Self {
+        match self {
+            CellContentFilter::All => CellContentFilter::NotesOnly,
+            CellContentFilter::NotesOnly => CellContentFilter::ChordsOnly,
+            CellContentFilter::ChordsOnly => CellContentFilter::All,
+        }
+    }
+
+    pub fn shows(self, kind: &CellKind) -> bool {
+        match self {
+            CellContentFilter::All => true,
+            CellContentFilter::NotesOnly => matches!(kind, CellKind::Note),
+            CellContentFilter::ChordsOnly => matches!(kind, CellKind::Chord),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CellContentFilter::All => "notes and chords",
+            CellContentFilter::NotesOnly => "notes only",
+            CellContentFilter::ChordsOnly => "chords only",
+        }
+    }
+}
+
+/// How typed digits map to a note velocity in velocity entry mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VelocityDigitScale {
+    /// Two digits 00-99 stretched over 1..=127.
+    #[default]
+    Scaled,
+    /// Up to three digits taken as the MIDI velocity itself.
+    Direct,
+}
+
+impl VelocityDigitScale {
+    pub fn next(self) -> Self {
+        match self {
+            VelocityDigitScale::Scaled => VelocityDigitScale::Direct,
+            VelocityDigitScale::Direct => VelocityDigitScale::Scaled,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            VelocityDigitScale::Scaled => "00-99 scaled",
+            VelocityDigitScale::Direct => "0-127 direct",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
 pub struct CellEntry {
@@ -85,11 +159,14 @@ pub struct CellEntry {
     pub chord_id: Option<u32>,
     pub column: usize,
     pub note_off: bool,
+    /// Optimistic edit not yet confirmed by the engine.
+    pub pending: bool,
 }
 
 #[derive(Clone, Debug)]
 pub struct AggregateCell {
     pub count: usize,
+    pub chord_count: usize,
     pub notes_only: bool,
     pub note_off_only: bool,
     pub unique_pitch: Option<u8>,
@@ -101,6 +178,7 @@ impl AggregateCell {
     pub fn new() -> Self {
         Self {
             count: 0,
+            chord_count: 0,
             notes_only: true,
             note_off_only: true,
             unique_pitch: None,
@@ -129,6 +207,7 @@ impl AggregateCell {
 
     pub fn add_chord(&mut self, chord: ClipChord) {
         self.count += 1;
+        self.chord_count += 1;
         if self.count == 1 {
             self.single = Some(AggregateSingle::Chord(chord));
         } else {
@@ -1,6 +1,7 @@
 pub const COLUMN_WIDTH: f32 = 52.0;
 pub const TIME_COLUMN_WIDTH: f32 = 105.0;
 pub const HARMONY_COLUMN_WIDTH: f32 = COLUMN_WIDTH;
+pub const PITCH_RULER_WIDTH: f32 = 32.0;
 pub const ROW_HEIGHT: f32 = 16.0;
 pub const FOLLOW_PLAYHEAD_LOWER: f32 = 0.25;
 pub const FOLLOW_PLAYHEAD_UPPER: f32 = 0.75;
@@ -30,6 +30,49 @@ actions!(
         ExpandSelectionRight,
         ToggleFollowPlayhead,
         ToggleHarmonyFocus,
+        TogglePitchRuler,
+        ToggleVelocityDisplay,
+        ToggleVersionOverlay,
+        PreviewChord,
+        ToggleSoloColumn,
+        ToggleCountIn,
+        ShiftOctaveUp,
+        ShiftOctaveDown,
+        ClearClipboard,
+        ToggleInsertMode,
+        ChordOctaveUp,
+        ChordOctaveDown,
+        ToggleDurationDisplay,
+        JumpToPlayhead,
+        CycleContentFilter,
+        AddMarker,
+        NextMarker,
+        PrevMarker,
+        FillBar,
+        FillBarAlternate,
+        ToggleVelocityEntry,
+        ToggleResolvedChords,
+        DeleteSelectionHarmony,
+        ScrollColumnsLeft,
+        ScrollColumnsRight,
+        ToggleReadOnly,
+        ToggleDiatonicEntry,
+        DeleteColumnNoteOffs,
+        ToggleVelocityFocus,
+        ToggleTrackArmed,
+        JumpToLoudestNote,
+        ToggleAdvanceDirection,
+        ToggleHarmonyAudition,
+        ToggleInheritedHarmony,
+        ToggleMinimapTrackDots,
+        ToggleEnharmonicSpelling,
+        CopyChordChart,
+        DuplicateBar,
+        JumpToDensestRegion,
+        ToggleChromaticDigits,
+        PlayheadZoomIn,
+        PlayheadZoomOut,
+        CopyEuclideanGrid,
         ColumnLeft,
         ColumnRight,
         CommitCellEdit,
@@ -38,6 +81,7 @@ actions!(
         FocusRight,
         OpenJump,
         CopySelection,
+        CopyHarmonyText,
         CutSelection,
         PasteSelection,
         PageCut,
@@ -67,6 +111,49 @@ pub fn bind_keys(cx: &mut App) {
         KeyBinding::new("space", TogglePlay, None),
         KeyBinding::new("f", ToggleFollowPlayhead, None),
         KeyBinding::new("ctrl-h", ToggleHarmonyFocus, None),
+        KeyBinding::new("ctrl-p", TogglePitchRuler, None),
+        KeyBinding::new("ctrl-shift-v", ToggleVelocityDisplay, None),
+        KeyBinding::new("ctrl-shift-d", ToggleVersionOverlay, None),
+        KeyBinding::new("ctrl-shift-a", PreviewChord, None),
+        KeyBinding::new("ctrl-shift-s", ToggleSoloColumn, None),
+        KeyBinding::new("ctrl-shift-k", ToggleCountIn, None),
+        KeyBinding::new("ctrl-shift-up", ShiftOctaveUp, None),
+        KeyBinding::new("ctrl-shift-down", ShiftOctaveDown, None),
+        KeyBinding::new("cmd-shift-x", ClearClipboard, None),
+        KeyBinding::new("insert", ToggleInsertMode, None),
+        KeyBinding::new("ctrl-alt-up", ChordOctaveUp, None),
+        KeyBinding::new("ctrl-alt-down", ChordOctaveDown, None),
+        KeyBinding::new("ctrl-shift-l", ToggleDurationDisplay, None),
+        KeyBinding::new("ctrl-shift-f", JumpToPlayhead, None),
+        KeyBinding::new("ctrl-shift-t", CycleContentFilter, None),
+        KeyBinding::new("ctrl-shift-m", AddMarker, None),
+        KeyBinding::new("ctrl-alt-right", NextMarker, None),
+        KeyBinding::new("ctrl-alt-left", PrevMarker, None),
+        KeyBinding::new("ctrl-shift-b", FillBar, None),
+        KeyBinding::new("ctrl-alt-shift-b", FillBarAlternate, None),
+        KeyBinding::new("ctrl-shift-e", ToggleVelocityEntry, None),
+        KeyBinding::new("ctrl-shift-r", ToggleResolvedChords, None),
+        KeyBinding::new("ctrl-shift-h", DeleteSelectionHarmony, None),
+        KeyBinding::new("ctrl-shift-left", ScrollColumnsLeft, None),
+        KeyBinding::new("ctrl-shift-right", ScrollColumnsRight, None),
+        KeyBinding::new("ctrl-shift-o", ToggleReadOnly, None),
+        KeyBinding::new("ctrl-shift-i", ToggleDiatonicEntry, None),
+        KeyBinding::new("ctrl-alt-o", DeleteColumnNoteOffs, None),
+        KeyBinding::new("ctrl-alt-v", ToggleVelocityFocus, None),
+        KeyBinding::new("ctrl-alt-r", ToggleTrackArmed, None),
+        KeyBinding::new("ctrl-alt-l", JumpToLoudestNote, None),
+        KeyBinding::new("ctrl-alt-a", ToggleAdvanceDirection, None),
+        KeyBinding::new("ctrl-alt-h", ToggleHarmonyAudition, None),
+        KeyBinding::new("ctrl-alt-k", ToggleInheritedHarmony, None),
+        KeyBinding::new("ctrl-alt-m", ToggleMinimapTrackDots, None),
+        KeyBinding::new("ctrl-alt-e", ToggleEnharmonicSpelling, None),
+        KeyBinding::new("ctrl-alt-c", CopyChordChart, None),
+        KeyBinding::new("ctrl-alt-d", DuplicateBar, None),
+        KeyBinding::new("ctrl-alt-j", JumpToDensestRegion, None),
+        KeyBinding::new("ctrl-alt-n", ToggleChromaticDigits, None),
+        KeyBinding::new("cmd-alt-=", PlayheadZoomIn, None),
+        KeyBinding::new("cmd-alt--", PlayheadZoomOut, None),
+        KeyBinding::new("ctrl-alt-g", CopyEuclideanGrid, None),
         KeyBinding::new("enter", PaletteConfirm, None),
         KeyBinding::new("backspace", PaletteBackspace, None),
         KeyBinding::new("delete", DeleteNote, None),
@@ -74,6 +161,7 @@ pub fn bind_keys(cx: &mut App) {
         KeyBinding::new("cmd-=", PageZoomIn, None),
         KeyBinding::new("cmd--", PageZoomOut, None),
         KeyBinding::new("cmd-c", CopySelection, None),
+        KeyBinding::new("cmd-shift-c", CopyHarmonyText, None),
         KeyBinding::new("cmd-x", CutSelection, None),
         KeyBinding::new("cmd-v", PasteSelection, None),
         KeyBinding::new("cmd-d", DuplicateDevice, None),
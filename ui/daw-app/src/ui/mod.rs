@@ -6,3 +6,4 @@ pub mod render_scale_browser;
 pub mod render_tracker;
 pub mod render_device_chain;
 pub mod render_patcher;
+pub mod render_meter;
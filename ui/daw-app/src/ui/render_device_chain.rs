@@ -3,7 +3,7 @@ use gpui::prelude::*;
 
 use crate::app::{
     ChainAddMode, ChainDevice, EngineView, TRACK_COUNT, PATCHER_NODE_AUDIO_PASSTHROUGH,
-    PATCHER_NODE_EUCLIDEAN, PATCHER_NODE_EVENT_OUT, PATCHER_NODE_LFO,
+    PATCHER_NODE_EUCLIDEAN, PATCHER_NODE_EVENT_OUT, PATCHER_NODE_HARMONY_ARP, PATCHER_NODE_LFO,
     PATCHER_NODE_PASSTHROUGH, PATCHER_NODE_RANDOM_DEGREE, PATCHER_NODE_RUST,
 };
 
@@ -34,6 +34,7 @@ fn patcher_node_type_label(node_type: u32) -> &'static str {
         PATCHER_NODE_LFO => "LFO",
         PATCHER_NODE_RANDOM_DEGREE => "Random Degree",
         PATCHER_NODE_EVENT_OUT => "Event Out",
+        PATCHER_NODE_HARMONY_ARP => "Harmony Arp",
         _ => "Node",
     }
 }
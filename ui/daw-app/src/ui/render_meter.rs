@@ -0,0 +1,59 @@
+use gpui::{div, px, rgb, Context, IntoElement, MouseButton};
+use gpui::prelude::*;
+
+use crate::app::EngineView;
+
+const METER_WIDTH: f32 = 80.0;
+const METER_BAR_HEIGHT: f32 = 4.0;
+
+impl EngineView {
+    /// Master L/R peak bars plus a clip LED that stays red until clicked.
+    pub(crate) fn render_master_meter(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let bars = self.snapshot.ui_master_peak.iter().map(|peak| {
+            let level = peak.clamp(0.0, 1.0);
+            let color = if *peak > 1.0 {
+                rgb(0xd9534f)
+            } else if level > 0.7 {
+                rgb(0xd0b040)
+            } else {
+                rgb(0x6fb27f)
+            };
+            div()
+                .w(px(METER_WIDTH))
+                .h(px(METER_BAR_HEIGHT))
+                .bg(rgb(0x18222b))
+                .child(
+                    div()
+                        .w(px(METER_WIDTH * level))
+                        .h_full()
+                        .bg(color),
+                )
+        });
+        let led_color = if self.master_clip_latched {
+            rgb(0xd9534f)
+        } else {
+            rgb(0x253240)
+        };
+        div()
+            .flex()
+            .items_center()
+            .gap_1()
+            .ml_2()
+            .child(div().flex().flex_col().gap_0p5().children(bars))
+            .child(
+                div()
+                    .w(px(10.0))
+                    .h(px(10.0))
+                    .rounded_full()
+                    .bg(led_color)
+                    .border_1()
+                    .border_color(rgb(0x3a4555))
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|view, _, _, cx| {
+                            view.clear_master_clip(cx);
+                        }),
+                    ),
+            )
+    }
+}
@@ -1,9 +1,68 @@
-use gpui::{div, px, rgb, Context, IntoElement, MouseButton, MouseMoveEvent};
+use gpui::{div, px, rgb, Context, IntoElement, MouseButton, MouseDownEvent, MouseMoveEvent};
 use gpui::prelude::*;
 
-use crate::app::EngineView;
+use crate::app::{EngineView, BEATS_PER_BAR, NANOTICKS_PER_QUARTER, TRACK_COUNT};
 use crate::tracker::{HEADER_HEIGHT, MINIMAP_WIDTH, ROW_HEIGHT, VISIBLE_ROWS};
 
+/// Closest two bar ticks may sit in the minimap before bars are skipped.
+const MINIMAP_BAR_TICK_SPACING: f32 = 4.0;
+/// Closest two bar-number labels may sit in the minimap.
+const MINIMAP_BAR_LABEL_SPACING: f32 = 24.0;
+
+/// Dot colors for track states in the minimap header.
+const TRACK_DOT_ARMED: u32 = 0xd04545;
+const TRACK_DOT_SOLOED: u32 = 0xd8b04a;
+const TRACK_DOT_MUTED: u32 = 0x4a5566;
+const TRACK_DOT_IDLE: u32 = 0x232a38;
+
+/// Color of a track's dot in the minimap header. Arming wins over solo, and
+/// solo over mute, since that is the state most likely to surprise.
+pub(crate) fn track_state_dot_color(armed: bool, soloed: bool, muted: bool) -> u32 {
+    if armed {
+        TRACK_DOT_ARMED
+    } else if soloed {
+        TRACK_DOT_SOLOED
+    } else if muted {
+        TRACK_DOT_MUTED
+    } else {
+        TRACK_DOT_IDLE
+    }
+}
+
+/// Bar ticks for a minimap of `body_height` covering `0..timeline_end`, as
+/// `(bar index, y, labelled)`. Bars are thinned to a whole stride so ticks
+/// stay at least a few pixels apart, and only some ticks carry a number.
+pub(crate) fn minimap_bar_ticks(timeline_end: u64, body_height: f32) -> Vec<(u64, f32, bool)> {
+    let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+    let span = timeline_end.max(1);
+    let bar_height = bar as f32 / span as f32 * body_height;
+    if bar_height <= 0.0 {
+        return Vec::new();
+    }
+    let stride = (MINIMAP_BAR_TICK_SPACING / bar_height).ceil().max(1.0) as u64;
+    let label_every = (MINIMAP_BAR_LABEL_SPACING / (bar_height * stride as f32))
+        .ceil()
+        .max(1.0) as u64;
+    (0..)
+        .map(|tick| tick * stride)
+        .take_while(|index| index * bar < timeline_end)
+        .map(|index| {
+            let y = (index * bar) as f32 / span as f32 * body_height;
+            (index, y, (index / stride).is_multiple_of(label_every))
+        })
+        .collect()
+}
+
+/// Where a minimap click at `nanotick` jumps to. Holding alt snaps the jump
+/// to the nearest bar line for coarse navigation.
+pub(crate) fn minimap_jump_target(nanotick: u64, snap_to_bar: bool) -> u64 {
+    if !snap_to_bar {
+        return nanotick;
+    }
+    let bar = BEATS_PER_BAR * NANOTICKS_PER_QUARTER;
+    nanotick.saturating_add(bar / 2) / bar * bar
+}
+
 impl EngineView {
     pub(crate) fn timeline_end_nanotick(&mut self) -> u64 {
         // Check if cache is valid
@@ -35,7 +94,7 @@ impl EngineView {
         for event in &self.harmony_events {
             max_tick = max_tick.max(event.nanotick);
         }
-        let row = self.row_nanoticks().max(1);
+        let row = self.row_nanoticks();
         let result = if max_tick == 0 {
             row.saturating_mul(VISIBLE_ROWS as u64)
         } else {
@@ -50,6 +109,28 @@ impl EngineView {
         result
     }
 
+    /// Number of density bins the minimap draws, one per pixel of its body.
+    pub(crate) fn minimap_segment_count() -> usize {
+        (ROW_HEIGHT * VISIBLE_ROWS as f32).ceil().max(1.0) as usize
+    }
+
+    /// Start of the minimap bin holding the most events, earliest first on
+    /// ties, or None when the timeline is empty.
+    pub(crate) fn densest_bin_start(&mut self, segments: usize) -> Option<u64> {
+        let end = self.timeline_end_nanotick();
+        let bins = self.minimap_bins(0, end, segments);
+        let mut densest = 0;
+        for (index, count) in bins.iter().enumerate() {
+            if *count > bins[densest] {
+                densest = index;
+            }
+        }
+        if bins[densest] == 0 {
+            return None;
+        }
+        Some((end as u128 * densest as u128 / bins.len() as u128) as u64)
+    }
+
     pub(crate) fn minimap_bins(&mut self, start: u64, end: u64, segments: usize) -> Vec<usize> {
         let segments = segments.max(1);
         let params = (end, segments);
@@ -106,11 +187,11 @@ impl EngineView {
         let timeline_end = self.timeline_end_nanotick();
         let timeline_start = 0_u64;
         let body_height = ROW_HEIGHT * VISIBLE_ROWS as f32;
-        let segment_count = body_height.ceil().max(1.0) as usize;
+        let segment_count = Self::minimap_segment_count();
         let bins = self.minimap_bins(timeline_start, timeline_end, segment_count);
         let segment_height = body_height / bins.len().max(1) as f32;
         let view_start = self.scroll_nanotick_offset.max(0) as u64;
-        let view_len = row_nanoticks.max(1) as u64 * VISIBLE_ROWS as u64;
+        let view_len = row_nanoticks as u64 * VISIBLE_ROWS as u64;
         let span = timeline_end.saturating_sub(timeline_start).max(1);
         let to_y = |tick: u64| {
             ((tick.saturating_sub(timeline_start) as f32) / span as f32) * body_height
@@ -140,20 +221,22 @@ impl EngineView {
                     .bg(color)
                     .on_mouse_down(
                         MouseButton::Left,
-                        cx.listener(move |view, _, _, cx| {
-                            view.jump_to_nanotick(seg_start, cx);
+                        cx.listener(move |view, event: &MouseDownEvent, _, cx| {
+                            let target = minimap_jump_target(seg_start, event.modifiers.alt);
+                            view.jump_to_nanotick(target, cx);
                         }),
                     )
                     .on_mouse_move(cx.listener(move |view, event: &MouseMoveEvent, _, cx| {
                         if event.dragging() {
-                            view.jump_to_nanotick(seg_start, cx);
+                            let target = minimap_jump_target(seg_start, event.modifiers.alt);
+                            view.jump_to_nanotick(target, cx);
                         }
                     })),
             );
         }
 
         let selection_marker = if let Some((start, end)) = self.selection_bounds() {
-            let end_tick = end.saturating_add(row_nanoticks.max(1) as u64);
+            let end_tick = end.saturating_add(row_nanoticks as u64);
             let mut y0 = to_y(start);
             let mut y1 = to_y(end_tick.min(timeline_end));
             y0 = y0.clamp(0.0, body_height);
@@ -210,6 +293,68 @@ impl EngineView {
             div()
         };
 
+        let marker_lines: Vec<_> = self
+            .markers
+            .iter()
+            .filter(|(tick, _)| *tick < timeline_end)
+            .map(|(tick, _)| {
+                div()
+                    .absolute()
+                    .left(px(0.0))
+                    .top(px(to_y(*tick).clamp(0.0, body_height - 1.0)))
+                    .w(px(MINIMAP_WIDTH))
+                    .h(px(1.0))
+                    .bg(rgb(0xc8a04a))
+            })
+            .collect();
+
+        let bar_ticks: Vec<_> = if self.minimap_bar_numbers {
+            minimap_bar_ticks(timeline_end, body_height)
+                .into_iter()
+                .map(|(index, y, labelled)| {
+                    let tick = div()
+                        .absolute()
+                        .left(px(0.0))
+                        .top(px(y.clamp(0.0, body_height - 1.0)))
+                        .w(px(MINIMAP_WIDTH))
+                        .h(px(1.0))
+                        .bg(rgb(0x2f3848));
+                    if labelled {
+                        tick.child(
+                            div()
+                                .absolute()
+                                .left(px(1.0))
+                                .top(px(1.0))
+                                .text_size(px(7.0))
+                                .text_color(rgb(0x5f6b7d))
+                                .child(format!("{}", index + 1)),
+                        )
+                    } else {
+                        tick
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let track_dots: Vec<_> = if self.minimap_track_dots {
+            (0..TRACK_COUNT)
+                .map(|track| {
+                    let armed = self.track_armed.get(track).copied().unwrap_or(false);
+                    let soloed = self.solo_column.is_some_and(|(solo, _)| solo == track);
+                    // Tracks have no mute state yet.
+                    div()
+                        .w(px(3.0))
+                        .h(px(3.0))
+                        .rounded_full()
+                        .bg(rgb(track_state_dot_color(armed, soloed, false)))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         div()
             .w(px(MINIMAP_WIDTH))
             .flex()
@@ -218,7 +363,13 @@ impl EngineView {
                 div()
                     .w(px(MINIMAP_WIDTH))
                     .h(px(HEADER_HEIGHT))
-                    .bg(rgb(0x1a1f2b)),
+                    .bg(rgb(0x1a1f2b))
+                    .flex()
+                    .flex_wrap()
+                    .content_center()
+                    .justify_center()
+                    .gap(px(1.0))
+                    .children(track_dots),
             )
             .child(
                 div()
@@ -226,8 +377,10 @@ impl EngineView {
                     .h(px(body_height))
                     .relative()
                     .child(div().flex().flex_col().children(segments))
+                    .children(bar_ticks)
                     .child(selection_marker)
                     .child(loop_marker)
+                    .children(marker_lines)
                     .child(
                         div()
                             .absolute()
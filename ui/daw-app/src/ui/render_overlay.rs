@@ -1,7 +1,7 @@
 use gpui::{div, px, rgb, Context, IntoElement};
 use gpui::prelude::*;
 
-use crate::app::EngineView;
+use crate::app::{queue_summary_text, EngineView, StashPrompt, TRACK_COUNT};
 use crate::tracker::TIME_COLUMN_WIDTH;
 
 impl EngineView {
@@ -10,7 +10,7 @@ impl EngineView {
             return div();
         }
         let content = if self.jump_text.is_empty() {
-            "Jump: bar[:beat[:tick]]".to_string()
+            "Jump: bar[:beat[:tick]] or s<samples>".to_string()
         } else {
             format!("Jump: {}", self.jump_text)
         };
@@ -28,6 +28,124 @@ impl EngineView {
             .child(content)
     }
 
+    pub(crate) fn render_tempo_overlay(&self, _cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.tempo_open {
+            return div();
+        }
+        let content = if self.tempo_text.is_empty() {
+            "Tempo: BPM (20-300)".to_string()
+        } else {
+            format!("Tempo: {}", self.tempo_text)
+        };
+        div()
+            .absolute()
+            .top(px(6.0))
+            .left(px(TIME_COLUMN_WIDTH + 8.0))
+            .bg(rgb(0x1b242e))
+            .text_color(rgb(0xd6dee6))
+            .border_1()
+            .border_color(rgb(0x2a3242))
+            .px_2()
+            .py_1()
+            .text_sm()
+            .child(content)
+    }
+
+    pub(crate) fn render_mirror_overlay(&self, _cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.mirror_open {
+            return div();
+        }
+        let content = if self.mirror_text.is_empty() {
+            format!("Mirror to track: 1-{TRACK_COUNT}")
+        } else {
+            format!("Mirror to track: {}", self.mirror_text)
+        };
+        div()
+            .absolute()
+            .top(px(6.0))
+            .left(px(TIME_COLUMN_WIDTH + 8.0))
+            .bg(rgb(0x1b242e))
+            .text_color(rgb(0xd6dee6))
+            .border_1()
+            .border_color(rgb(0x2a3242))
+            .px_2()
+            .py_1()
+            .text_sm()
+            .child(content)
+    }
+
+    pub(crate) fn render_bar_harmony_overlay(&self, _cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.bar_harmony_open {
+            return div();
+        }
+        let content = if self.bar_harmony_text.is_empty() {
+            "Bar roots: e.g. C,G,A,F".to_string()
+        } else {
+            format!("Bar roots: {}", self.bar_harmony_text)
+        };
+        div()
+            .absolute()
+            .top(px(6.0))
+            .left(px(TIME_COLUMN_WIDTH + 8.0))
+            .bg(rgb(0x1b242e))
+            .text_color(rgb(0xd6dee6))
+            .border_1()
+            .border_color(rgb(0x2a3242))
+            .px_2()
+            .py_1()
+            .text_sm()
+            .child(content)
+    }
+
+    pub(crate) fn render_euclidean_overlay(&self, _cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.euclidean_open {
+            return div();
+        }
+        let content = if self.euclidean_text.is_empty() {
+            "Steps,hits,offset,pitch: e.g. 8,3,0,C-4".to_string()
+        } else {
+            format!("Steps,hits,offset,pitch: {}", self.euclidean_text)
+        };
+        div()
+            .absolute()
+            .top(px(6.0))
+            .left(px(TIME_COLUMN_WIDTH + 8.0))
+            .bg(rgb(0x1b242e))
+            .text_color(rgb(0xd6dee6))
+            .border_1()
+            .border_color(rgb(0x2a3242))
+            .px_2()
+            .py_1()
+            .text_sm()
+            .child(content)
+    }
+
+    pub(crate) fn render_sustain_chord_overlay(
+        &self,
+        _cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        if !self.sustain_chord_open {
+            return div();
+        }
+        let content = if self.sustain_chord_text.is_empty() {
+            "Chord across selection: e.g. @1^7".to_string()
+        } else {
+            format!("Chord across selection: {}", self.sustain_chord_text)
+        };
+        div()
+            .absolute()
+            .top(px(6.0))
+            .left(px(TIME_COLUMN_WIDTH + 8.0))
+            .bg(rgb(0x1b242e))
+            .text_color(rgb(0xd6dee6))
+            .border_1()
+            .border_color(rgb(0x2a3242))
+            .px_2()
+            .py_1()
+            .text_sm()
+            .child(content)
+    }
+
     pub(crate) fn render_toast(&self, _cx: &mut Context<Self>) -> impl IntoElement {
         let Some(message) = self.toast_message.as_ref() else {
             return div();
@@ -46,6 +164,33 @@ impl EngineView {
             .child(message.clone())
     }
 
+    pub(crate) fn render_stash_prompt_overlay(&self, _cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(mode) = self.stash_prompt else {
+            return div();
+        };
+        let label = match mode {
+            StashPrompt::Stash => "Stash take as",
+            StashPrompt::Swap => "Swap with take",
+        };
+        let content = if self.stash_name_text.is_empty() {
+            format!("{label}: name... (Enter)")
+        } else {
+            format!("{label}: {}", self.stash_name_text)
+        };
+        div()
+            .absolute()
+            .top(px(6.0))
+            .left(px(TIME_COLUMN_WIDTH + 8.0))
+            .bg(rgb(0x1b242e))
+            .text_color(rgb(0xd6dee6))
+            .border_1()
+            .border_color(rgb(0x2a3242))
+            .px_2()
+            .py_1()
+            .text_sm()
+            .child(content)
+    }
+
     pub(crate) fn render_preset_save_overlay(&self, _cx: &mut Context<Self>) -> impl IntoElement {
         if !self.preset_save_open {
             return div();
@@ -69,6 +214,24 @@ impl EngineView {
             .child(content)
     }
 
+    pub(crate) fn render_version_overlay(&self, _cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.show_version_overlay {
+            return div();
+        }
+        div()
+            .absolute()
+            .top(px(6.0))
+            .right(px(12.0))
+            .bg(rgb(0x1b242e))
+            .text_color(rgb(0xd6dee6))
+            .border_1()
+            .border_color(rgb(0x2a3242))
+            .px_2()
+            .py_1()
+            .text_sm()
+            .child(self.version_overlay_line())
+    }
+
     pub(crate) fn render_debug_overlay(&self, _cx: &mut Context<Self>) -> impl IntoElement {
         if !self.ui_debug {
             return div();
@@ -79,6 +242,7 @@ impl EngineView {
             self.snapshot.ui_transport_state,
             self.snapshot.version
         );
+        let queue = queue_summary_text(&self.queued_command_summary());
         div()
             .absolute()
             .bottom(px(10.0))
@@ -90,6 +254,9 @@ impl EngineView {
             .px_2()
             .py_1()
             .text_sm()
+            .flex()
+            .flex_col()
             .child(content)
+            .child(queue)
     }
 }
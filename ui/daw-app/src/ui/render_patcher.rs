@@ -11,7 +11,7 @@ use crate::app::{
     PATCHER_AUDIO_INPUT_PORT, PATCHER_AUDIO_OUTPUT_PORT, PATCHER_CONTROL_INPUT_PORT,
     PATCHER_CONTROL_OUTPUT_PORT, PATCHER_EVENT_INPUT_PORT, PATCHER_EVENT_OUTPUT_PORT,
     PATCHER_NODE_AUDIO_PASSTHROUGH, PATCHER_NODE_EUCLIDEAN, PATCHER_NODE_EVENT_OUT,
-    PATCHER_NODE_LFO, PATCHER_NODE_PASSTHROUGH, PATCHER_NODE_RANDOM_DEGREE,
+    PATCHER_NODE_HARMONY_ARP, PATCHER_NODE_LFO, PATCHER_NODE_PASSTHROUGH, PATCHER_NODE_RANDOM_DEGREE,
     PATCHER_NODE_RUST, TRACK_COUNT,
 };
 use crate::tracker::{HEADER_HEIGHT, ROW_HEIGHT, VISIBLE_ROWS};
@@ -46,6 +46,7 @@ fn node_type_label(node_type: u32) -> &'static str {
         PATCHER_NODE_LFO => "LFO",
         PATCHER_NODE_RANDOM_DEGREE => "Random Degree",
         PATCHER_NODE_EVENT_OUT => "Event Out",
+        PATCHER_NODE_HARMONY_ARP => "Harmony Arp",
         _ => "Node",
     }
 }
@@ -164,7 +165,7 @@ fn node_output_ports(node_type: u32) -> &'static [PortSpec] {
         PATCHER_NODE_RUST => &RUST_OUTPUTS,
         PATCHER_NODE_EUCLIDEAN => &EUCLIDEAN_OUTPUTS,
         PATCHER_NODE_PASSTHROUGH => &PASSTHROUGH_OUTPUTS,
-        PATCHER_NODE_RANDOM_DEGREE => &RANDOM_OUTPUTS,
+        PATCHER_NODE_RANDOM_DEGREE | PATCHER_NODE_HARMONY_ARP => &RANDOM_OUTPUTS,
         PATCHER_NODE_AUDIO_PASSTHROUGH => &AUDIO_OUTPUTS,
         PATCHER_NODE_LFO => &LFO_CONTROL_OUTPUTS,
         _ => &EMPTY,
@@ -296,6 +297,22 @@ impl EngineView {
                         }),
                     ),
             )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0x93a1ad))
+                    .border_1()
+                    .border_color(rgb(0x2a3242))
+                    .rounded(px(3.0))
+                    .px_1()
+                    .child("Add Harmony Arp")
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|view, _, _, cx| {
+                            view.add_patcher_node(PATCHER_NODE_HARMONY_ARP, cx);
+                        }),
+                    ),
+            )
             .child(
                 div()
                     .text_xs()
@@ -69,7 +69,11 @@ impl EngineView {
                     .mt_1()
                     .text_xs()
                     .text_color(rgb(0x6f818f))
-                    .child("Enter: set scale  Esc: close  Backspace: edit"),
+                    .child(if self.scale_browser_reharmonize {
+                        "Enter: reharmonize track  Esc: close  Backspace: edit"
+                    } else {
+                        "Enter: set scale  Esc: close  Backspace: edit"
+                    }),
             )
             .children(filtered.iter().enumerate().map(|(row, index)| {
                 let scale = SCALE_LIBRARY[*index];
@@ -1,21 +1,57 @@
 use std::collections::HashMap;
 use std::sync::{Arc, OnceLock};
 
-use gpui::{div, px, rgb, Context, FontWeight, IntoElement, MouseButton, SharedString};
+use gpui::{
+    div, px, rgb, Context, FontWeight, IntoElement, MouseButton, MouseDownEvent, MouseMoveEvent,
+    MouseUpEvent, SharedString,
+};
 use gpui::prelude::*;
 
 use crate::app::{
-    chord_token_text, pitch_to_note, EngineView, DEFAULT_ZOOM_INDEX,
-    NANOTICKS_PER_QUARTER, TRACK_COUNT, ZOOM_LEVELS, TrackerCache, TrackerCacheKey,
-    TrackerRowCache,
+    is_rest_cell, note_duration_label, pitch_ruler_labels, pitch_to_note, velocity_label,
+    EngineView,
+    DEFAULT_ZOOM_INDEX,
+    KEYJAZZ_BASE_OCTAVE, NANOTICKS_PER_QUARTER, TRACK_COUNT, ZOOM_LEVELS, TrackerCache,
+    TrackerCacheKey, TrackerRowCache,
 };
 use crate::harmony::{harmony_root_name, harmony_scale_name};
-use crate::state::{AggregateCell, AggregateSingle, ClipChord, HarmonyAggregate};
+use crate::state::{AggregateCell, AggregateSingle, CellKind, ClipChord, HarmonyAggregate};
 use crate::tracker::{
-    COLUMN_WIDTH, HEADER_HEIGHT, HARMONY_COLUMN_WIDTH, ROW_HEIGHT, TIME_COLUMN_WIDTH,
-    VISIBLE_ROWS,
+    COLUMN_WIDTH, HEADER_HEIGHT, HARMONY_COLUMN_WIDTH, PITCH_RULER_WIDTH, ROW_HEIGHT,
+    TIME_COLUMN_WIDTH, VISIBLE_ROWS,
 };
 
+/// Left border accent for cells holding edits the engine has not confirmed.
+const PENDING_CELL_BORDER: u32 = 0x8a6d3b;
+/// Line and label color for rows that start at a marker.
+const MARKER_COLOR: u32 = 0xc8a04a;
+/// Fill for the record-arm dot in a track header while the track is armed.
+const ARMED_DOT_COLOR: u32 = 0xd04545;
+/// Dimmed text for the root:scale a harmony row inherits from an earlier event.
+const INHERITED_HARMONY_COLOR: u32 = 0x45566a;
+/// Glyph shown in the gaps between a column's events in rest display mode.
+const REST_GLYPH: &str = "·";
+
+/// Mouse handlers that carry a harmony drag over a cell in `row_index`. Every
+/// harmony and note cell needs them: `on_mouse_move` only fires while the
+/// element's own hitbox is hovered.
+fn with_harmony_drag<E: InteractiveElement>(
+    element: E,
+    row_index: usize,
+    cx: &mut Context<EngineView>,
+) -> E {
+    element
+        .on_mouse_move(cx.listener(move |view, event: &MouseMoveEvent, _, cx| {
+            view.harmony_drag_move(row_index, event.dragging(), cx);
+        }))
+        .on_mouse_up(
+            MouseButton::Left,
+            cx.listener(|view, _event: &MouseUpEvent, _, _| {
+                view.end_harmony_drag();
+            }),
+        )
+}
+
 fn empty_label() -> SharedString {
     static EMPTY: OnceLock<SharedString> = OnceLock::new();
     EMPTY.get_or_init(|| SharedString::from("")).clone()
@@ -26,6 +62,11 @@ fn off_label() -> SharedString {
     OFF.get_or_init(|| SharedString::from("OFF")).clone()
 }
 
+fn rest_label() -> SharedString {
+    static REST: OnceLock<SharedString> = OnceLock::new();
+    REST.get_or_init(|| SharedString::from(REST_GLYPH)).clone()
+}
+
 fn cached_note_label(pitch: u8) -> SharedString {
     static LABELS: OnceLock<Vec<SharedString>> = OnceLock::new();
     let labels = LABELS.get_or_init(|| {
@@ -36,6 +77,44 @@ fn cached_note_label(pitch: u8) -> SharedString {
     labels[pitch.min(127) as usize].clone()
 }
 
+fn cached_velocity_label(velocity: u8) -> SharedString {
+    static LABELS: OnceLock<Vec<SharedString>> = OnceLock::new();
+    let labels = LABELS.get_or_init(|| {
+        (0..128)
+            .map(|value| SharedString::from(velocity_label(value as u8)))
+            .collect()
+    });
+    labels[velocity.min(127) as usize].clone()
+}
+
+fn note_cell_label(
+    pitch: u8,
+    velocity: u8,
+    duration: u64,
+    row_nanoticks: u64,
+    show_velocity: bool,
+    show_durations: bool,
+) -> SharedString {
+    if show_velocity {
+        cached_velocity_label(velocity)
+    } else if show_durations {
+        SharedString::from(note_duration_label(pitch, duration, row_nanoticks))
+    } else {
+        cached_note_label(pitch)
+    }
+}
+
+fn cached_pitch_ruler_label(row_index: usize) -> SharedString {
+    static LABELS: OnceLock<Vec<SharedString>> = OnceLock::new();
+    let labels = LABELS.get_or_init(|| {
+        pitch_ruler_labels(KEYJAZZ_BASE_OCTAVE)
+            .into_iter()
+            .map(SharedString::from)
+            .collect()
+    });
+    labels.get(row_index).cloned().unwrap_or_else(empty_label)
+}
+
 fn time_label_for_nanotick(nanotick: u64) -> SharedString {
     let total_beats = nanotick / crate::app::NANOTICKS_PER_QUARTER;
     let bar = total_beats / crate::app::BEATS_PER_BAR + 1;
@@ -87,11 +166,16 @@ impl EngineView {
             clip_render_version: self.clip_render_version,
             harmony_render_version: self.harmony_render_version,
             track_columns: self.track_columns.clone(),
+            track_content_filter: self.track_content_filter.clone(),
+            show_velocity: self.show_velocity,
+            show_durations: self.show_durations,
+            show_resolved_chords: self.show_resolved_chords,
+            show_rests: self.show_rests,
         }
     }
 
     fn build_tracker_cache(&self, key: TrackerCacheKey) -> TrackerCache {
-        let row_nanoticks = key.row_nanoticks.max(1);
+        let row_nanoticks = key.row_nanoticks;
         let visible_start = key.window_start;
         let visible_end = visible_start.saturating_add(
             row_nanoticks.saturating_mul(VISIBLE_ROWS as u64),
@@ -131,7 +215,15 @@ impl EngineView {
             }
         };
 
+        let shows = |track_index: usize, kind: CellKind| {
+            key.track_content_filter
+                .get(track_index)
+                .is_none_or(|filter| filter.shows(&kind))
+        };
         for (track_index, notes) in self.clip_notes.iter().enumerate() {
+            if !shows(track_index, CellKind::Note) {
+                continue;
+            }
             let columns = key.track_columns.get(track_index).copied().unwrap_or(0);
             for note in notes.iter() {
                 let Some(row_index) = row_index_for(note.nanotick) else {
@@ -143,13 +235,32 @@ impl EngineView {
                 }
                 let label = if note.velocity == 0 && note.duration == 0 {
                     off_label()
+                } else if self.flat_spellings.contains(&(track_index, note.column, note.nanotick)) {
+                    SharedString::from(self.note_cell_text_at(
+                        track_index,
+                        note.column,
+                        note.nanotick,
+                        note.pitch,
+                        note.velocity,
+                        note.duration,
+                    ))
                 } else {
-                    cached_note_label(note.pitch)
+                    note_cell_label(
+                        note.pitch,
+                        note.velocity,
+                        note.duration,
+                        row_nanoticks,
+                        key.show_velocity,
+                        key.show_durations,
+                    )
                 };
                 set_cell_label(&mut rows, row_index, track_index, column, label);
             }
         }
         for (track_index, chords) in self.clip_chords.iter().enumerate() {
+            if !shows(track_index, CellKind::Chord) {
+                continue;
+            }
             let columns = key.track_columns.get(track_index).copied().unwrap_or(0);
             for chord in chords.iter() {
                 let Some(row_index) = row_index_for(chord.nanotick) else {
@@ -159,10 +270,23 @@ impl EngineView {
                 if column >= columns {
                     continue;
                 }
-                let label = SharedString::from(chord_token_text(chord));
+                let label = SharedString::from(self.chord_cell_text(chord));
                 set_cell_label(&mut rows, row_index, track_index, column, label);
             }
         }
+        if key.show_rests {
+            for track_index in 0..TRACK_COUNT {
+                let columns = key.track_columns.get(track_index).copied().unwrap_or(0);
+                for column in 0..columns {
+                    let spans = self.column_event_spans(track_index, column);
+                    for row_index in 0..rows.len() {
+                        if is_rest_cell(&spans, rows[row_index].row_start) {
+                            set_cell_label(&mut rows, row_index, track_index, column, rest_label());
+                        }
+                    }
+                }
+            }
+        }
 
         TrackerCache { key, rows }
     }
@@ -186,7 +310,7 @@ impl EngineView {
         if self.pending_notes.is_empty() && self.pending_chords.is_empty() {
             return None;
         }
-        let row_nanoticks = self.row_nanoticks().max(1);
+        let row_nanoticks = self.row_nanoticks();
         let visible_start = self.scroll_nanotick_offset.max(0) as u64;
         let visible_end = visible_start.saturating_add(
             row_nanoticks.saturating_mul(VISIBLE_ROWS as u64),
@@ -211,6 +335,9 @@ impl EngineView {
         );
         for note in self.pending_notes.iter() {
             let track_index = note.track_id as usize;
+            if !self.cell_kind_visible(track_index, CellKind::Note) {
+                continue;
+            }
             let columns = self.track_columns.get(track_index).copied().unwrap_or(0);
             let Some(row_index) = row_index_for(note.nanotick) else {
                 continue;
@@ -221,13 +348,32 @@ impl EngineView {
             }
             let label = if note.velocity == 0 && note.duration == 0 {
                 off_label()
+            } else if self.flat_spellings.contains(&(track_index, note.column, note.nanotick)) {
+                SharedString::from(self.note_cell_text_at(
+                    track_index,
+                    note.column,
+                    note.nanotick,
+                    note.pitch,
+                    note.velocity,
+                    note.duration,
+                ))
             } else {
-                cached_note_label(note.pitch)
+                note_cell_label(
+                    note.pitch,
+                    note.velocity,
+                    note.duration,
+                    self.row_nanoticks(),
+                    self.show_velocity,
+                    self.show_durations,
+                )
             };
             labels.insert(cell_id(row_index, track_index, column), label);
         }
         for chord in self.pending_chords.iter() {
             let track_index = chord.track_id as usize;
+            if !self.cell_kind_visible(track_index, CellKind::Chord) {
+                continue;
+            }
             let columns = self.track_columns.get(track_index).copied().unwrap_or(0);
             let Some(row_index) = row_index_for(chord.nanotick) else {
                 continue;
@@ -249,7 +395,7 @@ impl EngineView {
                 base_octave: chord.base_octave,
                 column: chord.column,
             };
-            let label = SharedString::from(chord_token_text(&temp));
+            let label = SharedString::from(self.chord_cell_text(&temp));
             labels.entry(cell_id(row_index, track_index, column)).or_insert(label);
         }
         if labels.is_empty() {
@@ -357,7 +503,7 @@ impl EngineView {
                     return Some(pitch_to_note(*pitch));
                 }
                 AggregateSingle::Chord(chord) => {
-                    return Some(chord_token_text(chord));
+                    return Some(self.chord_cell_text(chord));
                 }
             }
         }
@@ -366,6 +512,10 @@ impl EngineView {
                 return Some(format!("[{}x {}]", aggregate.count, pitch_to_note(pitch)));
             }
         }
+        if !aggregate.notes_only && !aggregate.chord_only {
+            let notes = aggregate.count - aggregate.chord_count;
+            return Some(format!("[{}N/{}C]", notes, aggregate.chord_count));
+        }
         Some(format!("[{}]", aggregate.count))
     }
 
@@ -413,9 +563,14 @@ impl EngineView {
         let is_playhead_row = playhead_pos >= row_start && playhead_pos < row_end;
         let should_aggregate = cache.is_none() && self.should_aggregate_rows();
 
-        let time_label = row_cache
-            .map(|row| row.time_label.clone())
-            .unwrap_or_else(|| time_label_for_nanotick(row_start));
+        let marker = self.marker_in_range(row_start, row_end);
+        let time_label = if let Some(name) = marker {
+            SharedString::from(name.to_string())
+        } else {
+            row_cache
+                .map(|row| row.time_label.clone())
+                .unwrap_or_else(|| time_label_for_nanotick(row_start))
+        };
 
         // Background color based on row state
         let bg_color = if is_playhead_row {
@@ -435,7 +590,7 @@ impl EngineView {
             .flex()
             .items_center()
             .text_xs()
-            .text_color(rgb(0x6a7a8a))
+            .text_color(rgb(if marker.is_some() { MARKER_COLOR } else { 0x6a7a8a }))
             .px_2()
             .child(time_label);
 
@@ -459,6 +614,20 @@ impl EngineView {
                 })
                 .unwrap_or_else(empty_label)
         };
+        let inherited_label =
+            if !should_aggregate && self.show_inherited_harmony && harmony_label.is_empty() {
+                self.inherited_harmony_label(row_start)
+            } else {
+                None
+            };
+        let harmony_text_color = if inherited_label.is_some() {
+            INHERITED_HARMONY_COLOR
+        } else {
+            0x7fa0c0
+        };
+        let harmony_label = inherited_label
+            .map(SharedString::from)
+            .unwrap_or(harmony_label);
         let harmony_bg = if is_cursor_row && self.harmony_focus {
             rgb(0x3a4a5a)
         } else {
@@ -470,7 +639,7 @@ impl EngineView {
             .flex()
             .items_center()
             .text_xs()
-            .text_color(rgb(0x7fa0c0))
+            .text_color(rgb(harmony_text_color))
             .bg(harmony_bg)
             .border_l_1()
             .border_r_2()
@@ -478,11 +647,12 @@ impl EngineView {
             .px_2()
             .on_mouse_down(
                 MouseButton::Left,
-                cx.listener(move |view, _, _, cx| {
-                    view.focus_harmony_row(row_index, cx);
+                cx.listener(move |view, event: &MouseDownEvent, _, cx| {
+                    view.begin_harmony_drag(row_index, event.modifiers.shift, cx);
                 }),
             )
             .child(harmony_label);
+        let harmony_col = with_harmony_drag(harmony_col, row_index, cx);
 
         // Build row with stable ID for efficient GPUI diffing
         let mut row = div()
@@ -491,8 +661,25 @@ impl EngineView {
             .gap_0()
             .h(px(ROW_HEIGHT))
             .bg(bg_color)
+            .when(marker.is_some(), |row| row.border_t_1().border_color(rgb(MARKER_COLOR)))
             .child(time_col)
             .child(harmony_col);
+        if self.pitch_ruler_visible {
+            row = row.child(
+                div()
+                    .w(px(PITCH_RULER_WIDTH))
+                    .h(px(ROW_HEIGHT))
+                    .flex()
+                    .items_center()
+                    .text_xs()
+                    .text_color(rgb(0x5a6a7a))
+                    .bg(rgb(0x111419))
+                    .border_r_1()
+                    .border_color(rgb(0x2a3545))
+                    .px_1()
+                    .child(cached_pitch_ruler_label(row_index)),
+            );
+        }
 
         // Track columns
         for track in 0..TRACK_COUNT {
@@ -504,11 +691,19 @@ impl EngineView {
             if should_aggregate {
                 let aggregates = self.aggregate_cells_in_range(row_start, row_end, track, columns);
                 for (col_idx, agg) in aggregates.iter().enumerate() {
+                    if self.column_view_x(track, col_idx).is_none() {
+                        continue;
+                    }
                     let is_cursor_cell = is_cursor_row
                         && !self.harmony_focus
                         && track == self.focused_track_index
                         && col_idx == self.cursor_col;
                     let cell_bg = if is_cursor_cell { rgb(0x3a4a5a) } else { bg_color };
+                    let cell_text = if self.column_dimmed(track, col_idx) {
+                        rgb(0x4a5565)
+                    } else {
+                        rgb(0xc0d0e0)
+                    };
                     let label = self.aggregate_cell_label(agg).unwrap_or_default();
                     let cell_id = cell_id(row_index, track, col_idx);
                     let cell = div()
@@ -518,7 +713,7 @@ impl EngineView {
                         .flex()
                         .items_center()
                         .text_xs()
-                        .text_color(rgb(0xc0d0e0))
+                        .text_color(cell_text)
                         .bg(cell_bg)
                         .border_l_1()
                         .border_color(rgb(0x2a3545))
@@ -530,18 +725,31 @@ impl EngineView {
                             }),
                         )
                             .child(label);
-                    row = row.child(cell);
+                    row = row.child(with_harmony_drag(cell, row_index, cx));
                 }
             } else if let Some(cells) = row_cache.and_then(|row| row.cell_labels.get(track)) {
                 for col_idx in 0..columns {
+                    if self.column_view_x(track, col_idx).is_none() {
+                        continue;
+                    }
                     let is_cursor_cell = is_cursor_row
                         && !self.harmony_focus
                         && track == self.focused_track_index
                         && col_idx == self.cursor_col;
                     let cell_bg = if is_cursor_cell { rgb(0x3a4a5a) } else { bg_color };
+                    let cell_text = if self.column_dimmed(track, col_idx) {
+                        rgb(0x4a5565)
+                    } else {
+                        rgb(0xc0d0e0)
+                    };
                     let cell_id = cell_id(row_index, track, col_idx);
                     let overlay_label = pending_overlay
                         .and_then(|overlay| overlay.label_for(cell_id));
+                    let cell_border = if overlay_label.is_some() {
+                        rgb(PENDING_CELL_BORDER)
+                    } else {
+                        rgb(0x2a3545)
+                    };
                     let label = overlay_label.or_else(|| {
                         cells.get(col_idx).and_then(|label| label.clone())
                     }).unwrap_or_else(empty_label);
@@ -552,10 +760,10 @@ impl EngineView {
                         .flex()
                         .items_center()
                         .text_xs()
-                        .text_color(rgb(0xc0d0e0))
+                        .text_color(cell_text)
                         .bg(cell_bg)
                         .border_l_1()
-                        .border_color(rgb(0x2a3545))
+                        .border_color(cell_border)
                         .px_1()
                         .on_mouse_down(
                             MouseButton::Left,
@@ -564,47 +772,57 @@ impl EngineView {
                             }),
                         )
                         .child(label);
-                    row = row.child(cell);
+                    row = row.child(with_harmony_drag(cell, row_index, cx));
                 }
             } else {
                 // Non-aggregated: show individual notes
+                let show_notes = self.cell_kind_visible(track, CellKind::Note);
+                let show_chords = self.cell_kind_visible(track, CellKind::Chord);
                 for col_idx in 0..columns {
+                    if self.column_view_x(track, col_idx).is_none() {
+                        continue;
+                    }
                     let is_cursor_cell = is_cursor_row
                         && !self.harmony_focus
                         && track == self.focused_track_index
                         && col_idx == self.cursor_col;
                     let cell_bg = if is_cursor_cell { rgb(0x3a4a5a) } else { bg_color };
+                    let cell_text = if self.column_dimmed(track, col_idx) {
+                        rgb(0x4a5565)
+                    } else {
+                        rgb(0xc0d0e0)
+                    };
 
                     // Find note at this position
-                    let note_label = self.clip_notes.get(track).and_then(|notes| {
+                    let note_label = self.clip_notes.get(track).filter(|_| show_notes).and_then(|notes| {
                         notes.iter().find(|n| n.nanotick == row_start && n.column as usize == col_idx)
                     }).map(|n| {
                         if n.velocity == 0 && n.duration == 0 {
                             "OFF".to_string()
                         } else {
-                            pitch_to_note(n.pitch)
+                            self.note_cell_text_at(track, n.column, n.nanotick, n.pitch, n.velocity, n.duration)
                         }
                     }).or_else(|| {
                         // Check pending notes
                         self.pending_notes.iter().find(|n| {
-                            n.track_id as usize == track && n.nanotick == row_start && n.column as usize == col_idx
+                            show_notes && n.track_id as usize == track && n.nanotick == row_start && n.column as usize == col_idx
                         }).map(|n| {
                             if n.velocity == 0 && n.duration == 0 {
                                 "OFF".to_string()
                             } else {
-                                pitch_to_note(n.pitch)
+                                self.note_cell_text_at(track, n.column, n.nanotick, n.pitch, n.velocity, n.duration)
                             }
                         })
                     }).or_else(|| {
                         // Check chords
-                        self.clip_chords.get(track).and_then(|chords| {
+                        self.clip_chords.get(track).filter(|_| show_chords).and_then(|chords| {
                             chords.iter().find(|c| c.nanotick == row_start && c.column as usize == col_idx)
-                        }).map(|c| chord_token_text(c))
+                        }).map(|c| self.chord_cell_text(c))
                     }).or_else(|| {
                         // Check pending chords
                         self.pending_chords.iter().find(|c| {
-                            c.track_id as usize == track && c.nanotick == row_start && c.column as usize == col_idx
-                        }).map(|c| chord_token_text(&crate::state::ClipChord {
+                            show_chords && c.track_id as usize == track && c.nanotick == row_start && c.column as usize == col_idx
+                        }).map(|c| self.chord_cell_text(&crate::state::ClipChord {
                             chord_id: 0,
                             nanotick: c.nanotick,
                             duration: c.duration,
@@ -619,6 +837,16 @@ impl EngineView {
                         }))
                     });
 
+                    let is_pending = self.pending_notes.iter().any(|n| {
+                        n.track_id as usize == track && n.nanotick == row_start && n.column as usize == col_idx
+                    }) || self.pending_chords.iter().any(|c| {
+                        c.track_id as usize == track && c.nanotick == row_start && c.column as usize == col_idx
+                    });
+                    let cell_border = if is_pending {
+                        rgb(PENDING_CELL_BORDER)
+                    } else {
+                        rgb(0x2a3545)
+                    };
                     let cell_id = cell_id(row_index, track, col_idx);
                     let cell = div()
                         .id(("cell", cell_id))
@@ -627,10 +855,10 @@ impl EngineView {
                         .flex()
                         .items_center()
                         .text_xs()
-                        .text_color(rgb(0xc0d0e0))
+                        .text_color(cell_text)
                         .bg(cell_bg)
                         .border_l_1()
-                        .border_color(rgb(0x2a3545))
+                        .border_color(cell_border)
                         .px_1()
                         .on_mouse_down(
                             MouseButton::Left,
@@ -638,8 +866,16 @@ impl EngineView {
                                 view.focus_note_cell(row_index, track, col_idx, cx);
                             }),
                         )
-                        .child(note_label.unwrap_or_default());
-                    row = row.child(cell);
+                        .child(note_label.unwrap_or_else(|| {
+                            if self.show_rests
+                                && is_rest_cell(&self.column_event_spans(track, col_idx), row_start)
+                            {
+                                REST_GLYPH.to_string()
+                            } else {
+                                String::new()
+                            }
+                        }));
+                    row = row.child(with_harmony_drag(cell, row_index, cx));
                 }
             }
         }
@@ -685,9 +921,31 @@ impl EngineView {
                     .px_2()
                     .child("HARM"),
             );
+        if self.pitch_ruler_visible {
+            header = header.child(
+                div()
+                    .w(px(PITCH_RULER_WIDTH))
+                    .h_full()
+                    .flex()
+                    .items_center()
+                    .text_xs()
+                    .text_color(rgb(0x5a6a7a))
+                    .bg(rgb(0x111419))
+                    .border_r_1()
+                    .border_color(rgb(0x3a4555))
+                    .px_1()
+                    .child("KEY"),
+            );
+        }
         for track in 0..TRACK_COUNT {
-            let columns = self.track_columns[track];
-            let track_label = format!("T{}", track + 1);
+            let columns = self.visible_track_columns(track);
+            if columns == 0 {
+                continue;
+            }
+            let track_label = match self.track_time_offset_label(track) {
+                Some(offset) => format!("T{} {offset}", track + 1),
+                None => format!("T{}", track + 1),
+            };
             let plus = div()
                 .w(px(12.0))
                 .text_xs()
@@ -699,6 +957,18 @@ impl EngineView {
                         view.adjust_columns(track, 1, cx);
                     }),
                 );
+            let armed = self.track_armed.get(track).copied().unwrap_or(false);
+            let arm_dot = div()
+                .w(px(8.0))
+                .h(px(8.0))
+                .rounded_full()
+                .bg(rgb(if armed { ARMED_DOT_COLOR } else { 0x2a3545 }))
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |view, _, _, cx| {
+                        view.toggle_track_armed(track, cx);
+                    }),
+                );
             let minus = div()
                 .w(px(12.0))
                 .text_xs()
@@ -726,7 +996,15 @@ impl EngineView {
                         .text_color(rgb(0xa0aab4))
                         .child(track_label),
                 )
-                .child(div().flex().items_center().gap_1().child(plus).child(minus));
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_1()
+                        .child(arm_dot)
+                        .child(plus)
+                        .child(minus),
+                );
             header = header.child(header_cell);
         }
         header
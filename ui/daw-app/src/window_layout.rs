@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context as AnyhowContext, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::app::DEFAULT_ZOOM_INDEX;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowLayout {
+    pub zoom_index: usize,
+    pub scroll_nanotick_offset: i64,
+    pub cursor_nanotick: u64,
+    pub focused_track_index: usize,
+    pub follow_playhead: bool,
+}
+
+impl Default for WindowLayout {
+    fn default() -> Self {
+        Self {
+            zoom_index: DEFAULT_ZOOM_INDEX,
+            scroll_nanotick_offset: 0,
+            cursor_nanotick: 0,
+            focused_track_index: 0,
+            follow_playhead: true,
+        }
+    }
+}
+
+fn default_window_layout_path() -> PathBuf {
+    if let Ok(path) = std::env::var("DAW_UI_LAYOUT") {
+        return PathBuf::from(path);
+    }
+    PathBuf::from("build/ui_layout.json")
+}
+
+pub fn load_window_layout() -> Option<WindowLayout> {
+    let path = default_window_layout_path();
+    let json = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<WindowLayout>(&json) {
+        Ok(layout) => Some(layout),
+        Err(err) => {
+            eprintln!(
+                "daw-app: ignoring layout file {}: {err}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+pub fn save_window_layout(layout: &WindowLayout) -> Result<()> {
+    let path = default_window_layout_path();
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(layout)
+        .context("failed to serialize layout")?;
+    fs::write(&path, json)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
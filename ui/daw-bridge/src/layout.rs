@@ -4,6 +4,7 @@ pub const K_UI_MAX_TRACKS: usize = 8;
 pub const K_UI_MAX_CLIP_NOTES: usize = 4096;
 pub const K_UI_MAX_CLIP_CHORDS: usize = 1024;
 pub const K_UI_MAX_HARMONY_EVENTS: usize = 512;
+pub const K_UI_MASTER_METER_CHANNELS: usize = 2;
 pub const K_CHAIN_DEVICE_ID_AUTO: u32 = 0xFFFF_FFFF;
 pub const UI_CLIP_WINDOW_FLAG_COMPLETE: u32 = 1 << 0;
 pub const UI_CLIP_WINDOW_FLAG_RESYNC: u32 = 1 << 1;
@@ -40,6 +41,7 @@ pub struct ShmHeader {
     pub ui_harmony_offset: u64,
     pub ui_harmony_bytes: u64,
     pub ui_track_peak_rms: [f32; K_UI_MAX_TRACKS],
+    pub ui_master_peak: [f32; K_UI_MASTER_METER_CHANNELS],
 }
 
 #[repr(C, align(64))]
@@ -276,6 +278,57 @@ pub enum UiCommandType {
     SetPatcherNodeConfig = 28,
     SavePatcherPreset = 29,
     RequestClipWindow = 30,
+    PreviewChord = 31,
+    SetCountIn = 32,
+    SetTempo = 33,
+    SetTrackArmed = 34,
+    PreviewHarmony = 35,
+    SetTrackTimeOffset = 36,
+}
+
+impl UiCommandType {
+    pub fn from_u16(value: u16) -> Option<Self> {
+        match value {
+            0 => Some(Self::None),
+            1 => Some(Self::LoadPluginOnTrack),
+            2 => Some(Self::WriteNote),
+            3 => Some(Self::TogglePlay),
+            4 => Some(Self::DeleteNote),
+            5 => Some(Self::Undo),
+            6 => Some(Self::WriteHarmony),
+            7 => Some(Self::DeleteHarmony),
+            8 => Some(Self::WriteChord),
+            9 => Some(Self::DeleteChord),
+            10 => Some(Self::SetTrackHarmonyQuantize),
+            11 => Some(Self::Redo),
+            12 => Some(Self::SetLoopRange),
+            13 => Some(Self::SetAutomationTarget),
+            14 => Some(Self::AddDevice),
+            15 => Some(Self::RemoveDevice),
+            16 => Some(Self::MoveDevice),
+            17 => Some(Self::UpdateDevice),
+            18 => Some(Self::SetDeviceEuclideanConfig),
+            19 => Some(Self::SetTrackRouting),
+            20 => Some(Self::AddModLink),
+            21 => Some(Self::RemoveModLink),
+            22 => Some(Self::SetModLinkUid16),
+            23 => Some(Self::SetModSourceValue),
+            24 => Some(Self::OpenPluginEditor),
+            25 => Some(Self::AddPatcherNode),
+            26 => Some(Self::RemovePatcherNode),
+            27 => Some(Self::ConnectPatcherNodes),
+            28 => Some(Self::SetPatcherNodeConfig),
+            29 => Some(Self::SavePatcherPreset),
+            30 => Some(Self::RequestClipWindow),
+            31 => Some(Self::PreviewChord),
+            32 => Some(Self::SetCountIn),
+            33 => Some(Self::SetTempo),
+            34 => Some(Self::SetTrackArmed),
+            35 => Some(Self::PreviewHarmony),
+            36 => Some(Self::SetTrackTimeOffset),
+            _ => None,
+        }
+    }
 }
 
 #[repr(u16)]
@@ -501,5 +554,6 @@ mod tests {
         assert_eq!(offset_of!(ShmHeader, ui_harmony_offset), 160);
         assert_eq!(offset_of!(ShmHeader, ui_harmony_bytes), 168);
         assert_eq!(offset_of!(ShmHeader, ui_track_peak_rms), 176);
+        assert_eq!(offset_of!(ShmHeader, ui_master_peak), 208);
     }
 }
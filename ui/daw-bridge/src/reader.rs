@@ -1,6 +1,6 @@
 use std::sync::atomic::{fence, Ordering};
 
-use crate::layout::{ShmHeader, K_UI_MAX_TRACKS};
+use crate::layout::{ShmHeader, K_UI_MASTER_METER_CHANNELS, K_UI_MAX_TRACKS};
 
 #[derive(Clone, Copy, Debug)]
 pub struct UiSnapshot {
@@ -16,6 +16,7 @@ pub struct UiSnapshot {
     pub ui_harmony_offset: u64,
     pub ui_harmony_bytes: u64,
     pub ui_track_peak_rms: [f32; K_UI_MAX_TRACKS],
+    pub ui_master_peak: [f32; K_UI_MASTER_METER_CHANNELS],
 }
 
 pub struct SeqlockReader {
@@ -49,6 +50,7 @@ impl SeqlockReader {
             let ui_harmony_offset = unsafe { (*self.header).ui_harmony_offset };
             let ui_harmony_bytes = unsafe { (*self.header).ui_harmony_bytes };
             let ui_track_peak_rms = unsafe { (*self.header).ui_track_peak_rms };
+            let ui_master_peak = unsafe { (*self.header).ui_master_peak };
 
             fence(Ordering::Acquire);
             let v1 = unsafe { (*self.header).ui_version.load(Ordering::Acquire) };
@@ -66,6 +68,7 @@ impl SeqlockReader {
                     ui_harmony_offset,
                     ui_harmony_bytes,
                     ui_track_peak_rms,
+                    ui_master_peak,
                 });
             }
         }